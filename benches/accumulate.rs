@@ -0,0 +1,47 @@
+//! `accumulate` through `entry` against the `contains_key` + `get_mut`
+//! version, totalling 10,000 updates spread over 100 keys.
+//!
+//! Most updates hit an existing key, which is where the two differ: `entry`
+//! needs an owned `String` for the key on every call, while the two-lookup
+//! version hashes twice but only allocates for new keys. Expect the
+//! two-lookup version to come out somewhat faster, the allocation costing
+//! more than the second hash; `cargo bench --bench accumulate` shows by how
+//! much on your machine. `entry` is still the clearer default; check
+//! `get_mut` first only when the path is hot and most keys already exist.
+
+use std::collections::HashMap;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use references_sharing_vs_mutation::references::{accumulate, accumulate_two_lookups};
+
+const UPDATES: usize = 10_000;
+const KEYS: usize = 100;
+
+fn bench_accumulate(c: &mut Criterion) {
+    let keys: Vec<String> = (0..KEYS).map(|i| format!("key{}", i)).collect();
+    let mut group = c.benchmark_group("accumulate");
+    group.throughput(Throughput::Elements(UPDATES as u64));
+
+    group.bench_function("entry", |b| {
+        b.iter(|| {
+            let mut map = HashMap::new();
+            for i in 0..UPDATES {
+                accumulate(&mut map, black_box(&keys[i % KEYS]), 1.0);
+            }
+            map
+        })
+    });
+    group.bench_function("two lookups", |b| {
+        b.iter(|| {
+            let mut map = HashMap::new();
+            for i in 0..UPDATES {
+                accumulate_two_lookups(&mut map, black_box(&keys[i % KEYS]), 1.0);
+            }
+            map
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_accumulate);
+criterion_main!(benches);
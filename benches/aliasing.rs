@@ -0,0 +1,90 @@
+//! The `perf` comparisons, each over 10,000 values: the version the borrow
+//! rules lead to against the aliased or roundabout alternative.
+//!
+//! - `scale`: `iter_mut` against an index loop. Expect them within noise
+//!   of each other, the bounds checks optimised out.
+//! - `running totals`: borrowing two fields separately against cloning one
+//!   so a method can be called in the loop. Expect the split borrow ahead
+//!   by the cost of copying the vector.
+//! - `add`: a plain `&mut` against a `RefCell` borrowed for each value.
+//!   Expect the `RefCell` a little behind, for its flag.
+//! - `shared counter`: `AtomicU64` against `Mutex<u64>`, uncontended.
+//!   Expect the `Mutex` well behind.
+//!
+//! `cargo bench --bench aliasing` gives the numbers for your machine;
+//! `perf report` gives rougher ones without criterion.
+
+use std::cell::RefCell;
+use std::sync::atomic::AtomicU64;
+use std::sync::Mutex;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use references_sharing_vs_mutation::perf::{
+    add_atomic, add_mut, add_mutex, add_refcell, scale_indexed, scale_iter, Ledger, LEN,
+};
+
+fn values() -> Vec<u64> {
+    (0..LEN as u64).map(|i| (i * 7919) % 1000).collect()
+}
+
+fn bench_scale(c: &mut Criterion) {
+    let mut floats: Vec<f64> = values().into_iter().map(|v| v as f64).collect();
+    let mut group = c.benchmark_group("scale");
+    group.throughput(Throughput::Elements(LEN as u64));
+    group.bench_function("iter_mut", |b| {
+        b.iter(|| scale_iter(&mut floats, black_box(1.0)))
+    });
+    group.bench_function("index loop", |b| {
+        b.iter(|| scale_indexed(&mut floats, black_box(1.0)))
+    });
+    group.finish();
+}
+
+fn bench_totals(c: &mut Criterion) {
+    let mut ledger = Ledger {
+        entries: values(),
+        totals: Vec::with_capacity(LEN),
+    };
+    let mut group = c.benchmark_group("running totals");
+    group.throughput(Throughput::Elements(LEN as u64));
+    group.bench_function("split borrows", |b| {
+        b.iter(|| black_box(&mut ledger).totals_split())
+    });
+    group.bench_function("clone", |b| {
+        b.iter(|| black_box(&mut ledger).totals_cloning())
+    });
+    group.finish();
+}
+
+fn bench_add(c: &mut Criterion) {
+    let values = values();
+    let mut group = c.benchmark_group("add");
+    group.throughput(Throughput::Elements(LEN as u64));
+    group.bench_function("&mut", |b| {
+        let mut total = 0;
+        b.iter(|| add_mut(black_box(&mut total), &values))
+    });
+    group.bench_function("RefCell", |b| {
+        let total = RefCell::new(0);
+        b.iter(|| add_refcell(black_box(&total), &values))
+    });
+    group.finish();
+}
+
+fn bench_counter(c: &mut Criterion) {
+    let values = values();
+    let mut group = c.benchmark_group("shared counter");
+    group.throughput(Throughput::Elements(LEN as u64));
+    group.bench_function("AtomicU64", |b| {
+        let total = AtomicU64::new(0);
+        b.iter(|| add_atomic(black_box(&total), &values))
+    });
+    group.bench_function("Mutex", |b| {
+        let total = Mutex::new(0);
+        b.iter(|| add_mutex(black_box(&total), &values))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_scale, bench_totals, bench_add, bench_counter);
+criterion_main!(benches);
@@ -0,0 +1,39 @@
+//! `remove_below` through `retain` against the hand-written write-cursor
+//! loop, on 10,000 values of which about half are removed.
+//!
+//! Expect the two to be within noise of each other (`cargo bench --bench
+//! retain`): the hand-written loop's bounds checks are optimised away, and
+//! it buys nothing over `retain` except more lines.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use references_sharing_vs_mutation::references::{remove_below, remove_below_manual};
+
+const LEN: usize = 10_000;
+
+fn bench_retain(c: &mut Criterion) {
+    // A fixed scramble of 0..1, so the branch isn't predictable.
+    let input: Vec<f64> = (0..LEN)
+        .map(|i| ((i * 7919) % 1000) as f64 / 1000.0)
+        .collect();
+    let mut group = c.benchmark_group("remove_below");
+    group.throughput(Throughput::Elements(LEN as u64));
+
+    group.bench_function("retain", |b| {
+        b.iter_batched_ref(
+            || input.clone(),
+            |v| remove_below(v, black_box(0.5)),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("manual", |b| {
+        b.iter_batched_ref(
+            || input.clone(),
+            |v| remove_below_manual(v, black_box(0.5)),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_retain);
+criterion_main!(benches);
@@ -0,0 +1,16 @@
+//! Compiles the C half of the `cpp_compare` appendix when that feature is
+//! on; otherwise there's nothing to build.
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "cpp_compare")]
+    {
+        println!("cargo:rerun-if-changed=src/cpp_compare/assign.c");
+        println!("cargo:rerun-if-changed=src/cpp_compare/extend.c");
+        cc::Build::new()
+            .file("src/cpp_compare/assign.c")
+            .file("src/cpp_compare/extend.c")
+            .warnings(true)
+            .compile("cpp_compare");
+    }
+}
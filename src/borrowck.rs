@@ -0,0 +1,784 @@
+//! The two rules as a checker you can run, over a toy instruction set.
+//!
+//! A program is a list of [`Event`]s: variables coming into being, borrows,
+//! moves, assignments, reads and scopes ending. [`check`] walks it the way
+//! rustc's borrow checker walks a function body and reports every
+//! [`Violation`] with the error code rustc would give:
+//!
+//! ```
+//! use references_sharing_vs_mutation::borrowck::{check, Borrow, Event, Rule};
+//!
+//! // let v = vec![...]; let r = &v; let aside = v; r[0];
+//! let errors = check(&[
+//!     Event::Let("v"),
+//!     Event::Borrow { name: "r", kind: Borrow::Shared, place: "v" },
+//!     Event::Move { from: "v", to: "aside" },
+//!     Event::Use("r"),
+//! ]);
+//! assert_eq!(errors.len(), 1);
+//! assert_eq!(errors[0].at, 2);
+//! assert_eq!(errors[0].code, "E0505");
+//! assert_eq!(errors[0].rule, Rule::SharedIsReadOnly);
+//! ```
+//!
+//! Lifetimes are worked out as rustc's non-lexical ones are: a reference is
+//! live from its borrow until the last event that goes through it, or
+//! through anything reborrowed from it. Places are variable names with
+//! optional fields, like `v` or `m.0`; naming a reference variable means
+//! the place it points at, so `Assign("m")` is `*m = ...`.
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// Which kind of reference a borrow makes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Borrow {
+    /// `&place`
+    Shared,
+    /// `&mut place`
+    Mut,
+}
+
+/// One step of a program.
+///
+/// Every name an event mentions must already be in scope; naming one that
+/// isn't is a mistake in the program description, and `check` panics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event<'a> {
+    /// `let name = <some value>;`
+    Let(&'a str),
+    /// `let name = &place;` or `let name = &mut place;`
+    Borrow {
+        name: &'a str,
+        kind: Borrow,
+        place: &'a str,
+    },
+    /// `let to = from;` for a type that isn't `Copy`.
+    Move { from: &'a str, to: &'a str },
+    /// `place = <some value>;`
+    Assign(&'a str),
+    /// Reading `place`, including copying a `Copy` value out of it.
+    Use(&'a str),
+    /// `name` goes out of scope.
+    Drop(&'a str),
+}
+
+/// Which rule, in the notes' terms, a violation breaks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    /// Shared access is read-only access: no writing, moving or mutable
+    /// borrowing while a shared reference is live.
+    SharedIsReadOnly,
+    /// Mutable access is exclusive access: nothing else reaches the value
+    /// while a mutable reference is live.
+    MutableIsExclusive,
+    /// A moved-out value can't be used, and a value can't be moved out from
+    /// behind a reference.
+    Ownership,
+    /// A value can't go out of scope while references to it are live.
+    Outlives,
+}
+
+/// Something rustc would reject.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// Index of the offending event.
+    pub at: usize,
+    /// rustc's error code, like `"E0502"`.
+    pub code: &'static str,
+    pub rule: Rule,
+    /// rustc's wording, or close to it.
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "event {}: error[{}]: {}",
+            self.at, self.code, self.message
+        )
+    }
+}
+
+/// Checks `events` in order, returning every violation found.
+///
+/// Like rustc, it carries on after an error as though the event had been
+/// allowed, so one mistake may lead to others further down.
+pub fn check(events: &[Event]) -> Vec<Violation> {
+    let mut checker = Checker {
+        last_use: last_uses(events),
+        vars: HashMap::new(),
+        loans: Vec::new(),
+        moved: Vec::new(),
+        violations: Vec::new(),
+    };
+    for (at, event) in events.iter().enumerate() {
+        checker.step(at, event);
+    }
+    checker.violations
+}
+
+/// A place as a variable name and fields: `m.0` is `["m", "0"]`.
+type Path = Vec<String>;
+
+fn parse(place: &str) -> Path {
+    place.split('.').map(str::to_owned).collect()
+}
+
+/// Whether one path is inside the other, so that touching one touches the
+/// other.
+fn overlaps(a: &[String], b: &[String]) -> bool {
+    a.iter().zip(b).all(|(x, y)| x == y)
+}
+
+/// The place an event goes through, if it names one.
+pub(crate) fn place_of<'a>(event: &Event<'a>) -> Option<&'a str> {
+    match *event {
+        Event::Borrow { place, .. } | Event::Assign(place) | Event::Use(place) => Some(place),
+        Event::Move { from, .. } => Some(from),
+        Event::Let(_) | Event::Drop(_) => None,
+    }
+}
+
+pub(crate) fn root(place: &str) -> &str {
+    place.split('.').next().unwrap_or(place)
+}
+
+/// For each loan, in the order the borrows appear, the last event that
+/// uses it directly or through a reborrow. `None` means never used.
+pub(crate) fn last_uses(events: &[Event]) -> Vec<Option<usize>> {
+    let mut refs: HashMap<&str, usize> = HashMap::new();
+    let mut ancestors: Vec<Vec<usize>> = Vec::new();
+    let mut last_use = Vec::new();
+    for (at, event) in events.iter().enumerate() {
+        let through = place_of(event).and_then(|place| refs.get(root(place)).copied());
+        if let Some(id) = through {
+            last_use[id] = Some(at);
+            for &a in &ancestors[id] {
+                last_use[a] = Some(at);
+            }
+        }
+        match *event {
+            Event::Borrow { name, .. } => {
+                let chain = through.map_or(Vec::new(), |id| {
+                    let mut chain = vec![id];
+                    chain.extend_from_slice(&ancestors[id]);
+                    chain
+                });
+                ancestors.push(chain);
+                last_use.push(None);
+                refs.insert(name, ancestors.len() - 1);
+            }
+            // Moving a reference moves its loan along with it.
+            Event::Move { from, to } => match refs.get(from).copied() {
+                Some(id) => {
+                    refs.insert(to, id);
+                }
+                None => {
+                    refs.remove(to);
+                }
+            },
+            Event::Let(name) | Event::Drop(name) => {
+                refs.remove(name);
+            }
+            Event::Assign(_) | Event::Use(_) => {}
+        }
+    }
+    last_use
+}
+
+enum Binding {
+    Value,
+    Ref(usize),
+}
+
+struct Loan {
+    kind: Borrow,
+    /// What it borrows, starting from a value variable.
+    path: Path,
+    /// The loans it was reborrowed through, nearest first.
+    ancestors: Vec<usize>,
+}
+
+/// Where an event's place really is, and how it was reached.
+struct Access {
+    path: Path,
+    /// The loans it goes through; these don't conflict with it.
+    via: Vec<usize>,
+    through_shared: bool,
+}
+
+struct Checker {
+    last_use: Vec<Option<usize>>,
+    vars: HashMap<String, Binding>,
+    loans: Vec<Loan>,
+    moved: Vec<Path>,
+    violations: Vec<Violation>,
+}
+
+impl Checker {
+    fn step(&mut self, at: usize, event: &Event) {
+        match *event {
+            Event::Let(name) => self.bind(name, Binding::Value),
+            Event::Borrow { name, kind, place } => self.borrow(at, name, kind, place),
+            Event::Move { from, to } => self.move_out(at, from, to),
+            Event::Assign(place) => self.assign(at, place),
+            Event::Use(place) => self.read(at, place),
+            Event::Drop(name) => self.drop_var(at, name),
+        }
+    }
+
+    fn borrow(&mut self, at: usize, name: &str, kind: Borrow, place: &str) {
+        let access = self.resolve(place);
+        if access.via.is_empty() && self.is_moved(&access.path) {
+            self.report(
+                at,
+                "E0382",
+                Rule::Ownership,
+                format!("borrow of moved value: `{}`", place),
+            );
+        }
+        let behind_shared = kind == Borrow::Mut && access.through_shared;
+        if behind_shared {
+            self.report(
+                at,
+                "E0596",
+                Rule::SharedIsReadOnly,
+                format!(
+                    "cannot borrow `{}` as mutable, as it is behind a `&` reference",
+                    place
+                ),
+            );
+        }
+        // Like rustc, report a place behind `&` once, not also as borrowed.
+        let conflict = self
+            .conflicts(at, &access)
+            .find(|&l| kind == Borrow::Mut || self.loans[l].kind == Borrow::Mut)
+            .filter(|_| !behind_shared);
+        if let Some(l) = conflict {
+            let (code, rule, message) = match (kind, self.loans[l].kind) {
+                (Borrow::Mut, Borrow::Mut) => (
+                    "E0499",
+                    Rule::MutableIsExclusive,
+                    format!(
+                        "cannot borrow `{}` as mutable more than once at a time",
+                        place
+                    ),
+                ),
+                (Borrow::Mut, Borrow::Shared) => (
+                    "E0502",
+                    Rule::SharedIsReadOnly,
+                    format!(
+                        "cannot borrow `{}` as mutable because it is also borrowed as immutable",
+                        place
+                    ),
+                ),
+                (Borrow::Shared, _) => (
+                    "E0502",
+                    Rule::MutableIsExclusive,
+                    format!(
+                        "cannot borrow `{}` as immutable because it is also borrowed as mutable",
+                        place
+                    ),
+                ),
+            };
+            self.report(at, code, rule, message);
+        }
+        self.loans.push(Loan {
+            kind,
+            path: access.path,
+            ancestors: access.via,
+        });
+        self.bind(name, Binding::Ref(self.loans.len() - 1));
+    }
+
+    fn move_out(&mut self, at: usize, from: &str, to: &str) {
+        if let Some(&Binding::Ref(id)) = self.vars.get(from) {
+            self.move_ref(at, id, from, to);
+            return;
+        }
+        let access = self.resolve(from);
+        if !access.via.is_empty() {
+            let kind = if access.through_shared {
+                "a shared"
+            } else {
+                "a mutable"
+            };
+            self.report(
+                at,
+                "E0507",
+                Rule::Ownership,
+                format!(
+                    "cannot move out of `{}`, which is behind {} reference",
+                    from, kind
+                ),
+            );
+        } else if self.is_moved(&access.path) {
+            self.report(
+                at,
+                "E0382",
+                Rule::Ownership,
+                format!("use of moved value: `{}`", from),
+            );
+        }
+        let conflict = self.conflicts(at, &access).next();
+        if let Some(l) = conflict {
+            let rule = self.rule_for(l);
+            self.report(
+                at,
+                "E0505",
+                rule,
+                format!("cannot move out of `{}` because it is borrowed", from),
+            );
+        }
+        if access.via.is_empty() {
+            self.moved.push(access.path);
+        }
+        self.bind(to, Binding::Value);
+    }
+
+    /// `let to = from;` where `from` is a reference: the reference moves,
+    /// or for `&`, is copied, and what it points at stays put.
+    fn move_ref(&mut self, at: usize, id: usize, from: &str, to: &str) {
+        if self.loans[id].kind == Borrow::Mut {
+            let access = self.resolve(from);
+            if self.conflicts(at, &access).next().is_some() {
+                self.report(
+                    at,
+                    "E0505",
+                    Rule::MutableIsExclusive,
+                    format!("cannot move out of `{}` because it is borrowed", from),
+                );
+            }
+            self.bind(from, Binding::Value);
+            self.moved.push(vec![from.to_owned()]);
+        }
+        self.bind(to, Binding::Ref(id));
+    }
+
+    fn assign(&mut self, at: usize, place: &str) {
+        let access = self.resolve(place);
+        if access.through_shared {
+            self.report(
+                at,
+                "E0594",
+                Rule::SharedIsReadOnly,
+                format!(
+                    "cannot assign to `{}`, which is behind a `&` reference",
+                    place
+                ),
+            );
+        }
+        let conflict = self
+            .conflicts(at, &access)
+            .next()
+            .filter(|_| !access.through_shared);
+        if let Some(l) = conflict {
+            let rule = self.rule_for(l);
+            self.report(
+                at,
+                "E0506",
+                rule,
+                format!("cannot assign to `{}` because it is borrowed", place),
+            );
+        }
+        if access.via.is_empty() {
+            // Assigning a whole value initializes it again.
+            let path = access.path;
+            self.moved.retain(|m| !m.starts_with(&path));
+        }
+    }
+
+    fn read(&mut self, at: usize, place: &str) {
+        let access = self.resolve(place);
+        if access.via.is_empty() && self.is_moved(&access.path) {
+            self.report(
+                at,
+                "E0382",
+                Rule::Ownership,
+                format!("use of moved value: `{}`", place),
+            );
+        }
+        let conflict = self
+            .conflicts(at, &access)
+            .find(|&l| self.loans[l].kind == Borrow::Mut);
+        if conflict.is_some() {
+            self.report(
+                at,
+                "E0503",
+                Rule::MutableIsExclusive,
+                format!("cannot use `{}` because it was mutably borrowed", place),
+            );
+        }
+    }
+
+    fn drop_var(&mut self, at: usize, name: &str) {
+        match self.vars.remove(name) {
+            None => panic!("unknown variable `{}`", name),
+            Some(Binding::Ref(_)) => {}
+            Some(Binding::Value) => {
+                let path = vec![name.to_owned()];
+                if (0..self.loans.len())
+                    .any(|l| self.live(l, at) && overlaps(&self.loans[l].path, &path))
+                {
+                    self.report(
+                        at,
+                        "E0597",
+                        Rule::Outlives,
+                        format!("`{}` does not live long enough", name),
+                    );
+                }
+                self.moved.retain(|m| m[0] != name);
+            }
+        }
+    }
+
+    fn bind(&mut self, name: &str, binding: Binding) {
+        self.moved.retain(|m| m[0] != name);
+        self.vars.insert(name.to_owned(), binding);
+    }
+
+    fn resolve(&self, place: &str) -> Access {
+        let path = parse(place);
+        match self.vars.get(&path[0]) {
+            None => panic!("unknown variable `{}`", path[0]),
+            Some(Binding::Value) => Access {
+                path,
+                via: Vec::new(),
+                through_shared: false,
+            },
+            Some(&Binding::Ref(id)) => {
+                let loan = &self.loans[id];
+                let mut via = vec![id];
+                via.extend_from_slice(&loan.ancestors);
+                // A mutable reborrow of a shared reference is already an
+                // error; what it refers to is `&mut` as far as uses go.
+                let through_shared = loan.kind == Borrow::Shared;
+                let mut full = loan.path.clone();
+                full.extend_from_slice(&path[1..]);
+                Access {
+                    path: full,
+                    via,
+                    through_shared,
+                }
+            }
+        }
+    }
+
+    /// Live loans that overlap `access` and that it doesn't go through.
+    ///
+    /// Going through a reference only conflicts with what was reborrowed
+    /// from that reference: as with rustc, `*m1` isn't the same place as
+    /// the `y` it points at, and a loan of `y` taken while `m1` was live
+    /// has already been reported.
+    fn conflicts<'a>(&'a self, at: usize, access: &'a Access) -> impl Iterator<Item = usize> + 'a {
+        (0..self.loans.len()).filter(move |&l| {
+            let loan = &self.loans[l];
+            self.live(l, at)
+                && !access.via.contains(&l)
+                && access
+                    .via
+                    .first()
+                    .iter()
+                    .all(|r| loan.ancestors.contains(r))
+                && overlaps(&loan.path, &access.path)
+        })
+    }
+
+    fn live(&self, loan: usize, at: usize) -> bool {
+        self.last_use[loan].is_some_and(|last| last > at)
+    }
+
+    fn is_moved(&self, path: &[String]) -> bool {
+        self.moved.iter().any(|m| overlaps(m, path))
+    }
+
+    /// Writing or moving conflicts with any loan; which rule that breaks
+    /// depends on the loan.
+    fn rule_for(&self, loan: usize) -> Rule {
+        match self.loans[loan].kind {
+            Borrow::Shared => Rule::SharedIsReadOnly,
+            Borrow::Mut => Rule::MutableIsExclusive,
+        }
+    }
+
+    fn report(&mut self, at: usize, code: &'static str, rule: Rule, message: String) {
+        self.violations.push(Violation {
+            at,
+            code,
+            rule,
+            message,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Borrow::{Mut, Shared};
+    use super::Event::{Assign, Drop, Let, Move, Use};
+    use super::*;
+
+    fn borrow<'a>(name: &'a str, kind: Borrow, place: &'a str) -> Event<'a> {
+        Event::Borrow { name, kind, place }
+    }
+
+    fn verdict(events: &[Event]) -> Vec<(usize, &'static str)> {
+        check(events).iter().map(|v| (v.at, v.code)).collect()
+    }
+
+    #[test]
+    fn moving_while_borrowed() {
+        let rejected = [
+            Let("v"),
+            borrow("r", Shared, "v"),
+            Move {
+                from: "v",
+                to: "aside",
+            },
+            Use("r"),
+        ];
+        let errors = check(&rejected);
+        assert_eq!(verdict(&rejected), [(2, "E0505")]);
+        assert_eq!(
+            errors[0].message,
+            "cannot move out of `v` because it is borrowed"
+        );
+
+        // The fix: the last use of `r` comes before the move.
+        let fixed = [
+            Let("v"),
+            borrow("r", Shared, "v"),
+            Use("r"),
+            Move {
+                from: "v",
+                to: "aside",
+            },
+            Use("aside"),
+        ];
+        assert_eq!(verdict(&fixed), []);
+    }
+
+    #[test]
+    fn extending_a_vector_with_itself() {
+        let errors = check(&[
+            Let("wave"),
+            borrow("vec", Mut, "wave"),
+            borrow("slice", Shared, "wave"),
+            Use("vec"),
+            Use("slice"),
+        ]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, "E0502");
+        assert_eq!(errors[0].rule, Rule::MutableIsExclusive);
+        assert_eq!(
+            errors[0].to_string(),
+            "event 2: error[E0502]: cannot borrow `wave` as immutable because it is also borrowed as mutable"
+        );
+    }
+
+    #[test]
+    fn shared_borrows_freeze_the_value() {
+        let base = [
+            Let("x"),
+            borrow("r1", Shared, "x"),
+            borrow("r2", Shared, "x"),
+        ];
+        let with = |event, used_after: &[Event<'static>]| {
+            let mut events = base.to_vec();
+            events.push(event);
+            events.extend_from_slice(used_after);
+            verdict(&events)
+        };
+
+        assert_eq!(with(Use("x"), &[Use("r1"), Use("r2")]), []);
+        assert_eq!(with(Assign("x"), &[Use("r1")]), [(3, "E0506")]);
+        assert_eq!(with(borrow("m", Mut, "x"), &[Use("r2")]), [(3, "E0502")]);
+        // Once the shared references are finished with, `x` is free again.
+        assert_eq!(with(Assign("x"), &[]), []);
+    }
+
+    #[test]
+    fn mutable_borrows_are_exclusive() {
+        let base = [Let("y"), borrow("m1", Mut, "y")];
+        let with = |event| {
+            let mut events = base.to_vec();
+            events.push(event);
+            events.push(Assign("m1"));
+            check(&events)
+        };
+
+        let twice = with(borrow("m2", Mut, "y"));
+        assert_eq!(
+            (twice[0].code, twice[0].rule),
+            ("E0499", Rule::MutableIsExclusive)
+        );
+        assert_eq!(with(Use("y"))[0].code, "E0503");
+        assert_eq!(with(Move { from: "y", to: "z" })[0].code, "E0505");
+        assert_eq!(with(borrow("r", Shared, "y"))[0].code, "E0502");
+    }
+
+    #[test]
+    fn errors_are_reported_where_the_borrow_is_taken() {
+        // `m2` is the error. Writing through `m1` afterwards reaches `*m1`,
+        // not `y` itself, so it isn't a second one.
+        let two_mutable = [
+            Let("y"),
+            borrow("m1", Mut, "y"),
+            borrow("m2", Mut, "y"),
+            Assign("m1"),
+            Assign("m2"),
+        ];
+        assert_eq!(verdict(&two_mutable), [(2, "E0499")]);
+
+        // Nor is writing through a reference that was wrongly made `&mut`
+        // from a shared one: it's rejected where it's made.
+        let from_shared = [
+            Let("w"),
+            borrow("r", Shared, "w"),
+            borrow("m1", Mut, "r.1"),
+            Assign("m1"),
+        ];
+        assert_eq!(verdict(&from_shared), [(2, "E0596")]);
+    }
+
+    #[test]
+    fn reborrowing_from_a_shared_reference() {
+        let errors = check(&[
+            Let("w"),
+            borrow("r", Shared, "w"),
+            borrow("r0", Shared, "r.0"),
+            borrow("m1", Mut, "r.1"),
+            Assign("r.0"),
+            Use("r0"),
+            Use("m1"),
+        ]);
+        let found: Vec<_> = errors.iter().map(|e| (e.at, e.code, e.rule)).collect();
+        assert_eq!(
+            found,
+            [
+                (3, "E0596", Rule::SharedIsReadOnly),
+                (4, "E0594", Rule::SharedIsReadOnly),
+            ]
+        );
+    }
+
+    #[test]
+    fn moving_a_reference_moves_its_loan() {
+        // `let n = m;` for `m: &mut _`: `v` stays borrowed for as long as
+        // `n` is used, and `m` is gone.
+        let base = [Let("v"), borrow("m", Mut, "v"), Move { from: "m", to: "n" }];
+        let with = |event| {
+            let mut events = base.to_vec();
+            events.push(event);
+            events.push(Assign("n"));
+            verdict(&events)
+        };
+        assert_eq!(with(Use("v")), [(3, "E0503")]);
+        assert_eq!(with(Use("m")), [(3, "E0382")]);
+        assert_eq!(with(Use("n")), []);
+
+        // `&` references are copied, and both copies keep `v` frozen.
+        let shared = [
+            Let("v"),
+            borrow("r", Shared, "v"),
+            Move { from: "r", to: "s" },
+            Use("r"),
+            Assign("v"),
+            Use("s"),
+        ];
+        assert_eq!(verdict(&shared), [(4, "E0506")]);
+    }
+
+    #[test]
+    fn reborrowing_from_a_mutable_reference() {
+        let base = [
+            Let("v"),
+            borrow("m", Mut, "v"),
+            borrow("m0", Mut, "m.0"),
+            borrow("r1", Shared, "m.1"),
+        ];
+        let with = |event| {
+            let mut events = base.to_vec();
+            events.push(event);
+            events.push(Assign("m0"));
+            events.push(Use("r1"));
+            verdict(&events)
+        };
+
+        assert_eq!(with(Use("m.1")), []);
+        // `*m = 137` overlaps `m0`, and `v.1` goes around `m` altogether.
+        assert_eq!(with(Assign("m")), [(4, "E0506")]);
+        assert_eq!(with(Use("v.1")), [(4, "E0503")]);
+    }
+
+    #[test]
+    fn self_assignment() {
+        let events = [
+            Let("f"),
+            borrow("this", Mut, "f"),
+            borrow("rhs", Shared, "f"),
+            Use("this"),
+        ];
+        let errors = check(&events);
+        assert_eq!(verdict(&events), [(2, "E0502")]);
+        assert_eq!(
+            errors[0].message,
+            "cannot borrow `f` as immutable because it is also borrowed as mutable"
+        );
+    }
+
+    #[test]
+    fn moves_and_scopes() {
+        assert_eq!(
+            verdict(&[
+                Let("v"),
+                Move { from: "v", to: "a" },
+                Use("v"),
+                Assign("v"),
+                Use("v"),
+            ]),
+            [(2, "E0382")]
+        );
+        // Moving one field leaves the others usable.
+        assert_eq!(
+            verdict(&[
+                Let("p"),
+                Move {
+                    from: "p.0",
+                    to: "a"
+                },
+                Use("p.1"),
+                Use("p.0")
+            ]),
+            [(3, "E0382")]
+        );
+        assert_eq!(
+            verdict(&[
+                Let("v"),
+                borrow("r", Mut, "v"),
+                Move {
+                    from: "r.0",
+                    to: "a"
+                }
+            ]),
+            [(2, "E0507")]
+        );
+        assert_eq!(
+            verdict(&[
+                Let("r"),
+                Let("x"),
+                borrow("r", Shared, "x"),
+                Drop("x"),
+                Use("r")
+            ]),
+            [(3, "E0597")]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown variable `q`")]
+    fn unknown_names_are_rejected() {
+        check(&[Let("v"), Use("q")]);
+    }
+}
@@ -0,0 +1,219 @@
+//! Every example in the lessons as plain data, for tools that want the
+//! snippets without the tour around them.
+//!
+//! A [`Snippet`] is one lesson example with what's needed to show it on its
+//! own: the code, whether rustc accepts it, the error if not, and the
+//! lesson's explanation. Each is tagged with its lesson, its chapter, how
+//! it fares (`compiles`, `panics` or `rejected`) and the codes of any
+//! errors it gets.
+//!
+//! ```
+//! use references_sharing_vs_mutation::catalog;
+//!
+//! let two_mutable = catalog::by_tag("E0499");
+//! assert!(two_mutable.iter().all(|s| !s.compiles));
+//! assert_eq!(two_mutable[0].id, "exclusive/2");
+//! ```
+
+use crate::cli::{Lesson, Outcome, LESSONS};
+use crate::playground;
+
+/// How snippets that use the library name it.
+pub(crate) const CRATE_NAME: &str = "references_sharing_vs_mutation";
+
+/// One lesson example.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snippet {
+    /// The lesson's name and the example's position in it, from 1:
+    /// `"dangling/1"`.
+    pub id: String,
+    /// The lesson's title.
+    pub title: &'static str,
+    pub tags: Vec<String>,
+    /// The program, without the note at the top of its file.
+    pub code: String,
+    /// Whether rustc accepts it. Some that it accepts panic when run.
+    pub compiles: bool,
+    /// The lesson's explanation as plain text, shared by all its examples.
+    pub explanation: String,
+    /// What rustc says when it rejects the program.
+    pub rustc_error: Option<String>,
+}
+
+impl Snippet {
+    fn new(lesson: &Lesson, index: usize) -> Snippet {
+        let example = &lesson.examples[index];
+        let mut tags = vec![lesson.name.to_owned(), lesson.chapter.to_owned()];
+        let rustc_error = match example.outcome {
+            Outcome::Compiles => {
+                tags.push("compiles".to_owned());
+                None
+            }
+            Outcome::Panics(_) => {
+                tags.push("panics".to_owned());
+                None
+            }
+            Outcome::Rejected(stderr) => {
+                tags.push("rejected".to_owned());
+                tags.extend(error_codes(stderr).map(str::to_owned));
+                Some(stderr.trim_end().to_owned())
+            }
+        };
+        let code: Vec<&str> = example.numbered_lines().map(|(_, line)| line).collect();
+        Snippet {
+            id: format!("{}/{}", lesson.name, index + 1),
+            title: lesson.title,
+            tags,
+            code: code.join("\n") + "\n",
+            compiles: example.compiles(),
+            explanation: lesson.explain().to_text(),
+            rustc_error,
+        }
+    }
+
+    /// Whether it has `tag`, ignoring case.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    /// Whether it uses this crate, and so needs it to build.
+    pub fn uses_crate(&self) -> bool {
+        self.code.contains(CRATE_NAME)
+    }
+
+    /// A Rust Playground link with the code in it. See the `playground`
+    /// module for links to gists instead.
+    ///
+    /// ```
+    /// use references_sharing_vs_mutation::catalog;
+    ///
+    /// let url = catalog::find("dangling/1")[0].playground_url();
+    /// assert!(url.starts_with("https://play.rust-lang.org/?"));
+    /// assert!(url.contains("&code=fn%20main%28%29"));
+    /// ```
+    pub fn playground_url(&self) -> String {
+        playground::code_url(&self.code)
+    }
+}
+
+/// The codes in `error[E....]` headings, each once.
+pub(crate) fn error_codes(stderr: &str) -> impl Iterator<Item = &str> {
+    let mut seen = Vec::new();
+    stderr
+        .lines()
+        .filter_map(|line| line.strip_prefix("error["))
+        .filter_map(|rest| rest.split(']').next())
+        .filter(move |code| {
+            let new = !seen.contains(code);
+            seen.push(*code);
+            new
+        })
+}
+
+/// Every snippet, in lesson order.
+pub fn all() -> Vec<Snippet> {
+    LESSONS
+        .iter()
+        .flat_map(|lesson| (0..lesson.examples.len()).map(move |i| Snippet::new(lesson, i)))
+        .collect()
+}
+
+/// The snippet with id `id`, or every snippet in the lesson named `id`,
+/// or none.
+pub fn find(id: &str) -> Vec<Snippet> {
+    all()
+        .into_iter()
+        .filter(|s| s.id == id || s.id.split('/').next() == Some(id))
+        .collect()
+}
+
+/// The snippets tagged `tag`, ignoring case.
+pub fn by_tag(tag: &str) -> Vec<Snippet> {
+    all().into_iter().filter(|s| s.has_tag(tag)).collect()
+}
+
+/// The snippets whose title or explanation has every word of `query`,
+/// ignoring case. An empty query matches everything.
+pub fn search(query: &str) -> Vec<Snippet> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    all()
+        .into_iter()
+        .filter(|s| {
+            let text = format!("{}\n{}", s.title, s.explanation).to_lowercase();
+            words.iter().all(|w| text.contains(w.as_str()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::find_lesson;
+
+    #[test]
+    fn one_snippet_per_example() {
+        let all = all();
+        let examples: usize = LESSONS.iter().map(|l| l.examples.len()).sum();
+        assert_eq!(all.len(), examples);
+        assert_eq!(all[0].id, "dangling/1");
+
+        let first = &all[0];
+        assert!(!first.compiles);
+        assert_eq!(first.tags, ["dangling", "sharing", "rejected", "E0505"]);
+        assert!(first.code.starts_with("fn main() {\n"));
+        assert!(first
+            .rustc_error
+            .as_deref()
+            .unwrap()
+            .starts_with("error[E0505]: cannot move out of `v`"));
+        assert_eq!(all[1].rustc_error, None);
+        assert!(all[1].has_tag("Compiles"));
+    }
+
+    #[test]
+    fn finds_by_tag() {
+        let reborrow = by_tag("reborrow");
+        assert!(!reborrow.is_empty());
+        assert!(reborrow.iter().all(|s| s.id.starts_with("reborrow/")));
+        assert_eq!(by_tag("panics").len(), 1);
+        assert!(by_tag("no such tag").is_empty());
+    }
+
+    #[test]
+    fn finds_by_id_or_lesson() {
+        let dangling = find("dangling");
+        assert_eq!(
+            dangling.len(),
+            find_lesson("dangling").unwrap().examples.len()
+        );
+        assert_eq!(find("dangling/2"), &dangling[1..2]);
+        assert!(find("dangling/9").is_empty());
+        assert!(find("dang").is_empty());
+    }
+
+    #[test]
+    fn links_snippets_that_stand_alone() {
+        let snippet = &find("dangling/1")[0];
+        assert!(!snippet.uses_crate());
+        assert_eq!(
+            snippet.playground_url(),
+            playground::code_url(&snippet.code)
+        );
+        assert!(find("exclusive").iter().any(Snippet::uses_crate));
+    }
+
+    #[test]
+    fn searches_explanations() {
+        let hits = search("READ-ONLY moved");
+        assert!(!hits.is_empty());
+        assert!(hits.iter().all(|s| s.id.starts_with("dangling/")));
+        assert!(search("zebra").is_empty());
+        assert_eq!(search("").len(), all().len());
+    }
+
+    #[test]
+    fn lists_each_error_code_once() {
+        let stderr = "error[E0502]: a\nerror[E0502]: b\nerror[E0499]: c\nerror: aborting";
+        assert_eq!(error_codes(stderr).collect::<Vec<_>>(), ["E0502", "E0499"]);
+    }
+}
@@ -0,0 +1,913 @@
+use crate::borrowck::Borrow::{self, Mut, Shared};
+use crate::borrowck::Event::{self, Assign, Drop, Let, Move, Use};
+use crate::diagram::Diagram;
+use crate::explain::{Explanation, Locale};
+use crate::{cow, interior, memo, mutation, nll, perf, rc, reborrow, selfref, sharing, split};
+
+/// One step of the notes, taken in order by the tour.
+#[derive(Debug)]
+pub struct Lesson {
+    /// What `--lesson` calls it: the lesson module's name under `sharing`,
+    /// `mutation` or `interior`, or the chapter's own for `rc`, `cow`,
+    /// `reborrowing`, `nll`, `selfref`, `split` and `perf`. `maps` is named for
+    /// `references::maps`, with its demo in `memo`.
+    pub name: &'static str,
+    /// The top-level module it belongs to: `sharing`, `mutation`,
+    /// `interior`, `rc`, `cow`, `nll`, `selfref`, `split` or `perf`, or
+    /// `reborrowing` for `reborrow`, whose own name is `sharing`'s second
+    /// lesson's.
+    pub chapter: &'static str,
+    pub title: &'static str,
+    /// The names of the lessons to take first, before which `path` won't
+    /// suggest this one. The tour's order already has them first; a test
+    /// checks.
+    pub requires: &'static [&'static str],
+    /// The code to show, in the order the learner sees it.
+    pub examples: &'static [Example],
+    /// Printed once all the examples have been shown: the Markdown source
+    /// from `lessons/<locale>/`, which `explain` parses, in each language
+    /// it's been translated into. English comes first, and every lesson
+    /// has it.
+    pub explanations: &'static [(Locale, &'static str)],
+    /// The lesson module's `diagrams`, printed before the explanation.
+    pub diagrams: fn() -> Vec<Diagram>,
+    /// What the rejected code would have done had it compiled, where the
+    /// lesson can show it happening for real, printed after the diagrams.
+    pub aftermath: Option<fn() -> String>,
+    /// The lesson module's `demo`.
+    pub demo: fn(),
+}
+
+impl Lesson {
+    /// The explanation in `locale`, or in English if it hasn't been
+    /// translated, parsed. Every lesson's parses; a test checks.
+    pub fn explanation(&self, locale: Locale) -> Explanation {
+        Explanation::parse(self.explanation_source(locale)).expect("lesson explanations parse")
+    }
+
+    /// The explanation in English.
+    pub fn explain(&self) -> Explanation {
+        self.explanation(Locale::En)
+    }
+
+    /// The Markdown for `locale`, falling back to English.
+    pub fn explanation_source(&self, locale: Locale) -> &'static str {
+        self.explanations
+            .iter()
+            .find(|(l, _)| *l == locale)
+            .unwrap_or(&self.explanations[0])
+            .1
+    }
+}
+
+impl PartialEq for Lesson {
+    fn eq(&self, other: &Lesson) -> bool {
+        self.name == other.name
+    }
+}
+
+/// A program the learner is asked about.
+#[derive(Debug)]
+pub struct Example {
+    /// The whole program. Leading `//` lines are a note for whoever reads
+    /// the file and aren't shown.
+    pub source: &'static str,
+    pub outcome: Outcome,
+    /// What the program does, in terms of the borrow checker's toy
+    /// instructions, for drawing a `Timeline`. Empty where the checks
+    /// happen at run time instead.
+    pub events: &'static [Event<'static>],
+}
+
+/// What happens when an `Example` is compiled.
+#[derive(Debug)]
+pub enum Outcome {
+    /// It builds, and running it does what the lesson's `demo` does.
+    Compiles,
+    /// It builds, but a run-time check panics with this message when it
+    /// runs.
+    Panics(&'static str),
+    /// rustc rejects it with this output, as locked in by the
+    /// `tests/compile_fail` suite.
+    Rejected(&'static str),
+}
+
+impl Example {
+    /// The lines to show, each with its line number in `source` so they
+    /// match the compiler's output.
+    pub fn numbered_lines(&self) -> impl Iterator<Item = (usize, &'static str)> {
+        self.source
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line))
+            .skip_while(|(_, line)| line.starts_with("//") || line.is_empty())
+    }
+
+    pub fn compiles(&self) -> bool {
+        !matches!(self.outcome, Outcome::Rejected(_))
+    }
+}
+
+macro_rules! rejected {
+    ($name:literal, $events:expr) => {
+        Example {
+            source: include_str!(concat!("../../tests/compile_fail/", $name, ".rs")),
+            outcome: Outcome::Rejected(include_str!(concat!(
+                "../../tests/compile_fail/",
+                $name,
+                ".stderr"
+            ))),
+            events: $events,
+        }
+    };
+}
+
+/// A lesson's `explanations`: `lessons/en/<name>.md`, and the same file
+/// under each other locale's directory.
+macro_rules! explanations {
+    ($name:literal $(, $locale:ident => $dir:literal)*) => {
+        &[
+            (Locale::En, include_str!(concat!("../../lessons/en/", $name, ".md"))),
+            $((
+                Locale::$locale,
+                include_str!(concat!("../../lessons/", $dir, "/", $name, ".md")),
+            ),)*
+        ]
+    };
+}
+
+const fn borrow(name: &'static str, kind: Borrow, place: &'static str) -> Event<'static> {
+    Event::Borrow { name, kind, place }
+}
+
+const MOVE_WHILE_BORROWED: &[Event] = &[
+    Let("v"),
+    borrow("r", Shared, "v"),
+    Move {
+        from: "v",
+        to: "aside",
+    },
+    Use("r"),
+    Use("aside"),
+];
+
+const MUTABLE_FROM_SHARED: &[Event] = &[
+    Let("w"),
+    borrow("r", Shared, "w"),
+    borrow("m1", Mut, "r.1"),
+    Assign("m1"),
+    Assign("w.0"),
+];
+
+const BYPASS_MUTABLE: &[Event] = &[
+    Let("v"),
+    borrow("m", Mut, "v"),
+    borrow("m0", Mut, "m.0"),
+    borrow("r1", Shared, "m.1"),
+    Use("v.1"),
+    Use("r1"),
+    Assign("m0"),
+];
+
+// `extend(&mut wave, &wave)`, naming the arguments as `extend` does.
+const EXTEND_WITH_ITSELF: &[Event] = &[
+    Let("wave"),
+    borrow("vec", Mut, "wave"),
+    borrow("slice", Shared, "wave"),
+    Use("vec"),
+    Use("slice"),
+];
+
+const TWO_MUTABLE_BORROWS: &[Event] = &[
+    Let("y"),
+    borrow("m1", Mut, "y"),
+    borrow("m2", Mut, "y"),
+    Assign("m1"),
+    Assign("m2"),
+];
+
+// `f.clone_from(&f)`, with `clone_from`'s `self` and `source`.
+const CLONE_FROM_SELF: &[Event] = &[
+    Let("f"),
+    borrow("this", Mut, "f"),
+    borrow("source", Shared, "f"),
+    Use("this"),
+];
+
+// `totals.insert(..)` takes `&mut totals` as `this`.
+const KEY_ACROSS_INSERT: &[Event] = &[
+    Let("totals"),
+    borrow("key", Shared, "totals"),
+    borrow("this", Mut, "totals"),
+    Use("this"),
+    Use("key"),
+];
+
+// `keep(m)`, with `keep`'s parameter as `kept`.
+const GENERIC_MOVES_REFERENCE: &[Event] = &[
+    Let("v"),
+    borrow("m", Mut, "v"),
+    Move {
+        from: "m",
+        to: "kept",
+    },
+    Use("m"),
+];
+
+// `Vec::push(&mut v, v.len())`, with `push`'s `self` as `this` and
+// `len`'s as `len_of`.
+const EXPLICIT_TWO_PHASE: &[Event] = &[
+    Let("v"),
+    borrow("this", Mut, "v"),
+    borrow("len_of", Shared, "v"),
+    Use("len_of"),
+    Use("this"),
+];
+
+// `v.push(3)` takes `&mut v` as `this`; `_look`'s destructor reads it at
+// the end of `main`.
+const BORROW_HELD_BY_DROP: &[Event] = &[
+    Let("v"),
+    borrow("_look", Shared, "v"),
+    borrow("this", Mut, "v"),
+    Use("this"),
+    Use("_look"),
+];
+
+// `Parser { buf, cursor }` moves `buf` into `p`, which holds `cursor`.
+const SELF_BORROWING_STRUCT: &[Event] = &[
+    Let("buf"),
+    borrow("cursor", Shared, "buf"),
+    Move {
+        from: "buf",
+        to: "p",
+    },
+    Use("cursor"),
+];
+
+const TWO_ELEMENTS_MUT: &[Event] = &[
+    Let("v"),
+    borrow("a", Mut, "v"),
+    borrow("b", Mut, "v"),
+    Use("a"),
+    Use("b"),
+];
+
+// The loop's iterator borrows `p.songs`; `p.note` borrows all of `p`.
+const METHOD_BORROWS_ALL_OF_SELF: &[Event] = &[
+    Let("p"),
+    borrow("songs", Shared, "p.songs"),
+    borrow("this", Mut, "p"),
+    Use("songs"),
+];
+
+// The same, with `ledger.record` in the loop over `ledger.entries`.
+const RECORD_WHILE_ITERATING: &[Event] = &[
+    Let("ledger"),
+    borrow("entries", Shared, "ledger.entries"),
+    borrow("this", Mut, "ledger"),
+    Use("entries"),
+];
+
+const ASSIGN_WHILE_SHARED: &[Event] = &[
+    Let("x"),
+    borrow("r1", Shared, "x"),
+    borrow("r2", Shared, "x"),
+    Assign("x"),
+    Use("r1"),
+    Use("r2"),
+];
+
+/// Every lesson, in the order the notes take them.
+pub static LESSONS: &[Lesson] = &[
+    Lesson {
+        name: "dangling",
+        chapter: "sharing",
+        title: "Moving a value out from under a reference",
+        requires: &[],
+        examples: &[
+            rejected!("move_while_borrowed", MOVE_WHILE_BORROWED),
+            Example {
+                source: "fn main() {
+    let v = vec![4, 8, 19, 27, 34, 10];
+    {
+        let r = &v;
+        println!(\"{}\", r[0]);
+    }
+    let aside = v;
+    println!(\"{:?}\", aside);
+}
+",
+                outcome: Outcome::Compiles,
+                events: &[
+                    Let("v"),
+                    borrow("r", Shared, "v"),
+                    Use("r"),
+                    Drop("r"),
+                    Move {
+                        from: "v",
+                        to: "aside",
+                    },
+                    Use("aside"),
+                ],
+            },
+        ],
+        explanations: explanations!("dangling", Es => "es"),
+        diagrams: sharing::dangling::diagrams,
+        aftermath: None,
+        demo: sharing::dangling::demo,
+    },
+    Lesson {
+        name: "reborrow",
+        chapter: "sharing",
+        title: "Borrowing from a reference you already hold",
+        requires: &["dangling"],
+        examples: &[
+            rejected!("mutable_from_shared", MUTABLE_FROM_SHARED),
+            rejected!("bypass_mutable", BYPASS_MUTABLE),
+            Example {
+                source: "fn main() {
+    let mut v = (136, 139);
+    let m = &mut v;
+    let m0 = &mut m.0;
+    let r1 = &m.1;
+    *m0 += *r1;
+    println!(\"{:?}\", v);
+}
+",
+                outcome: Outcome::Compiles,
+                events: &[
+                    Let("v"),
+                    borrow("m", Mut, "v"),
+                    borrow("m0", Mut, "m.0"),
+                    borrow("r1", Shared, "m.1"),
+                    Use("r1"),
+                    Assign("m0"),
+                    Use("v"),
+                ],
+            },
+        ],
+        explanations: explanations!("reborrow"),
+        diagrams: sharing::reborrow::diagrams,
+        aftermath: None,
+        demo: sharing::reborrow::demo,
+    },
+    Lesson {
+        name: "exclusive",
+        chapter: "mutation",
+        title: "Extending a vector with itself",
+        requires: &["dangling"],
+        examples: &[
+            rejected!("extend_with_itself", EXTEND_WITH_ITSELF),
+            rejected!("two_mutable_borrows", TWO_MUTABLE_BORROWS),
+            Example {
+                source: "use references_sharing_vs_mutation::vecops::extend_self;
+
+fn main() {
+    let mut wave = vec![0.0, 1.0, 0.0, -1.0];
+    let len = wave.len();
+    extend_self(&mut wave, 0..len);
+    println!(\"{:?}\", wave);
+}
+",
+                outcome: Outcome::Compiles,
+                events: &[
+                    Let("wave"),
+                    Use("wave"),
+                    Let("len"),
+                    borrow("vec", Mut, "wave"),
+                    Use("len"),
+                    Use("vec"),
+                    Use("wave"),
+                ],
+            },
+        ],
+        explanations: explanations!("exclusive", Es => "es"),
+        diagrams: mutation::exclusive::diagrams,
+        aftermath: Some(mutation::exclusive::aftermath),
+        demo: mutation::exclusive::demo,
+    },
+    Lesson {
+        name: "self_assignment",
+        chapter: "mutation",
+        title: "Assigning a file to itself",
+        requires: &["exclusive"],
+        examples: &[
+            rejected!("clone_from_self", CLONE_FROM_SELF),
+            Example {
+                source: "use references_sharing_vs_mutation::fd::File;
+
+fn main() {
+    let mut f = File::open(\"/dev/null\").unwrap();
+    let g = f.clone();
+    f.clone_from(&g);
+    println!(\"{:?}\", f);
+}
+",
+                outcome: Outcome::Compiles,
+                events: &[
+                    Let("f"),
+                    Use("f"),
+                    Let("g"),
+                    borrow("this", Mut, "f"),
+                    borrow("source", Shared, "g"),
+                    Use("this"),
+                    Use("f"),
+                ],
+            },
+        ],
+        explanations: explanations!("self_assignment"),
+        diagrams: mutation::self_assignment::diagrams,
+        aftermath: None,
+        demo: mutation::self_assignment::demo,
+    },
+    Lesson {
+        name: "maps",
+        chapter: "mutation",
+        title: "Holding a key while changing the map",
+        requires: &["exclusive"],
+        examples: &[
+            rejected!("key_across_insert", KEY_ACROSS_INSERT),
+            Example {
+                source: "use std::collections::HashMap;
+
+fn main() {
+    let mut totals: HashMap<String, f64> = HashMap::new();
+    totals.insert(\"wave\".to_string(), 1.5);
+    let key = totals.keys().next().unwrap().clone();
+    totals.insert(key.to_uppercase(), 0.0);
+    *totals.entry(key.clone()).or_default() += 1.0;
+    println!(\"copied {}: {:?}\", key, totals);
+}
+",
+                outcome: Outcome::Compiles,
+                events: &[
+                    Let("totals"),
+                    Use("totals"),
+                    Let("key"),
+                    borrow("this", Mut, "totals"),
+                    Use("key"),
+                    Use("this"),
+                    Use("key"),
+                    Use("totals"),
+                ],
+            },
+        ],
+        explanations: explanations!("maps"),
+        diagrams: Vec::new,
+        aftermath: None,
+        demo: memo::demo,
+    },
+    Lesson {
+        name: "cell",
+        chapter: "interior",
+        title: "Assigning through a shared reference with Cell",
+        requires: &["exclusive"],
+        examples: &[
+            rejected!("assign_while_shared", ASSIGN_WHILE_SHARED),
+            Example {
+                source: "use std::cell::Cell;
+
+fn main() {
+    let x = Cell::new(10);
+    let r1 = &x;
+    let r2 = &x;
+    x.set(x.get() + 10);
+    println!(\"{} {}\", r1.get(), r2.get());
+}
+",
+                outcome: Outcome::Compiles,
+                events: &[],
+            },
+        ],
+        explanations: explanations!("cell"),
+        diagrams: Vec::new,
+        aftermath: None,
+        demo: interior::cell::demo,
+    },
+    Lesson {
+        name: "refcell",
+        chapter: "interior",
+        title: "Extending a vector with itself through RefCell",
+        requires: &["cell"],
+        examples: &[
+            rejected!("extend_with_itself", EXTEND_WITH_ITSELF),
+            Example {
+                source: "use std::cell::RefCell;
+
+fn main() {
+    let wave = RefCell::new(vec![0.0, 1.0, 0.0, -1.0]);
+    let slice = wave.borrow();
+    wave.borrow_mut().extend(slice.iter());
+}
+",
+                outcome: Outcome::Panics("RefCell already borrowed"),
+                events: &[],
+            },
+            Example {
+                source: "use std::cell::RefCell;
+
+fn main() {
+    let wave = RefCell::new(vec![0.0, 1.0, 0.0, -1.0]);
+    let snapshot = wave.borrow().clone();
+    wave.borrow_mut().extend(snapshot);
+    println!(\"{:?}\", wave.borrow());
+}
+",
+                outcome: Outcome::Compiles,
+                events: &[],
+            },
+        ],
+        explanations: explanations!("refcell"),
+        diagrams: Vec::new,
+        aftermath: None,
+        demo: interior::refcell::demo,
+    },
+    Lesson {
+        name: "sync",
+        chapter: "interior",
+        title: "Exclusive access between threads with Mutex and RwLock",
+        requires: &["refcell"],
+        examples: &[
+            rejected!("two_mutable_borrows", TWO_MUTABLE_BORROWS),
+            Example {
+                source: "use std::sync::Mutex;
+
+fn main() {
+    let y = Mutex::new(20);
+    let m1 = y.lock().unwrap();
+    let m2 = y.try_lock();
+    println!(\"{} {}\", *m1, m2.is_err());
+}
+",
+                outcome: Outcome::Compiles,
+                events: &[],
+            },
+        ],
+        explanations: explanations!("sync"),
+        diagrams: Vec::new,
+        aftermath: None,
+        demo: interior::sync::demo,
+    },
+    Lesson {
+        name: "rc",
+        chapter: "rc",
+        title: "Shared ownership with Rc and Arc",
+        requires: &["refcell"],
+        examples: &[
+            rejected!("mutate_through_rc", &[]),
+            Example {
+                source: "use std::rc::Rc;
+
+fn main() {
+    let mut wave = Rc::new(vec![0.0, 1.0]);
+    let other = Rc::clone(&wave);
+    Rc::make_mut(&mut wave).push(0.0);
+    println!(\"{:?} {:?}\", wave, other);
+}
+",
+                outcome: Outcome::Compiles,
+                events: &[],
+            },
+        ],
+        explanations: explanations!("rc"),
+        diagrams: Vec::new,
+        aftermath: None,
+        demo: rc::demo,
+    },
+    Lesson {
+        name: "cow",
+        chapter: "cow",
+        title: "Borrowing or owning with Cow",
+        requires: &["dangling"],
+        examples: &[
+            rejected!("return_local_reference", &[]),
+            Example {
+                source: "use std::borrow::Cow;
+
+fn normalize_path(path: &str) -> Cow<str> {
+    if path.contains(\"//\") {
+        Cow::Owned(path.replace(\"//\", \"/\"))
+    } else {
+        Cow::Borrowed(path)
+    }
+}
+
+fn main() {
+    println!(\"{} {}\", normalize_path(\"a//b\"), normalize_path(\"a/b\"));
+}
+",
+                outcome: Outcome::Compiles,
+                events: &[],
+            },
+        ],
+        explanations: explanations!("cow"),
+        diagrams: Vec::new,
+        aftermath: None,
+        demo: cow::demo,
+    },
+    Lesson {
+        name: "reborrowing",
+        chapter: "reborrowing",
+        title: "The reborrows you don't write",
+        requires: &["reborrow", "exclusive"],
+        examples: &[
+            rejected!("generic_moves_reference", GENERIC_MOVES_REFERENCE),
+            rejected!("explicit_two_phase", EXPLICIT_TWO_PHASE),
+            // One `return` makes the borrow last on every path, which the
+            // toy checker, without control flow, can't show.
+            rejected!("returned_on_one_path", &[]),
+            Example {
+                source: "use references_sharing_vs_mutation::reborrow::push_len;
+
+fn main() {
+    let mut v = Vec::new();
+    let m = &mut v;
+    push_len(m);
+    push_len(m);
+    m.push(m.len());
+    println!(\"{:?}\", v);
+}
+",
+                outcome: Outcome::Compiles,
+                // Each `push_len(m)` is `push_len(&mut *m)`.
+                events: &[
+                    Let("v"),
+                    borrow("m", Mut, "v"),
+                    borrow("first", Mut, "m"),
+                    Use("first"),
+                    borrow("second", Mut, "m"),
+                    Use("second"),
+                    Use("m"),
+                    Use("v"),
+                ],
+            },
+        ],
+        explanations: explanations!("reborrowing"),
+        diagrams: Vec::new,
+        aftermath: None,
+        demo: reborrow::demo,
+    },
+    Lesson {
+        name: "nll",
+        chapter: "nll",
+        title: "Borrows end at their last use",
+        requires: &["dangling", "exclusive"],
+        examples: &[
+            rejected!("move_while_borrowed", MOVE_WHILE_BORROWED),
+            Example {
+                source: "fn main() {
+    let v = vec![4, 8, 19, 27, 34, 10];
+    let r = &v;
+    let first = r[0];
+    let aside = v;
+    println!(\"{} {:?}\", first, aside);
+}
+",
+                outcome: Outcome::Compiles,
+                events: &[
+                    Let("v"),
+                    borrow("r", Shared, "v"),
+                    Use("r"),
+                    Let("first"),
+                    Move {
+                        from: "v",
+                        to: "aside",
+                    },
+                    Use("first"),
+                    Use("aside"),
+                ],
+            },
+            rejected!("two_mutable_borrows", TWO_MUTABLE_BORROWS),
+            Example {
+                source: "fn main() {
+    let mut y = 20;
+    let m1 = &mut y;
+    *m1 += 1;
+    let m2 = &mut y;
+    *m2 += 1;
+    println!(\"{}\", y);
+}
+",
+                outcome: Outcome::Compiles,
+                events: &[
+                    Let("y"),
+                    borrow("m1", Mut, "y"),
+                    Assign("m1"),
+                    borrow("m2", Mut, "y"),
+                    Assign("m2"),
+                    Use("y"),
+                ],
+            },
+            rejected!("borrow_held_by_drop", BORROW_HELD_BY_DROP),
+            Example {
+                source: "use references_sharing_vs_mutation::nll::LastLook;
+
+fn main() {
+    let mut v = vec![1, 2];
+    let look = LastLook(&v);
+    drop(look);
+    v.push(3);
+    println!(\"{:?}\", v);
+}
+",
+                outcome: Outcome::Compiles,
+                // `drop(look)` runs the destructor, `look`'s last use.
+                events: &[
+                    Let("v"),
+                    borrow("look", Shared, "v"),
+                    Use("look"),
+                    borrow("this", Mut, "v"),
+                    Use("this"),
+                    Use("v"),
+                ],
+            },
+        ],
+        explanations: explanations!("nll"),
+        diagrams: Vec::new,
+        aftermath: None,
+        demo: nll::demo,
+    },
+    Lesson {
+        name: "selfref",
+        chapter: "selfref",
+        title: "A struct that borrows from itself",
+        requires: &["dangling"],
+        examples: &[
+            rejected!("self_borrowing_struct", SELF_BORROWING_STRUCT),
+            Example {
+                source: "use references_sharing_vs_mutation::selfref::Parser;
+
+fn main() {
+    let mut p = Parser::new(\"let x = 1;\");
+    let first = p.next_word().map(str::to_owned);
+    let moved = p;
+    println!(\"{:?} {:?}\", first, moved.rest());
+}
+",
+                outcome: Outcome::Compiles,
+                // `next_word` borrows `p` mutably for the `word` it returns.
+                events: &[
+                    Let("p"),
+                    borrow("word", Mut, "p"),
+                    Use("word"),
+                    Let("first"),
+                    Move {
+                        from: "p",
+                        to: "moved",
+                    },
+                    Use("first"),
+                    Use("moved"),
+                ],
+            },
+        ],
+        explanations: explanations!("selfref"),
+        diagrams: Vec::new,
+        aftermath: None,
+        demo: selfref::demo,
+    },
+    Lesson {
+        name: "split",
+        chapter: "split",
+        title: "Borrowing parts separately",
+        requires: &["reborrow", "exclusive"],
+        examples: &[
+            rejected!("two_elements_mut", TWO_ELEMENTS_MUT),
+            Example {
+                source: "use references_sharing_vs_mutation::split::get_two_mut;
+
+fn main() {
+    let mut v = vec![1, 2, 3];
+    if let Some((a, b)) = get_two_mut(&mut v, 0, 2) {
+        std::mem::swap(a, b);
+    }
+    println!(\"{:?}\", v);
+}
+",
+                outcome: Outcome::Compiles,
+                // One borrow of `v`, which `get_two_mut` splits in two.
+                events: &[Let("v"), borrow("pair", Mut, "v"), Use("pair"), Use("v")],
+            },
+            rejected!("method_borrows_all_of_self", METHOD_BORROWS_ALL_OF_SELF),
+            Example {
+                source: "struct Playlist {
+    songs: Vec<String>,
+    log: Vec<String>,
+}
+
+fn main() {
+    let mut p = Playlist {
+        songs: vec![String::from(\"Hercules\")],
+        log: Vec::new(),
+    };
+    let log = &mut p.log;
+    for song in &p.songs {
+        log.push(format!(\"now playing {}\", song));
+    }
+    println!(\"{:?}\", p.log);
+}
+",
+                outcome: Outcome::Compiles,
+                events: &[
+                    Let("p"),
+                    borrow("log", Mut, "p.log"),
+                    borrow("songs", Shared, "p.songs"),
+                    Use("log"),
+                    Use("songs"),
+                    Use("p"),
+                ],
+            },
+        ],
+        explanations: explanations!("split"),
+        diagrams: Vec::new,
+        aftermath: None,
+        demo: split::demo,
+    },
+    Lesson {
+        name: "perf",
+        chapter: "perf",
+        title: "What the rules cost",
+        requires: &["split", "refcell", "sync"],
+        examples: &[
+            rejected!("record_while_iterating", RECORD_WHILE_ITERATING),
+            Example {
+                source: "struct Ledger {
+    entries: Vec<u64>,
+    totals: Vec<u64>,
+}
+
+impl Ledger {
+    fn record(&mut self, total: u64) {
+        self.totals.push(total);
+    }
+}
+
+fn main() {
+    let mut ledger = Ledger {
+        entries: vec![3, 1, 4],
+        totals: Vec::new(),
+    };
+    let mut sum = 0;
+    for entry in ledger.entries.clone() {
+        sum += entry;
+        ledger.record(sum);
+    }
+    println!(\"{:?}\", ledger.totals);
+}
+",
+                outcome: Outcome::Compiles,
+                // The loop walks a copy, so nothing borrows `ledger` while
+                // `record` does.
+                events: &[
+                    Let("ledger"),
+                    borrow("r", Shared, "ledger.entries"),
+                    Use("r"),
+                    Let("copy"),
+                    borrow("this", Mut, "ledger"),
+                    Use("this"),
+                    Use("copy"),
+                    Use("ledger"),
+                ],
+            },
+            Example {
+                source: "struct Ledger {
+    entries: Vec<u64>,
+    totals: Vec<u64>,
+}
+
+fn main() {
+    let mut ledger = Ledger {
+        entries: vec![3, 1, 4],
+        totals: Vec::new(),
+    };
+    let mut sum = 0;
+    let totals = &mut ledger.totals;
+    for entry in &ledger.entries {
+        sum += entry;
+        totals.push(sum);
+    }
+    println!(\"{:?}\", ledger.totals);
+}
+",
+                outcome: Outcome::Compiles,
+                events: &[
+                    Let("ledger"),
+                    borrow("totals", Mut, "ledger.totals"),
+                    borrow("entries", Shared, "ledger.entries"),
+                    Use("totals"),
+                    Use("entries"),
+                    Use("ledger"),
+                ],
+            },
+        ],
+        explanations: explanations!("perf"),
+        diagrams: Vec::new,
+        aftermath: None,
+        demo: perf::demo,
+    },
+];
+
+/// Looks a lesson up by its `name`.
+pub fn find_lesson(name: &str) -> Option<&'static Lesson> {
+    LESSONS.iter().find(|l| l.name == name)
+}
@@ -0,0 +1,877 @@
+//! The command-line driver.
+//!
+//! With no arguments the binary runs every lesson's demo in order. `tour`
+//! walks through the lessons interactively instead, and `quiz` asks the
+//! lessons' exercises in a shuffled order. Both take `--lesson <name>` to
+//! stick to one lesson, `tour` takes `--lang <code>` for the explanations in
+//! another language, and `quiz` takes `--seed <n>` to repeat a shuffle.
+//!
+//! `workshop` is the quiz for a classroom: the exercises of each
+//! `--lesson` given, or all of them, in order, with `--minutes <n>` to
+//! answer each and hints on request. `--answer-key` prints the key
+//! instead, for the instructor.
+//!
+//! `tour` and `quiz` record what the learner has done in a
+//! [`Progress`](crate::progress::Progress) file, and a
+//! `tour` without `--lesson` picks up at the first unfinished lesson.
+//! Like `render`, they're only built on Unix.
+//! `progress show` summarises the file and `progress --reset` empties it.
+//!
+//! `path --to <name>` lists the lessons to take before the named one, in
+//! the order to take them, going by each lesson's `requires`; without
+//! `--to` it lists them all.
+//!
+//! `fix <name>` diffs each of a lesson's rejected examples against its
+//! fix; see the `diffview` module.
+//!
+//! `explain <code>` says what a rustc error code means and which lessons
+//! get it; see the `errors` module.
+//!
+//! `perf report` times the patterns the borrow rules lead to against the
+//! alternatives, best of `--rounds <n>` runs; see the `perf` module.
+//!
+//! `export` writes the lessons out for publishing; see the `export` module.
+//! `render --all` writes every snapshot the `tests/render` suite checks to
+//! `tests/render/snapshots`, or `--out <dir>`, and `render <name>` prints
+//! one; see the `render` module.
+//! `search <words>` lists the examples whose lessons mention them, and
+//! `playground <id>` links to a lesson's examples, or one example, in the
+//! Rust Playground, uploading them as gists first with `--gist`.
+//! `diagnostics report` checks the rejected examples against rustc, or each
+//! `--rustc <path>` given; see the `diagnostics` module.
+
+mod lesson;
+mod path;
+#[cfg(unix)]
+mod quiz;
+#[cfg(unix)]
+mod tour;
+mod workshop;
+
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+pub use self::lesson::{find_lesson, Example, Lesson, Outcome, LESSONS};
+pub use self::path::{order_all, run_path, study_order, Cycle};
+#[cfg(unix)]
+pub use self::quiz::quiz;
+#[cfg(unix)]
+pub use self::tour::tour;
+pub use self::workshop::{answer_key, workshop};
+use crate::catalog;
+use crate::errors::{self, ErrorCode};
+use crate::explain::Locale;
+use crate::export::Format;
+use crate::flashcards::Decks;
+use crate::playground;
+#[cfg(unix)]
+use crate::render;
+
+/// How the learner did: answers that were right, out of questions asked.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Score {
+    pub right: usize,
+    pub asked: usize,
+}
+
+/// Ends a tour, quiz or workshop with the score.
+fn finish<W: Write>(score: Score, out: &mut W) -> io::Result<Score> {
+    writeln!(out, "\nYou got {} of {} right.", score.right, score.asked)?;
+    Ok(score)
+}
+
+/// What to print when the arguments don't parse.
+pub const USAGE: &str = "usage: references-sharing-vs-mutation \
+[tour [--lesson <name>] [--lang <code>] | quiz [--lesson <name>] [--seed <n>] \
+| workshop [--lesson <name>]... [--minutes <n>] [--answer-key] | path [--to <name>] \
+| fix <name> | explain <code> | perf report [--rounds <n>] | progress [show | --reset] \
+| export [--format md | anki [--deck-tag <tag>]...] [--out <dir>] | render [<name> | --all [--out <dir>]] | search <words> | playground <id> [--gist] \
+| diagnostics report [--rustc <path>]...]";
+
+/// A parsed command line.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /// Run every lesson's demo.
+    Demos,
+    /// Walk through the lessons, or just the named one, with the
+    /// explanations in `lang`.
+    Tour {
+        lesson: Option<&'static Lesson>,
+        lang: Locale,
+    },
+    /// Ask the exercises for every lesson or just the named one, shuffled
+    /// from `seed` if one was given.
+    Quiz {
+        lesson: Option<&'static Lesson>,
+        seed: Option<u64>,
+    },
+    /// Ask the exercises for `lessons`, or every lesson if it's empty, in
+    /// order, allowing `minutes` for each; or with `answer_key`, print the
+    /// answers to them.
+    Workshop {
+        lessons: Vec<&'static Lesson>,
+        minutes: u64,
+        answer_key: bool,
+    },
+    /// List the lessons to take, in order, to get to `to`, or all of them.
+    Path { to: Option<&'static Lesson> },
+    /// Show what changed between each of the lesson's rejected examples
+    /// and its fix.
+    Fix { lesson: &'static Lesson },
+    /// Say what the error code means and where the lessons get it.
+    Explain { code: &'static ErrorCode },
+    /// Time each of the `perf` comparisons, best of `rounds` runs.
+    Perf { rounds: u32 },
+    /// Show the learner's progress, or forget it.
+    Progress { reset: bool },
+    /// Write every lesson to `out` in `format`.
+    Export { format: Format, out: PathBuf },
+    /// Print the rendering called `name`, or with `None`, write them all
+    /// under `out`.
+    #[cfg(unix)]
+    Render { name: Option<String>, out: PathBuf },
+    /// List the examples whose lesson mentions every word of `query`.
+    Search { query: String },
+    /// Link to the snippet with this id, or each in the lesson with this
+    /// name, in the Rust Playground, through gists if `gist`.
+    Playground { id: String, gist: bool },
+    /// Check the rejected examples against each of `rustcs`, or the
+    /// `rustc` on `PATH` if there are none.
+    #[cfg(feature = "diagnostics")]
+    Diagnostics { rustcs: Vec<PathBuf> },
+}
+
+/// Parses the arguments after the program name.
+///
+/// The error is a message for the user, naming the lessons when
+/// `--lesson` is given one that doesn't exist.
+pub fn parse_args<I>(args: I) -> Result<Command, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    let quiz = match args.next().as_deref() {
+        None => return Ok(Command::Demos),
+        Some("tour") => false,
+        Some("quiz") => true,
+        Some("progress") => {
+            let reset = match args.next().as_deref() {
+                None | Some("show") => false,
+                Some("--reset") => true,
+                Some(other) => return Err(format!("unexpected argument `{}`", other)),
+            };
+            if let Some(extra) = args.next() {
+                return Err(format!("unexpected argument `{}`", extra));
+            }
+            return Ok(Command::Progress { reset });
+        }
+        Some("workshop") => return parse_workshop(args),
+        Some("path") => {
+            let mut to = None;
+            while let Some(arg) = args.next() {
+                match flag_value("--to", &arg, &mut args)? {
+                    Some(name) => to = Some(lesson_named(&name)?),
+                    None => return Err(format!("unexpected argument `{}`", arg)),
+                }
+            }
+            return Ok(Command::Path { to });
+        }
+        Some("fix") => {
+            let name = args.next().ok_or("`fix` needs a lesson name")?;
+            let lesson = lesson_named(&name)?;
+            if let Some(extra) = args.next() {
+                return Err(format!("unexpected argument `{}`", extra));
+            }
+            return Ok(Command::Fix { lesson });
+        }
+        Some("explain") => {
+            let name = args.next().ok_or("`explain` needs an error code")?;
+            let code = errors::find(&name).ok_or_else(|| {
+                let codes: Vec<&str> = errors::CODES.iter().map(|c| c.code).collect();
+                format!(
+                    "no lesson gets `{}`; the codes they get are {}",
+                    name,
+                    codes.join(", ")
+                )
+            })?;
+            if let Some(extra) = args.next() {
+                return Err(format!("unexpected argument `{}`", extra));
+            }
+            return Ok(Command::Explain { code });
+        }
+        Some("perf") => {
+            if args.next().as_deref() != Some("report") {
+                return Err("`perf` needs `report`".to_owned());
+            }
+            let mut rounds = PERF_ROUNDS;
+            while let Some(arg) = args.next() {
+                match flag_value("--rounds", &arg, &mut args)? {
+                    Some(n) => {
+                        rounds = match n.parse() {
+                            Ok(n) if n > 0 => n,
+                            _ => {
+                                return Err(format!(
+                                    "`--rounds` needs a whole number above 0, not `{}`",
+                                    n
+                                ))
+                            }
+                        }
+                    }
+                    None => return Err(format!("unexpected argument `{}`", arg)),
+                }
+            }
+            return Ok(Command::Perf { rounds });
+        }
+        Some("export") => return parse_export(args),
+        #[cfg(unix)]
+        Some("render") => return parse_render(args),
+        #[cfg(feature = "diagnostics")]
+        Some("diagnostics") => {
+            if args.next().as_deref() != Some("report") {
+                return Err("`diagnostics` needs `report`".to_owned());
+            }
+            let mut rustcs = Vec::new();
+            while let Some(arg) = args.next() {
+                match flag_value("--rustc", &arg, &mut args)? {
+                    Some(path) => rustcs.push(PathBuf::from(path)),
+                    None => return Err(format!("unexpected argument `{}`", arg)),
+                }
+            }
+            return Ok(Command::Diagnostics { rustcs });
+        }
+        Some("search") => {
+            let words: Vec<String> = args.collect();
+            if words.is_empty() {
+                return Err("`search` needs something to search for".to_owned());
+            }
+            return Ok(Command::Search {
+                query: words.join(" "),
+            });
+        }
+        Some("playground") => return parse_playground(args),
+        Some(other) => return Err(format!("unknown command `{}`", other)),
+    };
+
+    let mut lesson = None;
+    let mut seed = None;
+    let mut lang = Locale::En;
+    while let Some(arg) = args.next() {
+        if let Some(name) = flag_value("--lesson", &arg, &mut args)? {
+            lesson = Some(lesson_named(&name)?);
+            continue;
+        }
+        if !quiz {
+            if let Some(code) = flag_value("--lang", &arg, &mut args)? {
+                lang = Locale::from_code(&code).ok_or_else(|| {
+                    let codes: Vec<&str> = Locale::ALL.iter().map(|l| l.code()).collect();
+                    format!(
+                        "no language `{}`; the languages are {}",
+                        code,
+                        codes.join(", ")
+                    )
+                })?;
+                continue;
+            }
+        }
+        if quiz {
+            if let Some(n) = flag_value("--seed", &arg, &mut args)? {
+                let n = n
+                    .parse()
+                    .map_err(|_| format!("`--seed` needs a number, not `{}`", n))?;
+                seed = Some(n);
+                continue;
+            }
+        }
+        return Err(format!("unexpected argument `{}`", arg));
+    }
+    Ok(if quiz {
+        Command::Quiz { lesson, seed }
+    } else {
+        Command::Tour { lesson, lang }
+    })
+}
+
+/// The lesson called `name`, or a message naming the lessons there are.
+fn lesson_named(name: &str) -> Result<&'static Lesson, String> {
+    find_lesson(name).ok_or_else(|| {
+        let names: Vec<&str> = LESSONS.iter().map(|l| l.name).collect();
+        format!("no lesson `{}`; the lessons are {}", name, names.join(", "))
+    })
+}
+
+/// Runs of each version `perf report` takes the best of, without
+/// `--rounds`.
+const PERF_ROUNDS: u32 = 20;
+
+/// Minutes allowed per exercise in a workshop without `--minutes`.
+const WORKSHOP_MINUTES: u64 = 3;
+
+fn parse_workshop<I>(mut args: I) -> Result<Command, String>
+where
+    I: Iterator<Item = String>,
+{
+    let mut lessons = Vec::new();
+    let mut minutes = WORKSHOP_MINUTES;
+    let mut answer_key = false;
+    while let Some(arg) = args.next() {
+        if arg == "--answer-key" {
+            answer_key = true;
+        } else if let Some(name) = flag_value("--lesson", &arg, &mut args)? {
+            lessons.push(lesson_named(&name)?);
+        } else if let Some(n) = flag_value("--minutes", &arg, &mut args)? {
+            minutes = match n.parse() {
+                Ok(n) if n > 0 => n,
+                _ => {
+                    return Err(format!(
+                        "`--minutes` needs a whole number above 0, not `{}`",
+                        n
+                    ))
+                }
+            };
+        } else {
+            return Err(format!("unexpected argument `{}`", arg));
+        }
+    }
+    Ok(Command::Workshop {
+        lessons,
+        minutes,
+        answer_key,
+    })
+}
+
+/// Parses the arguments after `export`, which default to Markdown in
+/// `./book`. Each `--deck-tag` makes a deck for the flashcards with that
+/// tag, in place of the decks per chapter.
+fn parse_export<I>(mut args: I) -> Result<Command, String>
+where
+    I: Iterator<Item = String>,
+{
+    let mut format = Format::Markdown;
+    let mut out = PathBuf::from("book");
+    let mut deck_tags = Vec::new();
+    while let Some(arg) = args.next() {
+        if let Some(name) = flag_value("--format", &arg, &mut args)? {
+            format = Format::from_name(&name)
+                .ok_or_else(|| format!("no format `{}`; the formats are md, anki", name))?;
+        } else if let Some(dir) = flag_value("--out", &arg, &mut args)? {
+            out = PathBuf::from(dir);
+        } else if let Some(tag) = flag_value("--deck-tag", &arg, &mut args)? {
+            deck_tags.push(tag);
+        } else {
+            return Err(format!("unexpected argument `{}`", arg));
+        }
+    }
+    if !deck_tags.is_empty() {
+        match &mut format {
+            Format::Anki { decks } => *decks = Decks::ByTag(deck_tags),
+            Format::Markdown => {
+                return Err("`--deck-tag` only goes with `--format anki`".to_owned())
+            }
+        }
+    }
+    Ok(Command::Export { format, out })
+}
+
+#[cfg(unix)]
+fn parse_render<I>(mut args: I) -> Result<Command, String>
+where
+    I: Iterator<Item = String>,
+{
+    let mut name = None;
+    let mut all = false;
+    let mut out = None;
+    while let Some(arg) = args.next() {
+        if arg == "--all" {
+            all = true;
+        } else if let Some(dir) = flag_value("--out", &arg, &mut args)? {
+            out = Some(PathBuf::from(dir));
+        } else if name.is_none() && !arg.starts_with('-') {
+            name = Some(arg);
+        } else {
+            return Err(format!("unexpected argument `{}`", arg));
+        }
+    }
+    match (name, all) {
+        (Some(name), false) if out.is_none() => {
+            if render::find(&name).is_none() {
+                return Err(format!("no rendering `{}`", name));
+            }
+            Ok(Command::Render {
+                name: Some(name),
+                out: PathBuf::new(),
+            })
+        }
+        (None, true) => Ok(Command::Render {
+            name: None,
+            out: out.unwrap_or_else(|| PathBuf::from("tests/render/snapshots")),
+        }),
+        _ => {
+            Err("`render` needs a rendering's name, or `--all` with an optional `--out`".to_owned())
+        }
+    }
+}
+
+fn parse_playground<I>(args: I) -> Result<Command, String>
+where
+    I: Iterator<Item = String>,
+{
+    let mut id = None;
+    let mut gist = false;
+    for arg in args {
+        if arg == "--gist" {
+            gist = true;
+        } else if id.is_none() && !arg.starts_with('-') {
+            id = Some(arg);
+        } else {
+            return Err(format!("unexpected argument `{}`", arg));
+        }
+    }
+    let id = id.ok_or("`playground` needs a lesson name or snippet id")?;
+    if catalog::find(&id).is_empty() {
+        let names: Vec<&str> = LESSONS.iter().map(|l| l.name).collect();
+        return Err(format!(
+            "no lesson or snippet `{}`; the lessons are {}",
+            id,
+            names.join(", ")
+        ));
+    }
+    Ok(Command::Playground { id, gist })
+}
+
+/// The value of `flag` if `arg` is it, given as `--flag value` or
+/// `--flag=value`.
+fn flag_value<I>(flag: &str, arg: &str, rest: &mut I) -> Result<Option<String>, String>
+where
+    I: Iterator<Item = String>,
+{
+    if arg == flag {
+        let value = rest
+            .next()
+            .ok_or_else(|| format!("`{}` needs a value", flag))?;
+        Ok(Some(value))
+    } else {
+        let value = arg.strip_prefix(flag).and_then(|v| v.strip_prefix('='));
+        Ok(value.map(str::to_owned))
+    }
+}
+
+/// Runs each lesson's demo under a heading naming it, followed by the
+/// lesson's diagrams.
+pub fn run_demos<W: Write>(out: &mut W) -> io::Result<()> {
+    for lesson in LESSONS {
+        writeln!(out, "== {}", lesson.name)?;
+        // The demos print to stdout themselves.
+        out.flush()?;
+        (lesson.demo)();
+        for diagram in (lesson.diagrams)() {
+            write!(out, "\n{}", diagram)?;
+        }
+    }
+    Ok(())
+}
+
+/// Lists the catalog snippets matching `query`, one per line with its
+/// tags, or says there are none.
+pub fn run_search<W: Write>(query: &str, out: &mut W) -> io::Result<()> {
+    let hits = catalog::search(query);
+    if hits.is_empty() {
+        return writeln!(out, "nothing mentions `{}`", query);
+    }
+    for snippet in hits {
+        writeln!(
+            out,
+            "{:<18} {}  [{}]",
+            snippet.id,
+            snippet.title,
+            snippet.tags.join(", ")
+        )?;
+    }
+    Ok(())
+}
+
+/// Prints a playground link for each snippet `id` names, noting those
+/// that won't build there.
+///
+/// With a GitHub `token`, each snippet is uploaded as a gist and the link
+/// names the gist; without one, the link carries the code.
+pub fn run_playground<W: Write>(id: &str, token: Option<&str>, out: &mut W) -> io::Result<()> {
+    for snippet in catalog::find(id) {
+        let url = match token {
+            Some(token) => {
+                let description = format!("{} ({})", snippet.title, snippet.id);
+                playground::gist_url(&playground::upload_gist(
+                    &snippet.code,
+                    &description,
+                    token,
+                )?)
+            }
+            None => snippet.playground_url(),
+        };
+        writeln!(out, "{:<18} {}", snippet.id, url)?;
+        if snippet.uses_crate() {
+            writeln!(
+                out,
+                "{:<18} (uses this crate, which the playground doesn't have)",
+                ""
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(parse(&[]), Ok(Command::Demos));
+        assert_eq!(
+            parse(&["tour"]),
+            Ok(Command::Tour {
+                lesson: None,
+                lang: Locale::En
+            })
+        );
+
+        let reborrow = find_lesson("reborrow");
+        assert!(reborrow.is_some());
+        assert_eq!(
+            parse(&["tour", "--lesson", "reborrow"]),
+            Ok(Command::Tour {
+                lesson: reborrow,
+                lang: Locale::En
+            })
+        );
+        assert_eq!(
+            parse(&["tour", "--lesson=reborrow"]),
+            Ok(Command::Tour {
+                lesson: reborrow,
+                lang: Locale::En
+            })
+        );
+    }
+
+    #[test]
+    fn parses_tour_language() {
+        assert_eq!(
+            parse(&["tour", "--lang", "es", "--lesson=dangling"]),
+            Ok(Command::Tour {
+                lesson: find_lesson("dangling"),
+                lang: Locale::Es
+            })
+        );
+        assert_eq!(
+            parse(&["tour", "--lang=fr"]).unwrap_err(),
+            "no language `fr`; the languages are en, es"
+        );
+        assert_eq!(
+            parse(&["quiz", "--lang", "es"]).unwrap_err(),
+            "unexpected argument `--lang`"
+        );
+    }
+
+    #[test]
+    fn parses_quiz_options() {
+        assert_eq!(
+            parse(&["quiz"]),
+            Ok(Command::Quiz {
+                lesson: None,
+                seed: None
+            })
+        );
+        assert_eq!(
+            parse(&["quiz", "--seed", "42", "--lesson=cell"]),
+            Ok(Command::Quiz {
+                lesson: find_lesson("cell"),
+                seed: Some(42)
+            })
+        );
+        assert!(parse(&["quiz", "--seed", "x"]).is_err());
+        assert_eq!(
+            parse(&["tour", "--seed", "1"]).unwrap_err(),
+            "unexpected argument `--seed`"
+        );
+    }
+
+    #[test]
+    fn parses_path() {
+        assert_eq!(parse(&["path"]), Ok(Command::Path { to: None }));
+        assert_eq!(
+            parse(&["path", "--to", "sync"]),
+            Ok(Command::Path {
+                to: find_lesson("sync")
+            })
+        );
+        assert!(parse(&["path", "--to=nope"])
+            .unwrap_err()
+            .starts_with("no lesson `nope`; the lessons are dangling, "));
+        assert!(parse(&["path", "sync"]).is_err());
+    }
+
+    #[test]
+    fn parses_workshop() {
+        assert_eq!(
+            parse(&["workshop"]),
+            Ok(Command::Workshop {
+                lessons: Vec::new(),
+                minutes: 3,
+                answer_key: false
+            })
+        );
+        assert_eq!(
+            parse(&[
+                "workshop",
+                "--lesson",
+                "cell",
+                "--lesson=dangling",
+                "--minutes=5",
+                "--answer-key"
+            ]),
+            Ok(Command::Workshop {
+                lessons: vec![
+                    find_lesson("cell").unwrap(),
+                    find_lesson("dangling").unwrap()
+                ],
+                minutes: 5,
+                answer_key: true
+            })
+        );
+        assert_eq!(
+            parse(&["workshop", "--minutes", "0"]),
+            Err("`--minutes` needs a whole number above 0, not `0`".to_owned())
+        );
+        assert!(parse(&["workshop", "--seed", "1"]).is_err());
+    }
+
+    #[test]
+    fn parses_fix() {
+        assert_eq!(
+            parse(&["fix", "nll"]),
+            Ok(Command::Fix {
+                lesson: find_lesson("nll").unwrap()
+            })
+        );
+        assert!(parse(&["fix"]).is_err());
+        assert!(parse(&["fix", "nope"]).is_err());
+        assert_eq!(
+            parse(&["fix", "nll", "cell"]).unwrap_err(),
+            "unexpected argument `cell`"
+        );
+    }
+
+    #[test]
+    fn parses_explain() {
+        assert_eq!(
+            parse(&["explain", "e0502"]),
+            Ok(Command::Explain {
+                code: errors::find("E0502").unwrap()
+            })
+        );
+        assert!(parse(&["explain"]).is_err());
+        assert!(parse(&["explain", "E0000"])
+            .unwrap_err()
+            .starts_with("no lesson gets `E0000`; the codes they get are E0382, "));
+    }
+
+    #[test]
+    fn parses_progress() {
+        let show = Ok(Command::Progress { reset: false });
+        assert_eq!(parse(&["progress"]), show);
+        assert_eq!(parse(&["progress", "show"]), show);
+        assert_eq!(
+            parse(&["progress", "--reset"]),
+            Ok(Command::Progress { reset: true })
+        );
+        assert_eq!(
+            parse(&["progress", "show", "--reset"]).unwrap_err(),
+            "unexpected argument `--reset`"
+        );
+    }
+
+    #[test]
+    fn parses_export() {
+        assert_eq!(
+            parse(&["export"]),
+            Ok(Command::Export {
+                format: Format::Markdown,
+                out: PathBuf::from("book")
+            })
+        );
+        assert_eq!(
+            parse(&["export", "--format", "md", "--out=site/src"]),
+            Ok(Command::Export {
+                format: Format::Markdown,
+                out: PathBuf::from("site/src")
+            })
+        );
+        assert_eq!(
+            parse(&["export", "--format", "pdf"]).unwrap_err(),
+            "no format `pdf`; the formats are md, anki"
+        );
+        assert_eq!(
+            parse(&[
+                "export",
+                "--deck-tag",
+                "E0502",
+                "--format=anki",
+                "--deck-tag=quiz"
+            ]),
+            Ok(Command::Export {
+                format: Format::Anki {
+                    decks: Decks::ByTag(vec!["E0502".to_owned(), "quiz".to_owned()])
+                },
+                out: PathBuf::from("book")
+            })
+        );
+        assert_eq!(
+            parse(&["export", "--deck-tag", "quiz"]).unwrap_err(),
+            "`--deck-tag` only goes with `--format anki`"
+        );
+    }
+
+    #[test]
+    fn parses_perf() {
+        assert_eq!(parse(&["perf", "report"]), Ok(Command::Perf { rounds: 20 }));
+        assert_eq!(
+            parse(&["perf", "report", "--rounds=3"]),
+            Ok(Command::Perf { rounds: 3 })
+        );
+        assert_eq!(
+            parse(&["perf", "report", "--rounds", "none"]),
+            Err("`--rounds` needs a whole number above 0, not `none`".to_owned())
+        );
+        assert!(parse(&["perf"]).is_err());
+        assert!(parse(&["perf", "report", "extra"]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn parses_render() {
+        assert_eq!(
+            parse(&["render", "--all"]),
+            Ok(Command::Render {
+                name: None,
+                out: PathBuf::from("tests/render/snapshots")
+            })
+        );
+        assert_eq!(
+            parse(&["render", "--all", "--out=snaps"]),
+            Ok(Command::Render {
+                name: None,
+                out: PathBuf::from("snaps")
+            })
+        );
+        assert_eq!(
+            parse(&["render", "export/SUMMARY.md"]),
+            Ok(Command::Render {
+                name: Some("export/SUMMARY.md".to_owned()),
+                out: PathBuf::new()
+            })
+        );
+        assert_eq!(
+            parse(&["render", "export/nope.md"]),
+            Err("no rendering `export/nope.md`".to_owned())
+        );
+        assert!(parse(&["render"]).is_err());
+        assert!(parse(&["render", "export/SUMMARY.md", "--all"]).is_err());
+        assert!(parse(&["render", "export/SUMMARY.md", "--out", "x"]).is_err());
+    }
+
+    #[test]
+    fn parses_search() {
+        assert_eq!(
+            parse(&["search", "read-only", "moved"]),
+            Ok(Command::Search {
+                query: "read-only moved".to_owned()
+            })
+        );
+        assert!(parse(&["search"]).is_err());
+    }
+
+    #[test]
+    fn parses_playground() {
+        assert_eq!(
+            parse(&["playground", "dangling"]),
+            Ok(Command::Playground {
+                id: "dangling".to_owned(),
+                gist: false
+            })
+        );
+        assert_eq!(
+            parse(&["playground", "--gist", "exclusive/2"]),
+            Ok(Command::Playground {
+                id: "exclusive/2".to_owned(),
+                gist: true
+            })
+        );
+        assert!(parse(&["playground"]).is_err());
+        assert!(parse(&["playground", "dangling", "cell"]).is_err());
+        assert!(parse(&["playground", "dangling", "--fast"]).is_err());
+        assert!(parse(&["playground", "nowhere"])
+            .unwrap_err()
+            .starts_with("no lesson or snippet `nowhere`; the lessons are dangling, "));
+    }
+
+    #[cfg(feature = "diagnostics")]
+    #[test]
+    fn parses_diagnostics() {
+        assert_eq!(
+            parse(&["diagnostics", "report"]),
+            Ok(Command::Diagnostics { rustcs: Vec::new() })
+        );
+        assert_eq!(
+            parse(&["diagnostics", "report", "--rustc", "a", "--rustc=b"]),
+            Ok(Command::Diagnostics {
+                rustcs: vec![PathBuf::from("a"), PathBuf::from("b")]
+            })
+        );
+        assert!(parse(&["diagnostics"]).is_err());
+        assert!(parse(&["diagnostics", "report", "--fast"]).is_err());
+    }
+
+    #[test]
+    fn searches_the_catalog() {
+        let mut out = Vec::new();
+        run_search("read-only moved", &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("dangling/1         Moving a value out from under a reference  [dangling, sharing, rejected, E0505]\n"));
+
+        let mut out = Vec::new();
+        run_search("zebra", &mut out).unwrap();
+        assert_eq!(out, b"nothing mentions `zebra`\n");
+    }
+
+    #[test]
+    fn links_to_the_playground() {
+        let mut out = Vec::new();
+        run_playground("dangling/1", None, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("dangling/1         https://play.rust-lang.org/?"));
+        assert_eq!(out.lines().count(), 1);
+
+        let mut out = Vec::new();
+        run_playground("exclusive", None, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains(
+            "\n                   (uses this crate, which the playground doesn't have)\n"
+        ));
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert_eq!(parse(&["exam"]).unwrap_err(), "unknown command `exam`");
+        assert_eq!(
+            parse(&["tour", "--fast"]).unwrap_err(),
+            "unexpected argument `--fast`"
+        );
+        assert!(parse(&["tour", "--lesson"]).is_err());
+
+        let err = parse(&["tour", "--lesson", "nope"]).unwrap_err();
+        assert!(err.starts_with("no lesson `nope`; the lessons are dangling, "));
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt;
+use std::io::{self, Write};
+
+use super::lesson::{Lesson, LESSONS};
+
+/// Lessons that each require the next, the last requiring the first.
+#[derive(Debug, PartialEq)]
+pub struct Cycle(pub Vec<&'static str>);
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "lessons require each other: {}", self.0.join(" -> "))?;
+        match self.0.first() {
+            Some(first) => write!(f, " -> {}", first),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mark {
+    Unvisited,
+    Visiting,
+    Done,
+}
+
+/// `targets` and everything they require, each lesson after what it
+/// requires and otherwise in the order of `lessons`.
+///
+/// Requirements are looked up by name in `lessons`; a name that isn't
+/// there is skipped, and a test checks `LESSONS` has none.
+pub fn study_order<'a>(
+    lessons: &'a [Lesson],
+    targets: &[&Lesson],
+) -> Result<Vec<&'a Lesson>, Cycle> {
+    let mut marks = vec![Mark::Unvisited; lessons.len()];
+    let mut order = Vec::new();
+    let mut stack = Vec::new();
+    for target in targets {
+        if let Some(i) = lessons.iter().position(|l| l == *target) {
+            visit(lessons, i, &mut marks, &mut stack, &mut order)?;
+        }
+    }
+    Ok(order)
+}
+
+/// Every lesson in `lessons`, each after what it requires.
+pub fn order_all(lessons: &[Lesson]) -> Result<Vec<&Lesson>, Cycle> {
+    let all: Vec<&Lesson> = lessons.iter().collect();
+    study_order(lessons, &all)
+}
+
+/// Depth-first from `lessons[i]`, pushing each lesson onto `order` once
+/// its requirements are there. `stack` holds the lessons being visited,
+/// which is the cycle when one of them turns up again.
+fn visit<'a>(
+    lessons: &'a [Lesson],
+    i: usize,
+    marks: &mut [Mark],
+    stack: &mut Vec<usize>,
+    order: &mut Vec<&'a Lesson>,
+) -> Result<(), Cycle> {
+    match marks[i] {
+        Mark::Done => return Ok(()),
+        Mark::Visiting => {
+            let start = stack.iter().position(|&s| s == i).unwrap_or(0);
+            return Err(Cycle(
+                stack[start..].iter().map(|&s| lessons[s].name).collect(),
+            ));
+        }
+        Mark::Unvisited => {}
+    }
+    marks[i] = Mark::Visiting;
+    stack.push(i);
+    for name in lessons[i].requires {
+        if let Some(j) = lessons.iter().position(|l| l.name == *name) {
+            visit(lessons, j, marks, stack, order)?;
+        }
+    }
+    stack.pop();
+    marks[i] = Mark::Done;
+    order.push(&lessons[i]);
+    Ok(())
+}
+
+/// Prints the lessons to study, numbered, ending at `to`, or every lesson
+/// without it.
+pub fn run_path<W: Write>(to: Option<&Lesson>, out: &mut W) -> io::Result<()> {
+    let order = match to {
+        Some(lesson) => study_order(LESSONS, &[lesson]),
+        None => order_all(LESSONS),
+    }
+    .map_err(|cycle| io::Error::other(cycle.to_string()))?;
+    for (n, lesson) in order.iter().enumerate() {
+        writeln!(out, "{:>2}. {:<16} {}", n + 1, lesson.name, lesson.title)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::find_lesson;
+
+    fn lesson(name: &'static str, requires: &'static [&'static str]) -> Lesson {
+        Lesson {
+            name,
+            requires,
+            ..*find_lesson("dangling").unwrap()
+        }
+    }
+
+    fn names(lessons: &[&Lesson]) -> Vec<&'static str> {
+        lessons.iter().map(|l| l.name).collect()
+    }
+
+    #[test]
+    fn requirements_name_lessons() {
+        for lesson in LESSONS {
+            for name in lesson.requires {
+                assert!(
+                    find_lesson(name).is_some(),
+                    "{} requires `{}`",
+                    lesson.name,
+                    name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn the_tour_already_takes_requirements_first() {
+        let order = order_all(LESSONS).unwrap();
+        assert_eq!(names(&order), names(&LESSONS.iter().collect::<Vec<_>>()));
+    }
+
+    #[test]
+    fn orders_only_what_the_target_needs() {
+        let lessons = [
+            lesson("a", &[]),
+            lesson("b", &["a"]),
+            lesson("c", &[]),
+            lesson("d", &["c", "b"]),
+            lesson("e", &["a"]),
+        ];
+        let order = study_order(&lessons, &[&lessons[3]]).unwrap();
+        assert_eq!(names(&order), ["c", "a", "b", "d"]);
+        let order = study_order(&lessons, &[&lessons[4], &lessons[1]]).unwrap();
+        assert_eq!(names(&order), ["a", "e", "b"]);
+    }
+
+    #[test]
+    fn finds_cycles() {
+        let lessons = [
+            lesson("a", &["c"]),
+            lesson("b", &["a"]),
+            lesson("c", &["b"]),
+            lesson("d", &[]),
+        ];
+        let cycle = order_all(&lessons).unwrap_err();
+        assert_eq!(cycle, Cycle(vec!["a", "c", "b"]));
+        assert_eq!(
+            cycle.to_string(),
+            "lessons require each other: a -> c -> b -> a"
+        );
+        assert_eq!(
+            names(&study_order(&lessons, &[&lessons[3]]).unwrap()),
+            ["d"]
+        );
+    }
+
+    #[test]
+    fn prints_the_path_to_a_lesson() {
+        let mut out = Vec::new();
+        run_path(find_lesson("sync"), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let names: Vec<&str> = out
+            .lines()
+            .map(|l| l.split_whitespace().nth(1).unwrap())
+            .collect();
+        assert_eq!(names, ["dangling", "exclusive", "cell", "refcell", "sync"]);
+        assert!(out.starts_with(" 1. dangling         Moving a value out from under a reference\n"));
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+
+use super::tour::ask_compiles;
+use super::workshop::letter;
+use super::{finish, Score};
+use crate::exercises::{Answer, Exercise, Question, Rng};
+use crate::progress::Progress;
+
+/// Asks each of `exercises` in an order `rng` picks, with multiple choices
+/// shuffled too, reading answers from `input`.
+///
+/// After each answer it says whether it was right, gives the right answer
+/// if not, and explains, then saves the score so far for the exercise's
+/// lesson in `progress`. Like `tour` it stops early at end of input,
+/// keeping the score so far.
+pub fn quiz<R, W>(
+    exercises: &[&Exercise],
+    rng: &mut Rng,
+    progress: &mut Progress,
+    input: &mut R,
+    out: &mut W,
+) -> io::Result<Score>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut order = exercises.to_vec();
+    rng.shuffle(&mut order);
+
+    let mut score = Score::default();
+    let mut by_lesson: BTreeMap<&str, Score> = BTreeMap::new();
+    for (n, exercise) in order.iter().enumerate() {
+        writeln!(
+            out,
+            "\n== Question {} of {} ({})",
+            n + 1,
+            order.len(),
+            exercise.lesson
+        )?;
+        let (answer, right_answer) = match exercise.question {
+            Question::Compiles { code, answer } => {
+                writeln!(out)?;
+                for line in code.lines() {
+                    writeln!(out, "    {}", line)?;
+                }
+                let given = ask_compiles(input, out)?.map(Answer::Compiles);
+                let right = if answer {
+                    "it compiles"
+                } else {
+                    "it doesn't compile"
+                };
+                (given, right.to_owned())
+            }
+            Question::Choice {
+                prompt,
+                choices,
+                answer,
+            } => {
+                writeln!(out, "\n{}", prompt)?;
+                let mut shown: Vec<usize> = (0..choices.len()).collect();
+                rng.shuffle(&mut shown);
+                for (slot, &choice) in shown.iter().enumerate() {
+                    writeln!(out, "  {}) {}", letter(slot), choices[choice])?;
+                }
+                let given =
+                    ask_choice(shown.len(), input, out)?.map(|slot| Answer::Choice(shown[slot]));
+                let slot = shown.iter().position(|&c| c == answer).unwrap();
+                (given, format!("{}) {}", letter(slot), choices[answer]))
+            }
+        };
+        let answer = match answer {
+            Some(answer) => answer,
+            None => return finish(score, out),
+        };
+        let lesson_score = by_lesson.entry(exercise.lesson).or_default();
+        score.asked += 1;
+        lesson_score.asked += 1;
+        if exercise.is_correct(answer) {
+            score.right += 1;
+            lesson_score.right += 1;
+            writeln!(out, "Right.")?;
+        } else {
+            writeln!(out, "Not quite: {}.", right_answer)?;
+        }
+        writeln!(out, "{}", exercise.explanation)?;
+        progress.record_quiz(exercise.lesson, *lesson_score);
+        progress.save()?;
+    }
+    finish(score, out)
+}
+
+/// Asks for a letter naming one of `count` choices until it gets one, or
+/// `None` at end of input.
+fn ask_choice<R: BufRead, W: Write>(
+    count: usize,
+    input: &mut R,
+    out: &mut W,
+) -> io::Result<Option<usize>> {
+    let last = letter(count - 1);
+    let mut line = String::new();
+    loop {
+        write!(out, "Your answer [a-{}]: ", last)?;
+        out.flush()?;
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            writeln!(out)?;
+            return Ok(None);
+        }
+        let trimmed = line.trim().to_ascii_lowercase();
+        let mut chars = trimmed.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if ('a'..=last).contains(&c) => {
+                return Ok(Some((c as u8 - b'a') as usize))
+            }
+            _ => writeln!(out, "Please answer with a letter from a to {}.", last)?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::exercises::for_lesson;
+
+    fn run(exercises: &[&Exercise], seed: u64, answers: &str) -> (Score, String) {
+        let mut out = Vec::new();
+        let mut rng = Rng::new(seed);
+        let mut progress = Progress::default();
+        let input = &mut Cursor::new(answers);
+        let score = quiz(exercises, &mut rng, &mut progress, input, &mut out).unwrap();
+        (score, String::from_utf8(out).unwrap())
+    }
+
+    /// The letter the right choice ended up under in `out`.
+    fn right_letter(out: &str, right: &str) -> char {
+        let line = out.lines().find(|l| l.ends_with(right)).unwrap();
+        line.trim_start().chars().next().unwrap()
+    }
+
+    #[test]
+    fn marks_shuffled_choices_by_content() {
+        let exercise = for_lesson("self_assignment").nth(1).unwrap();
+        let right = "It closes the descriptor it's about to `dup`";
+        for seed in 0..8 {
+            let (_, shown) = run(&[exercise], seed, "");
+            let answer = format!("z\n{}\n", right_letter(&shown, right));
+            let (score, out) = run(&[exercise], seed, &answer);
+            assert_eq!(score, Score { right: 1, asked: 1 }, "seed {}", seed);
+            assert!(out.contains("Please answer with a letter from a to d."));
+            assert!(out.contains(exercise.explanation));
+        }
+    }
+
+    #[test]
+    fn wrong_answers_show_the_right_one() {
+        let exercises: Vec<&Exercise> = for_lesson("exclusive").take(1).collect();
+        let (score, out) = run(&exercises, 1, "y\n");
+        assert_eq!(score, Score { right: 0, asked: 1 });
+        assert!(out.contains("Not quite: it doesn't compile."));
+        assert!(out.ends_with("You got 0 of 1 right.\n"));
+    }
+
+    #[test]
+    fn records_scores_per_lesson() {
+        let exercises: Vec<&Exercise> = for_lesson("exclusive").take(1).collect();
+        let mut progress = Progress::default();
+        let input = &mut Cursor::new("n\n");
+        quiz(
+            &exercises,
+            &mut Rng::new(1),
+            &mut progress,
+            input,
+            &mut Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            progress.quiz_score("exclusive"),
+            Some(Score { right: 1, asked: 1 })
+        );
+        assert_eq!(progress.quiz_score("cell"), None);
+    }
+
+    #[test]
+    fn stops_when_input_runs_out() {
+        let exercises: Vec<&Exercise> = for_lesson("cell").collect();
+        let (score, out) = run(&exercises, 3, "");
+        assert_eq!(score, Score::default());
+        assert!(out.contains("Question 1 of 2"));
+        assert!(!out.contains("Question 2 of 2"));
+    }
+}
@@ -0,0 +1,256 @@
+use std::io::{self, BufRead, Write};
+
+use super::{finish, Lesson, Outcome, Score};
+use crate::explain::Locale;
+use crate::progress::Progress;
+use crate::timeline::Timeline;
+
+/// Takes the learner through `lessons` in order, reading answers from
+/// `input` and writing everything else to `out`.
+///
+/// For each example it shows the code, asks whether it compiles, then
+/// either runs it or shows the error rustc gives, followed by a timeline of
+/// its borrows if it has one. The lesson's diagrams and explanation follow
+/// its last example, in `locale` where it's been translated, and the
+/// lesson is marked complete in `progress`, which is saved. The tour ends
+/// early, keeping the score so far, if `input` runs out.
+pub fn tour<R, W>(
+    lessons: &[Lesson],
+    locale: Locale,
+    progress: &mut Progress,
+    input: &mut R,
+    out: &mut W,
+) -> io::Result<Score>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut score = Score::default();
+    for (n, lesson) in lessons.iter().enumerate() {
+        writeln!(
+            out,
+            "\n== Lesson {} of {}: {}",
+            n + 1,
+            lessons.len(),
+            lesson.title
+        )?;
+        for example in lesson.examples {
+            writeln!(out)?;
+            for (number, line) in example.numbered_lines() {
+                writeln!(
+                    out,
+                    "{:2} |{}{}",
+                    number,
+                    if line.is_empty() { "" } else { " " },
+                    line
+                )?;
+            }
+            let answer = match ask_compiles(input, out)? {
+                Some(answer) => answer,
+                None => return finish(score, out),
+            };
+            score.asked += 1;
+            if answer == example.compiles() {
+                score.right += 1;
+                writeln!(out, "Right.")?;
+            } else {
+                writeln!(out, "Not quite.")?;
+            }
+            match example.outcome {
+                Outcome::Compiles => {
+                    writeln!(out, "It compiles. The lesson's demo runs it:")?;
+                    // The demo prints to stdout itself.
+                    out.flush()?;
+                    (lesson.demo)();
+                }
+                Outcome::Panics(message) => {
+                    writeln!(out, "It compiles, but panics when it runs:\n\n{}", message)?;
+                }
+                Outcome::Rejected(stderr) => {
+                    writeln!(out, "rustc rejects it:\n\n{}", stderr.trim_end())?;
+                }
+            }
+            if !example.events.is_empty() {
+                write!(out, "\n{}", Timeline::new(example.events))?;
+            }
+        }
+        for diagram in (lesson.diagrams)() {
+            write!(out, "\n{}", diagram)?;
+        }
+        if let Some(aftermath) = lesson.aftermath {
+            writeln!(out, "\n{}\n", aftermath())?;
+        }
+        write!(out, "\n{}", lesson.explanation(locale).to_text())?;
+        progress.complete(lesson.name);
+        progress.save()?;
+    }
+    finish(score, out)
+}
+
+/// Asks until it gets a yes or a no, or `None` at end of input.
+pub(super) fn ask_compiles<R: BufRead, W: Write>(
+    input: &mut R,
+    out: &mut W,
+) -> io::Result<Option<bool>> {
+    let mut line = String::new();
+    loop {
+        write!(out, "Does this compile? [y/n] ")?;
+        out.flush()?;
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            writeln!(out)?;
+            return Ok(None);
+        }
+        match line.trim().to_ascii_lowercase().as_str() {
+            "y" | "yes" => return Ok(Some(true)),
+            "n" | "no" => return Ok(Some(false)),
+            _ => writeln!(out, "Please answer y or n.")?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::slice;
+
+    use super::*;
+    use crate::cli::{find_lesson, LESSONS};
+
+    fn run(lessons: &[Lesson], answers: &str) -> (Score, String) {
+        let mut out = Vec::new();
+        let mut progress = Progress::default();
+        let score = tour(
+            lessons,
+            Locale::En,
+            &mut progress,
+            &mut Cursor::new(answers),
+            &mut out,
+        )
+        .unwrap();
+        (score, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn every_lesson_has_a_rejected_and_a_compiling_example() {
+        for lesson in LESSONS {
+            assert!(
+                lesson.examples.iter().any(|e| !e.compiles()),
+                "{}",
+                lesson.name
+            );
+            assert!(
+                lesson.examples.iter().any(|e| e.compiles()),
+                "{}",
+                lesson.name
+            );
+        }
+    }
+
+    #[test]
+    fn timelines_agree_with_rustc() {
+        for lesson in LESSONS {
+            for example in lesson.examples {
+                let found: Vec<&str> = Timeline::new(example.events)
+                    .violations()
+                    .iter()
+                    .map(|v| v.code)
+                    .collect();
+                let expected: Vec<&str> = match example.outcome {
+                    Outcome::Rejected(stderr) => crate::catalog::error_codes(stderr).collect(),
+                    _ => Vec::new(),
+                };
+                if !example.events.is_empty() {
+                    assert_eq!(found, expected, "{}", lesson.name);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "diagnostics")]
+    #[test]
+    fn panicking_examples_panic_as_documented() {
+        let rustc = crate::diagnostics::Rustc::new("rustc");
+        for lesson in LESSONS {
+            for example in lesson.examples {
+                if let Outcome::Panics(message) = example.outcome {
+                    let output = rustc.run(example.source).unwrap();
+                    assert!(!output.status.success(), "{}", lesson.name);
+                    // The message is the line after `thread 'main' panicked
+                    // at <location>:`.
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    let said = stderr
+                        .lines()
+                        .skip_while(|line| !line.contains("panicked at"))
+                        .nth(1);
+                    assert_eq!(said, Some(message), "{}: {}", lesson.name, stderr);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn scores_one_lesson() {
+        let dangling = find_lesson("dangling").unwrap();
+        let (score, out) = run(slice::from_ref(dangling), "maybe\nn\nn\n");
+
+        assert_eq!(score, Score { right: 1, asked: 2 });
+        assert!(out.contains("Please answer y or n."));
+        assert!(out.contains("error[E0505]: cannot move out of `v` because it is borrowed"));
+        assert!(out.contains("Not quite.\nIt compiles."));
+        assert!(out.contains(&dangling.explain().to_text()));
+        assert!(out.contains("makes its referent read-only for its whole lifetime"));
+        assert!(out.ends_with("You got 1 of 2 right.\n"));
+        // The file's header comment isn't shown, but line numbers still
+        // match rustc's.
+        assert!(!out.contains("// sharing::dangling"));
+        assert!(out.contains(" 6 |     let aside = v;"));
+        assert!(out.contains("r = &v      &---#---*\n"));
+    }
+
+    #[test]
+    fn shows_the_reallocation_a_rejected_extend_would_miss() {
+        let exclusive = find_lesson("exclusive").unwrap();
+        let (_, out) = run(slice::from_ref(exclusive), "n\nn\ny\n");
+        let line = out
+            .lines()
+            .find(|line| line.contains("buffer moved from 0x"))
+            .expect("no reallocation shown");
+        assert!(line.contains(" to 0x"), "{}", line);
+        assert!(line.ends_with("(capacity 4 -> 8)"), "{}", line);
+    }
+
+    #[test]
+    fn panicking_examples_count_as_compiling() {
+        let refcell = find_lesson("refcell").unwrap();
+        let (score, out) = run(slice::from_ref(refcell), "n\ny\ny\n");
+        assert_eq!(score, Score { right: 3, asked: 3 });
+        assert!(out.contains("It compiles, but panics when it runs:\n\nRefCell already borrowed"));
+    }
+
+    #[test]
+    fn marks_finished_lessons_complete() {
+        let mut progress = Progress::default();
+        let mut out = Vec::new();
+        let answers = "n\nn\ny\n";
+        tour(
+            &LESSONS[..2],
+            Locale::En,
+            &mut progress,
+            &mut Cursor::new(answers),
+            &mut out,
+        )
+        .unwrap();
+        assert!(progress.is_complete("dangling"));
+        assert!(!progress.is_complete("reborrow"));
+        assert_eq!(progress.resume_at(), Some(1));
+    }
+
+    #[test]
+    fn stops_when_input_runs_out() {
+        let (score, out) = run(LESSONS, "y\n");
+        assert_eq!(score, Score { right: 0, asked: 1 });
+        assert!(out.contains(&format!("Lesson 1 of {}", LESSONS.len())));
+        assert!(!out.contains(&format!("Lesson 2 of {}", LESSONS.len())));
+    }
+}
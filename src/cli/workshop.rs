@@ -0,0 +1,273 @@
+use std::fmt::Write as _;
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+
+use super::{finish, Score};
+use crate::exercises::{Answer, Exercise, Question};
+
+/// Asks each of `exercises` in the order given, allowing `limit` for each
+/// and reading answers from `input`.
+///
+/// Everyone in a room sees the same questions in the same order, with the
+/// choices as listed, so the letters match the [`answer_key`]. Answering
+/// `?` instead shows the next of the exercise's hints. `clock` gives the
+/// time since some fixed start; an answer that comes more than `limit`
+/// after its question is marked but doesn't score. Like `quiz` it stops
+/// early at end of input, keeping the score so far. It doesn't record
+/// progress, as a classroom's machines may not be the learners' own.
+pub fn workshop<R, W, C>(
+    exercises: &[&Exercise],
+    limit: Duration,
+    mut clock: C,
+    input: &mut R,
+    out: &mut W,
+) -> io::Result<Score>
+where
+    R: BufRead,
+    W: Write,
+    C: FnMut() -> Duration,
+{
+    let mut score = Score::default();
+    let mut hints_used = 0;
+    for (n, exercise) in exercises.iter().enumerate() {
+        writeln!(
+            out,
+            "\n== Question {} of {} ({}, {} to answer)",
+            n + 1,
+            exercises.len(),
+            exercise.lesson,
+            clock_time(limit)
+        )?;
+        write!(out, "{}", question_text(exercise))?;
+        let asked_at = clock();
+        let (answer, hints) = match ask(exercise, input, out)? {
+            Some(answered) => answered,
+            None => break,
+        };
+        let took = clock().saturating_sub(asked_at);
+        hints_used += hints;
+        score.asked += 1;
+        let on_time = took <= limit;
+        match (exercise.is_correct(answer), on_time) {
+            (true, true) => {
+                score.right += 1;
+                writeln!(out, "Right, in {}.", clock_time(took))?;
+            }
+            (true, false) => writeln!(
+                out,
+                "Right, but after {} of the {} allowed.",
+                clock_time(took),
+                clock_time(limit)
+            )?,
+            (false, _) => writeln!(out, "Not quite: {}.", right_answer(exercise))?,
+        }
+        writeln!(out, "{}", exercise.explanation)?;
+    }
+    writeln!(out, "\nHints used: {}.", hints_used)?;
+    finish(score, out)
+}
+
+/// The letter for the choice in `slot`, from `a`.
+pub(super) fn letter(slot: usize) -> char {
+    (b'a' + slot as u8) as char
+}
+
+/// `duration` as minutes and seconds, `3:05`.
+fn clock_time(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// The question as the learner sees it: the program indented, or the
+/// prompt and its choices lettered in order.
+fn question_text(exercise: &Exercise) -> String {
+    let mut text = String::new();
+    match exercise.question {
+        Question::Compiles { code, .. } => {
+            text.push('\n');
+            for line in code.lines() {
+                let _ = writeln!(text, "    {}", line);
+            }
+        }
+        Question::Choice {
+            prompt, choices, ..
+        } => {
+            let _ = writeln!(text, "\n{}", prompt);
+            for (slot, choice) in choices.iter().enumerate() {
+                let _ = writeln!(text, "  {}) {}", letter(slot), choice);
+            }
+        }
+    }
+    text
+}
+
+fn right_answer(exercise: &Exercise) -> String {
+    match exercise.question {
+        Question::Compiles { answer: true, .. } => "it compiles".to_owned(),
+        Question::Compiles { answer: false, .. } => "it doesn't compile".to_owned(),
+        Question::Choice {
+            choices, answer, ..
+        } => format!("{}) {}", letter(answer), choices[answer]),
+    }
+}
+
+/// Reads until it gets an answer to `exercise`, showing a hint for each
+/// `?`, and returns the answer and how many hints it took, or `None` at
+/// end of input.
+fn ask<R: BufRead, W: Write>(
+    exercise: &Exercise,
+    input: &mut R,
+    out: &mut W,
+) -> io::Result<Option<(Answer, usize)>> {
+    let (prompt, expected) = match exercise.question {
+        Question::Compiles { .. } => ("Does this compile? [y/n".to_owned(), "y or n".to_owned()),
+        Question::Choice { choices, .. } => {
+            let last = letter(choices.len() - 1);
+            (
+                format!("Your answer [a-{}", last),
+                format!("a letter from a to {}", last),
+            )
+        }
+    };
+    let mut hints = 0;
+    let mut line = String::new();
+    loop {
+        write!(out, "{}, ? for a hint]: ", prompt)?;
+        out.flush()?;
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            writeln!(out)?;
+            return Ok(None);
+        }
+        let given = line.trim().to_ascii_lowercase();
+        if given == "?" {
+            match exercise.hints.get(hints) {
+                Some(hint) => {
+                    hints += 1;
+                    writeln!(out, "Hint {} of {}: {}", hints, exercise.hints.len(), hint)?;
+                }
+                None => writeln!(out, "That's all the hints.")?,
+            }
+            continue;
+        }
+        let answer = match (&exercise.question, given.as_str()) {
+            (Question::Compiles { .. }, "y") | (Question::Compiles { .. }, "yes") => {
+                Some(Answer::Compiles(true))
+            }
+            (Question::Compiles { .. }, "n") | (Question::Compiles { .. }, "no") => {
+                Some(Answer::Compiles(false))
+            }
+            (Question::Choice { choices, .. }, given) => {
+                let mut chars = given.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if ('a'..letter(choices.len())).contains(&c) => {
+                        Some(Answer::Choice((c as u8 - b'a') as usize))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        match answer {
+            Some(answer) => return Ok(Some((answer, hints))),
+            None => writeln!(out, "Please answer with {}, or ? for a hint.", expected)?,
+        }
+    }
+}
+
+/// The instructor's key to `exercises`: each question as the workshop
+/// shows it, with its answer, the explanation, its hints, and the wrong
+/// answer to listen for.
+pub fn answer_key(exercises: &[&Exercise]) -> String {
+    let mut key = String::new();
+    for (n, exercise) in exercises.iter().enumerate() {
+        let _ = writeln!(key, "== Question {} ({})", n + 1, exercise.lesson);
+        key.push_str(&question_text(exercise));
+        let _ = writeln!(key, "\nAnswer: {}.", right_answer(exercise));
+        let _ = writeln!(key, "Why: {}", exercise.explanation);
+        let _ = writeln!(key, "Often answered: {}", exercise.pitfall);
+        for (i, hint) in exercise.hints.iter().enumerate() {
+            let _ = writeln!(key, "Hint {}: {}", i + 1, hint);
+        }
+        key.push('\n');
+    }
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::exercises::for_lesson;
+
+    /// Runs a workshop in which each question takes `took` to answer.
+    fn run(exercises: &[&Exercise], took: Duration, answers: &str) -> (Score, String) {
+        let mut out = Vec::new();
+        let now = Cell::new(Duration::ZERO);
+        let clock = || {
+            let t = now.get();
+            now.set(t + took);
+            t
+        };
+        let input = &mut Cursor::new(answers);
+        let score = workshop(exercises, Duration::from_secs(180), clock, input, &mut out).unwrap();
+        (score, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn gives_hints_one_at_a_time() {
+        let exercise = for_lesson("exclusive").next().unwrap();
+        let (score, out) = run(
+            &[exercise],
+            Duration::from_secs(30),
+            "?\n?\n?\n?\nmaybe\nn\n",
+        );
+        assert_eq!(score, Score { right: 1, asked: 1 });
+        assert!(out.contains("(exclusive, 3:00 to answer)"));
+        assert!(out.contains(&format!("Hint 1 of 3: {}\n", exercise.hints[0])));
+        assert!(out.contains(&format!("Hint 3 of 3: {}\n", exercise.hints[2])));
+        assert!(out.contains("That's all the hints.\n"));
+        assert!(out.contains("Please answer with y or n, or ? for a hint.\n"));
+        assert!(out.contains("Right, in 0:30.\n"));
+        assert!(out.ends_with("Hints used: 3.\n\nYou got 1 of 1 right.\n"));
+    }
+
+    #[test]
+    fn late_answers_dont_score() {
+        let exercise = for_lesson("exclusive").nth(1).unwrap();
+        let (score, out) = run(&[exercise], Duration::from_secs(200), "a\n");
+        assert_eq!(score, Score { right: 0, asked: 1 });
+        assert!(out.contains("Right, but after 3:20 of the 3:00 allowed.\n"));
+    }
+
+    #[test]
+    fn letters_choices_as_listed() {
+        let exercise = for_lesson("exclusive").nth(1).unwrap();
+        let (score, out) = run(&[exercise], Duration::from_secs(1), "e\nb\n");
+        assert_eq!(score, Score { right: 0, asked: 1 });
+        assert!(out.contains("  a) Only when a push has to reallocate the buffer\n"));
+        assert!(out.contains("Please answer with a letter from a to d, or ? for a hint.\n"));
+        assert!(out.contains("Not quite: a) Only when a push has to reallocate the buffer.\n"));
+    }
+
+    #[test]
+    fn stops_when_input_runs_out() {
+        let exercises: Vec<&Exercise> = for_lesson("cell").collect();
+        let (score, out) = run(&exercises, Duration::from_secs(1), "?\n");
+        assert_eq!(score, Score::default());
+        assert!(!out.contains("Question 2 of 2"));
+    }
+
+    #[test]
+    fn keys_every_question() {
+        let exercises: Vec<&Exercise> = for_lesson("dangling").collect();
+        let key = answer_key(&exercises);
+        assert!(key.starts_with("== Question 1 (dangling)\n\n    let v = vec![1, 2, 3];\n"));
+        assert!(key.contains("\nAnswer: it compiles.\n"));
+        assert!(key.contains("\nAnswer: a) The move would leave `r` pointing at"));
+        assert!(key.contains(&format!("Often answered: {}\n", exercises[1].pitfall)));
+        assert_eq!(key.matches("Hint 1: ").count(), 2);
+    }
+}
@@ -0,0 +1,153 @@
+//! Three threads of code bumping one counter: two safe ways and a racing
+//! one.
+//!
+//! The notes' last claim is that a concurrent Rust program without `unsafe`
+//! is free of data races by construction. A race needs a value that is both
+//! mutable and shared between threads, and the reference rules forbid
+//! exactly that combination: `thread::spawn` demands `'static + Send`, a
+//! plain `&mut usize` can't be handed to two threads, and the types that do
+//! allow shared mutation, like `Mutex` and the atomics, synchronize it.
+//! Getting a race takes `unsafe`, and `count_racing` (behind the
+//! `unsound-demos` feature) shows what it buys: increments lost whenever two
+//! threads read the same old value.
+//!
+//! Built with `--cfg loom`, the module runs on `loom`'s model of threads
+//! and atomics instead of the real ones, and `tests/loom.rs` checks every
+//! interleaving: the two safe counters always reach the full count, and the
+//! racing one is reported as a data race. Miri catches the race too:
+//!
+//! ```text
+//! RUSTFLAGS="--cfg loom" cargo test --release --features unsound-demos --test loom
+//! cargo +nightly miri test --features unsound-demos concurrency
+//! ```
+
+#[cfg(loom)]
+use loom::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(loom)]
+use loom::sync::{Arc, Mutex};
+#[cfg(loom)]
+use loom::thread;
+#[cfg(not(loom))]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(not(loom))]
+use std::sync::{Arc, Mutex};
+#[cfg(not(loom))]
+use std::thread;
+
+/// Runs `work` on `threads` threads, each given its own handle on `shared`,
+/// and waits for them all.
+fn on_threads<T, F>(threads: usize, shared: &Arc<T>, work: F)
+where
+    T: Send + Sync + 'static,
+    F: Fn(&T) + Send + Sync + Clone + 'static,
+{
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let shared = Arc::clone(shared);
+            let work = work.clone();
+            thread::spawn(move || work(&shared))
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+/// Counts to `threads * per_thread` with each increment made under a lock.
+pub fn count_with_mutex(threads: usize, per_thread: usize) -> usize {
+    let counter = Arc::new(Mutex::new(0));
+    on_threads(threads, &counter, move |counter| {
+        for _ in 0..per_thread {
+            *counter.lock().unwrap() += 1;
+        }
+    });
+    let total = *counter.lock().unwrap();
+    total
+}
+
+/// Counts to `threads * per_thread` with atomic `fetch_add`.
+///
+/// `Relaxed` is enough: each increment is indivisible whatever the
+/// ordering, and joining the threads makes every one of them visible to the
+/// final load.
+pub fn count_with_atomic(threads: usize, per_thread: usize) -> usize {
+    let counter = Arc::new(AtomicUsize::new(0));
+    on_threads(threads, &counter, move |counter| {
+        for _ in 0..per_thread {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+    counter.load(Ordering::Relaxed)
+}
+
+/// A counter that claims to be `Sync` without synchronizing anything.
+#[cfg(feature = "unsound-demos")]
+struct RacyCounter(UnsafeCell<usize>);
+
+// This is the lie. Nothing makes concurrent access to the cell safe.
+#[cfg(feature = "unsound-demos")]
+unsafe impl Sync for RacyCounter {}
+
+#[cfg(all(feature = "unsound-demos", loom))]
+use loom::cell::UnsafeCell;
+
+/// `std`'s `UnsafeCell` behind the closure-taking API of `loom`'s, so the
+/// racing code reads the same either way.
+#[cfg(all(feature = "unsound-demos", not(loom)))]
+struct UnsafeCell<T>(std::cell::UnsafeCell<T>);
+
+#[cfg(all(feature = "unsound-demos", not(loom)))]
+impl<T> UnsafeCell<T> {
+    fn new(value: T) -> UnsafeCell<T> {
+        UnsafeCell(std::cell::UnsafeCell::new(value))
+    }
+
+    fn with_mut<R>(&self, f: impl FnOnce(*mut T) -> R) -> R {
+        f(self.0.get())
+    }
+}
+
+/// Counts to `threads * per_thread` with unsynchronized reads and writes.
+///
+/// **This has undefined behavior** whenever two threads run at once. Each
+/// increment is a separate load and store, volatile so the compiler can't
+/// merge a thread's increments into one, and two threads that load the
+/// same value both store the same successor: one increment is lost. In
+/// practice the result comes out short; in principle, being undefined,
+/// anything could happen. It's here to be run under loom or Miri and
+/// watched failing.
+#[cfg(feature = "unsound-demos")]
+pub fn count_racing(threads: usize, per_thread: usize) -> usize {
+    let counter = Arc::new(RacyCounter(UnsafeCell::new(0)));
+    on_threads(threads, &counter, move |counter| {
+        for _ in 0..per_thread {
+            counter
+                .0
+                .with_mut(|p| unsafe { p.write_volatile(p.read_volatile() + 1) });
+        }
+    });
+    counter.0.with_mut(|p| unsafe { *p })
+}
+
+#[cfg(all(test, not(loom)))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_counters_reach_the_full_count() {
+        assert_eq!(count_with_mutex(8, 10_000), 80_000);
+        assert_eq!(count_with_atomic(8, 10_000), 80_000);
+    }
+
+    /// A data race, so undefined behaviour, not a test that can pass.
+    /// Under Miri it shows the race being caught as it happens; run
+    /// natively it would usually show lost updates, but a racy program
+    /// promises nothing, so it isn't run that way at all.
+    #[cfg(feature = "unsound-demos")]
+    #[test]
+    #[cfg_attr(not(miri), ignore = "undefined behaviour, for Miri to report")]
+    fn racing_counter_never_gains() {
+        let total = count_racing(8, 10_000);
+        assert!(total <= 80_000, "{}", total);
+    }
+}
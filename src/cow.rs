@@ -0,0 +1,95 @@
+//! `Cow`: borrowing when that's enough, owning when it isn't.
+//!
+//! The rest of the crate picks one side of the line for each value: shared
+//! and read-only, or exclusive and writable, owned or borrowed. A function
+//! that usually returns its input unchanged but sometimes has to fix it up
+//! sits on both sides. Returning a reference can't work, because the fixed
+//! version is a new value that dies with the function:
+//!
+//! ```compile_fail,E0515
+//! fn normalize_path(path: &str) -> &str {
+//!     let fixed = path.replace("//", "/");
+//!     &fixed // cannot return reference to local variable `fixed`
+//! }
+//! ```
+//!
+//! and returning a `String` always allocates, even for the paths that were
+//! fine. [`Cow<str>`](std::borrow::Cow) is either: `Borrowed` hands back
+//! the caller's `&str` and costs nothing, `Owned` carries the new `String`.
+//! The caller reads either through `Deref`, and calls `into_owned` or
+//! `to_mut` only if it needs a `String` of its own, at which point a
+//! borrowed one is copied. That's the mutation rule again, deferred: shared
+//! until someone writes.
+//!
+//! [`references::maybe_normalize`](crate::references::maybe_normalize) is
+//! that function, done right. Paths already in normal form, most of them
+//! in practice, come back borrowed, without allocating:
+//!
+//! ```
+//! use std::borrow::Cow;
+//! use references_sharing_vs_mutation::references::maybe_normalize;
+//!
+//! assert!(matches!(maybe_normalize("/usr/lib"), Cow::Borrowed("/usr/lib")));
+//! assert_eq!(maybe_normalize("/usr//lib/./"), "/usr/lib");
+//! ```
+//!
+//! [`normalize_path_owned`] is the version that always allocates, for
+//! `benches/cow.rs` to compare it with.
+
+use std::borrow::Cow;
+
+use crate::references::maybe_normalize;
+
+/// `maybe_normalize` that always returns a new `String`, for comparison:
+/// the version you write when a borrowed result can't express "sometimes
+/// new".
+pub fn normalize_path_owned(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    if path.starts_with('/') {
+        out.push('/');
+    }
+    for part in path.split('/').filter(|p| !p.is_empty() && *p != ".") {
+        if !out.is_empty() && !out.ends_with('/') {
+            out.push('/');
+        }
+        out.push_str(part);
+    }
+    if out.is_empty() {
+        out.push('.');
+    }
+    out
+}
+
+/// Shows which paths come back borrowed and which had to be rebuilt.
+pub fn demo() {
+    for path in &["/usr/lib", "src//cow.rs", "./notes/", "a/../b"] {
+        let kind = match maybe_normalize(path) {
+            Cow::Borrowed(_) => "borrowed",
+            Cow::Owned(_) => "owned",
+        };
+        println!("{:?} -> {:?} ({})", path, maybe_normalize(path), kind);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agrees_with_borrowing_when_it_can() {
+        let paths = [
+            "/",
+            "",
+            ".",
+            "a//b/",
+            "./x/./y",
+            "/usr/lib",
+            "..//..",
+            "a/./../b/",
+            "//",
+        ];
+        for path in &paths {
+            assert_eq!(maybe_normalize(path), normalize_path_owned(path));
+        }
+    }
+}
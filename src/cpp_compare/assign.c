@@ -0,0 +1,54 @@
+/* The C++ `File::operator=` from `mutation::self_assignment`, in C, over a
+ * pretend descriptor table so that the bug can run without a kernel. */
+
+#define FD_MAX 16
+
+struct fd_table {
+    /* Nonzero where a descriptor is open. */
+    unsigned char open[FD_MAX];
+    int dups;
+    int closes;
+};
+
+struct file {
+    int descriptor;
+};
+
+/* Opens the lowest free descriptor, as POSIX does, or returns -1. */
+int fd_open(struct fd_table *t)
+{
+    for (int fd = 0; fd < FD_MAX; fd++) {
+        if (!t->open[fd]) {
+            t->open[fd] = 1;
+            return fd;
+        }
+    }
+    return -1;
+}
+
+/* Like dup(2): -1, as for EBADF, if `fd` isn't open. */
+int fd_dup(struct fd_table *t, int fd)
+{
+    t->dups++;
+    if (fd < 0 || fd >= FD_MAX || !t->open[fd])
+        return -1;
+    return fd_open(t);
+}
+
+/* Like close(2): -1 if `fd` isn't open. */
+int fd_close(struct fd_table *t, int fd)
+{
+    t->closes++;
+    if (fd < 0 || fd >= FD_MAX || !t->open[fd])
+        return -1;
+    t->open[fd] = 0;
+    return 0;
+}
+
+/* `*self = *rhs`, the way the operator does it: close, then dup. Nothing
+ * checks what either call returns, as in the notes' version. */
+void file_assign(struct fd_table *t, struct file *self, const struct file *rhs)
+{
+    fd_close(t, self->descriptor);
+    self->descriptor = fd_dup(t, rhs->descriptor);
+}
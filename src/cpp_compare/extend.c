@@ -0,0 +1,66 @@
+/* `extend(&mut wave, &wave)` from `mutation::exclusive`, in C, with the
+ * vector's buffers carved out of an arena. Freeing a buffer fills it with
+ * `FREED` and never hands it out again, so reading it after a reallocation
+ * reads what a real allocator might have left there, without the
+ * undefined behaviour of reading memory that's really been freed. */
+
+#include <stddef.h>
+
+#define ARENA_CELLS 256
+#define FREED -7777.0
+
+struct arena {
+    double cells[ARENA_CELLS];
+    size_t used;
+};
+
+struct vec {
+    double *data;
+    size_t len;
+    size_t cap;
+};
+
+static double *arena_alloc(struct arena *a, size_t n)
+{
+    if (ARENA_CELLS - a->used < n)
+        return NULL;
+    double *p = &a->cells[a->used];
+    a->used += n;
+    return p;
+}
+
+static void arena_free(double *p, size_t n)
+{
+    for (size_t i = 0; i < n; i++)
+        p[i] = FREED;
+}
+
+/* Pushes `x`, doubling the buffer when it's full. Returns 0, or -1 if the
+ * arena has run out. */
+int vec_push(struct arena *a, struct vec *v, double x)
+{
+    if (v->len == v->cap) {
+        size_t cap = v->cap ? v->cap * 2 : 4;
+        double *data = arena_alloc(a, cap);
+        if (!data)
+            return -1;
+        for (size_t i = 0; i < v->len; i++)
+            data[i] = v->data[i];
+        arena_free(v->data, v->cap);
+        v->data = data;
+        v->cap = cap;
+    }
+    v->data[v->len++] = x;
+    return 0;
+}
+
+/* Pushes each of `src[0..n]` in turn, as `vecops::extend` does, reading
+ * `src` afresh for each. */
+int vec_extend(struct arena *a, struct vec *v, const double *src, size_t n)
+{
+    for (size_t i = 0; i < n; i++) {
+        if (vec_push(a, v, src[i]) != 0)
+            return -1;
+    }
+    return 0;
+}
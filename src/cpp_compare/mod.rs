@@ -0,0 +1,304 @@
+//! Appendix: the notes' C and C++ bugs, running.
+//!
+//! The lessons say what the C++ `operator=` does to a file assigned to
+//! itself, and what extending a vector from itself does when the push
+//! reallocates, and show the Rust that won't compile. With the
+//! `cpp_compare` feature, `build.rs` compiles the same code in C, from
+//! `assign.c` and `extend.c` beside this module, so it can be run and
+//! looked at. Neither touches the system: the descriptors come from a
+//! pretend table like [`fd::mock::Table`](crate::fd::mock::Table), and the
+//! vector's buffers from an arena whose freed cells read as [`FREED`], so
+//! reading one after it's been freed is a defined, visible mistake rather
+//! than undefined behaviour.
+//!
+//! In both, the C goes wrong without a word:
+//!
+//! ```
+//! use references_sharing_vs_mutation::cpp_compare::{CTable, CVec, FREED};
+//!
+//! let mut table = CTable::new();
+//! let mut f = table.open().unwrap();
+//! table.assign_to_itself(&mut f);
+//! assert_eq!(f.descriptor(), -1);
+//!
+//! let mut wave = CVec::with_capacity(4);
+//! wave.extend_from_slice(&[0.0, 1.0, 0.0, -1.0]);
+//! wave.extend_from_itself();
+//! assert_eq!(wave.as_slice()[5..], [FREED, FREED, FREED]);
+//! ```
+//!
+//! The Rust versions are `f.clone_from(&f)` and `extend(&mut wave,
+//! &wave)`, which the borrow checker rejects (see `tests/compile_fail`),
+//! and the fixes it leads to, `clone_from` that dups before it closes and
+//! `vecops::extend_self`, which indexes instead of holding a slice.
+
+use std::convert::TryFrom;
+use std::os::raw::c_int;
+use std::ptr;
+use std::slice;
+
+/// What the C arena leaves in a freed cell.
+pub const FREED: f64 = -7777.0;
+
+const FD_MAX: usize = 16;
+const ARENA_CELLS: usize = 256;
+
+#[repr(C)]
+struct FdTable {
+    open: [u8; FD_MAX],
+    dups: c_int,
+    closes: c_int,
+}
+
+#[repr(C)]
+struct Arena {
+    cells: [f64; ARENA_CELLS],
+    used: usize,
+}
+
+#[repr(C)]
+struct RawVec {
+    data: *mut f64,
+    len: usize,
+    cap: usize,
+}
+
+extern "C" {
+    fn fd_open(t: *mut FdTable) -> c_int;
+    fn file_assign(t: *mut FdTable, this: *mut CFile, rhs: *const CFile);
+    fn vec_push(a: *mut Arena, v: *mut RawVec, x: f64) -> c_int;
+    fn vec_extend(a: *mut Arena, v: *mut RawVec, src: *const f64, n: usize) -> c_int;
+}
+
+/// The C `struct file`: one descriptor, which the struct means to own.
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+pub struct CFile {
+    descriptor: c_int,
+}
+
+impl CFile {
+    /// The descriptor, or -1 where a failed `dup` left it.
+    pub fn descriptor(&self) -> i32 {
+        self.descriptor
+    }
+}
+
+/// The C pretend descriptor table, with room for 16.
+pub struct CTable {
+    raw: Box<FdTable>,
+}
+
+impl Default for CTable {
+    fn default() -> CTable {
+        CTable::new()
+    }
+}
+
+impl CTable {
+    pub fn new() -> CTable {
+        CTable {
+            raw: Box::new(FdTable {
+                open: [0; FD_MAX],
+                dups: 0,
+                closes: 0,
+            }),
+        }
+    }
+
+    /// A file on the lowest free descriptor, or `None` if they're all
+    /// taken.
+    pub fn open(&mut self) -> Option<CFile> {
+        // SAFETY: `raw` is a valid, exclusively borrowed table.
+        let descriptor = unsafe { fd_open(&mut *self.raw) };
+        if descriptor < 0 {
+            None
+        } else {
+            Some(CFile { descriptor })
+        }
+    }
+
+    /// Whether `fd` is open.
+    pub fn is_open(&self, fd: i32) -> bool {
+        usize::try_from(fd).is_ok_and(|i| i < FD_MAX && self.raw.open[i] != 0)
+    }
+
+    /// How many descriptors are open.
+    pub fn open_count(&self) -> usize {
+        self.raw.open.iter().filter(|&&o| o != 0).count()
+    }
+
+    /// How many times `dup` and `close` have been called, successfully or
+    /// not.
+    pub fn calls(&self) -> (usize, usize) {
+        (self.raw.dups as usize, self.raw.closes as usize)
+    }
+
+    /// `*this = *rhs` in C: closes `this`'s descriptor, then dups `rhs`'s.
+    pub fn assign(&mut self, this: &mut CFile, rhs: &CFile) {
+        // SAFETY: all three point to live values, and `this` and `rhs` are
+        // different objects, as the references guarantee.
+        unsafe { file_assign(&mut *self.raw, this, rhs) }
+    }
+
+    /// `f = f` in C: the same file as both sides. Safe Rust can't hand
+    /// one value over as `&mut` and `&` at once, so this passes the one
+    /// pointer twice.
+    pub fn assign_to_itself(&mut self, file: &mut CFile) {
+        let file: *mut CFile = file;
+        // SAFETY: `file` points to a live `CFile`; C doesn't mind the
+        // aliasing, and nothing in Rust holds a reference meanwhile.
+        unsafe { file_assign(&mut *self.raw, file, file) }
+    }
+}
+
+/// The C vector of doubles, growing by doubling in its own arena of 256
+/// cells. Pushing past what the arena holds panics.
+pub struct CVec {
+    // Boxed so the buffer stays put when the `CVec` moves.
+    arena: Box<Arena>,
+    raw: RawVec,
+}
+
+impl CVec {
+    /// An empty vector with room for `cap` before its first reallocation.
+    pub fn with_capacity(cap: usize) -> CVec {
+        let mut arena = Box::new(Arena {
+            cells: [0.0; ARENA_CELLS],
+            used: 0,
+        });
+        assert!(cap <= ARENA_CELLS, "the arena holds {}", ARENA_CELLS);
+        arena.used = cap;
+        let data = if cap == 0 {
+            ptr::null_mut()
+        } else {
+            arena.cells.as_mut_ptr()
+        };
+        CVec {
+            arena,
+            raw: RawVec { data, len: 0, cap },
+        }
+    }
+
+    pub fn as_slice(&self) -> &[f64] {
+        if self.raw.len == 0 {
+            return &[];
+        }
+        // SAFETY: the C code keeps `len` cells at `data` initialized, in
+        // the arena this owns.
+        unsafe { slice::from_raw_parts(self.raw.data, self.raw.len) }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.raw.cap
+    }
+
+    pub fn push(&mut self, x: f64) {
+        // SAFETY: the vector and its arena are valid and exclusively
+        // borrowed.
+        let status = unsafe { vec_push(&mut *self.arena, &mut self.raw, x) };
+        assert_eq!(status, 0, "the arena is full");
+    }
+
+    /// `vec_extend(v, items, n)`, with `items` from elsewhere.
+    pub fn extend_from_slice(&mut self, items: &[f64]) {
+        // SAFETY: `items` is a live slice outside the arena, which no push
+        // can free.
+        let status =
+            unsafe { vec_extend(&mut *self.arena, &mut self.raw, items.as_ptr(), items.len()) };
+        assert_eq!(status, 0, "the arena is full");
+    }
+
+    /// `vec_extend(v, v->data, v->len)`: extending from itself, which
+    /// reads freed cells once a push reallocates.
+    pub fn extend_from_itself(&mut self) {
+        let (src, n) = (self.raw.data, self.raw.len);
+        // SAFETY: the arena never gives memory back, so `src` stays
+        // readable after a push frees it; it reads as `FREED` then.
+        let status = unsafe { vec_extend(&mut *self.arena, &mut self.raw, src, n) };
+        assert_eq!(status, 0, "the arena is full");
+    }
+}
+
+/// Runs both C versions and prints what they did.
+pub fn demo() {
+    let mut table = CTable::new();
+    let mut f = table.open().expect("an empty table has room");
+    let before = f.descriptor();
+    table.assign_to_itself(&mut f);
+    println!(
+        "C `f = f`: descriptor {} became {}, {} left open",
+        before,
+        f.descriptor(),
+        table.open_count()
+    );
+
+    let mut wave = CVec::with_capacity(4);
+    wave.extend_from_slice(&[0.0, 1.0, 0.0, -1.0]);
+    wave.extend_from_itself();
+    println!("C extend from itself: {:?}", wave.as_slice());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fd::mock::Table;
+    use crate::vecops::extend_self;
+
+    #[test]
+    fn assignment_works_between_different_files() {
+        let mut table = CTable::new();
+        let mut f = table.open().unwrap();
+        let g = table.open().unwrap();
+        table.assign(&mut f, &g);
+        assert_eq!(f.descriptor(), 0);
+        assert!(table.is_open(0) && table.is_open(1));
+        assert_eq!(table.calls(), (1, 1));
+    }
+
+    #[test]
+    fn c_self_assignment_loses_the_file() {
+        let mut table = CTable::new();
+        let mut f = table.open().unwrap();
+        table.assign_to_itself(&mut f);
+        assert_eq!(f.descriptor(), -1);
+        assert_eq!(table.open_count(), 0);
+        assert_eq!(table.calls(), (1, 1));
+
+        // The Rust fix dups before it closes, and never sees itself.
+        let table = Table::new();
+        let mut f = table.open("foo.txt");
+        let g = table.open("bar.txt");
+        f.clone_from(&g);
+        assert_eq!(f.name(), "bar.txt");
+        assert_eq!(table.open_count(), 2);
+    }
+
+    #[test]
+    fn c_extend_from_itself_reads_freed_cells_when_it_reallocates() {
+        let mut wave = CVec::with_capacity(4);
+        wave.extend_from_slice(&[0.0, 1.0, 0.0, -1.0]);
+        wave.extend_from_itself();
+        assert_eq!(wave.capacity(), 8);
+        // The first element was read before the push that moved the
+        // buffer; the rest after.
+        assert_eq!(
+            wave.as_slice(),
+            [0.0, 1.0, 0.0, -1.0, 0.0, FREED, FREED, FREED]
+        );
+
+        let mut rust = vec![0.0, 1.0, 0.0, -1.0];
+        rust.shrink_to_fit();
+        extend_self(&mut rust, 0..4);
+        assert_eq!(rust, [0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0]);
+    }
+
+    #[test]
+    fn c_extend_from_itself_works_with_room_to_spare() {
+        let mut wave = CVec::with_capacity(8);
+        wave.extend_from_slice(&[1.0, 2.0]);
+        wave.push(3.0);
+        wave.extend_from_itself();
+        assert_eq!(wave.as_slice(), [1.0, 2.0, 3.0, 1.0, 2.0, 3.0]);
+    }
+}
@@ -0,0 +1,430 @@
+//! The rejected examples put to whichever rustc is at hand.
+//!
+//! `tests/compile_fail` pins rustc's exact output on the toolchain the notes
+//! are written against. This checks the part that matters on any toolchain:
+//! each rejected [`Snippet`] is compiled with `--error-format=json`, and the
+//! error codes rustc reports are compared with the ones its lesson
+//! documents. A message that's been reworded but keeps its code is reported
+//! as such, so a run over several toolchains shows which releases changed
+//! the wording.
+//!
+//! Snippets that use this crate need it built by the same rustc; point
+//! [`Rustc::cargo_target`] at the `target/debug` directory it was built in.
+
+use std::collections::hash_map::RandomState;
+use std::env;
+use std::fs::{self, DirBuilder};
+use std::hash::BuildHasher;
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::DirBuilderExt;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Output};
+
+use serde_json::Value;
+
+use crate::catalog::{self, error_codes, Snippet, CRATE_NAME};
+
+/// A new, empty directory under the temporary directory, which only this
+/// user can get into.
+///
+/// The name ends in a random number, and the directory is made with
+/// `create_dir`, which fails if the path exists, trying another number if
+/// it does. Reusing a directory someone else made first would let them
+/// plant a symlink where a snippet is about to be written.
+fn private_dir(prefix: &str) -> io::Result<PathBuf> {
+    // Each `RandomState` is seeded afresh, from the OS the first time.
+    let random = RandomState::new();
+    let mut builder = DirBuilder::new();
+    #[cfg(unix)]
+    builder.mode(0o700);
+    for attempt in 0u32.. {
+        let dir = env::temp_dir().join(format!(
+            "{}-{}-{:016x}",
+            prefix,
+            process::id(),
+            random.hash_one(attempt)
+        ));
+        match builder.create(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!("ran out of names for a temporary directory")
+}
+
+/// One diagnostic from rustc's JSON output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// `error`, `warning`, `note` and so on.
+    pub level: String,
+    pub code: Option<String>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// The diagnostic's first line as rustc prints it for humans:
+    /// `error[E0502]: cannot borrow ...`.
+    pub fn headline(&self) -> String {
+        match self.code {
+            Some(ref code) => format!("{}[{}]: {}", self.level, code, self.message),
+            None => format!("{}: {}", self.level, self.message),
+        }
+    }
+}
+
+/// Parses rustc's `--error-format=json` output, one diagnostic per line.
+pub fn parse_json(output: &str) -> io::Result<Vec<Diagnostic>> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+    let mut diagnostics = Vec::new();
+    for line in output.lines().filter(|l| !l.trim().is_empty()) {
+        let value: Value = serde_json::from_str(line).map_err(|e| {
+            invalid(format!(
+                "rustc printed something other than JSON ({}): {}",
+                e, line
+            ))
+        })?;
+        let field = |name: &str| value.get(name).and_then(Value::as_str).map(str::to_owned);
+        diagnostics.push(Diagnostic {
+            level: field("level").ok_or_else(|| invalid(format!("no `level` in {}", line)))?,
+            code: value
+                .get("code")
+                .and_then(|c| c.get("code"))
+                .and_then(Value::as_str)
+                .map(str::to_owned),
+            message: field("message")
+                .ok_or_else(|| invalid(format!("no `message` in {}", line)))?,
+        });
+    }
+    Ok(diagnostics)
+}
+
+/// How a snippet fared against the compiler.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    /// The same error codes the lesson documents, worded the same, or a
+    /// compiling snippet that still compiles.
+    Matches,
+    /// The same codes, but messages that differ from the lesson's.
+    Reworded {
+        documented: Vec<String>,
+        actual: Vec<String>,
+    },
+    /// Different codes than the lesson documents, as headlines.
+    WrongCode {
+        documented: Vec<String>,
+        actual: Vec<String>,
+    },
+    /// A snippet the lesson says is rejected compiled.
+    Compiled,
+    /// It couldn't be checked, for the reason given.
+    Skipped(&'static str),
+}
+
+impl Status {
+    /// Whether the lesson is wrong about this compiler: anything but a
+    /// match, a rewording or a skip.
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Status::WrongCode { .. } | Status::Compiled)
+    }
+}
+
+/// A rustc to check snippets with.
+#[derive(Debug, Clone)]
+pub struct Rustc {
+    program: PathBuf,
+    externs: Vec<(String, PathBuf)>,
+    library_dirs: Vec<PathBuf>,
+}
+
+impl Rustc {
+    /// `program` is run as is, so a bare `rustc` is looked up on `PATH`.
+    pub fn new<P: Into<PathBuf>>(program: P) -> Rustc {
+        Rustc {
+            program: program.into(),
+            externs: Vec::new(),
+            library_dirs: Vec::new(),
+        }
+    }
+
+    /// Passes `--extern name=rlib` to every compilation.
+    pub fn extern_crate<P: Into<PathBuf>>(mut self, name: &str, rlib: P) -> Rustc {
+        self.externs.push((name.to_owned(), rlib.into()));
+        self
+    }
+
+    /// Passes `-L dependency=dir` to every compilation.
+    pub fn library_dir<P: Into<PathBuf>>(mut self, dir: P) -> Rustc {
+        self.library_dirs.push(dir.into());
+        self
+    }
+
+    /// Makes this crate available to snippets, as built by cargo in `dir`
+    /// (a `target/debug` or `target/release`), if it's been built there.
+    pub fn cargo_target(self, dir: &Path) -> Rustc {
+        let rlib = dir.join(format!("lib{}.rlib", CRATE_NAME));
+        if !rlib.is_file() {
+            return self;
+        }
+        self.extern_crate(CRATE_NAME, rlib)
+            .library_dir(dir.join("deps"))
+    }
+
+    /// What `rustc --version` says.
+    pub fn version(&self) -> io::Result<String> {
+        let output = Command::new(&self.program).arg("--version").output()?;
+        if !output.status.success() {
+            let msg = format!("`{} --version` failed", self.program.display());
+            return Err(io::Error::other(msg));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    }
+
+    /// Compiles `source` as a 2021-edition binary as far as borrow
+    /// checking, returning everything rustc said about it.
+    pub fn compile(&self, source: &str) -> io::Result<Vec<Diagnostic>> {
+        let dir = private_dir("rsvm-diagnostics")?;
+        let result = self.compile_in(&dir, source);
+        let _ = fs::remove_dir_all(&dir);
+        result
+    }
+
+    fn compile_in(&self, dir: &Path, source: &str) -> io::Result<Vec<Diagnostic>> {
+        let main = dir.join("main.rs");
+        fs::write(&main, source)?;
+        let mut command = self.command();
+        command
+            .args(["--error-format=json", "--emit=metadata", "-o"])
+            .arg(dir.join("snippet.rmeta"));
+        let output = command.arg(&main).output()?;
+        parse_json(&String::from_utf8_lossy(&output.stderr))
+    }
+
+    /// Builds `source` into a program and runs it, returning what it
+    /// printed and how it exited. Fails if it doesn't build.
+    pub fn run(&self, source: &str) -> io::Result<Output> {
+        let dir = private_dir("rsvm-run")?;
+        let result = self.run_in(&dir, source);
+        let _ = fs::remove_dir_all(&dir);
+        result
+    }
+
+    fn run_in(&self, dir: &Path, source: &str) -> io::Result<Output> {
+        let main = dir.join("main.rs");
+        fs::write(&main, source)?;
+        let program = dir.join("snippet");
+        let built = self.command().arg("-o").arg(&program).arg(&main).output()?;
+        if !built.status.success() {
+            let msg = format!(
+                "snippet didn't build:\n{}",
+                String::from_utf8_lossy(&built.stderr)
+            );
+            return Err(io::Error::other(msg));
+        }
+        Command::new(&program).output()
+    }
+
+    /// rustc with the flags every build here shares.
+    fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command.args([
+            "--edition",
+            "2021",
+            "--crate-type",
+            "bin",
+            "--crate-name",
+            "snippet",
+        ]);
+        for (name, rlib) in &self.externs {
+            command
+                .arg("--extern")
+                .arg(format!("{}={}", name, rlib.display()));
+        }
+        for lib in &self.library_dirs {
+            command
+                .arg("-L")
+                .arg(format!("dependency={}", lib.display()));
+        }
+        command
+    }
+
+    /// Compiles `snippet` and compares what rustc says with what its
+    /// lesson documents.
+    pub fn check(&self, snippet: &Snippet) -> io::Result<Status> {
+        if snippet.uses_crate() && !self.externs.iter().any(|(n, _)| n == CRATE_NAME) {
+            return Ok(Status::Skipped(
+                "it needs this crate, which hasn't been built",
+            ));
+        }
+        let errors: Vec<Diagnostic> = self
+            .compile(&snippet.code)?
+            .into_iter()
+            .filter(|d| d.level == "error" && d.code.is_some())
+            .collect();
+        // Metadata from another rustc, or none at all.
+        if errors.iter().any(|d| {
+            matches!(
+                d.code.as_deref(),
+                Some("E0460") | Some("E0463") | Some("E0514")
+            )
+        }) {
+            return Ok(Status::Skipped(
+                "it needs this crate built by the same rustc",
+            ));
+        }
+        let actual: Vec<String> = errors.iter().map(Diagnostic::headline).collect();
+        let stderr = match snippet.rustc_error {
+            Some(ref stderr) => stderr,
+            None if actual.is_empty() => return Ok(Status::Matches),
+            None => {
+                return Ok(Status::WrongCode {
+                    documented: Vec::new(),
+                    actual,
+                })
+            }
+        };
+        if actual.is_empty() {
+            return Ok(Status::Compiled);
+        }
+        let documented: Vec<String> = stderr
+            .lines()
+            .filter(|l| l.starts_with("error["))
+            .map(str::to_owned)
+            .collect();
+        let mut documented_codes: Vec<&str> = error_codes(stderr).collect();
+        let mut actual_codes: Vec<&str> = errors.iter().filter_map(|d| d.code.as_deref()).collect();
+        documented_codes.sort_unstable();
+        actual_codes.sort_unstable();
+        actual_codes.dedup();
+        Ok(if documented_codes != actual_codes {
+            Status::WrongCode { documented, actual }
+        } else if documented != actual {
+            Status::Reworded { documented, actual }
+        } else {
+            Status::Matches
+        })
+    }
+}
+
+/// Checks every rejected snippet against each of `rustcs`, writing a
+/// section per compiler to `out`. Returns how many checks found the
+/// lesson wrong about a compiler.
+pub fn report<W: Write>(rustcs: &[Rustc], out: &mut W) -> io::Result<usize> {
+    let rejected: Vec<Snippet> = catalog::all().into_iter().filter(|s| !s.compiles).collect();
+    let mut failures = 0;
+    for (n, rustc) in rustcs.iter().enumerate() {
+        if n > 0 {
+            writeln!(out)?;
+        }
+        writeln!(out, "{}", rustc.version()?)?;
+        let mut reworded = 0;
+        for snippet in &rejected {
+            let stderr = snippet.rustc_error.as_deref().unwrap_or("");
+            let codes: Vec<&str> = error_codes(stderr).collect();
+            write!(out, "  {:<18} {:<6} ", snippet.id, codes.join(","))?;
+            let status = rustc.check(snippet)?;
+            failures += status.is_failure() as usize;
+            let (documented, actual) = match status {
+                Status::Matches => {
+                    writeln!(out, "as documented")?;
+                    continue;
+                }
+                Status::Compiled => {
+                    writeln!(out, "COMPILES")?;
+                    continue;
+                }
+                Status::Skipped(why) => {
+                    writeln!(out, "skipped: {}", why)?;
+                    continue;
+                }
+                Status::Reworded { documented, actual } => {
+                    reworded += 1;
+                    writeln!(out, "reworded")?;
+                    (documented, actual)
+                }
+                Status::WrongCode { documented, actual } => {
+                    writeln!(out, "DIFFERENT ERROR")?;
+                    (documented, actual)
+                }
+            };
+            for line in &documented {
+                writeln!(out, "      documented: {}", line)?;
+            }
+            for line in &actual {
+                writeln!(out, "      now:        {}", line)?;
+            }
+        }
+        writeln!(out, "  {} of {} reworded", reworded, rejected.len())?;
+    }
+    Ok(failures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OUTPUT: &str = r#"{"$message_type":"diagnostic","message":"cannot move out of `v` because it is borrowed","code":{"code":"E0505","explanation":"..."},"level":"error","spans":[],"children":[],"rendered":"error[E0505]: ..."}
+{"$message_type":"diagnostic","message":"aborting due to 1 previous error","code":null,"level":"error","spans":[],"children":[],"rendered":"error: aborting due to 1 previous error"}
+"#;
+
+    fn snippet(id: &str) -> Snippet {
+        catalog::all().into_iter().find(|s| s.id == id).unwrap()
+    }
+
+    #[test]
+    fn parses_rustc_json() {
+        let diagnostics = parse_json(OUTPUT).unwrap();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            diagnostics[0].headline(),
+            "error[E0505]: cannot move out of `v` because it is borrowed"
+        );
+        assert_eq!(diagnostics[1].code, None);
+        assert_eq!(
+            parse_json("thread 'rustc' panicked").unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn rejected_snippets_get_their_documented_codes() {
+        let rustc = Rustc::new("rustc");
+        for snippet in catalog::all() {
+            let status = rustc.check(&snippet).unwrap();
+            assert!(!status.is_failure(), "{}: {:?}", snippet.id, status);
+        }
+        // Checked, with the right code; whether the wording matches too
+        // depends on the rustc on `PATH`.
+        let status = rustc.check(&snippet("dangling/1")).unwrap();
+        assert!(
+            matches!(status, Status::Matches | Status::Reworded { .. }),
+            "{:?}",
+            status
+        );
+        assert_eq!(
+            rustc.check(&snippet("exclusive/1")).unwrap(),
+            Status::Skipped("it needs this crate, which hasn't been built")
+        );
+    }
+
+    #[test]
+    fn notices_the_wrong_code() {
+        let mut wrong = snippet("dangling/1");
+        wrong.rustc_error = Some(wrong.rustc_error.unwrap().replace("E0505", "E0382"));
+        match Rustc::new("rustc").check(&wrong).unwrap() {
+            Status::WrongCode { documented, actual } => {
+                assert!(documented[0].starts_with("error[E0382]"));
+                assert!(actual[0].starts_with("error[E0505]"));
+            }
+            other => panic!("{:?}", other),
+        }
+
+        let mut compiles = snippet("dangling/2");
+        compiles.rustc_error = snippet("dangling/1").rustc_error;
+        assert_eq!(
+            Rustc::new("rustc").check(&compiles).unwrap(),
+            Status::Compiled
+        );
+    }
+}
@@ -0,0 +1,418 @@
+//! Stack-and-heap pictures for the lessons, drawn as text.
+//!
+//! The book points at a figure whenever a buffer moves or a reference is
+//! left dangling. A `Diagram` describes the same picture: stack variables
+//! top to bottom, what each owns or points at, and heap buffers with their
+//! contents. It renders as box-drawing characters, or plain ASCII for
+//! terminals that can't show them.
+//!
+//! ```
+//! use references_sharing_vs_mutation::diagram::{Diagram, Style, Target};
+//!
+//! let d = Diagram::new()
+//!     .stack_var("wave")
+//!     .heap_buffer(4)
+//!     .values(&["0.0", "1.0"])
+//!     .ptr("slice", Target::Buffer(0));
+//! assert_eq!(
+//!     d.render(Style::Ascii),
+//!     "\
+//! stack               heap
+//! +-------+
+//! | wave  |---------> #0 [ 0.0 | 1.0 |     |     ]
+//! +-------+
+//! | slice |----&----> #0
+//! +-------+
+//! "
+//! );
+//! ```
+
+use std::fmt;
+use std::iter;
+
+/// Which characters to draw with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Ascii,
+    Unicode,
+}
+
+/// What a reference points at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target<'a> {
+    /// A stack variable, or a field of one such as `"v.0"`.
+    Var(&'a str),
+    /// A heap buffer, numbered in the order `heap_buffer` added them.
+    Buffer(usize),
+}
+
+/// A picture of some stack variables and the heap buffers they reach.
+///
+/// Built with chained calls that each describe one more piece; methods that
+/// refine "the last" variable or buffer panic if there isn't one yet, and
+/// references panic if their target doesn't exist.
+#[derive(Debug, Clone, Default)]
+pub struct Diagram {
+    caption: Option<String>,
+    rows: Vec<Row>,
+    buffers: Vec<Buffer>,
+}
+
+#[derive(Debug, Clone)]
+struct Row {
+    name: String,
+    value: Option<String>,
+    link: Option<(Link, OwnedTarget)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Link {
+    Owns,
+    Shared,
+    Mut,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum OwnedTarget {
+    Var(String),
+    Buffer(usize),
+}
+
+#[derive(Debug, Clone)]
+struct Buffer {
+    capacity: usize,
+    values: Vec<String>,
+    freed: bool,
+}
+
+struct Glyphs {
+    horizontal: char,
+    vertical: char,
+    corner_top: (char, char),
+    corner_middle: (char, char),
+    corner_bottom: (char, char),
+    head: char,
+}
+
+const ASCII: Glyphs = Glyphs {
+    horizontal: '-',
+    vertical: '|',
+    corner_top: ('+', '+'),
+    corner_middle: ('+', '+'),
+    corner_bottom: ('+', '+'),
+    head: '>',
+};
+
+const UNICODE: Glyphs = Glyphs {
+    horizontal: '─',
+    vertical: '│',
+    corner_top: ('┌', '┐'),
+    corner_middle: ('├', '┤'),
+    corner_bottom: ('└', '┘'),
+    head: '▶',
+};
+
+/// Width of an arrow, head included, so every row's targets line up.
+const ARROW_WIDTH: usize = 10;
+
+impl Diagram {
+    pub fn new() -> Diagram {
+        Diagram::default()
+    }
+
+    /// Sets a line to print above the picture.
+    pub fn caption(mut self, caption: &str) -> Diagram {
+        self.caption = Some(caption.to_owned());
+        self
+    }
+
+    /// Adds a stack variable below the ones already there.
+    pub fn stack_var(mut self, name: &str) -> Diagram {
+        self.rows.push(Row {
+            name: name.to_owned(),
+            value: None,
+            link: None,
+        });
+        self
+    }
+
+    /// Shows a value inside the last stack variable's box, as
+    /// `name = value`.
+    pub fn value(mut self, value: &str) -> Diagram {
+        self.last_row().value = Some(value.to_owned());
+        self
+    }
+
+    /// Adds an empty heap buffer with room for `capacity` elements.
+    ///
+    /// If the last stack variable doesn't point at anything yet it owns the
+    /// new buffer, the way a `Vec` does. Otherwise the buffer stands on its
+    /// own, for references to point at.
+    pub fn heap_buffer(mut self, capacity: usize) -> Diagram {
+        let index = self.buffers.len();
+        self.buffers.push(Buffer {
+            capacity,
+            values: Vec::new(),
+            freed: false,
+        });
+        if let Some(row) = self.rows.last_mut() {
+            if row.link.is_none() {
+                row.link = Some((Link::Owns, OwnedTarget::Buffer(index)));
+            }
+        }
+        self
+    }
+
+    /// Fills the first elements of the last heap buffer.
+    ///
+    /// Panics if there are more values than the buffer has room for.
+    pub fn values(mut self, values: &[&str]) -> Diagram {
+        let buffer = self.last_buffer();
+        assert!(
+            values.len() <= buffer.capacity,
+            "{} values don't fit in a buffer of {}",
+            values.len(),
+            buffer.capacity
+        );
+        buffer.values = values.iter().map(|&v| v.to_owned()).collect();
+        self
+    }
+
+    /// Marks the last heap buffer as freed, for references left pointing at
+    /// it.
+    pub fn freed(mut self) -> Diagram {
+        self.last_buffer().freed = true;
+        self
+    }
+
+    /// Adds a stack variable holding a shared reference to `target`.
+    pub fn ptr(self, name: &str, target: Target) -> Diagram {
+        self.reference(name, Link::Shared, target)
+    }
+
+    /// Adds a stack variable holding a mutable reference to `target`.
+    pub fn mut_ptr(self, name: &str, target: Target) -> Diagram {
+        self.reference(name, Link::Mut, target)
+    }
+
+    fn reference(mut self, name: &str, link: Link, target: Target) -> Diagram {
+        let target = match target {
+            Target::Var(path) => {
+                let root = path.split('.').next().unwrap_or(path);
+                assert!(
+                    self.rows.iter().any(|row| row.name == root),
+                    "no stack variable `{}`",
+                    root
+                );
+                OwnedTarget::Var(path.to_owned())
+            }
+            Target::Buffer(index) => {
+                assert!(index < self.buffers.len(), "no heap buffer #{}", index);
+                OwnedTarget::Buffer(index)
+            }
+        };
+        self.rows.push(Row {
+            name: name.to_owned(),
+            value: None,
+            link: Some((link, target)),
+        });
+        self
+    }
+
+    fn last_row(&mut self) -> &mut Row {
+        self.rows.last_mut().expect("no stack variable yet")
+    }
+
+    fn last_buffer(&mut self) -> &mut Buffer {
+        self.buffers.last_mut().expect("no heap buffer yet")
+    }
+
+    /// Draws the diagram, one line per row of the picture, each ending in
+    /// a newline.
+    pub fn render(&self, style: Style) -> String {
+        let glyphs = match style {
+            Style::Ascii => &ASCII,
+            Style::Unicode => &UNICODE,
+        };
+        let labels: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| match row.value {
+                Some(ref value) => format!("{} = {}", row.name, value),
+                None => row.name.clone(),
+            })
+            .collect();
+        let width = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let border = |(left, right): (char, char)| {
+            let rule: String = iter::repeat_n(glyphs.horizontal, width + 2).collect();
+            format!("{}{}{}\n", left, rule, right)
+        };
+
+        let mut out = String::new();
+        if let Some(ref caption) = self.caption {
+            out.push_str(caption);
+            out.push('\n');
+        }
+        // Targets start after the box, the arrow and a space.
+        let indent = width + 4 + ARROW_WIDTH + 1;
+        out.push_str(&format!("{:w$}heap\n", "stack", w = indent));
+
+        let mut drawn = vec![false; self.buffers.len()];
+        for (i, (row, label)) in self.rows.iter().zip(&labels).enumerate() {
+            out.push_str(&border(if i == 0 {
+                glyphs.corner_top
+            } else {
+                glyphs.corner_middle
+            }));
+            out.push_str(&format!(
+                "{v} {:w$} {v}",
+                label,
+                v = glyphs.vertical,
+                w = width
+            ));
+            if let Some((link, ref target)) = row.link {
+                out.push_str(&arrow(glyphs, link));
+                out.push(' ');
+                match *target {
+                    OwnedTarget::Var(ref path) => out.push_str(path),
+                    OwnedTarget::Buffer(index) => {
+                        out.push_str(&self.buffer(glyphs, index, !drawn[index]));
+                        drawn[index] = true;
+                    }
+                }
+            }
+            out.push('\n');
+        }
+        if !self.rows.is_empty() {
+            out.push_str(&border(glyphs.corner_bottom));
+        }
+        let undrawn = drawn.iter().enumerate().filter(|&(_, &drawn)| !drawn);
+        for (index, _) in undrawn {
+            out.push_str(&format!("{:w$}", "", w = indent));
+            out.push_str(&self.buffer(glyphs, index, true));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// `#n`, followed by the buffer's cells the first time it's drawn.
+    fn buffer(&self, glyphs: &Glyphs, index: usize, full: bool) -> String {
+        let mut s = format!("#{}", index);
+        if !full {
+            return s;
+        }
+        let buffer = &self.buffers[index];
+        let cell = buffer
+            .values
+            .iter()
+            .map(|v| v.chars().count())
+            .max()
+            .unwrap_or(1);
+        let cells: Vec<String> = (0..buffer.capacity)
+            .map(|i| {
+                let value = buffer.values.get(i).map_or("", |v| v.as_str());
+                format!("{:>w$}", value, w = cell)
+            })
+            .collect();
+        let separator = format!(" {} ", glyphs.vertical);
+        s.push_str(&format!(" [ {} ]", cells.join(&separator)));
+        if buffer.freed {
+            s.push_str(" freed");
+        }
+        s
+    }
+}
+
+/// A fixed-width arrow, labelled with the kind of reference.
+fn arrow(glyphs: &Glyphs, link: Link) -> String {
+    let label = match link {
+        Link::Owns => "",
+        Link::Shared => "&",
+        Link::Mut => "&mut",
+    };
+    let shaft = ARROW_WIDTH - 1 - label.len();
+    let before = shaft / 2;
+    let line = |n| -> String { iter::repeat_n(glyphs.horizontal, n).collect() };
+    format!(
+        "{}{}{}{}",
+        line(before),
+        label,
+        line(shaft - before),
+        glyphs.head
+    )
+}
+
+impl fmt::Display for Diagram {
+    /// Draws the diagram with box-drawing characters.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render(Style::Unicode))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_a_dangling_reference() {
+        let d = Diagram::new()
+            .caption("after the push reallocates")
+            .stack_var("wave")
+            .heap_buffer(3)
+            .values(&["0", "1", "-1"])
+            .heap_buffer(2)
+            .values(&["0"])
+            .freed()
+            .ptr("slice", Target::Buffer(1))
+            .mut_ptr("m", Target::Var("wave"));
+        assert_eq!(
+            d.to_string(),
+            "\
+after the push reallocates
+stack               heap
+┌───────┐
+│ wave  │─────────▶ #0 [  0 │  1 │ -1 ]
+├───────┤
+│ slice │────&────▶ #1 [ 0 │   ] freed
+├───────┤
+│ m     │──&mut───▶ wave
+└───────┘
+"
+        );
+    }
+
+    #[test]
+    fn lists_unreferenced_buffers_under_the_heap() {
+        let d = Diagram::new()
+            .stack_var("v")
+            .value("moved")
+            .stack_var("aside")
+            .heap_buffer(1)
+            .values(&["4"])
+            .heap_buffer(2);
+        assert_eq!(
+            d.render(Style::Ascii),
+            "\
+stack                   heap
++-----------+
+| v = moved |
++-----------+
+| aside     |---------> #0 [ 4 ]
++-----------+
+                        #1 [   |   ]
+"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "no stack variable `w`")]
+    fn rejects_unknown_targets() {
+        Diagram::new().stack_var("v").ptr("r", Target::Var("w.0"));
+    }
+
+    #[test]
+    #[should_panic(expected = "3 values don't fit in a buffer of 2")]
+    fn rejects_overfull_buffers() {
+        Diagram::new().heap_buffer(2).values(&["a", "b", "c"]);
+    }
+}
@@ -0,0 +1,208 @@
+//! What changed between a rejected example and its fix, as a line diff.
+//!
+//! Most fixes in the notes move a line or two: a read before a move, a
+//! borrow into a block, a copy taken before a push. [`diff`] finds the
+//! lines the two programs share, as the longest common subsequence of
+//! their lines, and marks the rest removed or added, and [`unified`] prints
+//! that the way `diff -u` does, in red and green on a terminal. `fix
+//! <lesson>` shows each of a lesson's rejected examples against the
+//! compiling one after it.
+//!
+//! ```
+//! use references_sharing_vs_mutation::diffview::{diff, unified};
+//!
+//! let rejected = "let r = &v;\nlet aside = v;\nprintln!(\"{}\", r[0]);\n";
+//! let fixed = "let r = &v;\nlet first = r[0];\nlet aside = v;\n";
+//! assert_eq!(
+//!     unified(&diff(rejected, fixed), false),
+//!     " let r = &v;\n+let first = r[0];\n let aside = v;\n-println!(\"{}\", r[0]);\n"
+//! );
+//! ```
+
+use std::fmt::Write;
+
+use crate::catalog::error_codes;
+use crate::cli::{Example, Lesson, Outcome};
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// One line of a diff.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Line<'a> {
+    /// In both programs.
+    Same(&'a str),
+    /// Only in the first.
+    Removed(&'a str),
+    /// Only in the second.
+    Added(&'a str),
+}
+
+/// The lines of `old` and `new`, in order, each marked as in both or only
+/// one. Where lines are both removed and added in one place, the removed
+/// ones come first.
+pub fn diff<'a>(old: &'a str, new: &'a str) -> Vec<Line<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    diff_lines(&old, &new)
+}
+
+/// `diff`, on programs already split into lines.
+pub fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Line<'a>> {
+    // common[i][j] is the length of the longest common subsequence of
+    // old[i..] and new[j..].
+    let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(Line::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            lines.push(Line::Removed(old[i]));
+            i += 1;
+        } else {
+            lines.push(Line::Added(new[j]));
+            j += 1;
+        }
+    }
+    lines.extend(old[i..].iter().map(|l| Line::Removed(l)));
+    lines.extend(new[j..].iter().map(|l| Line::Added(l)));
+    lines
+}
+
+/// `lines` one to a line, marked ` `, `-` or `+`, with the removed lines
+/// in red and the added ones in green if `color`.
+pub fn unified(lines: &[Line], color: bool) -> String {
+    let mut out = String::new();
+    for line in lines {
+        let (mark, text, paint) = match *line {
+            Line::Same(text) => (' ', text, ""),
+            Line::Removed(text) => ('-', text, RED),
+            Line::Added(text) => ('+', text, GREEN),
+        };
+        if color && !paint.is_empty() {
+            let _ = writeln!(out, "{}{}{}{}", paint, mark, text, RESET);
+        } else {
+            let _ = writeln!(out, "{}{}", mark, text);
+        }
+    }
+    out
+}
+
+/// Each of the lesson's rejected examples with the first compiling one
+/// after it, which is its fix.
+pub fn fixes(lesson: &Lesson) -> Vec<(&Example, &Example)> {
+    let examples = lesson.examples;
+    examples
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| !e.compiles())
+        .filter_map(|(i, rejected)| {
+            let fixed = examples[i + 1..]
+                .iter()
+                .find(|e| matches!(e.outcome, Outcome::Compiles))?;
+            Some((rejected, fixed))
+        })
+        .collect()
+}
+
+/// Every rejected example in `lesson` diffed against its fix, under a
+/// heading naming the errors it fixes. The leading comments the examples
+/// carry for the test suite are left out, as the tour leaves them out.
+pub fn lesson_fixes(lesson: &Lesson, color: bool) -> String {
+    let (bold, reset) = if color { (BOLD, RESET) } else { ("", "") };
+    let mut out = String::new();
+    for (rejected, fixed) in fixes(lesson) {
+        let codes: Vec<&str> = match rejected.outcome {
+            Outcome::Rejected(stderr) => error_codes(stderr).collect(),
+            _ => Vec::new(),
+        };
+        let old: Vec<&str> = rejected.numbered_lines().map(|(_, l)| l).collect();
+        let new: Vec<&str> = fixed.numbered_lines().map(|(_, l)| l).collect();
+        let _ = writeln!(
+            out,
+            "{}--- rejected: {}\n+++ fixed{}",
+            bold,
+            codes.join(", "),
+            reset
+        );
+        out.push_str(&unified(&diff_lines(&old, &new), color));
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{find_lesson, LESSONS};
+
+    #[test]
+    fn keeps_the_longest_run_of_shared_lines() {
+        let lines = diff("a\nb\nc\nd\n", "a\nc\nd\ne\n");
+        assert_eq!(
+            lines,
+            [
+                Line::Same("a"),
+                Line::Removed("b"),
+                Line::Same("c"),
+                Line::Same("d"),
+                Line::Added("e"),
+            ]
+        );
+        assert_eq!(diff("", "x"), [Line::Added("x")]);
+        assert_eq!(diff("x", ""), [Line::Removed("x")]);
+        assert_eq!(diff("x\ny", "x\ny"), [Line::Same("x"), Line::Same("y")]);
+    }
+
+    #[test]
+    fn removes_before_adding_a_changed_line() {
+        assert_eq!(
+            diff("a\nb\nc", "a\nB\nc"),
+            [
+                Line::Same("a"),
+                Line::Removed("b"),
+                Line::Added("B"),
+                Line::Same("c"),
+            ]
+        );
+    }
+
+    #[test]
+    fn colors_only_changed_lines() {
+        let lines = [Line::Same("a"), Line::Removed("b"), Line::Added("c")];
+        assert_eq!(
+            unified(&lines, true),
+            " a\n\x1b[31m-b\x1b[0m\n\x1b[32m+c\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn every_lesson_has_a_fix_to_show() {
+        for lesson in LESSONS {
+            assert!(!fixes(lesson).is_empty(), "{}", lesson.name);
+        }
+    }
+
+    #[test]
+    fn shows_the_nll_fix() {
+        let out = lesson_fixes(find_lesson("nll").unwrap(), false);
+        assert!(out.starts_with("--- rejected: E0505\n+++ fixed\n fn main() {\n"));
+        assert!(out.contains("\n+    let first = r[0];\n"));
+        assert_eq!(out.matches("--- rejected").count(), 3);
+    }
+}
@@ -0,0 +1,166 @@
+//! The borrow checker's error codes, and the lessons that get each.
+//!
+//! Someone who reaches these notes from a compiler error has a code, not a
+//! lesson name. [`CODES`] says in the notes' terms what each code the
+//! rejected examples get means, and [`lessons_for`] finds the lessons whose
+//! rejected examples get it, from their `.stderr` files, so the index can't
+//! fall behind them. `explain <code>` prints the whole entry with the first
+//! example that gets it:
+//!
+//! ```
+//! use references_sharing_vs_mutation::errors;
+//!
+//! let code = errors::find("e0505").unwrap();
+//! assert_eq!(code.code, "E0505");
+//! assert_eq!(errors::lessons_for(code.code)[0].name, "dangling");
+//! ```
+
+use std::fmt::Write;
+
+use crate::catalog::{self, error_codes};
+use crate::cli::{Lesson, Outcome, LESSONS};
+
+/// One error code.
+#[derive(Debug, PartialEq)]
+pub struct ErrorCode {
+    /// As rustc prints it: `"E0502"`.
+    pub code: &'static str,
+    /// What rustc's message says, with the names left out.
+    pub message: &'static str,
+    /// What it means, in the notes' terms.
+    pub explanation: &'static str,
+}
+
+/// Every code the rejected examples get, in numeric order. A test checks
+/// none is missing.
+pub static CODES: &[ErrorCode] = &[
+    ErrorCode {
+        code: "E0382",
+        message: "borrow of moved value",
+        explanation: "The value was moved, so the variable no longer holds one to borrow. A \
+`&mut` isn't `Copy`, so passing one where the type isn't known to be a reference moves it too; \
+reborrowing with `&mut *r` passes a fresh loan instead.",
+    },
+    ErrorCode {
+        code: "E0499",
+        message: "cannot borrow as mutable more than once at a time",
+        explanation: "Mutable access is exclusive: while one `&mut` is live, nothing else may \
+reach the value, including a second `&mut`. End the first borrow before taking the second, or \
+split the value so each borrow is of a different part.",
+    },
+    ErrorCode {
+        code: "E0502",
+        message: "cannot borrow as mutable because it is also borrowed as immutable",
+        explanation: "A shared reference makes its referent read-only for as long as it's \
+live, and a mutable borrow would let it change under the reader. Finish with the shared \
+reference first, or copy out what it was needed for.",
+    },
+    ErrorCode {
+        code: "E0503",
+        message: "cannot use because it was mutably borrowed",
+        explanation: "While a `&mut` is live it's the only way to the value; using the value \
+directly would go around it. Use it through the reference, or after the reference's last use.",
+    },
+    ErrorCode {
+        code: "E0505",
+        message: "cannot move out because it is borrowed",
+        explanation: "Being read-only includes not being moved: a move would leave a live \
+reference pointing at an emptied variable. Use the reference before the move, or move a clone.",
+    },
+    ErrorCode {
+        code: "E0506",
+        message: "cannot assign because it is borrowed",
+        explanation: "A shared reference freezes its referent, so the variable can't be \
+assigned while the reference is live. Read through the reference first, or put the value in a \
+`Cell` to assign through shared references.",
+    },
+    ErrorCode {
+        code: "E0515",
+        message: "cannot return reference to local variable",
+        explanation: "A reference can't outlive its referent, and a local is dropped when the \
+function returns. Return the owned value instead.",
+    },
+    ErrorCode {
+        code: "E0596",
+        message: "cannot borrow as mutable, as it is behind a `&` reference",
+        explanation: "Shared access is read-only all the way down: nothing reached through a \
+`&` can be borrowed mutably. Take a `&mut` to begin with, or use a `RefCell` where the value \
+really is shared.",
+    },
+];
+
+/// The entry for `code`, ignoring case.
+pub fn find(code: &str) -> Option<&'static ErrorCode> {
+    CODES.iter().find(|c| c.code.eq_ignore_ascii_case(code))
+}
+
+/// The lessons with a rejected example that gets `code`, in tour order.
+pub fn lessons_for(code: &str) -> Vec<&'static Lesson> {
+    LESSONS
+        .iter()
+        .filter(|lesson| {
+            lesson.examples.iter().any(|e| match e.outcome {
+                Outcome::Rejected(stderr) => error_codes(stderr).any(|c| c == code),
+                _ => false,
+            })
+        })
+        .collect()
+}
+
+/// `code`'s entry as text: what it means, the lessons to read, and the
+/// first example that gets it, with rustc's output.
+pub fn explain(code: &ErrorCode) -> String {
+    let mut out = format!("{}: {}\n\n", code.code, code.message);
+    let _ = writeln!(out, "{}\n", code.explanation);
+    out.push_str("Lessons:\n");
+    for lesson in lessons_for(code.code) {
+        let _ = writeln!(out, "  {:<16} {}", lesson.name, lesson.title);
+    }
+    if let Some(snippet) = catalog::by_tag(code.code).first() {
+        let _ = write!(out, "\nFrom {}:\n\n{}", snippet.id, snippet.code);
+        if let Some(error) = &snippet.rustc_error {
+            let _ = write!(out, "\n{}\n", error);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_code_the_examples_get_has_an_entry() {
+        for snippet in catalog::all() {
+            for code in snippet.tags.iter().filter(|t| t.starts_with('E')) {
+                assert!(find(code).is_some(), "{} gets {}", snippet.id, code);
+            }
+        }
+    }
+
+    #[test]
+    fn every_entry_has_a_lesson() {
+        for code in CODES {
+            assert!(!lessons_for(code.code).is_empty(), "{}", code.code);
+        }
+        let mut sorted: Vec<&str> = CODES.iter().map(|c| c.code).collect();
+        sorted.sort_unstable();
+        assert!(CODES.iter().map(|c| c.code).eq(sorted));
+    }
+
+    #[test]
+    fn finds_codes_in_any_case() {
+        assert_eq!(find("E0502").map(|c| c.code), Some("E0502"));
+        assert_eq!(find("e0502").map(|c| c.code), Some("E0502"));
+        assert_eq!(find("E0000"), None);
+    }
+
+    #[test]
+    fn explains_with_the_first_example() {
+        let text = explain(find("E0499").unwrap());
+        assert!(text.starts_with("E0499: cannot borrow as mutable more than once at a time\n\n"));
+        assert!(text.contains("\nLessons:\n  exclusive        "));
+        assert!(text.contains("\nFrom exclusive/2:\n\nfn main() {\n"));
+        assert!(text.contains("\nerror[E0499]: cannot borrow "));
+    }
+}
@@ -0,0 +1,1002 @@
+//! Questions for checking that a lesson sank in.
+//!
+//! Each lesson in `cli::LESSONS` has two or three [`Exercise`]s, found by
+//! [`for_lesson`] or all together in [`EXERCISES`]. A question is either
+//! "will this compile?" about a short program or a multiple choice, and
+//! [`Exercise::is_correct`] marks an answer. Each also has hints to give
+//! out one at a time and the wrong answer it most often gets, for a
+//! workshop's answer key. [`Rng`] shuffles questions and choices so a class
+//! can't pass answers along by position.
+
+/// A question and what makes its answer right.
+#[derive(Debug)]
+pub struct Exercise {
+    /// The `cli::Lesson` it belongs to.
+    pub lesson: &'static str,
+    /// Names it for good, whatever is added or moved around it, for the
+    /// flashcards to make GUIDs from. A test checks they're unique.
+    pub id: &'static str,
+    pub question: Question,
+    /// Shown after the answer, right or wrong.
+    pub explanation: &'static str,
+    /// Two or three nudges towards the answer, each giving away more than
+    /// the one before, for a learner to ask for one at a time.
+    pub hints: &'static [&'static str],
+    /// The wrong answer learners most often give, and what leads them to
+    /// it, for an instructor's answer key.
+    pub pitfall: &'static str,
+}
+
+#[derive(Debug)]
+pub enum Question {
+    /// Does `code` get past the compiler? It's a whole program, or the
+    /// body of `main` after any `use`s, or items for a program whose
+    /// `main` does nothing.
+    Compiles { code: &'static str, answer: bool },
+    /// Which of `choices` answers `prompt`? `answer` indexes `choices`.
+    Choice {
+        prompt: &'static str,
+        choices: &'static [&'static str],
+        answer: usize,
+    },
+}
+
+/// A learner's answer to a `Question`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Answer {
+    Compiles(bool),
+    /// An index into the question's `choices`, as listed there rather
+    /// than as shown after shuffling.
+    Choice(usize),
+}
+
+impl Exercise {
+    /// Whether `answer` is the right one. An answer of the wrong kind for
+    /// the question is never right.
+    pub fn is_correct(&self, answer: Answer) -> bool {
+        match (&self.question, answer) {
+            (Question::Compiles { answer: right, .. }, Answer::Compiles(given)) => *right == given,
+            (Question::Choice { answer: right, .. }, Answer::Choice(given)) => *right == given,
+            _ => false,
+        }
+    }
+}
+
+/// The exercises for the lesson called `lesson`, in the order listed.
+pub fn for_lesson(lesson: &str) -> impl Iterator<Item = &'static Exercise> + '_ {
+    EXERCISES.iter().filter(move |e| e.lesson == lesson)
+}
+
+/// A small xorshift generator, good enough for shuffling quiz questions
+/// and not for anything that needs real randomness.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// A generator that produces the same shuffles for the same seed.
+    pub fn new(seed: u64) -> Rng {
+        // Zero is xorshift's one fixed point; `| 1` keeps clear of it.
+        Rng {
+            state: seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1,
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+
+    /// A number in `0..bound`. `bound` must not be zero.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    /// Puts `items` in a random order (Fisher–Yates).
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+/// Every exercise, grouped by lesson in lesson order.
+pub static EXERCISES: &[Exercise] = &[
+    Exercise {
+        lesson: "dangling",
+        id: "move-while-borrowed",
+        question: Question::Compiles {
+            code: "let v = vec![1, 2, 3];
+let r = &v;
+let w = v;
+println!(\"{}\", w[0]);",
+            answer: true,
+        },
+        explanation: "`r` is never used after the move, so its lifetime ends where it was \
+created and nothing is left pointing at `v` when the vector moves to `w`.",
+        hints: &[
+            "Where is `r` last used?",
+            "A reference's lifetime ends at its last use, not at the end of its scope.",
+        ],
+        pitfall: "No, because `r` is still in scope when `v` moves. Scope doesn't decide; use \
+does.",
+    },
+    Exercise {
+        lesson: "dangling",
+        id: "why-move-is-rejected",
+        question: Question::Choice {
+            prompt: "Why is `let aside = v;` rejected while `r = &v` is still in use?",
+            choices: &[
+                "The move would leave `r` pointing at an uninitialized variable",
+                "Vectors can never be moved",
+                "`r` is a mutable reference",
+                "`aside` has a different type from `v`",
+            ],
+            answer: 0,
+        },
+        explanation: "A shared reference makes its referent read-only for its whole \
+lifetime, and that includes not moving it out.",
+        hints: &[
+            "What would `r` point at after `v` moves?",
+            "Think about what a shared reference promises about its referent.",
+        ],
+        pitfall: "\"`r` is a mutable reference\": it's a shared one, and shared references \
+forbid moves too.",
+    },
+    Exercise {
+        lesson: "reborrow",
+        id: "reborrow-field-of-shared",
+        question: Question::Compiles {
+            code: "let w = (107, 109);
+let r = &w;
+let r0 = &r.0;
+println!(\"{:?} {}\", r, r0);",
+            answer: true,
+        },
+        explanation: "Borrowing a shared reference from a shared reference is always fine; \
+everything stays read-only.",
+        hints: &[
+            "Is anything here mutable?",
+            "`r0` borrows through `r`; what kind of borrow is each?",
+        ],
+        pitfall: "No, from thinking `r0` conflicts with `r`. Two shared borrows never \
+conflict.",
+    },
+    Exercise {
+        lesson: "reborrow",
+        id: "shared-field-of-mut-reborrow",
+        question: Question::Compiles {
+            code: "let mut v = (136, 139);
+let m = &mut v;
+let m0 = &mut m.0;
+let r1 = &m.1;
+*m0 += *r1;
+println!(\"{}\", v.1);",
+            answer: true,
+        },
+        explanation: "`m0` and `r1` cover different fields, so they may coexist. By the time \
+`v.1` is read directly, `m`, `m0` and `r1` are all finished with.",
+        hints: &[
+            "Which parts of `v` do `m0` and `r1` each cover?",
+            "When is `m` last used, compared with the read of `v.1`?",
+        ],
+        pitfall: "No, from reading `&m.1` as a borrow of all of `m` while `m0` is live. A \
+borrow through a field covers only that field.",
+    },
+    Exercise {
+        lesson: "reborrow",
+        id: "what-shared-reference-lends",
+        question: Question::Choice {
+            prompt: "What may you borrow through a shared reference `r: &(i32, i32)`?",
+            choices: &[
+                "Only more shared references",
+                "Shared or mutable references",
+                "Only mutable references",
+                "Nothing; shared references can't be reborrowed",
+            ],
+            answer: 0,
+        },
+        explanation: "A mutable reference borrowed through `r` would make a frozen value \
+writable, so only shared reborrows are allowed.",
+        hints: &[
+            "What would a `&mut` borrowed through `r` let you do to the tuple?",
+            "Everything reached through a `&` is read-only.",
+        ],
+        pitfall: "\"Shared or mutable references\", by analogy with `&mut`, which allows \
+both. A `&mut` through a `&` would undo the freeze.",
+    },
+    Exercise {
+        lesson: "exclusive",
+        id: "extend-from-itself",
+        question: Question::Compiles {
+            code: "use references_sharing_vs_mutation::vecops::extend;
+
+let mut wave = vec![0.0, 1.0];
+extend(&mut wave, &wave);",
+            answer: false,
+        },
+        explanation: "`&mut wave` must be the only way to reach the vector while it's \
+live, and `&wave` is another.",
+        hints: &[
+            "How many ways to reach `wave` are live during the call?",
+            "`&mut` means the only way, for as long as it's live.",
+            "`&wave` is a second way in, live for the whole call.",
+        ],
+        pitfall: "Yes, from thinking reading from and writing to the same vector is harmless. \
+The slice may be freed by a reallocation part way through.",
+    },
+    Exercise {
+        lesson: "exclusive",
+        id: "when-aliased-extend-breaks",
+        question: Question::Choice {
+            prompt: "In a language that allowed `extend(&mut wave, &wave)`, when would it \
+go wrong?",
+            choices: &[
+                "Only when a push has to reallocate the buffer",
+                "On every call",
+                "Never; it would just be slow",
+                "Only for vectors of floating-point numbers",
+            ],
+            answer: 0,
+        },
+        explanation: "A reallocation moves the elements and frees the old buffer the slice \
+still points into. Whether that happens depends on the capacity, which is why testing \
+can miss it.",
+        hints: &[
+            "What does `push` do when the vector is full?",
+            "Where does the slice point after the buffer moves?",
+        ],
+        pitfall: "\"On every call\": a push only moves the buffer when the capacity runs out, \
+which is how the bug hides in testing.",
+    },
+    Exercise {
+        lesson: "exclusive",
+        id: "shared-after-last-mut-use",
+        question: Question::Compiles {
+            code: "let mut x = 10;
+let m = &mut x;
+*m += 1;
+let r = &x;
+println!(\"{}\", r);",
+            answer: true,
+        },
+        explanation: "`m` is last used before `r` is created, so the two lifetimes never \
+overlap.",
+        hints: &[
+            "When is `m` last used?",
+            "Do the lifetimes of `m` and `r` overlap?",
+        ],
+        pitfall: "No, from thinking `m` lasts to the end of the block. It ends at `*m += 1`.",
+    },
+    Exercise {
+        lesson: "self_assignment",
+        id: "clone-from-itself",
+        question: Question::Compiles {
+            code: "use references_sharing_vs_mutation::fd::File;
+
+let mut f = File::open(\"/dev/null\").unwrap();
+f.clone_from(&f);",
+            answer: false,
+        },
+        explanation: "`clone_from` takes `&mut self` and `&source`, and the same `f` can't \
+be borrowed both ways at once.",
+        hints: &[
+            "What does `clone_from` take `self` as, and `source` as?",
+            "Can `f` be borrowed as `&mut` and `&` at once?",
+        ],
+        pitfall: "Yes, because `f = f` is legal in C++ and looks harmless. Rust rejects the \
+overlapping borrows before it could run.",
+    },
+    Exercise {
+        lesson: "self_assignment",
+        id: "cpp-self-assignment",
+        question: Question::Choice {
+            prompt: "What goes wrong when the C++ `operator=` runs for `f = f`?",
+            choices: &[
+                "It closes the descriptor it's about to `dup`",
+                "It leaks the old descriptor",
+                "It `dup`s the descriptor twice",
+                "Nothing; self-assignment is harmless",
+            ],
+            answer: 0,
+        },
+        explanation: "`rhs` and `*this` are the same object, so `close(descriptor)` \
+destroys the very resource `dup(rhs.descriptor)` was meant to copy.",
+        hints: &[
+            "In `f = f`, what are `*this` and `rhs`?",
+            "What does `close` do to the descriptor `dup` reads next?",
+        ],
+        pitfall: "\"It leaks the old descriptor\": that's the bug of leaving the `close` out, \
+not the self-assignment one.",
+    },
+    Exercise {
+        lesson: "maps",
+        id: "insert-while-entry-held",
+        question: Question::Compiles {
+            code: "use std::collections::HashMap;
+
+fn main() {
+    let mut counts: HashMap<&str, u32> = HashMap::new();
+    let first = counts.entry(\"a\").or_insert(0);
+    counts.insert(\"b\", 1);
+    *first += 1;
+}",
+            answer: false,
+        },
+        explanation: "`or_insert` returns a `&mut u32` into the map, which keeps `counts` \
+mutably borrowed until `first` is last used. The `insert` in between is a second mutable \
+borrow of `counts`.",
+        hints: &[
+            "What type does `or_insert` return, and what does it borrow?",
+            "Is `first` used after the `insert`?",
+        ],
+        pitfall: "Yes, from thinking `first` is a value of its own. It's a `&mut` into the \
+map, live until `*first += 1`.",
+    },
+    Exercise {
+        lesson: "maps",
+        id: "insert-while-key-borrowed",
+        question: Question::Choice {
+            prompt: "Why does rustc refuse `map.insert(k, v)` while a `&String` key from \
+`map.keys()` is still live?",
+            choices: &[
+                "The insert may grow the table and move the key the reference points at",
+                "Keys are immutable once inserted",
+                "`insert` could replace that key's value",
+                "`HashMap` isn't `Sync`",
+            ],
+            answer: 0,
+        },
+        explanation: "Keys live in the map's table. Growing it moves every entry, like a \
+vector's buffer, so a mutable borrow of the map can't overlap a reference into it.",
+        hints: &[
+            "Where do a `HashMap`'s keys live?",
+            "What happens to a table's entries when it grows?",
+        ],
+        pitfall: "\"Keys are immutable once inserted\": they are, but the conflict is about \
+the key moving, not changing.",
+    },
+    Exercise {
+        lesson: "cell",
+        id: "cell-get-of-vec",
+        question: Question::Compiles {
+            code: "use std::cell::Cell;
+
+let wave = Cell::new(vec![0.0, 1.0]);
+let copy = wave.get();",
+            answer: false,
+        },
+        explanation: "`Cell::get` copies the value out, so it needs `T: Copy`, and `Vec` \
+isn't. `take` or `replace` move a non-`Copy` value out instead.",
+        hints: &["What does `Cell::get` need of `T`?", "Is `Vec` `Copy`?"],
+        pitfall: "Yes, from thinking `get` hands out a reference. It copies, so it needs `T: \
+Copy`.",
+    },
+    Exercise {
+        lesson: "cell",
+        id: "why-cell-set-takes-shared",
+        question: Question::Choice {
+            prompt: "Why can `Cell::set` take `&self` without breaking the rules?",
+            choices: &[
+                "A `Cell` never hands out references to its contents",
+                "It takes a lock around the write",
+                "It counts borrows at run time",
+                "It only works in single-threaded programs because of a runtime check",
+            ],
+            answer: 0,
+        },
+        explanation: "With no reference into the value anywhere, there's nothing a write \
+could invalidate.",
+        hints: &[
+            "What does a `Cell` never hand out?",
+            "With no references into the value, what could a write invalidate?",
+        ],
+        pitfall: "\"It counts borrows at run time\": that's `RefCell`. `Cell` needs no checks \
+at all.",
+    },
+    Exercise {
+        lesson: "refcell",
+        id: "borrow-mut-while-borrowed",
+        question: Question::Compiles {
+            code: "use std::cell::RefCell;
+
+let wave = RefCell::new(vec![0.0, 1.0]);
+let slice = wave.borrow();
+wave.borrow_mut().extend(slice.iter());",
+            answer: true,
+        },
+        explanation: "It compiles, because both borrows start from `&RefCell`. It panics \
+when it runs: the shared borrow is still out when `borrow_mut` is called.",
+        hints: &[
+            "Both borrows start from `&RefCell`; what does the compiler see?",
+            "Compiling and running without a panic are different questions.",
+            "When is the shared borrow's guard dropped?",
+        ],
+        pitfall: "No. The program is wrong, but `RefCell` moves the check to run time, so it \
+compiles and then panics.",
+    },
+    Exercise {
+        lesson: "refcell",
+        id: "borrow-mut-with-guard-alive",
+        question: Question::Choice {
+            prompt: "What does `borrow_mut` do while a `borrow()` guard is still alive?",
+            choices: &[
+                "Panics",
+                "Fails to compile",
+                "Waits for the guard to be dropped",
+                "Returns a copy of the value",
+            ],
+            answer: 0,
+        },
+        explanation: "`RefCell` enforces the same rules as the compiler, just at run time; \
+`try_borrow_mut` returns an error instead of panicking.",
+        hints: &[
+            "`RefCell` checks the same rules as the compiler; when?",
+            "What can a check at run time do when the rules are broken?",
+        ],
+        pitfall: "\"Fails to compile\": the compiler only sees two calls taking `&self`. The \
+conflict is found when it runs.",
+    },
+    Exercise {
+        lesson: "sync",
+        id: "rc-refcell-to-thread",
+        question: Question::Compiles {
+            code: "use std::cell::RefCell;
+use std::rc::Rc;
+use std::thread;
+
+let count = Rc::new(RefCell::new(0));
+thread::spawn(move || *count.borrow_mut() += 1);",
+            answer: false,
+        },
+        explanation: "`Rc` isn't `Send`: its reference count isn't atomic. The threaded \
+version is `Arc<Mutex<i32>>`.",
+        hints: &[
+            "What must a value be to move into another thread?",
+            "Is `Rc`'s reference count atomic?",
+        ],
+        pitfall: "Yes, since there's only one `Rc` here. `Send` goes by the type, and `Rc` \
+never is.",
+    },
+    Exercise {
+        lesson: "sync",
+        id: "mutex-relock",
+        question: Question::Choice {
+            prompt: "A thread calls `lock()` on a `Mutex` whose guard it already holds. \
+What happens?",
+            choices: &[
+                "It deadlocks or panics",
+                "It gets a second guard",
+                "The program fails to compile",
+                "`lock` returns an error",
+            ],
+            answer: 0,
+        },
+        explanation: "The second `lock` waits for the first guard to drop, which can't \
+happen while the same thread is waiting. `try_lock` reports the conflict instead.",
+        hints: &[
+            "What does `lock` wait for?",
+            "Can the guard be dropped while its thread is waiting?",
+        ],
+        pitfall: "\"It gets a second guard\": that would be two ways to write the data at \
+once, which is what the mutex is there to prevent.",
+    },
+    Exercise {
+        lesson: "rc",
+        id: "get-mut-on-shared-rc",
+        question: Question::Compiles {
+            code: "use std::rc::Rc;
+
+let mut a = Rc::new(vec![1]);
+let b = Rc::clone(&a);
+Rc::get_mut(&mut a).unwrap().push(2);
+println!(\"{:?}\", b);",
+            answer: true,
+        },
+        explanation: "It compiles, then panics: `get_mut` returns `None` while `b` is \
+another owner. The check that `a` isn't aliased happens at run time.",
+        hints: &[
+            "Does `Rc::get_mut` return a reference or an `Option`?",
+            "When is the check that `a` has no other owners made?",
+        ],
+        pitfall: "No. The aliasing is real, but `get_mut` checks for it at run time and \
+returns `None`, so it panics instead.",
+    },
+    Exercise {
+        lesson: "rc",
+        id: "make-mut-on-shared-rc",
+        question: Question::Choice {
+            prompt: "`a` and `b` are `Rc`s to the same vector. What does \
+`Rc::make_mut(&mut a).push(2)` do?",
+            choices: &[
+                "Copies the vector for `a`, then pushes onto the copy",
+                "Pushes onto the vector both share",
+                "Panics, because `b` is another owner",
+                "Fails to compile",
+            ],
+            answer: 0,
+        },
+        explanation: "With other owners `make_mut` clones the value so the writer has one \
+of its own; `b` still sees the original. With no others it doesn't copy at all.",
+        hints: &[
+            "What does `make_mut` do when it's the only owner?",
+            "And when there are others?",
+        ],
+        pitfall: "\"Pushes onto the vector both share\": that would change `b` behind its \
+back. `make_mut` clones first.",
+    },
+    Exercise {
+        lesson: "cow",
+        id: "return-one-of-two-borrows",
+        question: Question::Compiles {
+            code: "fn longest(a: &str, b: &str) -> &str {
+    if a.len() > b.len() { a } else { b }
+}",
+            answer: false,
+        },
+        explanation: "With two reference arguments rustc can't tell which one the result \
+borrows from, so it needs a named lifetime: `fn longest<'a>(a: &'a str, b: &'a str) -> \
+&'a str`. Returning a reference into an argument is fine once that's said.",
+        hints: &[
+            "Which argument does the result borrow from?",
+            "How would a caller know how long the result lives?",
+        ],
+        pitfall: "Yes, from lifetime elision, which only applies with a single reference \
+argument or `&self`.",
+    },
+    Exercise {
+        lesson: "cow",
+        id: "when-cow-borrowed-copies",
+        question: Question::Choice {
+            prompt: "`normalize_path` returns `Cow::Borrowed(path)` for a path that's \
+already normal. When is that path copied?",
+            choices: &[
+                "Only if the caller asks for it owned or mutable, with `into_owned` or `to_mut`",
+                "When `normalize_path` returns",
+                "When the caller reads it through `Deref`",
+                "Never; a borrowed `Cow` can't become owned",
+            ],
+            answer: 0,
+        },
+        explanation: "A borrowed `Cow` reads like the `&str` it holds. `into_owned` and \
+`to_mut` copy it into a `String` the first time they need one, and never before.",
+        hints: &[
+            "What does `Cow::Borrowed` hold?",
+            "Which of `Cow`'s methods need a `String`?",
+        ],
+        pitfall: "\"When the caller reads it through `Deref`\": reading a borrowed `Cow` \
+reads the `&str`, with no copy.",
+    },
+    Exercise {
+        lesson: "reborrowing",
+        id: "explicit-reborrow",
+        question: Question::Compiles {
+            code: "fn keep<T>(_: T) {}
+
+fn main() {
+    let mut v = vec![1];
+    let m = &mut v;
+    keep(&mut *m);
+    m.push(2);
+}",
+            answer: true,
+        },
+        explanation: "`&mut *m` is a new loan of `*m` for `keep` to take, which ends with \
+the call; `m` itself was never moved.",
+        hints: &[
+            "Is `m` moved into `keep`, or is something else passed?",
+            "When does the loan `&mut *m` end?",
+        ],
+        pitfall: "No, from thinking `keep` takes `m` itself. It takes a fresh reborrow, and \
+`m` is still usable.",
+    },
+    Exercise {
+        lesson: "reborrowing",
+        id: "two-phase-push-len",
+        question: Question::Choice {
+            prompt: "Why does `v.push(v.len())` compile when `push` needs `&mut v`?",
+            choices: &[
+                "The `&mut v` is only reserved until the arguments are evaluated",
+                "`v.len()` runs before `v` is borrowed at all",
+                "`len` copies `v` first",
+                "Method calls skip the borrow checker",
+            ],
+            answer: 0,
+        },
+        explanation: "The receiver's mutable borrow is two-phase: reserved while \
+`v.len()` reads `v`, and only used, exclusively, once `push` runs.",
+        hints: &[
+            "In what order are the receiver and the arguments evaluated?",
+            "Is the receiver's `&mut` exclusive from the moment it's taken?",
+        ],
+        pitfall: "\"`v.len()` runs before `v` is borrowed at all\": the borrow is taken \
+first, but only reserved until `push` runs.",
+    },
+    Exercise {
+        lesson: "reborrowing",
+        id: "ufcs-push-len",
+        question: Question::Compiles {
+            code: "let mut v = vec![1];
+Vec::push(&mut v, v.len());",
+            answer: false,
+        },
+        explanation: "Two-phase borrows only cover method-call receivers. Written out, \
+`&mut v` is a mutable borrow from the start, and `v.len()` overlaps it.",
+        hints: &[
+            "Is this a method call?",
+            "Which borrows get the two-phase treatment?",
+            "`v.push(v.len())` is the method-call version.",
+        ],
+        pitfall: "Yes, since `v.push(v.len())` compiles. Written as a function call, the \
+borrow isn't two-phase.",
+    },
+    Exercise {
+        lesson: "nll",
+        id: "push-after-last-use",
+        question: Question::Compiles {
+            code: "let mut v = vec![1, 2];
+let first = &v[0];
+println!(\"{}\", first);
+v.push(3);",
+            answer: true,
+        },
+        explanation: "`first` is last used by the `println!`, so its borrow is over before \
+`push` needs `v` mutably, though `first` is still in scope.",
+        hints: &[
+            "Where is `first` last used?",
+            "Does being in scope keep a borrow alive?",
+        ],
+        pitfall: "No, from thinking `first` borrows `v` to the end of the block. The borrow \
+ends after the `println!`.",
+    },
+    Exercise {
+        lesson: "nll",
+        id: "drop-guard-borrow-end",
+        question: Question::Choice {
+            prompt: "`guard` borrows `v` and its type implements `Drop`. Where does the \
+borrow end if `guard` isn't used again?",
+            choices: &[
+                "At the end of `guard`'s scope, when it's dropped",
+                "Right after the line that made `guard`",
+                "At the end of the function, whatever the scope",
+                "When `v` is next used",
+            ],
+            answer: 0,
+        },
+        explanation: "The destructor may use the borrow, so the drop at the end of the \
+scope is its last use. `drop(guard)` ends it sooner.",
+        hints: &[
+            "What runs when `guard` goes out of scope?",
+            "Could that code use the borrow?",
+        ],
+        pitfall: "\"Right after the line that made `guard`\": that's so for plain references, \
+but a destructor is a use.",
+    },
+    Exercise {
+        lesson: "selfref",
+        id: "move-struct-with-offset",
+        question: Question::Compiles {
+            code: "struct Parser {
+    text: String,
+    pos: usize,
+}
+
+fn main() {
+    let p = Parser { text: String::from(\"let x\"), pos: 4 };
+    let moved = p;
+    println!(\"{}\", &moved.text[moved.pos..]);
+}",
+            answer: true,
+        },
+        explanation: "An index borrows nothing, so the struct owns all it holds and can be \
+moved like any other value.",
+        hints: &[
+            "Does `Parser` hold any references?",
+            "What is `pos`, as a type?",
+        ],
+        pitfall: "No, from taking `pos` for a pointer into `text`. It's a number, and a move \
+doesn't change it.",
+    },
+    Exercise {
+        lesson: "selfref",
+        id: "why-pinned-parser-boxed",
+        question: Question::Choice {
+            prompt: "Why is `PinnedParser` handed out as a `Pin<Box<_>>`?",
+            choices: &[
+                "So safe code can't move it, or its text, away from the pointer into it",
+                "Because a `Box` is needed to use raw pointers",
+                "So it can be sent between threads",
+                "Pinning makes the borrow checker follow the pointer",
+            ],
+            answer: 0,
+        },
+        explanation: "The borrow checker doesn't follow a raw pointer, so the struct has to \
+keep what it points at in place itself. Pinning stops anyone getting the `&mut` that moving \
+it would need.",
+        hints: &[
+            "What would moving the struct do to a pointer into it?",
+            "What does moving a value out of a `Pin` need?",
+        ],
+        pitfall: "\"Pinning makes the borrow checker follow the pointer\": it doesn't. \
+Pinning only stops moves.",
+    },
+    Exercise {
+        lesson: "split",
+        id: "split-at-mut-halves",
+        question: Question::Compiles {
+            code: "let mut v = vec![1, 2, 3, 4];
+let (front, back) = v.split_at_mut(2);
+front[0] += back[1];
+back[0] += front[1];",
+            answer: true,
+        },
+        explanation: "`split_at_mut` gives two borrows of halves that can't overlap, so \
+both may be used at once.",
+        hints: &[
+            "Can `front` and `back` overlap?",
+            "How many borrows of `v` does `split_at_mut` take?",
+        ],
+        pitfall: "No, from thinking two `&mut`s into one vector always conflict. These are of \
+halves that can't overlap.",
+    },
+    Exercise {
+        lesson: "split",
+        id: "two-index-borrows",
+        question: Question::Compiles {
+            code: "let mut v = vec![1, 2, 3, 4];
+let first = &mut v[0];
+let last = &mut v[3];
+*first += *last;",
+            answer: false,
+        },
+        explanation: "Each index borrows all of `v`, so the second `&mut` overlaps the first. \
+`split::get_two_mut(&mut v, 0, 3)` checks the indices and borrows once.",
+        hints: &[
+            "What does `&mut v[0]` borrow, as the compiler sees it?",
+            "Does the compiler compare index values?",
+        ],
+        pitfall: "Yes, since indices 0 and 3 can't overlap. The compiler doesn't look at the \
+values, and each index borrows all of `v`.",
+    },
+    Exercise {
+        lesson: "split",
+        id: "method-call-while-iterating-field",
+        question: Question::Choice {
+            prompt: "Inside `impl Playlist`, why is `self.note(..)` rejected in a loop \
+over `&self.songs`, when `note` only pushes to `self.log`?",
+            choices: &[
+                "A method call borrows all of `self`, including `songs`",
+                "`songs` and `log` are stored in the same allocation",
+                "Loops hold a mutable borrow of their whole scope",
+                "`note` might reallocate `songs`",
+            ],
+            answer: 0,
+        },
+        explanation: "The compiler checks a call against its signature, `&mut self`, not \
+its body. Borrowing `self.log` directly is a borrow of that field alone.",
+        hints: &[
+            "Does the compiler check a call against the method's body or its signature?",
+            "What does `&mut self` borrow?",
+        ],
+        pitfall: "\"`note` might reallocate `songs`\": it doesn't touch `songs`, but the \
+signature borrows all of `self`.",
+    },
+    Exercise {
+        lesson: "perf",
+        id: "refcell-in-loop",
+        question: Question::Compiles {
+            code: "use std::cell::RefCell;
+
+let total = RefCell::new(0);
+for v in [3, 1, 4] {
+    *total.borrow_mut() += v;
+}",
+            answer: true,
+        },
+        explanation: "Each `borrow_mut` guard is dropped at the end of its statement, so \
+the borrows never overlap. The check runs, and passes, on every pass of the loop.",
+        hints: &[
+            "When is each `borrow_mut` guard dropped?",
+            "Do any two of the borrows overlap?",
+        ],
+        pitfall: "No, from thinking a loop of `borrow_mut`s holds several at once. Each \
+guard is a temporary, gone by the end of its statement.",
+    },
+    Exercise {
+        lesson: "perf",
+        id: "runtime-checked-borrow",
+        question: Question::Choice {
+            prompt: "Which of these checks the borrow rules while the program runs?",
+            choices: &[
+                "Borrowing from a `RefCell`",
+                "Borrowing two fields of a struct separately",
+                "Walking a slice with `iter_mut`",
+                "Passing a `&mut` to a function",
+            ],
+            answer: 0,
+        },
+        explanation: "A `RefCell` keeps a borrow flag and checks it on each borrow. The \
+compiler checks all the others, and they compile to plain pointer operations.",
+        hints: &[
+            "Which of these can fail while the program runs?",
+            "Which one keeps count of its borrows?",
+        ],
+        pitfall: "\"Walking a slice with `iter_mut`\", from expecting an iterator to cost \
+more than an index loop. It compiles to the same loop.",
+    },
+    Exercise {
+        lesson: "perf",
+        id: "push-while-iterating",
+        question: Question::Compiles {
+            code: "let mut v = vec![1, 2, 3];
+for x in &v {
+    if *x == 2 {
+        v.push(4);
+    }
+}",
+            answer: false,
+        },
+        explanation: "The loop borrows `v` for as long as it runs, and `push` needs it \
+mutably. Cloning `v` for the loop gets past the compiler at the cost of a copy; noting what \
+to push and pushing after the loop needs none.",
+        hints: &[
+            "How long does the loop's borrow of `v` last?",
+            "What does `push` need, and what might it do to the buffer?",
+        ],
+        pitfall: "Yes, since the push only happens once. The compiler goes by what the code \
+could do, and a push may move the buffer the loop is reading.",
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{find_lesson, LESSONS};
+
+    #[test]
+    fn every_lesson_has_two_or_three_exercises() {
+        for lesson in LESSONS {
+            let n = for_lesson(lesson.name).count();
+            assert!((2..=3).contains(&n), "{} has {}", lesson.name, n);
+        }
+        for exercise in EXERCISES {
+            assert!(
+                find_lesson(exercise.lesson).is_some(),
+                "{}",
+                exercise.lesson
+            );
+            if let Question::Choice {
+                choices, answer, ..
+            } = exercise.question
+            {
+                assert!(answer < choices.len());
+            }
+        }
+    }
+
+    #[test]
+    fn every_exercise_has_hints_and_a_pitfall() {
+        for exercise in EXERCISES {
+            assert!(
+                (2..=3).contains(&exercise.hints.len()),
+                "{}: {}",
+                exercise.lesson,
+                exercise.explanation
+            );
+            assert!(!exercise.pitfall.is_empty());
+        }
+    }
+
+    #[test]
+    fn ids_are_unique_and_kebab_case() {
+        for (i, exercise) in EXERCISES.iter().enumerate() {
+            assert!(
+                !exercise.id.is_empty()
+                    && exercise
+                        .id
+                        .bytes()
+                        .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-'),
+                "{:?}",
+                exercise.id
+            );
+            assert!(
+                EXERCISES[..i].iter().all(|e| e.id != exercise.id),
+                "{} is used twice",
+                exercise.id
+            );
+        }
+    }
+
+    /// `code` from a `Question::Compiles` as a program rustc can build.
+    #[cfg(feature = "diagnostics")]
+    fn program(code: &str) -> String {
+        if code.contains("fn main") {
+            return code.to_owned();
+        }
+        let (uses, body): (Vec<&str>, Vec<&str>) =
+            code.lines().partition(|line| line.starts_with("use "));
+        if body.iter().any(|line| line.starts_with("fn ")) {
+            format!("{}\nfn main() {{}}\n", code)
+        } else {
+            format!(
+                "{}\nfn main() {{\n{}\n}}\n",
+                uses.join("\n"),
+                body.join("\n")
+            )
+        }
+    }
+
+    #[cfg(feature = "diagnostics")]
+    #[test]
+    fn compiles_answers_agree_with_rustc() {
+        use crate::diagnostics::Rustc;
+
+        // The library as cargo built it for this test run, in the
+        // `target/debug` above this binary's `deps`.
+        let exe = std::env::current_exe().unwrap();
+        let target = exe.parent().and_then(|deps| deps.parent()).unwrap();
+        let rustc = Rustc::new("rustc").cargo_target(target);
+        let have_crate = target
+            .join("libreferences_sharing_vs_mutation.rlib")
+            .is_file();
+        for exercise in EXERCISES {
+            let (code, answer) = match exercise.question {
+                Question::Compiles { code, answer } => (code, answer),
+                Question::Choice { .. } => continue,
+            };
+            if code.contains("references_sharing_vs_mutation") && !have_crate {
+                continue;
+            }
+            let diagnostics = rustc.compile(&program(code)).unwrap();
+            let errors: Vec<String> = diagnostics
+                .iter()
+                .filter(|d| d.level == "error")
+                .map(|d| d.headline())
+                .collect();
+            assert_eq!(errors.is_empty(), answer, "{}\n{:?}", code, errors);
+            // Failing for want of an import or a `main` would be the
+            // wrong lesson.
+            for unrelated in &["E0425", "E0432", "E0433", "E0412", "E0601"] {
+                assert!(
+                    diagnostics
+                        .iter()
+                        .all(|d| d.code.as_deref() != Some(*unrelated)),
+                    "{}\n{:?}",
+                    code,
+                    errors
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn marks_answers() {
+        let compiles = for_lesson("exclusive").next().unwrap();
+        assert!(compiles.is_correct(Answer::Compiles(false)));
+        assert!(!compiles.is_correct(Answer::Compiles(true)));
+        assert!(!compiles.is_correct(Answer::Choice(0)));
+
+        let choice = for_lesson("exclusive").nth(1).unwrap();
+        assert!(choice.is_correct(Answer::Choice(0)));
+        assert!(!choice.is_correct(Answer::Choice(1)));
+    }
+
+    #[test]
+    fn shuffles_are_permutations_and_repeatable() {
+        let mut items: Vec<usize> = (0..20).collect();
+        Rng::new(7).shuffle(&mut items);
+        let mut again: Vec<usize> = (0..20).collect();
+        Rng::new(7).shuffle(&mut again);
+        assert_eq!(items, again);
+        assert_ne!(items, (0..20).collect::<Vec<_>>());
+
+        items.sort_unstable();
+        assert_eq!(items, (0..20).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,544 @@
+//! Lesson explanations as structured data.
+//!
+//! Each lesson's explanation is a Markdown file under `lessons/`, embedded
+//! with `include_str!` and parsed into an [`Explanation`]: sections of
+//! paragraphs and code blocks, with references to the rules and links to
+//! other lessons. The tour, the exported book and the `wasm` module's HTML
+//! all render from it, so the prose is written once.
+//!
+//! The files are kept a directory per [`Locale`], `lessons/en/`,
+//! `lessons/es/` and so on. English has every lesson; a translation can
+//! have as few as it likes, and the rest fall back to English. Only the
+//! prose is translated: the examples are code, and the same in every
+//! language.
+//!
+//! The Markdown is a small subset:
+//!
+//! - `## ` starts a section with that title. Text before the first heading
+//!   is a section without one.
+//! - Blank lines separate paragraphs, whose lines are joined with spaces.
+//! - ```` ``` ```` fences a code block, with a language after the opening
+//!   fence if it has one.
+//! - In paragraphs, backticks mark code, `[text](rule:slug)` refers to one
+//!   of the borrow checker's [`Rule`]s and `[text](lesson:name)` links to
+//!   the lesson called `name`.
+//!
+//! ```
+//! use references_sharing_vs_mutation::borrowck::Rule;
+//! use references_sharing_vs_mutation::explain::{Explanation, Inline};
+//!
+//! let e = Explanation::parse("A `&T` is [read-only](rule:shared-is-read-only).").unwrap();
+//! assert_eq!(e.to_text(), "A `&T` is read-only.\n");
+//! assert!(e.inlines().any(|i| *i == Inline::Rule {
+//!     text: "read-only".to_owned(),
+//!     rule: Rule::SharedIsReadOnly,
+//! }));
+//! ```
+
+use std::fmt::Write as _;
+
+use crate::borrowck::Rule;
+
+/// A language the explanations are written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    Es,
+}
+
+impl Locale {
+    /// Every locale, English first.
+    pub const ALL: &'static [Locale] = &[Locale::En, Locale::Es];
+
+    /// Its code, which names its directory under `lessons/`.
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Es => "es",
+        }
+    }
+
+    /// The locale with `code`, ignoring case.
+    pub fn from_code(code: &str) -> Option<Locale> {
+        Locale::ALL
+            .iter()
+            .copied()
+            .find(|l| l.code().eq_ignore_ascii_case(code))
+    }
+}
+
+/// A lesson's explanation, parsed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Explanation {
+    pub sections: Vec<Section>,
+}
+
+/// A run of blocks, under a heading unless it's the first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Section {
+    pub title: Option<String>,
+    pub blocks: Vec<Block>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    Paragraph(Vec<Inline>),
+    /// `lang` is empty if the fence didn't give one.
+    Code {
+        lang: String,
+        code: String,
+    },
+}
+
+/// A piece of a paragraph.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inline {
+    Text(String),
+    Code(String),
+    Rule { text: String, rule: Rule },
+    Lesson { text: String, name: String },
+}
+
+/// The rules as `rule:` links name them.
+const RULES: &[(&str, Rule)] = &[
+    ("shared-is-read-only", Rule::SharedIsReadOnly),
+    ("mutable-is-exclusive", Rule::MutableIsExclusive),
+    ("ownership", Rule::Ownership),
+    ("outlives", Rule::Outlives),
+];
+
+impl Explanation {
+    /// Parses the Markdown subset in the module docs. The error says what's
+    /// wrong, and where if it's a fence left open.
+    pub fn parse(source: &str) -> Result<Explanation, String> {
+        let mut explanation = Explanation::default();
+        let mut section = Section::default();
+        let mut paragraph: Vec<&str> = Vec::new();
+        let mut lines = source.lines().enumerate().map(|(i, line)| (i + 1, line));
+        while let Some((n, line)) = lines.next() {
+            let fence = line.strip_prefix("```");
+            if line.trim().is_empty() || line.starts_with("## ") || fence.is_some() {
+                end_paragraph(&mut paragraph, &mut section)?;
+            }
+            if let Some(title) = line.strip_prefix("## ") {
+                if section != Section::default() {
+                    explanation.sections.push(section);
+                }
+                section = Section {
+                    title: Some(title.trim().to_owned()),
+                    blocks: Vec::new(),
+                };
+            } else if let Some(lang) = fence {
+                let mut code = String::new();
+                loop {
+                    match lines.next() {
+                        Some((_, "```")) => break,
+                        Some((_, line)) => {
+                            code.push_str(line);
+                            code.push('\n');
+                        }
+                        None => return Err(format!("line {}: unclosed code block", n)),
+                    }
+                }
+                section.blocks.push(Block::Code {
+                    lang: lang.trim().to_owned(),
+                    code,
+                });
+            } else if !line.trim().is_empty() {
+                paragraph.push(line.trim());
+            }
+        }
+        end_paragraph(&mut paragraph, &mut section)?;
+        if section != Section::default() {
+            explanation.sections.push(section);
+        }
+        Ok(explanation)
+    }
+
+    /// Every inline piece of every paragraph, in order.
+    pub fn inlines(&self) -> impl Iterator<Item = &Inline> {
+        self.sections
+            .iter()
+            .flat_map(|s| &s.blocks)
+            .filter_map(|b| match b {
+                Block::Paragraph(inlines) => Some(inlines),
+                Block::Code { .. } => None,
+            })
+            .flatten()
+    }
+
+    /// For the terminal: headings underlined, code indented, and links as
+    /// their text, a lesson's followed by its name for `--lesson`.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for (title, block) in self.blocks() {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            if let Some(title) = title {
+                let _ = write!(out, "{}\n{}\n\n", title, "-".repeat(title.chars().count()));
+            }
+            match block {
+                Block::Paragraph(inlines) => {
+                    for inline in inlines {
+                        match inline {
+                            Inline::Text(text) => out.push_str(text),
+                            Inline::Code(code) => {
+                                let _ = write!(out, "`{}`", code);
+                            }
+                            Inline::Rule { text, .. } => out.push_str(text),
+                            Inline::Lesson { text, name } => {
+                                let _ = write!(out, "{} (lesson `{}`)", text, name);
+                            }
+                        }
+                    }
+                    out.push('\n');
+                }
+                Block::Code { code, .. } => {
+                    for line in code.lines() {
+                        let _ = writeln!(out, "    {}", line);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// For the book: standard Markdown, with lesson links pointing at the
+    /// lessons' pages and rules in italics.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        for (title, block) in self.blocks() {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            if let Some(title) = title {
+                let _ = write!(out, "## {}\n\n", title);
+            }
+            match block {
+                Block::Paragraph(inlines) => {
+                    for inline in inlines {
+                        match inline {
+                            Inline::Text(text) => out.push_str(text),
+                            Inline::Code(code) => {
+                                let _ = write!(out, "`{}`", code);
+                            }
+                            Inline::Rule { text, .. } => {
+                                let _ = write!(out, "*{}*", text);
+                            }
+                            Inline::Lesson { text, name } => {
+                                let _ = write!(out, "[{}]({}.md)", text, name);
+                            }
+                        }
+                    }
+                    out.push('\n');
+                }
+                Block::Code { lang, code } => {
+                    let _ = write!(out, "```{}\n{}```\n", lang, code);
+                }
+            }
+        }
+        out
+    }
+
+    /// For a web page: `<h2>`, `<p>` and `<pre>`, code classed by language
+    /// the usual way, with lesson links to
+    /// the `id` `lesson_html` gives each lesson's `<article>`, and rules in
+    /// a `<span class="rule">` naming the rule in `data-rule`.
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        for (title, block) in self.blocks() {
+            if let Some(title) = title {
+                let _ = writeln!(out, "<h2>{}</h2>", escape_html(title));
+            }
+            match block {
+                Block::Paragraph(inlines) => {
+                    out.push_str("<p>");
+                    for inline in inlines {
+                        match inline {
+                            Inline::Text(text) => out.push_str(&escape_html(text)),
+                            Inline::Code(code) => {
+                                let _ = write!(out, "<code>{}</code>", escape_html(code));
+                            }
+                            Inline::Rule { text, rule } => {
+                                let _ = write!(
+                                    out,
+                                    "<span class=\"rule\" data-rule=\"{}\">{}</span>",
+                                    rule_slug(*rule),
+                                    escape_html(text)
+                                );
+                            }
+                            Inline::Lesson { text, name } => {
+                                let _ = write!(
+                                    out,
+                                    "<a href=\"#{}\">{}</a>",
+                                    escape_html(name),
+                                    escape_html(text)
+                                );
+                            }
+                        }
+                    }
+                    out.push_str("</p>\n");
+                }
+                Block::Code { lang, code } => {
+                    out.push_str("<pre>");
+                    if lang.is_empty() {
+                        out.push_str("<code>");
+                    } else {
+                        let _ = write!(out, "<code class=\"language-{}\">", escape_html(lang));
+                    }
+                    let _ = writeln!(out, "{}</code></pre>", escape_html(code.trim_end()));
+                }
+            }
+        }
+        out
+    }
+
+    /// Every block, with the title of its section if it's the first block
+    /// in a titled one.
+    fn blocks(&self) -> impl Iterator<Item = (Option<&str>, &Block)> {
+        self.sections.iter().flat_map(|section| {
+            section.blocks.iter().enumerate().map(move |(i, block)| {
+                let title = if i == 0 {
+                    section.title.as_deref()
+                } else {
+                    None
+                };
+                (title, block)
+            })
+        })
+    }
+}
+
+fn end_paragraph(lines: &mut Vec<&str>, section: &mut Section) -> Result<(), String> {
+    if !lines.is_empty() {
+        let inlines = parse_inlines(&lines.join(" "))?;
+        section.blocks.push(Block::Paragraph(inlines));
+        lines.clear();
+    }
+    Ok(())
+}
+
+fn parse_inlines(text: &str) -> Result<Vec<Inline>, String> {
+    let mut inlines = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+    while let Some(at) = rest.find(['`', '[']) {
+        plain.push_str(&rest[..at]);
+        rest = &rest[at..];
+        let (inline, after) = if let Some(code) = rest.strip_prefix('`') {
+            let end = code
+                .find('`')
+                .ok_or_else(|| format!("unclosed ` in {:?}", text))?;
+            (Inline::Code(code[..end].to_owned()), &code[end + 1..])
+        } else {
+            match parse_link(rest)? {
+                Some(link) => link,
+                None => {
+                    plain.push('[');
+                    rest = &rest[1..];
+                    continue;
+                }
+            }
+        };
+        if !plain.is_empty() {
+            inlines.push(Inline::Text(std::mem::take(&mut plain)));
+        }
+        inlines.push(inline);
+        rest = after;
+    }
+    plain.push_str(rest);
+    if !plain.is_empty() {
+        inlines.push(Inline::Text(plain));
+    }
+    Ok(inlines)
+}
+
+/// `[text](kind:target)` at the start of `s`, and what follows it, or
+/// `None` if `s` doesn't start with a link.
+fn parse_link(s: &str) -> Result<Option<(Inline, &str)>, String> {
+    let close = match s.find("](") {
+        Some(close) => close,
+        None => return Ok(None),
+    };
+    let text = s[1..close].to_owned();
+    let target_and_rest = &s[close + 2..];
+    let end = match target_and_rest.find(')') {
+        Some(end) => end,
+        None => return Ok(None),
+    };
+    let target = &target_and_rest[..end];
+    let inline = if let Some(slug) = target.strip_prefix("rule:") {
+        let rule = RULES
+            .iter()
+            .find(|(s, _)| *s == slug)
+            .map(|&(_, rule)| rule)
+            .ok_or_else(|| format!("unknown rule `{}`", slug))?;
+        Inline::Rule { text, rule }
+    } else if let Some(name) = target.strip_prefix("lesson:") {
+        Inline::Lesson {
+            text,
+            name: name.to_owned(),
+        }
+    } else {
+        return Err(format!("link to `{}` isn't to a rule or a lesson", target));
+    };
+    Ok(Some((inline, &target_and_rest[end + 1..])))
+}
+
+fn rule_slug(rule: Rule) -> &'static str {
+    RULES.iter().find(|&&(_, r)| r == rule).unwrap().0
+}
+
+/// `text` with the characters HTML gives meaning to escaped.
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{find_lesson, LESSONS};
+
+    const SOURCE: &str = "\
+A `Cell` never lends out a reference,
+so a write can't [invalidate](rule:mutable-is-exclusive) one.
+
+## Compare
+
+```rust
+let c = Cell::new(1);
+```
+
+See [RefCell](lesson:refcell) & v[0].
+";
+
+    #[test]
+    fn parses_sections_blocks_and_links() {
+        let e = Explanation::parse(SOURCE).unwrap();
+        assert_eq!(e.sections.len(), 2);
+        assert_eq!(e.sections[0].title, None);
+        assert_eq!(
+            e.sections[0].blocks,
+            vec![Block::Paragraph(vec![
+                Inline::Text("A ".to_owned()),
+                Inline::Code("Cell".to_owned()),
+                Inline::Text(" never lends out a reference, so a write can't ".to_owned()),
+                Inline::Rule {
+                    text: "invalidate".to_owned(),
+                    rule: Rule::MutableIsExclusive,
+                },
+                Inline::Text(" one.".to_owned()),
+            ])]
+        );
+        assert_eq!(e.sections[1].title.as_deref(), Some("Compare"));
+        assert_eq!(
+            e.sections[1].blocks[0],
+            Block::Code {
+                lang: "rust".to_owned(),
+                code: "let c = Cell::new(1);\n".to_owned(),
+            }
+        );
+        assert_eq!(
+            e.sections[1].blocks[1],
+            Block::Paragraph(vec![
+                Inline::Text("See ".to_owned()),
+                Inline::Lesson {
+                    text: "RefCell".to_owned(),
+                    name: "refcell".to_owned(),
+                },
+                Inline::Text(" & v[0].".to_owned()),
+            ])
+        );
+    }
+
+    #[test]
+    fn renders_for_each_consumer() {
+        let e = Explanation::parse(SOURCE).unwrap();
+        assert_eq!(
+            e.to_text(),
+            "A `Cell` never lends out a reference, so a write can't invalidate one.\n\n\
+Compare\n-------\n\n    let c = Cell::new(1);\n\nSee RefCell (lesson `refcell`) & v[0].\n"
+        );
+        assert_eq!(
+            e.to_markdown(),
+            "A `Cell` never lends out a reference, so a write can't *invalidate* one.\n\n\
+## Compare\n\n```rust\nlet c = Cell::new(1);\n```\n\nSee [RefCell](refcell.md) & v[0].\n"
+        );
+        assert_eq!(
+            e.to_html(),
+            "<p>A <code>Cell</code> never lends out a reference, so a write can't \
+<span class=\"rule\" data-rule=\"mutable-is-exclusive\">invalidate</span> one.</p>\n\
+<h2>Compare</h2>\n<pre><code class=\"language-rust\">let c = Cell::new(1);</code></pre>\n\
+<p>See <a href=\"#refcell\">RefCell</a> &amp; v[0].</p>\n"
+        );
+    }
+
+    #[test]
+    fn reports_what_it_cant_parse() {
+        assert_eq!(
+            Explanation::parse("one\n\n```\nlet x;\n"),
+            Err("line 3: unclosed code block".to_owned())
+        );
+        assert_eq!(
+            Explanation::parse("a `b"),
+            Err("unclosed ` in \"a `b\"".to_owned())
+        );
+        assert_eq!(
+            Explanation::parse("[x](rule:be-nice)"),
+            Err("unknown rule `be-nice`".to_owned())
+        );
+        assert_eq!(
+            Explanation::parse("[x](https://example.com)"),
+            Err("link to `https://example.com` isn't to a rule or a lesson".to_owned())
+        );
+    }
+
+    #[test]
+    fn every_lesson_explanation_parses_and_links_to_lessons() {
+        for lesson in LESSONS {
+            assert_eq!(lesson.explanations[0].0, Locale::En, "{}", lesson.name);
+            for (locale, source) in lesson.explanations {
+                let e = Explanation::parse(source)
+                    .unwrap_or_else(|e| panic!("{} ({:?}): {}", lesson.name, locale, e));
+                assert!(!e.sections.is_empty(), "{}", lesson.name);
+                for inline in e.inlines() {
+                    if let Inline::Lesson { name, .. } = inline {
+                        assert!(find_lesson(name).is_some(), "{} -> {}", lesson.name, name);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn translations_fall_back_to_english() {
+        let dangling = find_lesson("dangling").unwrap();
+        let spanish = dangling.explanation(Locale::Es).to_text();
+        assert!(spanish.starts_with("Una referencia compartida"));
+        assert_ne!(spanish, dangling.explain().to_text());
+
+        let nll = find_lesson("nll").unwrap();
+        assert_eq!(nll.explanation(Locale::Es), nll.explain());
+    }
+
+    #[test]
+    fn names_locales() {
+        assert_eq!(Locale::from_code("es"), Some(Locale::Es));
+        assert_eq!(Locale::from_code("EN"), Some(Locale::En));
+        assert_eq!(Locale::from_code("fr"), None);
+        assert!(Locale::ALL
+            .iter()
+            .all(|&l| Locale::from_code(l.code()) == Some(l)));
+    }
+}
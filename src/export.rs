@@ -0,0 +1,306 @@
+//! The lessons as a book, for publishing outside the terminal.
+//!
+//! [`write_book`] lays out an mdBook source tree: a `book.toml`, and under
+//! `src/` a `SUMMARY.md` with one part per chapter and one page per lesson.
+//! Each page has the lesson's examples, the rustc output for the ones it
+//! rejects, its diagrams and its explanation. Code blocks carry mdBook's
+//! `compile_fail` and `should_panic` attributes, so `mdbook test`, pointed
+//! at the crate's build output with `-L` for the examples that use it,
+//! checks them the same way the `tests/compile_fail` suite does.
+//!
+//! [`lesson_html`] renders the same page as an HTML fragment, for the
+//! `wasm` module to hand to a web page.
+//!
+//! [`Format::Anki`] writes flashcards instead; see the `flashcards`
+//! module.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::cli::{Lesson, Outcome, LESSONS};
+use crate::diagram::Diagram;
+use crate::explain::escape_html;
+use crate::flashcards::{self, Decks};
+
+/// What to export the lessons as.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Format {
+    /// An mdBook source tree.
+    Markdown,
+    /// A `flashcards.csv` for Anki to import, split into `decks`.
+    Anki { decks: Decks },
+}
+
+impl Format {
+    /// The format `--format` names, if it names one.
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "md" | "markdown" => Some(Format::Markdown),
+            "anki" => Some(Format::Anki {
+                decks: Decks::default(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Writes the lessons to `dir` in `format`, creating it if need be and
+/// replacing any files from an earlier export.
+pub fn export(format: Format, dir: &Path) -> io::Result<()> {
+    match format {
+        Format::Markdown => write_book(dir),
+        Format::Anki { decks } => {
+            fs::create_dir_all(dir)?;
+            fs::write(
+                dir.join("flashcards.csv"),
+                flashcards::csv(&flashcards::cards(&decks)),
+            )
+        }
+    }
+}
+
+/// Writes an mdBook source tree for every lesson to `dir`.
+pub fn write_book(dir: &Path) -> io::Result<()> {
+    let src = dir.join("src");
+    fs::create_dir_all(&src)?;
+    fs::write(dir.join("book.toml"), BOOK_TOML)?;
+    fs::write(src.join("SUMMARY.md"), summary())?;
+    for lesson in LESSONS {
+        fs::write(src.join(page_name(lesson)), lesson_page(lesson))?;
+    }
+    Ok(())
+}
+
+const BOOK_TOML: &str = "[book]
+title = \"References: sharing versus mutation\"
+src = \"src\"
+
+[rust]
+edition = \"2018\"
+";
+
+/// How a chapter is headed in the book.
+pub(crate) fn chapter_title(chapter: &str) -> &str {
+    match chapter {
+        "sharing" => "Sharing",
+        "mutation" => "Mutation",
+        "interior" => "Interior mutability",
+        "rc" => "Shared ownership",
+        "cow" => "Borrow or own",
+        "reborrowing" => "Reborrowing",
+        "nll" => "Non-lexical lifetimes",
+        "selfref" => "Self-referential structs",
+        "split" => "Split borrows",
+        "perf" => "Performance",
+        other => other,
+    }
+}
+
+fn page_name(lesson: &Lesson) -> String {
+    format!("{}.md", lesson.name)
+}
+
+/// The book's `SUMMARY.md`: a part for each chapter, listing its lessons
+/// in order.
+pub fn summary() -> String {
+    let mut out = String::from("# Summary\n");
+    let mut chapter = "";
+    for lesson in LESSONS {
+        if lesson.chapter != chapter {
+            chapter = lesson.chapter;
+            let _ = write!(out, "\n# {}\n\n", chapter_title(chapter));
+        }
+        let _ = writeln!(out, "- [{}]({})", lesson.title, page_name(lesson));
+    }
+    out
+}
+
+/// One lesson as a Markdown page.
+pub fn lesson_page(lesson: &Lesson) -> String {
+    let mut out = format!("# {}\n", lesson.title);
+    for example in lesson.examples {
+        let fence = match example.outcome {
+            Outcome::Compiles => "rust",
+            Outcome::Panics(_) => "rust,should_panic",
+            Outcome::Rejected(_) => "rust,compile_fail",
+        };
+        let code: Vec<&str> = example.numbered_lines().map(|(_, line)| line).collect();
+        let _ = write!(out, "\n```{}\n{}\n```\n\n", fence, code.join("\n"));
+        match example.outcome {
+            Outcome::Compiles => out.push_str("This compiles and runs.\n"),
+            Outcome::Panics(message) => {
+                let _ = write!(
+                    out,
+                    "This compiles, but panics when it runs:\n\n```text\n{}\n```\n",
+                    message
+                );
+            }
+            Outcome::Rejected(stderr) => {
+                let _ = write!(
+                    out,
+                    "rustc rejects this:\n\n```text\n{}\n```\n",
+                    stderr.trim_end()
+                );
+            }
+        }
+    }
+    for diagram in (lesson.diagrams)() {
+        let _ = write!(out, "\n```text\n{}```\n", diagram);
+    }
+    let _ = write!(out, "\n{}", lesson.explain().to_markdown());
+    out
+}
+
+/// One lesson as an HTML `<article>`, laid out like its Markdown page.
+/// Examples are classed `compiles`, `panics` or `rejected`.
+pub fn lesson_html(lesson: &Lesson) -> String {
+    let mut out = format!(
+        "<article class=\"lesson\" id=\"{}\">\n<h1>{}</h1>\n",
+        lesson.name,
+        escape_html(lesson.title)
+    );
+    for example in lesson.examples {
+        let class = match example.outcome {
+            Outcome::Compiles => "compiles",
+            Outcome::Panics(_) => "panics",
+            Outcome::Rejected(_) => "rejected",
+        };
+        let code: Vec<&str> = example.numbered_lines().map(|(_, line)| line).collect();
+        let _ = writeln!(
+            out,
+            "<pre class=\"example {}\"><code>{}</code></pre>",
+            class,
+            escape_html(&code.join("\n"))
+        );
+        match example.outcome {
+            Outcome::Compiles => out.push_str("<p>This compiles and runs.</p>\n"),
+            Outcome::Panics(message) => {
+                let _ = writeln!(
+                    out,
+                    "<p>This compiles, but panics when it runs:</p>\n<pre class=\"output\">{}</pre>",
+                    escape_html(message)
+                );
+            }
+            Outcome::Rejected(stderr) => {
+                let _ = writeln!(
+                    out,
+                    "<p>rustc rejects this:</p>\n<pre class=\"output\">{}</pre>",
+                    escape_html(stderr.trim_end())
+                );
+            }
+        }
+    }
+    for diagram in (lesson.diagrams)() {
+        out.push_str(&diagram_html(&diagram));
+    }
+    out.push_str(&lesson.explain().to_html());
+    out.push_str("</article>\n");
+    out
+}
+
+/// `diagram` drawn with box-drawing characters in a `<pre>`.
+pub fn diagram_html(diagram: &Diagram) -> String {
+    format!(
+        "<pre class=\"diagram\">{}</pre>\n",
+        escape_html(diagram.to_string().trim_end())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::find_lesson;
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn summary_groups_lessons_by_chapter() {
+        let summary = summary();
+        assert!(summary.starts_with(
+            "# Summary\n\n# Sharing\n\n- [Moving a value out from under a reference](dangling.md)\n"
+        ));
+        assert!(summary.contains("\n# Interior mutability\n\n"));
+        assert_eq!(summary.matches("](").count(), LESSONS.len());
+    }
+
+    #[test]
+    fn pages_mark_how_each_example_fares() {
+        let page = lesson_page(find_lesson("dangling").unwrap());
+        assert!(page.starts_with(
+            "# Moving a value out from under a reference\n\n```rust,compile_fail\nfn main() {\n"
+        ));
+        assert!(page.contains("error[E0505]: cannot move out of `v` because it is borrowed"));
+        assert!(page.contains("\n```rust\nfn main() {\n"));
+        assert!(page.contains("This compiles and runs.\n"));
+        assert!(!page.contains("// sharing::dangling"));
+
+        let page = lesson_page(find_lesson("refcell").unwrap());
+        assert!(page.contains("```rust,should_panic\n"));
+        assert!(page.contains("panics when it runs:\n\n```text\nRefCell already borrowed\n```\n"));
+    }
+
+    #[test]
+    fn html_pages_escape_code_and_mark_outcomes() {
+        let page = lesson_html(find_lesson("dangling").unwrap());
+        assert!(page.starts_with(
+            "<article class=\"lesson\" id=\"dangling\">\n<h1>Moving a value out from under a reference</h1>\n"
+        ));
+        assert!(page.contains("<pre class=\"example rejected\"><code>fn main() {\n"));
+        assert!(page.contains("cannot move out of `v` because it is borrowed"));
+        assert!(page.contains("let r = &amp;v;"));
+        assert!(page.contains("<pre class=\"diagram\">"));
+        assert!(page.ends_with("</p>\n</article>\n"));
+        assert!(page.contains("<span class=\"rule\" data-rule=\"shared-is-read-only\">"));
+    }
+
+    #[test]
+    fn writes_a_book() {
+        let dir = TempDir::new();
+        let book = dir.join("book");
+        export(Format::Markdown, &book).unwrap();
+        assert!(fs::read_to_string(book.join("book.toml"))
+            .unwrap()
+            .contains("[book]"));
+        assert_eq!(
+            fs::read_to_string(book.join("src/SUMMARY.md")).unwrap(),
+            summary()
+        );
+        for lesson in LESSONS {
+            assert_eq!(
+                fs::read_to_string(book.join("src").join(page_name(lesson))).unwrap(),
+                lesson_page(lesson)
+            );
+        }
+    }
+
+    #[test]
+    fn writes_flashcards() {
+        let dir = TempDir::new();
+        let cards = dir.join("cards");
+        let decks = Decks::ByTag(vec!["E0502".to_owned()]);
+        export(
+            Format::Anki {
+                decks: decks.clone(),
+            },
+            &cards,
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(cards.join("flashcards.csv")).unwrap(),
+            flashcards::csv(&flashcards::cards(&decks))
+        );
+    }
+
+    #[test]
+    fn names_formats() {
+        assert_eq!(Format::from_name("md"), Some(Format::Markdown));
+        assert_eq!(
+            Format::from_name("anki"),
+            Some(Format::Anki {
+                decks: Decks::ByChapter
+            })
+        );
+        assert_eq!(Format::from_name("pdf"), None);
+    }
+}
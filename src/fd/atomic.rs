@@ -0,0 +1,206 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{cstr, cvt, cvt_r, File};
+
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Replaces the contents of `path` with `data`, crash-safely.
+///
+/// This is `File::edit_via_temp` plus the last step of the recipe: after
+/// the rename, the directory holding `path` is fsynced too, so that the
+/// rename itself is on disk. Without it a crash can leave the directory
+/// still naming the old file even though the new data was synced.
+pub fn atomic_write(path: &Path, data: &[u8]) -> io::Result<()> {
+    File::edit_via_temp(path, |f| f.write_all_raw(data))?;
+    let parent = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let parent = cstr(parent)?;
+    let fd = cvt_r(|| unsafe {
+        libc::open(
+            parent.as_ptr(),
+            libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC,
+        )
+    })?;
+    File::from_descriptor(fd).sync_all()
+}
+
+impl File {
+    /// Rewrites `path` through a temporary file so that nobody ever sees it
+    /// half written.
+    ///
+    /// `f` is handed a new, empty file in the same directory and writes the
+    /// new contents into it. If it succeeds the temporary file is flushed to
+    /// disk and renamed over `path` in one step: anyone opening `path` gets
+    /// either the old file or the new one, and descriptors already open on
+    /// the old file keep reading the old contents. If `f` or anything after
+    /// it fails, the temporary file is removed and `path` is untouched.
+    ///
+    /// The new file gets the old one's permission bits, or if there was no
+    /// old file, the usual ones for a new file under the process's umask.
+    pub fn edit_via_temp<P, F>(path: P, f: F) -> io::Result<()>
+    where
+        P: AsRef<Path>,
+        F: FnOnce(&mut File) -> io::Result<()>,
+    {
+        let path = path.as_ref();
+        let (mut temp, temp_path) = create_sibling_temp(path)?;
+        let result = f(&mut temp)
+            .and_then(|()| temp.sync_all())
+            .and_then(|()| fs::rename(&temp_path, path));
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+        result
+    }
+
+    /// Flushes the file's data and metadata to disk with `fsync`.
+    pub fn sync_all(&self) -> io::Result<()> {
+        cvt_r(|| unsafe { libc::fsync(self.descriptor) })?;
+        Ok(())
+    }
+}
+
+/// Creates a new, uniquely named file next to `path`, so a rename from it
+/// to `path` never crosses a filesystem boundary, with the same permission
+/// bits as `path` if it exists.
+fn create_sibling_temp(path: &Path) -> io::Result<(File, PathBuf)> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let original = mode_of(path)?;
+    loop {
+        let mut temp_name = OsString::from(".");
+        temp_name.push(name);
+        temp_name.push(format!(
+            ".{}.{}.tmp",
+            std::process::id(),
+            TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let temp_path = path.with_file_name(temp_name);
+        match File::options()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            // Private until it has the original's mode, which the umask
+            // might otherwise narrow.
+            .mode(if original.is_some() { 0o600 } else { 0o666 })
+            .open(&temp_path)
+        {
+            Ok(f) => {
+                if let Some(mode) = original {
+                    if let Err(e) = cvt_r(|| unsafe { libc::fchmod(f.descriptor, mode) }) {
+                        let _ = fs::remove_file(&temp_path);
+                        return Err(e);
+                    }
+                }
+                return Ok((f, temp_path));
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// The permission bits of the file at `path`, or `None` if there isn't
+/// one.
+fn mode_of(path: &Path) -> io::Result<Option<libc::mode_t>> {
+    let path = cstr(path)?;
+    let mut stat = std::mem::MaybeUninit::<libc::stat>::uninit();
+    match cvt(unsafe { libc::stat(path.as_ptr(), stat.as_mut_ptr()) }) {
+        Ok(_) => Ok(Some(unsafe { stat.assume_init() }.st_mode & 0o7777)),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::{self, Read};
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    use crate::fd::{self, File};
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn atomic_write_leaves_exactly_the_data() {
+        let dir = TempDir::new();
+        let path = dir.file("state", b"a much longer previous version");
+        fd::atomic_write(&path, b"v2").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"v2");
+
+        let fresh = dir.join("fresh");
+        fd::atomic_write(&fresh, b"").unwrap();
+        assert_eq!(fs::read(&fresh).unwrap(), b"");
+
+        let names: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(names.len(), 2, "leftover temp files: {:?}", names);
+    }
+
+    #[test]
+    fn swaps_in_new_contents_atomically() {
+        let dir = TempDir::new();
+        let path = dir.file("config", b"old contents");
+        let old_inode = fs::metadata(&path).unwrap().ino();
+        let mut early_reader = File::open(&path).unwrap();
+
+        File::edit_via_temp(&path, |f| f.replace_contents(b"new contents")).unwrap();
+
+        // The path now names a different inode: the new file was put in
+        // place whole, not rewritten where it stood.
+        assert_ne!(fs::metadata(&path).unwrap().ino(), old_inode);
+        assert_eq!(fs::read(&path).unwrap(), b"new contents");
+
+        // A reader that opened the file before the edit sees the old
+        // contents in full, never a mixture.
+        let mut seen = String::new();
+        early_reader.read_to_string(&mut seen).unwrap();
+        assert_eq!(seen, "old contents");
+
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn keeps_the_original_mode() {
+        let dir = TempDir::new();
+        let mode =
+            |path: &std::path::Path| fs::metadata(path).unwrap().permissions().mode() & 0o7777;
+        for &original in &[0o644, 0o600, 0o755, 0o640] {
+            let path = dir.file("script", b"old");
+            fs::set_permissions(&path, fs::Permissions::from_mode(original)).unwrap();
+            fd::atomic_write(&path, b"new").unwrap();
+            assert_eq!(mode(&path), original, "{:o}", original);
+            File::edit_via_temp(&path, |f| f.replace_contents(b"newer")).unwrap();
+            assert_eq!(mode(&path), original, "{:o}", original);
+        }
+
+        // A new file gets what `fs::write` would give it.
+        fs::write(dir.join("expected"), b"").unwrap();
+        fd::atomic_write(&dir.join("fresh"), b"").unwrap();
+        assert_eq!(mode(&dir.join("fresh")), mode(&dir.join("expected")));
+    }
+
+    #[test]
+    fn failed_edit_leaves_file_alone() {
+        let dir = TempDir::new();
+        let path = dir.file("config", b"keep me");
+
+        let err = File::edit_via_temp(&path, |f| {
+            f.replace_contents(b"half")?;
+            Err(io::Error::other("changed my mind"))
+        })
+        .unwrap_err();
+        assert_eq!(err.to_string(), "changed my mind");
+        assert_eq!(fs::read(&path).unwrap(), b"keep me");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
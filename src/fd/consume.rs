@@ -0,0 +1,198 @@
+use std::io::{self, Read};
+use std::mem;
+
+use super::{cvt_r, File};
+
+const SCRATCH_LEN: usize = 64 * 1024;
+
+static CRC32_TABLE: [u32; 256] = crc32_table();
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+impl File {
+    /// Reads and discards everything from the current offset to end of file,
+    /// returning how many bytes went by.
+    ///
+    /// The scratch buffer is kept on the `File`, so draining a pipe over and
+    /// over doesn't allocate each time.
+    pub fn drain(&mut self) -> io::Result<u64> {
+        self.consume(|_| {})
+    }
+
+    /// Counts the bytes between the current offset and end of file, for pipes
+    /// and special files where `fstat` can't say.
+    ///
+    /// The only way to count a stream is to read it, so this consumes the
+    /// data just like `drain`: afterwards the `File` is at end of file.
+    pub fn count_remaining(&mut self) -> io::Result<u64> {
+        self.drain()
+    }
+
+    /// Computes the CRC-32 (IEEE, as used by zlib and gzip) of everything
+    /// from the current offset to end of file.
+    ///
+    /// The data is streamed through the scratch buffer, so the file can be
+    /// any size. Like `drain`, it leaves the `File` at end of file.
+    pub fn crc32(&mut self) -> io::Result<u32> {
+        let mut crc = !0u32;
+        self.consume(|chunk| {
+            for &byte in chunk {
+                crc = CRC32_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8);
+            }
+        })?;
+        Ok(!crc)
+    }
+
+    /// Reads from the current offset to end of file, appending to `buf`, and
+    /// returns how many bytes were appended. Whatever `buf` held before is
+    /// kept.
+    ///
+    /// For regular files the size from `fstat` is reserved up front, so the
+    /// common case is one allocation; beyond that the buffer at least doubles
+    /// each time it fills. Bytes are read straight into the vector's spare
+    /// capacity, with no zeroing or intermediate copy.
+    pub fn read_to_end_buffered(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        let start = buf.len();
+        let stat = self.fstat()?;
+        if stat.st_mode & libc::S_IFMT == libc::S_IFREG {
+            let remaining = (stat.st_size as u64).saturating_sub(self.stream_position()?);
+            // One extra byte so the read that finds end of file doesn't have
+            // to grow the buffer first.
+            buf.reserve(remaining as usize + 1);
+        }
+        loop {
+            if buf.len() == buf.capacity() {
+                buf.reserve(buf.capacity().max(8 * 1024));
+            }
+            let spare = buf.capacity() - buf.len();
+            let n = cvt_r(|| unsafe {
+                libc::read(
+                    self.descriptor,
+                    buf.as_mut_ptr().add(buf.len()) as *mut libc::c_void,
+                    spare,
+                )
+            })?;
+            if n == 0 {
+                return Ok(buf.len() - start);
+            }
+            // The kernel initialized exactly `n` bytes past the end.
+            unsafe { buf.set_len(buf.len() + n as usize) }
+        }
+    }
+
+    /// Reads to end of file, handing each chunk to `f`, and returns the total
+    /// number of bytes read.
+    fn consume<F: FnMut(&[u8])>(&mut self, f: F) -> io::Result<u64> {
+        // Take the buffer out so `self.read` can borrow `self` mutably.
+        let mut scratch = mem::take(&mut self.scratch);
+        if scratch.is_empty() {
+            scratch = vec![0; SCRATCH_LEN];
+        }
+        let result = self.consume_into(&mut scratch, f);
+        self.scratch = scratch;
+        result
+    }
+
+    fn consume_into<F: FnMut(&[u8])>(&mut self, scratch: &mut [u8], mut f: F) -> io::Result<u64> {
+        let mut total = 0;
+        // `read` retries `EINTR` itself.
+        loop {
+            match self.read(scratch)? {
+                0 => return Ok(total),
+                n => {
+                    f(&scratch[..n]);
+                    total += n as u64;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::thread;
+
+    use crate::fd::File;
+    use crate::testutil::{self, TempDir};
+
+    #[test]
+    fn drains_a_pipe() {
+        let (mut reader, mut writer) = testutil::pipe();
+        let feeder = thread::spawn(move || {
+            writer.write_all(&[7; 100 * 1024]).unwrap();
+        });
+        assert_eq!(reader.drain().unwrap(), 100 * 1024);
+        feeder.join().unwrap();
+        // Already at end of file: nothing left to drain.
+        assert_eq!(reader.drain().unwrap(), 0);
+    }
+
+    #[test]
+    fn counts_what_was_written() {
+        let (mut reader, mut writer) = testutil::pipe();
+        writer.write_all(b"sharing versus mutation").unwrap();
+        drop(writer);
+        assert_eq!(reader.count_remaining().unwrap(), 23);
+        assert_eq!(reader.count_remaining().unwrap(), 0);
+    }
+
+    #[test]
+    fn crc32_of_check_string() {
+        let dir = TempDir::new();
+        let mut f = File::open(dir.file("check", b"123456789")).unwrap();
+        assert_eq!(f.crc32().unwrap(), 0xcbf4_3926);
+
+        let mut empty = File::open(dir.file("empty", b"")).unwrap();
+        assert_eq!(empty.crc32().unwrap(), 0);
+    }
+
+    #[test]
+    fn crc32_spans_scratch_buffers() {
+        let dir = TempDir::new();
+        let mut f = File::open(dir.file("big", &[b'a'; 200 * 1024])).unwrap();
+        // Computed with zlib.crc32(b"a" * 200 * 1024).
+        assert_eq!(f.crc32().unwrap(), 0x1380_65c7);
+    }
+
+    #[test]
+    fn read_to_end_buffered_keeps_prefix() {
+        let dir = TempDir::new();
+        let data: Vec<u8> = (0..10 * 1024 * 1024).map(|i| (i % 253) as u8).collect();
+        let mut f = File::open(dir.file("big", &data)).unwrap();
+
+        let mut buf = b"prefix:".to_vec();
+        assert_eq!(f.read_to_end_buffered(&mut buf).unwrap(), data.len());
+        assert_eq!(&buf[..7], b"prefix:");
+        assert!(buf[7..] == data[..]);
+        assert_eq!(f.read_to_end_buffered(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn read_to_end_buffered_from_pipe() {
+        let (mut reader, mut writer) = testutil::pipe();
+        let feeder = thread::spawn(move || writer.write_all(&[3; 100 * 1024]).unwrap());
+        let mut buf = Vec::new();
+        assert_eq!(reader.read_to_end_buffered(&mut buf).unwrap(), 100 * 1024);
+        assert!(buf.iter().all(|&b| b == 3));
+        feeder.join().unwrap();
+    }
+}
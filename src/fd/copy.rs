@@ -0,0 +1,163 @@
+use std::io;
+
+use super::{cvt_r, File};
+
+/// How `File::reflink_or_copy_from` got the data across.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CopyMethod {
+    /// The filesystem shared the source's blocks with the destination
+    /// (`FICLONE`); nothing was copied.
+    Reflink,
+    /// The kernel copied the data with `copy_file_range(2)`.
+    CopyFileRange,
+    /// The data was read into a buffer and written back out.
+    ReadWrite,
+}
+
+impl File {
+    /// Makes this file's contents a copy of `src`'s, as cheaply as the
+    /// platform allows, and says which way it went.
+    ///
+    /// A reflink is tried first, then `copy_file_range`, then a plain
+    /// read/write loop. Either way the whole of `src` is copied, starting at
+    /// offset zero, and neither file's offset moves. This file must be open
+    /// for writing and `src` for reading.
+    ///
+    /// Copying a file onto itself, through the same descriptor or another,
+    /// fails with `InvalidInput` and leaves it as it was: each way of
+    /// copying starts by emptying the destination, which would empty the
+    /// source too.
+    pub fn reflink_or_copy_from(&self, src: &File) -> io::Result<CopyMethod> {
+        let (to, from) = (self.fstat()?, src.fstat()?);
+        if (to.st_dev, to.st_ino) == (from.st_dev, from.st_ino) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "can't copy a file onto itself",
+            ));
+        }
+        #[cfg(target_os = "linux")]
+        {
+            if unsafe { libc::ioctl(self.descriptor, libc::FICLONE, src.descriptor) } == 0 {
+                return Ok(CopyMethod::Reflink);
+            }
+            let err = io::Error::last_os_error();
+            if !can_fall_back(&err) {
+                return Err(err);
+            }
+
+            cvt_r(|| unsafe { libc::ftruncate(self.descriptor, 0) })?;
+            match copy_file_range(src, self) {
+                Ok(()) => return Ok(CopyMethod::CopyFileRange),
+                Err(ref e) if can_fall_back(e) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        copy_read_write(src, self)?;
+        Ok(CopyMethod::ReadWrite)
+    }
+}
+
+/// Errors meaning "this filesystem or kernel can't do that", as opposed to
+/// something being wrong with the files.
+#[cfg(target_os = "linux")]
+fn can_fall_back(err: &io::Error) -> bool {
+    match err.raw_os_error() {
+        Some(code) => [
+            libc::EOPNOTSUPP,
+            libc::ENOTSUP,
+            libc::ENOTTY,
+            libc::EXDEV,
+            libc::EINVAL,
+            libc::ENOSYS,
+        ]
+        .contains(&code),
+        None => false,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn copy_file_range(src: &File, dst: &File) -> io::Result<()> {
+    let mut src_off: libc::loff_t = 0;
+    let mut dst_off: libc::loff_t = 0;
+    loop {
+        let n = cvt_r(|| unsafe {
+            libc::copy_file_range(
+                src.descriptor,
+                &mut src_off,
+                dst.descriptor,
+                &mut dst_off,
+                1 << 30,
+                0,
+            )
+        })?;
+        if n == 0 {
+            return Ok(());
+        }
+    }
+}
+
+fn copy_read_write(src: &File, dst: &File) -> io::Result<()> {
+    cvt_r(|| unsafe { libc::ftruncate(dst.descriptor, 0) })?;
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut offset = 0;
+    loop {
+        let n = src.read_at(&mut buf, offset)?;
+        if n == 0 {
+            return Ok(());
+        }
+        dst.write_all_at(&buf[..n], offset)?;
+        offset += n as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::testutil::TempDir;
+
+    fn pattern(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn copies_by_some_method() {
+        let dir = TempDir::new();
+        let data = pattern(300 * 1024);
+        let src = File::open(dir.file("src", &data)).unwrap();
+        let dst_path = dir.file("dst", b"old contents that are longer than nothing");
+        let dst = File::options().write(true).open(&dst_path).unwrap();
+
+        let method = dst.reflink_or_copy_from(&src).unwrap();
+        assert!(matches!(
+            method,
+            CopyMethod::Reflink | CopyMethod::CopyFileRange | CopyMethod::ReadWrite
+        ));
+        assert_eq!(fs::read(&dst_path).unwrap(), data);
+    }
+
+    #[test]
+    fn read_write_fallback_replaces_contents() {
+        let dir = TempDir::new();
+        let src = File::open(dir.file("src", b"short")).unwrap();
+        let dst_path = dir.file("dst", b"much longer old contents");
+        let dst = File::options().write(true).open(&dst_path).unwrap();
+
+        copy_read_write(&src, &dst).unwrap();
+        assert_eq!(fs::read(&dst_path).unwrap(), b"short");
+    }
+
+    #[test]
+    fn refuses_to_copy_a_file_onto_itself() {
+        let dir = TempDir::new();
+        let path = dir.file("f", b"keep me");
+        let f = File::options().read(true).write(true).open(&path).unwrap();
+        let err = f.reflink_or_copy_from(&f).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        let again = File::open(&path).unwrap();
+        assert!(f.reflink_or_copy_from(&again).is_err());
+        assert_eq!(fs::read(&path).unwrap(), b"keep me");
+    }
+}
@@ -0,0 +1,239 @@
+use std::io;
+use std::path::Path;
+
+use super::{cstr, cvt, cvt_r, File, OpenOptions};
+
+/// A handle on the current directory, opened with `O_DIRECTORY`.
+///
+/// The handle keeps referring to the directory that was current when it was
+/// opened, so paths opened through `File::openat` on it aren't thrown off by
+/// a `chdir` elsewhere in the process.
+pub fn cwd() -> io::Result<File> {
+    let dot = cstr(Path::new("."))?;
+    let fd = cvt_r(|| unsafe {
+        libc::open(
+            dot.as_ptr(),
+            libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC,
+        )
+    })?;
+    Ok(File::from_descriptor(fd))
+}
+
+impl File {
+    /// Opens `path` relative to this directory with `openat(2)`.
+    ///
+    /// Absolute paths ignore the directory, as they do for `openat`.
+    pub fn openat<P: AsRef<Path>>(&self, path: P, options: &OpenOptions) -> io::Result<File> {
+        options.open_at(self.descriptor, path.as_ref())
+    }
+
+    /// Opens `name` relative to this directory, refusing any path that
+    /// would resolve outside it.
+    ///
+    /// `flags` are raw `open(2)` flags; `O_CLOEXEC` is always added. The
+    /// kernel enforces the restriction with `openat2(2)` and
+    /// `RESOLVE_BENEATH`, which also catches `..` and absolute symlinks met
+    /// along the way. Where `openat2` isn't available, on older kernels or
+    /// under seccomp filters that refuse it with `EPERM`, this falls back
+    /// to rejecting absolute paths and `..` components itself and opening
+    /// one component at a time with `O_NOFOLLOW`. That refuses every
+    /// symlink, with `ELOOP`, or `ENOTDIR` where a directory was expected,
+    /// which is stricter than it needs to be but never looser. Absolute
+    /// paths and `..` fail with `EXDEV` either way.
+    #[cfg(target_os = "linux")]
+    pub fn open_beneath(&self, name: &str, flags: i32) -> io::Result<File> {
+        let path = cstr(Path::new(name))?;
+        let mut how: libc::open_how = unsafe { std::mem::zeroed() };
+        how.flags = (flags | libc::O_CLOEXEC) as u64;
+        // `O_TMPFILE` includes the `O_DIRECTORY` bit, so it's only asked
+        // for when all its bits are set. `openat2` refuses a mode on an
+        // open that doesn't create.
+        let creates = flags & libc::O_CREAT != 0 || flags & libc::O_TMPFILE == libc::O_TMPFILE;
+        how.mode = if creates { 0o666 } else { 0 };
+        how.resolve = libc::RESOLVE_BENEATH;
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_openat2,
+                self.descriptor,
+                path.as_ptr(),
+                &how as *const libc::open_how,
+                std::mem::size_of::<libc::open_how>(),
+            )
+        };
+        match cvt(ret) {
+            Ok(fd) => Ok(File::from_descriptor(fd as libc::c_int)),
+            Err(ref e) if matches!(e.raw_os_error(), Some(libc::ENOSYS) | Some(libc::EPERM)) => {
+                self.open_beneath_fallback(name, flags)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn open_beneath_fallback(&self, name: &str, flags: i32) -> io::Result<File> {
+        use std::path::Component;
+
+        let escapes = Path::new(name).components().any(|c| {
+            matches!(
+                c,
+                Component::RootDir | Component::ParentDir | Component::Prefix(_)
+            )
+        });
+        if escapes {
+            return Err(io::Error::from_raw_os_error(libc::EXDEV));
+        }
+        let mut parts: Vec<&Path> = Path::new(name)
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(part) => Some(Path::new(part)),
+                _ => None,
+            })
+            .collect();
+        let last = parts.pop().unwrap_or_else(|| Path::new("."));
+        // Each directory on the way is opened with `O_NOFOLLOW` too, so a
+        // symlink can't stand in for one and lead out of `self`.
+        let mut dirs = Vec::with_capacity(parts.len());
+        for part in parts {
+            let at = dirs.last().unwrap_or(self);
+            let dir = at.openat_raw(part, libc::O_RDONLY | libc::O_DIRECTORY)?;
+            dirs.push(dir);
+        }
+        dirs.last().unwrap_or(self).openat_raw(last, flags)
+    }
+
+    /// `openat(2)` with raw `flags`, adding `O_CLOEXEC` and `O_NOFOLLOW`.
+    #[cfg(target_os = "linux")]
+    fn openat_raw(&self, name: &Path, flags: i32) -> io::Result<File> {
+        let path = cstr(name)?;
+        let fd = cvt_r(|| unsafe {
+            libc::openat(
+                self.descriptor,
+                path.as_ptr(),
+                flags | libc::O_CLOEXEC | libc::O_NOFOLLOW,
+                0o666 as libc::c_uint,
+            )
+        })?;
+        Ok(File::from_descriptor(fd))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{ErrorKind, Read};
+
+    use crate::fd::{self, File};
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn opens_relative_to_cwd_handle() {
+        // Cargo runs tests from the package root.
+        let cwd = fd::cwd().unwrap();
+        let mut manifest = cwd
+            .openat("Cargo.toml", File::options().read(true))
+            .unwrap();
+        let mut contents = String::new();
+        manifest.read_to_string(&mut contents).unwrap();
+        assert!(contents.contains("[package]"));
+    }
+
+    #[test]
+    fn opens_relative_to_any_directory() {
+        let tmp = TempDir::new();
+        tmp.file("inside", b"found");
+        let dir = File::open(tmp.path()).unwrap();
+
+        let mut f = dir.openat("inside", File::options().read(true)).unwrap();
+        let mut contents = String::new();
+        f.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "found");
+
+        let missing = dir.openat("missing", File::options().read(true));
+        assert_eq!(missing.err().unwrap().kind(), ErrorKind::NotFound);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn open_beneath_rejects_escapes() {
+        let tmp = TempDir::new();
+        tmp.file("inside", b"ok");
+        std::fs::create_dir(tmp.join("sub")).unwrap();
+        let dir = File::open(tmp.path()).unwrap();
+
+        let mut f = dir.open_beneath("inside", libc::O_RDONLY).unwrap();
+        let mut contents = String::new();
+        f.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "ok");
+
+        for escape in &["../escape", "/etc/passwd", "sub/../../escape"] {
+            let err = dir.open_beneath(escape, libc::O_RDONLY).err().unwrap();
+            assert_eq!(err.raw_os_error(), Some(libc::EXDEV), "{}", escape);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn open_beneath_opens_directories() {
+        let tmp = TempDir::new();
+        std::fs::create_dir(tmp.join("sub")).unwrap();
+        tmp.file("sub/inside", b"ok");
+        let dir = File::open(tmp.path()).unwrap();
+
+        let sub = dir
+            .open_beneath("sub", libc::O_RDONLY | libc::O_DIRECTORY)
+            .unwrap();
+        let mut f = sub.open_beneath("inside", libc::O_RDONLY).unwrap();
+        let mut contents = String::new();
+        f.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "ok");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn open_beneath_fallback_rejects_escapes() {
+        let tmp = TempDir::new();
+        tmp.file("inside", b"ok");
+        let dir = File::open(tmp.path()).unwrap();
+
+        assert!(dir.open_beneath_fallback("inside", libc::O_RDONLY).is_ok());
+        let err = dir
+            .open_beneath_fallback("../escape", libc::O_RDONLY)
+            .err()
+            .unwrap();
+        assert_eq!(err.raw_os_error(), Some(libc::EXDEV));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn symlinked_directories_dont_lead_out() {
+        let outside = TempDir::new();
+        outside.file("secret", b"outside");
+        let tmp = TempDir::new();
+        std::fs::create_dir(tmp.join("sub")).unwrap();
+        tmp.file("sub/inside", b"ok");
+        std::os::unix::fs::symlink(outside.path(), tmp.join("out")).unwrap();
+        let dir = File::open(tmp.path()).unwrap();
+
+        let mut f = dir
+            .open_beneath_fallback("./sub/inside", libc::O_RDONLY)
+            .unwrap();
+        let mut contents = String::new();
+        f.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "ok");
+
+        let err = dir
+            .open_beneath("out/secret", libc::O_RDONLY)
+            .err()
+            .unwrap();
+        assert_eq!(err.raw_os_error(), Some(libc::EXDEV));
+        let err = dir
+            .open_beneath_fallback("out/secret", libc::O_RDONLY)
+            .err()
+            .unwrap();
+        assert_eq!(err.raw_os_error(), Some(libc::ENOTDIR));
+        let err = dir
+            .open_beneath_fallback("out", libc::O_RDONLY)
+            .err()
+            .unwrap();
+        assert_eq!(err.raw_os_error(), Some(libc::ELOOP));
+    }
+}
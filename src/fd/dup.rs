@@ -0,0 +1,269 @@
+use std::io;
+
+use super::{cvt, cvt_r, Descriptors, File};
+
+impl File {
+    /// Duplicates the descriptor with `dup`, reporting failure instead of
+    /// panicking the way `clone` does. The duplicate is close-on-exec.
+    pub fn try_clone(&self) -> io::Result<File> {
+        self.try_clone_with(true, false)
+    }
+
+    /// Duplicates the descriptor with plain `dup(2)`.
+    ///
+    /// The two `File`s have different descriptor numbers but share one open
+    /// file description, and with it the offset and status flags. Unlike
+    /// `try_clone`, the new descriptor is not close-on-exec, which is what
+    /// `dup` does and what a descriptor meant for a child program wants.
+    pub fn duplicate(&self) -> io::Result<File> {
+        let fd = cvt_r(|| unsafe { libc::dup(self.descriptor) })?;
+        Ok(File::from_descriptor(fd))
+    }
+
+    /// Closes the descriptor now and reports how that went.
+    ///
+    /// Dropping a `File` closes it too but has to throw the result away. On
+    /// some filesystems `close` is where a deferred write error finally
+    /// shows up, so code that cares should close explicitly. Either way the
+    /// descriptor is gone afterwards; retrying a failed `close` is never
+    /// right on Linux, so this doesn't offer the chance.
+    pub fn close(self) -> io::Result<()> {
+        let fd = self.into_descriptor();
+        cvt(unsafe { libc::close(fd) })?;
+        Ok(())
+    }
+
+    /// Duplicates the descriptor, choosing the new one's flags.
+    ///
+    /// Close-on-exec belongs to the descriptor, so it is set atomically by
+    /// `F_DUPFD_CLOEXEC` and no `fork` on another thread can slip in and
+    /// leak the duplicate without it. `O_NONBLOCK` is different: it lives on
+    /// the open file description the two descriptors share, so asking for it
+    /// makes the original nonblocking too. Passing `false` leaves whatever
+    /// the original had.
+    pub fn try_clone_with(&self, cloexec: bool, nonblocking: bool) -> io::Result<File> {
+        let cmd = if cloexec {
+            libc::F_DUPFD_CLOEXEC
+        } else {
+            libc::F_DUPFD
+        };
+        let clone = File::from_descriptor(self.fcntl(cmd, 0)?);
+        if nonblocking {
+            let flags = clone.fcntl(libc::F_GETFL, 0)?;
+            clone.fcntl(libc::F_SETFL, flags | libc::O_NONBLOCK)?;
+        }
+        Ok(clone)
+    }
+}
+
+/// `clone` is `dup`; the new descriptor is close-on-exec and shares the
+/// original's offset and status flags, but not its saved offsets.
+///
+/// # Panics
+///
+/// If `dup` fails, typically because the process is out of descriptors.
+/// `try_clone_with` reports that as an error instead.
+impl<D: Descriptors> Clone for File<D> {
+    fn clone(&self) -> File<D> {
+        let fd = self
+            .table
+            .dup(self.descriptor)
+            .expect("failed to duplicate file descriptor");
+        File::in_table(self.table.clone(), fd)
+    }
+
+    /// The notes' `clone_from`, without the self-assignment bug.
+    ///
+    /// Taking `&mut self` and `&source` already rules out `f.clone_from(&f)`:
+    ///
+    /// ```compile_fail,E0502
+    /// use references_sharing_vs_mutation::fd::File;
+    ///
+    /// let mut f = File::open("/dev/null").unwrap();
+    /// f.clone_from(&f);
+    /// ```
+    ///
+    /// Two `File`s holding the same descriptor can only come from unsafe
+    /// code, but if they do, this leaves the descriptor alone rather than
+    /// closing it and then duplicating a closed descriptor. The duplicate is
+    /// also made before the old descriptor is closed, so a failed `dup`
+    /// panics with `self` still intact.
+    fn clone_from(&mut self, source: &File<D>) {
+        if self.table.is(&source.table) && self.descriptor == source.descriptor {
+            return;
+        }
+        let fd = source
+            .table
+            .dup(source.descriptor)
+            .expect("failed to duplicate file descriptor");
+        let _ = self.table.close(self.descriptor);
+        self.table = source.table.clone();
+        self.descriptor = fd;
+        self.offsets.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+    use std::mem::{self, ManuallyDrop};
+
+    use crate::fd::{pipe, File};
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn duplicate_shares_the_open_file() {
+        let dir = TempDir::new();
+        let mut writer = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(dir.join("shared"))
+            .unwrap();
+        let mut reader = writer.duplicate().unwrap();
+        assert_ne!(reader.descriptor, writer.descriptor);
+        let fd_flags = reader.fcntl(libc::F_GETFD, 0).unwrap();
+        assert_eq!(fd_flags & libc::FD_CLOEXEC, 0);
+
+        writer.write_all(b"one description").unwrap();
+        assert_eq!(reader.stream_position().unwrap(), 15);
+        reader.seek(SeekFrom::Start(4)).unwrap();
+        let mut contents = String::new();
+        reader.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "description");
+    }
+
+    #[test]
+    fn clone_with_both_flags() {
+        let (reader, _writer) = pipe().unwrap();
+        let clone = reader.try_clone_with(true, true).unwrap();
+
+        assert_ne!(clone.descriptor, reader.descriptor);
+        let fd_flags = clone.fcntl(libc::F_GETFD, 0).unwrap();
+        assert_ne!(fd_flags & libc::FD_CLOEXEC, 0);
+        let status = clone.fcntl(libc::F_GETFL, 0).unwrap();
+        assert_ne!(status & libc::O_NONBLOCK, 0);
+    }
+
+    #[test]
+    fn clone_without_flags() {
+        let (reader, _writer) = pipe().unwrap();
+        let clone = reader.try_clone_with(false, false).unwrap();
+
+        let fd_flags = clone.fcntl(libc::F_GETFD, 0).unwrap();
+        assert_eq!(fd_flags & libc::FD_CLOEXEC, 0);
+        let status = clone.fcntl(libc::F_GETFL, 0).unwrap();
+        assert_eq!(status & libc::O_NONBLOCK, 0);
+    }
+
+    #[test]
+    fn clone_gets_its_own_descriptor() {
+        let dir = TempDir::new();
+        let f = File::open(dir.file("f", b"shared offset")).unwrap();
+        let mut g = f.clone();
+        assert_ne!(f.descriptor, g.descriptor);
+
+        let mut buf = [0; 6];
+        g.read_exact(&mut buf).unwrap();
+        assert_eq!(f.stream_position().unwrap(), 6);
+        drop(f);
+        assert_eq!(g.stream_position().unwrap(), 6);
+    }
+
+    #[test]
+    fn clone_from_replaces_the_descriptor() {
+        let dir = TempDir::new();
+        let mut target = File::open(dir.file("old", b"old")).unwrap();
+        let source = File::open(dir.file("new", b"new")).unwrap();
+        target.clone_from(&source);
+        assert_ne!(target.descriptor, source.descriptor);
+
+        let mut contents = String::new();
+        target.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "new");
+    }
+
+    #[test]
+    fn clone_from_closes_the_old_descriptor_once() {
+        // Pipes show which descriptors are still open without depending on
+        // descriptor numbers, which other tests' threads may reuse: a write
+        // fails with `BrokenPipe` once every read end is closed.
+        let (old_reader, old_writer) = pipe().unwrap();
+        let (new_reader, new_writer) = pipe().unwrap();
+        let mut target = old_reader;
+
+        target.clone_from(&new_reader);
+        let err = old_writer.write_all_raw(b"x").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+
+        // The duplicate is open and reads the new pipe, and survives the
+        // source going away.
+        drop(new_reader);
+        new_writer.write_all_raw(b"new").unwrap();
+        let mut buf = [0; 3];
+        target.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"new");
+
+        // Dropping the target closes the duplicate, which was the last read
+        // end of the new pipe.
+        drop(target);
+        let err = new_writer.write_all_raw(b"x").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn self_clone_from_leaves_the_descriptor_open() {
+        let dir = TempDir::new();
+        let mut f = File::open(dir.file("f", b"still here")).unwrap();
+        // A second owner of the same descriptor, which must never close it.
+        let alias = ManuallyDrop::new(File::from_descriptor(f.descriptor));
+        f.clone_from(&alias);
+        assert_eq!(f.descriptor, alias.descriptor);
+
+        let mut contents = String::new();
+        f.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "still here");
+    }
+
+    #[test]
+    fn close_reports_success_and_closes() {
+        let (reader, writer) = pipe().unwrap();
+        reader.close().unwrap();
+        let err = writer.write_all_raw(b"x").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn try_clone_reports_running_out_of_descriptors() {
+        let (reader, _writer) = pipe().unwrap();
+        // Lowering the descriptor limit would starve every other test, so do
+        // it in a child. Nothing the child does after fork allocates.
+        let pid = unsafe { libc::fork() };
+        assert!(pid >= 0);
+        if pid == 0 {
+            let limit = libc::rlimit {
+                rlim_cur: 1,
+                rlim_max: 1,
+            };
+            let code = unsafe {
+                if libc::setrlimit(libc::RLIMIT_NOFILE, &limit) != 0 {
+                    3
+                } else {
+                    match reader.try_clone() {
+                        Err(ref e) if e.raw_os_error() == Some(libc::EMFILE) => 0,
+                        Err(_) => 2,
+                        Ok(clone) => {
+                            mem::forget(clone);
+                            1
+                        }
+                    }
+                }
+            };
+            unsafe { libc::_exit(code) };
+        }
+        let mut status = 0;
+        assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+        assert!(libc::WIFEXITED(status));
+        assert_eq!(libc::WEXITSTATUS(status), 0);
+    }
+}
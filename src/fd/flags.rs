@@ -0,0 +1,62 @@
+use std::io;
+
+use super::{cvt_r, File};
+
+impl File {
+    /// Whether the descriptor has `O_APPEND` set, for checking the mode of a
+    /// descriptor handed in from elsewhere.
+    pub fn is_append(&self) -> io::Result<bool> {
+        Ok(self.status_flags()? & libc::O_APPEND != 0)
+    }
+
+    /// Calls `fcntl(2)` on the descriptor with an integer argument.
+    ///
+    /// This is the escape hatch for operations the crate doesn't wrap.
+    /// Nothing checks that `cmd` makes sense, and commands that hand back a
+    /// new descriptor, such as `F_DUPFD`, leave closing it to the caller.
+    pub fn fcntl(&self, cmd: i32, arg: i32) -> io::Result<i32> {
+        cvt_r(|| unsafe { libc::fcntl(self.descriptor, cmd, arg) })
+    }
+
+    fn status_flags(&self) -> io::Result<libc::c_int> {
+        self.fcntl(libc::F_GETFL, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fd::File;
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn reports_append_mode() {
+        let dir = TempDir::new();
+        let log = File::open_append(dir.join("log")).unwrap();
+        assert!(log.is_append().unwrap());
+
+        let plain = File::open(dir.file("plain", b"")).unwrap();
+        assert!(!plain.is_append().unwrap());
+    }
+
+    #[test]
+    fn fcntl_reports_access_mode() {
+        let dir = TempDir::new();
+        let path = dir.file("modes", b"");
+
+        let read_only = File::open(&path).unwrap();
+        let flags = read_only.fcntl(libc::F_GETFL, 0).unwrap();
+        assert_eq!(flags & libc::O_ACCMODE, libc::O_RDONLY);
+
+        let read_write = File::options().read(true).write(true).open(&path).unwrap();
+        let flags = read_write.fcntl(libc::F_GETFL, 0).unwrap();
+        assert_eq!(flags & libc::O_ACCMODE, libc::O_RDWR);
+    }
+
+    #[test]
+    fn fcntl_errors_come_back() {
+        let dir = TempDir::new();
+        let f = File::open(dir.file("f", b"")).unwrap();
+        let err = f.fcntl(-1, 0).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EINVAL));
+    }
+}
@@ -0,0 +1,102 @@
+use std::io::{self, Chain, Cursor, Read};
+
+use flate2::read::MultiGzDecoder;
+
+use super::File;
+
+/// The two bytes every gzip member starts with.
+const MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+type Peeked = Chain<Cursor<Vec<u8>>, File>;
+
+/// What `read_gzip` hands back: decompressed bytes, or the file as it was.
+enum MaybeGzip {
+    Gzip(Box<MultiGzDecoder<Peeked>>),
+    Plain(Peeked),
+}
+
+impl Read for MaybeGzip {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            MaybeGzip::Gzip(decoder) => decoder.read(buf),
+            MaybeGzip::Plain(file) => file.read(buf),
+        }
+    }
+}
+
+impl File {
+    /// Reads the rest of the file through a streaming gzip decoder if it
+    /// starts with the gzip magic bytes, and unchanged if it doesn't.
+    ///
+    /// Checking means reading the first two bytes, so the `File` is taken
+    /// by value: the returned reader replays them before carrying on from
+    /// the file, and nothing else can read from it in between. Files made
+    /// of several gzip members, as `cat a.gz b.gz` produces, decompress to
+    /// the concatenation. Corrupt gzip data is an `InvalidInput` or
+    /// `InvalidData` error from the reader.
+    pub fn read_gzip(mut self) -> io::Result<impl Read> {
+        let mut magic = [0; 2];
+        let mut n = 0;
+        while n < magic.len() {
+            match self.read(&mut magic[n..])? {
+                0 => break,
+                k => n += k,
+            }
+        }
+        let peeked = Cursor::new(magic[..n].to_vec()).chain(self);
+        Ok(if magic[..n] == MAGIC {
+            MaybeGzip::Gzip(Box::new(MultiGzDecoder::new(peeked)))
+        } else {
+            MaybeGzip::Plain(peeked)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    use crate::fd::File;
+    use crate::testutil::TempDir;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn read_all(f: File) -> Vec<u8> {
+        let mut out = Vec::new();
+        f.read_gzip().unwrap().read_to_end(&mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn decompresses_gzip() {
+        let dir = TempDir::new();
+        let text = b"shared access is read-only access\n".repeat(100);
+        let f = File::open(dir.file("notes.gz", &gzip(&text))).unwrap();
+        assert_eq!(read_all(f), text);
+    }
+
+    #[test]
+    fn concatenated_members_decompress_in_order() {
+        let dir = TempDir::new();
+        let mut both = gzip(b"first, ");
+        both.extend(gzip(b"second"));
+        let f = File::open(dir.file("both.gz", &both)).unwrap();
+        assert_eq!(read_all(f), b"first, second");
+    }
+
+    #[test]
+    fn passes_plain_files_through() {
+        let dir = TempDir::new();
+        for contents in &[&b"plain text"[..], b"\x1f", b""] {
+            let f = File::open(dir.file("plain", contents)).unwrap();
+            assert_eq!(read_all(f), *contents);
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::io;
+
+use super::{cvt_r, File};
+
+impl File {
+    /// Deallocates `len` bytes starting at `offset`, leaving the file size
+    /// unchanged. Reads from the hole return zeros.
+    ///
+    /// Filesystems that can't punch holes report `EOPNOTSUPP`, which comes
+    /// back as the error. The file must be open for writing.
+    pub fn punch_hole(&self, offset: u64, len: u64) -> io::Result<()> {
+        cvt_r(|| unsafe {
+            libc::fallocate(
+                self.descriptor,
+                libc::FALLOC_FL_PUNCH_HOLE | libc::FALLOC_FL_KEEP_SIZE,
+                offset as libc::off_t,
+                len as libc::off_t,
+            )
+        })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::OpenOptions;
+    use std::io::{Read, Seek, SeekFrom};
+
+    use crate::testutil::{self, TempDir};
+
+    #[test]
+    fn hole_reads_back_as_zeros() {
+        let dir = TempDir::new();
+        let path = dir.file("data", &[0xaa; 3 * 4096]);
+        let mut f = testutil::adopt(
+            OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(path)
+                .unwrap(),
+        );
+
+        match f.punch_hole(4096, 4096) {
+            Err(ref e) if e.raw_os_error() == Some(libc::EOPNOTSUPP) => return,
+            result => result.unwrap(),
+        }
+
+        let mut contents = Vec::new();
+        f.seek(SeekFrom::Start(0)).unwrap();
+        f.read_to_end(&mut contents).unwrap();
+        assert_eq!(contents.len(), 3 * 4096);
+        assert!(contents[..4096].iter().all(|&b| b == 0xaa));
+        assert!(contents[4096..8192].iter().all(|&b| b == 0));
+        assert!(contents[8192..].iter().all(|&b| b == 0xaa));
+    }
+}
@@ -0,0 +1,72 @@
+use std::io::{self, Read};
+use std::str;
+
+use super::File;
+
+impl File {
+    /// Reads up to and including the next `\n`, or to end of file, and
+    /// appends it to `buf`. Returns the number of bytes read, so `0` means
+    /// end of file.
+    ///
+    /// This is `BufRead::read_line` without the buffer: it reads a byte at a
+    /// time so it never consumes anything past the newline, which matters
+    /// when the descriptor is shared with other code. Wrap the `File` in a
+    /// `BufReader` when that doesn't matter and speed does.
+    ///
+    /// If the line isn't valid UTF-8 this fails with `InvalidData` and `buf`
+    /// is left as it was, though the bytes have still been consumed.
+    pub fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        let mut line = Vec::new();
+        let mut byte = [0u8; 1];
+        // `read` retries `EINTR` itself.
+        while self.read(&mut byte)? != 0 {
+            line.push(byte[0]);
+            if byte[0] == b'\n' {
+                break;
+            }
+        }
+        let line =
+            str::from_utf8(&line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        buf.push_str(line);
+        Ok(line.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{ErrorKind, Read};
+
+    use crate::fd::File;
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn reads_lines_into_reused_buffer() {
+        let dir = TempDir::new();
+        let mut f = File::open(dir.file("lines", "first\nsecond ✓\nrest".as_bytes())).unwrap();
+        let mut line = String::new();
+
+        assert_eq!(f.read_line(&mut line).unwrap(), 6);
+        assert_eq!(line, "first\n");
+
+        line.clear();
+        assert_eq!(f.read_line(&mut line).unwrap(), 11);
+        assert_eq!(line, "second ✓\n");
+
+        // Nothing past the newline was consumed.
+        let mut rest = String::new();
+        f.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "rest");
+
+        assert_eq!(f.read_line(&mut line).unwrap(), 0);
+    }
+
+    #[test]
+    fn invalid_utf8_leaves_buffer_alone() {
+        let dir = TempDir::new();
+        let mut f = File::open(dir.file("bad", b"\xff\xfe\n")).unwrap();
+        let mut line = String::from("kept");
+        let err = f.read_line(&mut line).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(line, "kept");
+    }
+}
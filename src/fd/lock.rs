@@ -0,0 +1,218 @@
+use std::io;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{cvt, cvt_r, File};
+
+/// Holds an `flock` on a `File` and releases it when dropped, so early
+/// returns through `?` can't leave the file locked.
+struct Flock<'a> {
+    file: &'a File,
+}
+
+impl<'a> Flock<'a> {
+    fn exclusive(file: &'a File) -> io::Result<Flock<'a>> {
+        cvt_r(|| unsafe { libc::flock(file.descriptor, libc::LOCK_EX) })?;
+        Ok(Flock { file })
+    }
+}
+
+impl<'a> Drop for Flock<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            libc::flock(self.file.descriptor, libc::LOCK_UN);
+        }
+    }
+}
+
+impl File {
+    /// Replaces the whole contents of the file with `data`.
+    ///
+    /// Holds an exclusive `flock` while it truncates and rewrites, so other
+    /// processes that lock before reading never see the file half written.
+    /// The file's own offset is left alone. It must be open for writing.
+    pub fn replace_contents(&self, data: &[u8]) -> io::Result<()> {
+        let _lock = Flock::exclusive(self)?;
+        cvt_r(|| unsafe { libc::ftruncate(self.descriptor, 0) })?;
+        self.write_all_at(data, 0)
+    }
+
+    /// Appends all of `data` at the end of the file while holding an
+    /// exclusive `flock`.
+    ///
+    /// Appenders that all go through this never interleave, even on
+    /// filesystems such as NFS where `O_APPEND` writes aren't atomic,
+    /// because finding the end and writing to it happen under one lock.
+    /// The file's offset is left at the new end. It must be open for
+    /// writing, but doesn't need `O_APPEND`.
+    pub fn append_locked(&self, data: &[u8]) -> io::Result<()> {
+        let _lock = Flock::exclusive(self)?;
+        cvt(unsafe { libc::lseek(self.descriptor, 0, libc::SEEK_END) })?;
+        self.write_all_raw(data)
+    }
+
+    /// Tries to take an exclusive `flock` for up to `deadline`, returning
+    /// whether it got it.
+    ///
+    /// It polls with `LOCK_NB`, sleeping a little longer after each miss, so
+    /// it never blocks indefinitely. On success the lock stays held until
+    /// `unlock` is called or the file is closed.
+    pub fn lock_exclusive_timeout(&self, deadline: Duration) -> io::Result<bool> {
+        let start = Instant::now();
+        let mut backoff = Duration::from_millis(1);
+        loop {
+            let ret = unsafe { libc::flock(self.descriptor, libc::LOCK_EX | libc::LOCK_NB) };
+            match cvt(ret) {
+                Ok(_) => return Ok(true),
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+            let elapsed = start.elapsed();
+            if elapsed >= deadline {
+                return Ok(false);
+            }
+            thread::sleep(backoff.min(deadline - elapsed));
+            backoff = (backoff * 2).min(Duration::from_millis(50));
+        }
+    }
+
+    /// Opens an existing file read-write and takes an exclusive `flock` on
+    /// it, failing with `WouldBlock` instead of waiting if anyone else
+    /// holds a lock on it.
+    ///
+    /// This is only as exclusive as the other users are polite: `flock` is
+    /// advisory, so it keeps out processes that lock the file too, not ones
+    /// that simply open it. The lock lasts until the returned `File` is
+    /// closed or `unlock` is called.
+    pub fn open_exclusive_existing<P: AsRef<Path>>(path: P) -> io::Result<File> {
+        let file = File::options().read(true).write(true).open(path)?;
+        cvt_r(|| unsafe { libc::flock(file.descriptor, libc::LOCK_EX | libc::LOCK_NB) })?;
+        Ok(file)
+    }
+
+    /// Releases an `flock` held through this file.
+    pub fn unlock(&self) -> io::Result<()> {
+        cvt(unsafe { libc::flock(self.descriptor, libc::LOCK_UN) })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::ErrorKind;
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::fd::File;
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn replaces_contents() {
+        let dir = TempDir::new();
+        let path = dir.file("data", &[b'x'; 1024]);
+        let f = File::options().read(true).write(true).open(&path).unwrap();
+
+        f.replace_contents(b"fresh contents").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"fresh contents");
+
+        // The lock was released: taking it again doesn't deadlock.
+        f.replace_contents(b"again").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"again");
+    }
+
+    #[test]
+    fn locked_appends_never_interleave() {
+        let dir = TempDir::new();
+        let path = dir.file("log", b"");
+
+        let appenders: Vec<_> = (0..2u8)
+            .map(|id| {
+                // Each thread opens the file itself so the two flocks contend.
+                let f = File::options().write(true).open(&path).unwrap();
+                thread::spawn(move || {
+                    for n in 0..1000 {
+                        let mut record = vec![b'a' + id; 60];
+                        record.extend_from_slice(format!("{:03}\n", n).as_bytes());
+                        f.append_locked(&record).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for appender in appenders {
+            appender.join().unwrap();
+        }
+
+        let contents = fs::read(&path).unwrap();
+        let records: Vec<&[u8]> = contents.split(|&b| b == b'\n').collect();
+        assert_eq!(records.len(), 2001);
+        assert!(records[2000].is_empty());
+        let mut next = [0; 2];
+        for record in &records[..2000] {
+            assert_eq!(record.len(), 63);
+            let id = record[0];
+            assert!(id == b'a' || id == b'b');
+            assert!(record[..60].iter().all(|&b| b == id));
+            let n: usize = std::str::from_utf8(&record[60..]).unwrap().parse().unwrap();
+            let slot = &mut next[(id - b'a') as usize];
+            assert_eq!(n, *slot);
+            *slot += 1;
+        }
+        assert_eq!(next, [1000, 1000]);
+    }
+
+    #[test]
+    fn lock_times_out_while_held() {
+        let dir = TempDir::new();
+        let path = dir.file("locked", b"");
+        // flock locks belong to the open file, so two opens contend even
+        // within one process.
+        let holder = File::open(&path).unwrap();
+        let waiter = File::open(&path).unwrap();
+
+        assert!(holder
+            .lock_exclusive_timeout(Duration::from_secs(1))
+            .unwrap());
+        assert!(!waiter
+            .lock_exclusive_timeout(Duration::from_millis(30))
+            .unwrap());
+    }
+
+    #[test]
+    fn exclusive_open_fails_while_locked() {
+        let dir = TempDir::new();
+        let path = dir.file("locked", b"");
+        let first = File::open_exclusive_existing(&path).unwrap();
+
+        let err = File::open_exclusive_existing(&path).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::WouldBlock);
+
+        drop(first);
+        File::open_exclusive_existing(&path).unwrap();
+
+        let missing = File::open_exclusive_existing(dir.join("missing"));
+        assert_eq!(missing.unwrap_err().kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn lock_acquired_once_released() {
+        let dir = TempDir::new();
+        let path = dir.file("locked", b"");
+        let holder = File::open(&path).unwrap();
+        let waiter = File::open(&path).unwrap();
+        assert!(holder
+            .lock_exclusive_timeout(Duration::from_secs(1))
+            .unwrap());
+
+        let releaser = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            holder.unlock().unwrap();
+        });
+        assert!(waiter
+            .lock_exclusive_timeout(Duration::from_secs(5))
+            .unwrap());
+        releaser.join().unwrap();
+    }
+}
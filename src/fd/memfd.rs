@@ -0,0 +1,37 @@
+use std::ffi::CString;
+use std::io;
+
+use super::{cvt, File};
+
+impl File {
+    /// Creates an anonymous file that lives in memory, with `memfd_create(2)`.
+    ///
+    /// It behaves like a regular file opened for reading and writing but has
+    /// no path; it disappears once every descriptor to it is closed, which
+    /// makes it a handy buffer to pass to another process as a descriptor.
+    /// `name` only shows up in `/proc/self/fd` listings.
+    pub fn memfd(name: &str) -> io::Result<File> {
+        let name = CString::new(name)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "name contains a nul byte"))?;
+        let fd = cvt(unsafe { libc::memfd_create(name.as_ptr(), libc::MFD_CLOEXEC) })?;
+        Ok(File::from_descriptor(fd))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Seek, SeekFrom};
+
+    use crate::fd::File;
+
+    #[test]
+    fn memfd_round_trip() {
+        let mut f = File::memfd("round-trip").unwrap();
+        f.write_all_raw(b"kept in memory").unwrap();
+        f.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut contents = String::new();
+        f.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "kept in memory");
+    }
+}
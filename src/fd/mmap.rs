@@ -0,0 +1,165 @@
+use std::io;
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+use std::slice;
+
+use super::File;
+
+/// A writable memory map of a whole file, unmapped when dropped.
+///
+/// The map doesn't borrow the `File` it came from: the kernel keeps the
+/// mapping valid after the descriptor is closed.
+pub struct MmapMut {
+    ptr: *mut u8,
+    len: usize,
+}
+
+impl File {
+    /// Maps the whole file into memory for reading and writing.
+    ///
+    /// The mapping is `MAP_SHARED`, so writes through it reach the file; they
+    /// are flushed with `msync` when the map is dropped, or earlier with
+    /// `MmapMut::flush`. The file must be open for reading and writing.
+    ///
+    /// Nothing stops another process, or another map of the same file, from
+    /// changing the bytes underneath; this is the one place where the
+    /// exclusivity `&mut [u8]` promises is up to the caller.
+    pub fn map_mut(&self) -> io::Result<MmapMut> {
+        self.map(libc::MAP_SHARED)
+    }
+
+    /// Maps the whole file copy-on-write, with `MAP_PRIVATE`.
+    ///
+    /// The map starts out as the file's contents and can be changed freely,
+    /// but the changes stay in this process's own copies of the pages and
+    /// never reach the file; `flush` has nothing to write. Read access to
+    /// the file is enough.
+    pub fn map_copy(&self) -> io::Result<MmapMut> {
+        self.map(libc::MAP_PRIVATE)
+    }
+
+    fn map(&self, flags: libc::c_int) -> io::Result<MmapMut> {
+        let len = self.fstat()?.st_size as usize;
+        if len == 0 {
+            // mmap rejects empty mappings; an empty slice needs no memory.
+            return Ok(MmapMut {
+                ptr: ptr::NonNull::dangling().as_ptr(),
+                len: 0,
+            });
+        }
+        let ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                flags,
+                self.descriptor,
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(MmapMut {
+            ptr: ptr as *mut u8,
+            len,
+        })
+    }
+}
+
+impl MmapMut {
+    /// Writes changed pages back to the file and waits for it to finish. A
+    /// copy-on-write map has none to write.
+    pub fn flush(&self) -> io::Result<()> {
+        if self.len == 0 {
+            return Ok(());
+        }
+        let ret = unsafe { libc::msync(self.ptr as *mut libc::c_void, self.len, libc::MS_SYNC) };
+        if ret == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl Deref for MmapMut {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl DerefMut for MmapMut {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+impl Drop for MmapMut {
+    fn drop(&mut self) {
+        if self.len == 0 {
+            return;
+        }
+        let _ = self.flush();
+        unsafe {
+            libc::munmap(self.ptr as *mut libc::c_void, self.len);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::Read;
+
+    use crate::fd::File;
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn writes_through_map_reach_file() {
+        let dir = TempDir::new();
+        let path = dir.file("mapped", b"hello, world");
+        let f = File::options().read(true).write(true).open(&path).unwrap();
+
+        let mut map = f.map_mut().unwrap();
+        drop(f);
+        assert_eq!(&map[..], b"hello, world");
+        map[..5].copy_from_slice(b"HELLO");
+        drop(map);
+
+        let mut contents = Vec::new();
+        File::open(&path)
+            .unwrap()
+            .read_to_end(&mut contents)
+            .unwrap();
+        assert_eq!(contents, b"HELLO, world");
+    }
+
+    #[test]
+    fn private_map_changes_stay_in_memory() {
+        let dir = TempDir::new();
+        let path = dir.file("original", b"hello, world");
+        let f = File::open(&path).unwrap();
+
+        let mut map = f.map_copy().unwrap();
+        map[..5].copy_from_slice(b"HELLO");
+        assert_eq!(&map[..], b"HELLO, world");
+        map.flush().unwrap();
+        drop(map);
+
+        assert_eq!(fs::read(&path).unwrap(), b"hello, world");
+        assert_eq!(&f.map_copy().unwrap()[..], b"hello, world");
+    }
+
+    #[test]
+    fn empty_file_maps_to_empty_slice() {
+        let dir = TempDir::new();
+        let path = dir.file("empty", b"");
+        let f = File::options().read(true).write(true).open(&path).unwrap();
+        let map = f.map_mut().unwrap();
+        assert!(map.is_empty());
+        map.flush().unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"");
+    }
+}
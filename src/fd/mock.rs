@@ -0,0 +1,274 @@
+//! A pretend descriptor table, for exercising [`File`]'s ownership rules
+//! without a kernel.
+//!
+//! [`Table`] hands out the lowest free number on each open, as POSIX does,
+//! and counts every `dup` and `close`. A [`MockFile`] is a `File` over the
+//! table rather than the process's, so it's `File`'s own `Drop`, `clone`
+//! and `clone_from` that close, dup, and dup before they close. Nothing
+//! here calls into the OS.
+//!
+//! The table is also where the C++ assignment operator's bug can be shown
+//! running, since nothing stops [`Table::close_then_dup`] from being given
+//! the same descriptor twice:
+//!
+//! ```
+//! use references_sharing_vs_mutation::fd::mock::Table;
+//!
+//! let table = Table::new();
+//! let f = table.open("foo.txt");
+//! let fd = f.descriptor();
+//! // `f = f` in C++: closes `fd`, then tries to dup it.
+//! assert!(table.close_then_dup(fd, fd).is_err());
+//! assert!(!table.is_open(fd));
+//! ```
+
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io;
+use std::rc::Rc;
+
+use super::{Descriptors, File};
+
+/// A descriptor table: each open slot names the file it refers to.
+///
+/// Cloning a `Table` gives another handle on the same table.
+#[derive(Clone, Default)]
+pub struct Table {
+    state: Rc<RefCell<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    slots: Vec<Option<Rc<str>>>,
+    dups: usize,
+    closes: usize,
+}
+
+impl Table {
+    pub fn new() -> Table {
+        Table::default()
+    }
+
+    /// Opens `name` in the lowest free slot.
+    pub fn open(&self, name: &str) -> MockFile {
+        let descriptor = self.install(Rc::from(name));
+        File::in_table(self.clone(), descriptor)
+    }
+
+    /// Whether `fd` is open.
+    pub fn is_open(&self, fd: i32) -> bool {
+        self.name(fd).is_some()
+    }
+
+    /// The file `fd` refers to, if it's open.
+    pub fn name(&self, fd: i32) -> Option<String> {
+        self.lookup(fd).ok().map(|name| name.to_string())
+    }
+
+    /// How many descriptors are open.
+    pub fn open_count(&self) -> usize {
+        self.state
+            .borrow()
+            .slots
+            .iter()
+            .filter(|s| s.is_some())
+            .count()
+    }
+
+    /// How many `dup`s and `close`s have run, in that order.
+    pub fn calls(&self) -> (usize, usize) {
+        let state = self.state.borrow();
+        (state.dups, state.closes)
+    }
+
+    /// `dup(fd)`: a new slot referring to the same file.
+    pub fn dup(&self, fd: i32) -> io::Result<i32> {
+        let name = self.lookup(fd)?;
+        self.state.borrow_mut().dups += 1;
+        Ok(self.install(name))
+    }
+
+    /// `close(fd)`, failing if it isn't open.
+    pub fn close(&self, fd: i32) -> io::Result<()> {
+        self.lookup(fd)?;
+        let mut state = self.state.borrow_mut();
+        state.closes += 1;
+        state.slots[fd as usize] = None;
+        Ok(())
+    }
+
+    /// The C++ `operator=`: closes `this`, then returns `dup(rhs)`.
+    ///
+    /// With `this == rhs` the `dup` finds the descriptor already closed and
+    /// fails, and the file is gone either way. This takes plain numbers
+    /// because that is the only way to hand it the same descriptor twice;
+    /// `File::clone_from` can't be.
+    pub fn close_then_dup(&self, this: i32, rhs: i32) -> io::Result<i32> {
+        self.close(this)?;
+        self.dup(rhs)
+    }
+
+    fn lookup(&self, fd: i32) -> io::Result<Rc<str>> {
+        let state = self.state.borrow();
+        let slot = usize::try_from(fd).ok().and_then(|i| state.slots.get(i));
+        match slot {
+            Some(Some(name)) => Ok(name.clone()),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("bad file descriptor {}", fd),
+            )),
+        }
+    }
+
+    fn install(&self, name: Rc<str>) -> i32 {
+        let mut state = self.state.borrow_mut();
+        let index = match state.slots.iter().position(Option::is_none) {
+            Some(free) => free,
+            None => {
+                state.slots.push(None);
+                state.slots.len() - 1
+            }
+        };
+        state.slots[index] = Some(name);
+        index as i32
+    }
+}
+
+impl fmt::Debug for Table {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = self.state.borrow();
+        f.debug_map()
+            .entries(
+                state
+                    .slots
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(fd, s)| s.as_deref().map(|name| (fd, name))),
+            )
+            .finish()
+    }
+}
+
+impl Descriptors for Table {
+    fn dup(&self, fd: i32) -> io::Result<i32> {
+        Table::dup(self, fd)
+    }
+
+    fn close(&self, fd: i32) -> io::Result<()> {
+        Table::close(self, fd)
+    }
+
+    fn is(&self, other: &Table) -> bool {
+        Rc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+/// Owns a slot in a [`Table`], as a plain `File` owns a descriptor.
+///
+/// `f.clone_from(&f)` doesn't compile, just as with a real file:
+///
+/// ```compile_fail,E0502
+/// use references_sharing_vs_mutation::fd::mock::Table;
+///
+/// let table = Table::new();
+/// let mut f = table.open("foo.txt");
+/// f.clone_from(&f);
+/// ```
+pub type MockFile = File<Table>;
+
+impl File<Table> {
+    /// The slot number, for checking against the table.
+    pub fn descriptor(&self) -> i32 {
+        self.descriptor
+    }
+
+    /// The file this refers to.
+    pub fn name(&self) -> String {
+        self.table
+            .name(self.descriptor)
+            .expect("MockFile's descriptor was closed behind its back")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem;
+
+    use super::*;
+
+    #[test]
+    fn drop_closes_exactly_once() {
+        let table = Table::new();
+        let f = table.open("foo.txt");
+        let g = f.clone();
+        assert_eq!((f.descriptor(), g.descriptor()), (0, 1));
+        assert_eq!(g.name(), "foo.txt");
+
+        drop(f);
+        assert!(!table.is_open(0));
+        assert_eq!(table.open_count(), 1);
+        drop(g);
+        assert_eq!(table.open_count(), 0);
+        assert_eq!(table.calls(), (1, 2));
+    }
+
+    #[test]
+    fn clone_from_takes_over_the_source_file() {
+        let table = Table::new();
+        let mut f = table.open("foo.txt");
+        let bar = table.open("bar.txt");
+
+        f.clone_from(&bar);
+        assert_eq!(f.name(), "bar.txt");
+        assert_ne!(f.descriptor(), bar.descriptor());
+        // The dup ran before the close, so it couldn't reuse foo's slot.
+        assert_eq!(f.descriptor(), 2);
+        assert!(!table.is_open(0));
+        assert_eq!(table.open_count(), 2);
+    }
+
+    #[test]
+    fn assigning_a_clone_of_itself_keeps_the_file() {
+        let table = Table::new();
+        let mut f = table.open("foo.txt");
+        let same = f.clone();
+
+        // The nearest Rust gets to `f = f`: the source is a distinct owner
+        // of the same file.
+        f.clone_from(&same);
+        drop(same);
+        assert_eq!(f.name(), "foo.txt");
+        assert_eq!(table.open_count(), 1);
+    }
+
+    #[test]
+    fn clone_from_ignores_an_identical_descriptor() {
+        let table = Table::new();
+        let mut f = table.open("foo.txt");
+        // Two owners of one slot only arise from a bug, like a second
+        // `from_raw_fd` of the same descriptor.
+        let alias = File::in_table(table.clone(), f.descriptor());
+        f.clone_from(&alias);
+        mem::forget(alias);
+        assert_eq!(f.name(), "foo.txt");
+        assert_eq!(table.calls(), (0, 0));
+    }
+
+    #[test]
+    fn cpp_assignment_loses_the_file() {
+        let table = Table::new();
+        let f = table.open("foo.txt");
+        let other = table.open("bar.txt");
+
+        let fd = table
+            .close_then_dup(f.descriptor(), other.descriptor())
+            .unwrap();
+        assert_eq!(table.name(fd).unwrap(), "bar.txt");
+
+        let err = table.close_then_dup(fd, fd).unwrap_err();
+        assert_eq!(err.to_string(), format!("bad file descriptor {}", fd));
+        assert!(!table.is_open(fd));
+        mem::forget(f);
+    }
+}
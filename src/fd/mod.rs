@@ -0,0 +1,443 @@
+//! A Rust owner for a raw file descriptor.
+//!
+//! This is the `File` from the self-assignment example in the notes, made
+//! real: the descriptor is private and every operation goes through `&self`
+//! or `&mut self`, so the borrow checker decides who may touch it and when.
+
+use std::ffi::CString;
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::RawFd;
+use std::path::Path;
+
+mod atomic;
+mod consume;
+mod copy;
+mod dir;
+mod dup;
+mod flags;
+#[cfg(feature = "gzip")]
+mod gzip;
+#[cfg(target_os = "linux")]
+mod hole;
+mod line;
+mod lock;
+#[cfg(target_os = "linux")]
+mod memfd;
+mod mmap;
+pub mod mock;
+mod nonblocking;
+mod offset;
+mod options;
+mod pod;
+mod poll;
+mod process;
+mod raw;
+mod redirect;
+mod socket;
+mod transfer;
+
+pub use self::atomic::atomic_write;
+pub use self::copy::CopyMethod;
+pub use self::dir::cwd;
+pub use self::mmap::MmapMut;
+pub use self::nonblocking::NonBlockingFile;
+pub use self::options::OpenOptions;
+pub use self::poll::wait_any;
+pub use self::process::{fork_with, Pid};
+pub use self::redirect::{redirect_stderr_to, StderrRedirect};
+pub use self::socket::socketpair;
+
+/// An open file descriptor, closed when the `File` is dropped.
+///
+/// The descriptor can't be reached from outside the crate, and ownership of
+/// the `File` is what decides when `close` runs, so it runs exactly once.
+/// Descriptors are the process's own unless `D` says otherwise: a `File`
+/// over the [`mock`] table runs the same `Drop`, `clone` and `clone_from`
+/// without a kernel, and everything else is only for the real thing.
+/// Using a `File` after it has been handed off (or dropped) is rejected:
+///
+/// ```compile_fail,E0382
+/// use references_sharing_vs_mutation::fd::File;
+///
+/// let f = File::open("/dev/null").unwrap();
+/// drop(f);
+/// f.stream_position(); // `f` was moved into `drop`
+/// ```
+pub struct File<D: Descriptors = Os> {
+    descriptor: RawFd,
+    offsets: Vec<u64>,
+    scratch: Vec<u8>,
+    table: D,
+}
+
+/// The table a `File`'s descriptor is a number in, and the two calls
+/// ownership needs from it.
+pub trait Descriptors: Clone {
+    /// A new descriptor for the file `fd` refers to.
+    fn dup(&self, fd: RawFd) -> io::Result<RawFd>;
+
+    /// Releases `fd`, which is gone afterwards even if this fails.
+    fn close(&self, fd: RawFd) -> io::Result<()>;
+
+    /// Whether `other` is this same table, so that equal numbers in the
+    /// two are the same descriptor.
+    fn is(&self, other: &Self) -> bool;
+}
+
+/// The process's descriptor table.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Os;
+
+impl Descriptors for Os {
+    /// `F_DUPFD_CLOEXEC`, as everything this module opens is close-on-exec.
+    fn dup(&self, fd: RawFd) -> io::Result<RawFd> {
+        cvt_r(|| unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, 0) })
+    }
+
+    /// Not retried on `EINTR`; see `cvt_r`.
+    fn close(&self, fd: RawFd) -> io::Result<()> {
+        cvt(unsafe { libc::close(fd) }).map(drop)
+    }
+
+    fn is(&self, _: &Os) -> bool {
+        true
+    }
+}
+
+impl File {
+    /// Opens `path` read-only.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<File> {
+        OpenOptions::new().read(true).open(path)
+    }
+
+    pub(crate) fn fstat(&self) -> io::Result<libc::stat> {
+        let mut stat = std::mem::MaybeUninit::<libc::stat>::uninit();
+        cvt(unsafe { libc::fstat(self.descriptor, stat.as_mut_ptr()) })?;
+        Ok(unsafe { stat.assume_init() })
+    }
+
+    /// Reads into `buf` at `offset` with `pread`, leaving the file's own
+    /// offset where it was.
+    pub(crate) fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let n = cvt_r(|| unsafe {
+            libc::pread(
+                self.descriptor,
+                buf.as_mut_ptr() as *mut libc::c_void,
+                buf.len(),
+                offset as libc::off_t,
+            )
+        })?;
+        Ok(n as usize)
+    }
+
+    /// One `write` at the current offset, retried if a signal interrupts it
+    /// before anything is written.
+    fn write_raw(&self, buf: &[u8]) -> io::Result<usize> {
+        let n = cvt_r(|| unsafe {
+            libc::write(
+                self.descriptor,
+                buf.as_ptr() as *const libc::c_void,
+                buf.len(),
+            )
+        })?;
+        Ok(n as usize)
+    }
+
+    /// Writes all of `buf` at the current offset, retrying short writes.
+    pub(crate) fn write_all_raw(&self, mut buf: &[u8]) -> io::Result<()> {
+        while !buf.is_empty() {
+            match self.write_raw(buf)? {
+                0 => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write whole buffer",
+                    ))
+                }
+                n => buf = &buf[n..],
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes all of `buf` at `offset` with `pwrite`, leaving the file's own
+    /// offset where it was.
+    pub(crate) fn write_all_at(&self, mut buf: &[u8], mut offset: u64) -> io::Result<()> {
+        while !buf.is_empty() {
+            let n = cvt_r(|| unsafe {
+                libc::pwrite(
+                    self.descriptor,
+                    buf.as_ptr() as *const libc::c_void,
+                    buf.len(),
+                    offset as libc::off_t,
+                )
+            })?;
+            if n == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "failed to write whole buffer",
+                ));
+            }
+            buf = &buf[n as usize..];
+            offset += n as u64;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn from_descriptor(descriptor: RawFd) -> File {
+        File::in_table(Os, descriptor)
+    }
+}
+
+impl<D: Descriptors> File<D> {
+    /// Takes ownership of `descriptor` in `table`.
+    pub(crate) fn in_table(table: D, descriptor: RawFd) -> File<D> {
+        File {
+            descriptor,
+            offsets: Vec::new(),
+            scratch: Vec::new(),
+            table,
+        }
+    }
+}
+
+/// Creates a pipe, returning its read and write ends.
+pub fn pipe() -> io::Result<(File, File)> {
+    let mut fds = [0; 2];
+    cvt(unsafe { libc::pipe(fds.as_mut_ptr()) })?;
+    let (reader, writer) = (File::from_descriptor(fds[0]), File::from_descriptor(fds[1]));
+    // Set close-on-exec like everything else we open. Linux could do this
+    // atomically with pipe2, but this keeps the helper portable.
+    for f in &[&reader, &writer] {
+        cvt(unsafe { libc::fcntl(f.descriptor, libc::F_SETFD, libc::FD_CLOEXEC) })?;
+    }
+    Ok((reader, writer))
+}
+
+/// Shows the descriptor number and nothing else, through `&self`: the
+/// `File` keeps ownership, and the buffers are just noise.
+impl<D: Descriptors> fmt::Debug for File<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("File")
+            .field("fd", &self.descriptor)
+            .finish()
+    }
+}
+
+/// Just the descriptor number, e.g. `3`.
+impl<D: Descriptors> fmt::Display for File<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.descriptor)
+    }
+}
+
+/// Reads and writes go straight to the descriptor, one system call each
+/// (retried on `EINTR`), so they compose with `BufReader`, `io::copy` and
+/// the rest. Short reads and writes are passed on as they are; `read_exact`
+/// and `write_all` loop over them.
+impl Read for File {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = cvt_r(|| unsafe {
+            libc::read(
+                self.descriptor,
+                buf.as_mut_ptr() as *mut libc::c_void,
+                buf.len(),
+            )
+        })?;
+        Ok(n as usize)
+    }
+}
+
+impl Write for File {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_raw(buf)
+    }
+
+    /// Nothing is buffered here, so there is nothing to flush. Use
+    /// `sync_all` to push data through to the disk.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for File {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (offset, whence) = match pos {
+            SeekFrom::Start(n) => (n as libc::off_t, libc::SEEK_SET),
+            SeekFrom::End(n) => (n as libc::off_t, libc::SEEK_END),
+            SeekFrom::Current(n) => (n as libc::off_t, libc::SEEK_CUR),
+        };
+        let n = cvt(unsafe { libc::lseek(self.descriptor, offset, whence) })?;
+        Ok(n as u64)
+    }
+}
+
+impl<D: Descriptors> Drop for File<D> {
+    fn drop(&mut self) {
+        // Nothing useful can be done with a failed close here.
+        let _ = self.table.close(self.descriptor);
+    }
+}
+
+pub(crate) trait IsMinusOne {
+    fn is_minus_one(&self) -> bool;
+}
+
+macro_rules! impl_is_minus_one {
+    ($($t:ty)*) => ($(impl IsMinusOne for $t {
+        fn is_minus_one(&self) -> bool {
+            *self == -1
+        }
+    })*)
+}
+
+impl_is_minus_one! { i32 i64 isize }
+
+/// Turns the C convention of returning `-1` and setting `errno` into a `Result`.
+pub(crate) fn cvt<T: IsMinusOne>(ret: T) -> io::Result<T> {
+    if ret.is_minus_one() {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret)
+    }
+}
+
+/// `cvt` for calls a signal can interrupt: retries `f` for as long as it
+/// fails with `EINTR`.
+///
+/// `close` must not go through this. On Linux the descriptor is released
+/// even when `close` reports `EINTR`, so a retry could close a descriptor
+/// another thread has just been given.
+pub(crate) fn cvt_r<T: IsMinusOne, F: FnMut() -> T>(mut f: F) -> io::Result<T> {
+    loop {
+        match cvt(f()) {
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            other => return other,
+        }
+    }
+}
+
+pub(crate) fn cstr(path: &Path) -> io::Result<CString> {
+    CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "path contains a nul byte"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, BufRead, BufReader, ErrorKind, Read, Seek, SeekFrom, Write};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc;
+    use std::time::Duration;
+    use std::{mem, ptr, thread};
+
+    use super::{pipe, File};
+    use crate::testutil::TempDir;
+
+    static SIGNALS: AtomicUsize = AtomicUsize::new(0);
+
+    extern "C" fn count_signal(_: libc::c_int) {
+        SIGNALS.fetch_add(1, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn reads_survive_interrupting_signals() {
+        // No SA_RESTART, so a blocked read really does fail with EINTR.
+        unsafe {
+            let mut action: libc::sigaction = mem::zeroed();
+            action.sa_sigaction = count_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            libc::sigemptyset(&mut action.sa_mask);
+            assert_eq!(libc::sigaction(libc::SIGUSR1, &action, ptr::null_mut()), 0);
+        }
+
+        let (mut reader, writer) = pipe().unwrap();
+        let len = 1 << 20;
+        let (tx, rx) = mpsc::channel();
+        let reading = thread::spawn(move || {
+            tx.send(unsafe { libc::pthread_self() }).unwrap();
+            let mut buf = vec![0; len];
+            reader.read_exact(&mut buf).map(|()| buf)
+        });
+        let reader_thread = rx.recv().unwrap();
+
+        let chunk: Vec<u8> = (0..len / 16).map(|i| i as u8).collect();
+        for _ in 0..16 {
+            // Give the reader time to block, then interrupt it mid-read.
+            thread::sleep(Duration::from_millis(2));
+            unsafe { libc::pthread_kill(reader_thread, libc::SIGUSR1) };
+            thread::sleep(Duration::from_millis(2));
+            writer.write_all_raw(&chunk).unwrap();
+        }
+
+        let buf = reading.join().unwrap().unwrap();
+        assert!(buf.chunks(chunk.len()).all(|c| c == &chunk[..]));
+        assert!(SIGNALS.load(Ordering::SeqCst) > 0);
+    }
+
+    #[test]
+    fn formats_show_the_descriptor() {
+        let (reader, _writer) = pipe().unwrap();
+        let fd = reader.descriptor;
+        assert_eq!(format!("{:?}", reader), format!("File {{ fd: {} }}", fd));
+        assert_eq!(reader.to_string(), fd.to_string());
+    }
+
+    #[test]
+    fn writes_seeks_and_reads_back() {
+        let dir = TempDir::new();
+        let mut f = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(dir.join("round-trip"))
+            .unwrap();
+        f.write_all(b"first line\nsecond line\n").unwrap();
+        f.flush().unwrap();
+
+        f.seek(SeekFrom::Start(0)).unwrap();
+        let mut contents = Vec::new();
+        f.read_to_end(&mut contents).unwrap();
+        assert_eq!(contents, b"first line\nsecond line\n");
+
+        f.seek(SeekFrom::Start(0)).unwrap();
+        let lines: Vec<String> = BufReader::new(f).lines().map(Result::unwrap).collect();
+        assert_eq!(lines, ["first line", "second line"]);
+    }
+
+    #[test]
+    fn io_copy_between_files() {
+        let dir = TempDir::new();
+        let mut src = File::open(dir.file("src", b"copied through std")).unwrap();
+        let mut dst = File::options()
+            .write(true)
+            .create(true)
+            .open(dir.join("dst"))
+            .unwrap();
+        assert_eq!(io::copy(&mut src, &mut dst).unwrap(), 18);
+        assert_eq!(
+            std::fs::read(dir.join("dst")).unwrap(),
+            b"copied through std"
+        );
+    }
+
+    #[test]
+    fn dropping_the_reader_closes_the_pipe() {
+        let (reader, writer) = pipe().unwrap();
+        writer.write_all_raw(b"before").unwrap();
+
+        drop(reader);
+        let err = writer.write_all_raw(b"after").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn moved_files_are_closed_by_their_new_owner() {
+        let (reader, writer) = pipe().unwrap();
+        let mut owners = vec![reader];
+        writer.write_all_raw(b"still open").unwrap();
+
+        owners.clear();
+        let err = writer.write_all_raw(b"closed").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+    }
+}
@@ -0,0 +1,59 @@
+use std::io::{self, Read};
+
+use super::{cvt, File};
+
+/// A `File` whose descriptor has `O_NONBLOCK` set.
+///
+/// Reads that would otherwise wait for data fail with
+/// `io::ErrorKind::WouldBlock` instead, which is what event loops expect.
+pub struct NonBlockingFile {
+    inner: File,
+}
+
+impl File {
+    /// Sets `O_NONBLOCK` on the descriptor and wraps it as a `NonBlockingFile`.
+    pub fn into_nonblocking(self) -> io::Result<NonBlockingFile> {
+        let flags = cvt(unsafe { libc::fcntl(self.descriptor, libc::F_GETFL) })?;
+        cvt(unsafe { libc::fcntl(self.descriptor, libc::F_SETFL, flags | libc::O_NONBLOCK) })?;
+        Ok(NonBlockingFile { inner: self })
+    }
+}
+
+impl NonBlockingFile {
+    /// The underlying `File`, still in non-blocking mode.
+    pub fn get_ref(&self) -> &File {
+        &self.inner
+    }
+}
+
+impl Read for NonBlockingFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf).map_err(|e| match e.raw_os_error() {
+            Some(code) if code == libc::EAGAIN || code == libc::EWOULDBLOCK => {
+                io::Error::new(io::ErrorKind::WouldBlock, e)
+            }
+            _ => e,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{ErrorKind, Read, Write};
+
+    use crate::testutil;
+
+    #[test]
+    fn empty_pipe_would_block() {
+        let (reader, mut writer) = testutil::pipe();
+        let mut reader = reader.into_nonblocking().unwrap();
+        let mut buf = [0; 8];
+
+        let err = reader.read(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::WouldBlock);
+
+        writer.write_all(b"ready").unwrap();
+        assert_eq!(reader.read(&mut buf).unwrap(), 5);
+        assert_eq!(&buf[..5], b"ready");
+    }
+}
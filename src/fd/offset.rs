@@ -0,0 +1,148 @@
+use std::io::{self, Seek, SeekFrom};
+
+use super::{cvt, cvt_r, File};
+
+impl File {
+    /// The current offset, read with `lseek(fd, 0, SEEK_CUR)`.
+    ///
+    /// Unlike `Seek::stream_position` this only needs `&self`, since asking
+    /// for the offset doesn't change it.
+    pub fn stream_position(&self) -> io::Result<u64> {
+        let pos = cvt(unsafe { libc::lseek(self.descriptor, 0, libc::SEEK_CUR) })?;
+        Ok(pos as u64)
+    }
+
+    /// Whether the descriptor has an offset to move at all.
+    ///
+    /// Pipes, sockets and terminals fail `lseek` with `ESPIPE`; callers can
+    /// use this to pick between positioned reads and plain streaming. Any
+    /// other failure also means the offset can't be relied on, so it counts
+    /// as not seekable too.
+    pub fn is_seekable(&self) -> bool {
+        self.stream_position().is_ok()
+    }
+
+    /// Opens this file again, with its own offset set to `offset`.
+    ///
+    /// A `dup`ed descriptor shares the original's open file description, so
+    /// seeking one seeks both. This instead reopens the file through
+    /// `/proc/self/fd`, which gives a new open file description with the same
+    /// access mode and an offset of its own.
+    #[cfg(target_os = "linux")]
+    pub fn clone_at(&self, offset: u64) -> io::Result<File> {
+        let flags = self.fcntl(libc::F_GETFL, 0)?;
+        let path = format!("/proc/self/fd/{}", self.descriptor);
+        let path = super::cstr(path.as_ref())?;
+        let reopen_flags = (flags & (libc::O_ACCMODE | libc::O_APPEND)) | libc::O_CLOEXEC;
+        let fd = cvt_r(|| unsafe { libc::open(path.as_ptr(), reopen_flags) })?;
+        let mut clone = File::from_descriptor(fd);
+        clone.seek(SeekFrom::Start(offset))?;
+        Ok(clone)
+    }
+
+    /// Saves the current offset so a later `pop_offset` can return to it.
+    ///
+    /// Pushes nest, so a helper that seeks around can bracket its work with
+    /// a push/pop pair without caring what its caller saved.
+    pub fn push_offset(&mut self) -> io::Result<()> {
+        let pos = self.stream_position()?;
+        self.offsets.push(pos);
+        Ok(())
+    }
+
+    /// Seeks back to the most recently pushed offset and forgets it.
+    ///
+    /// Fails with `InvalidInput` if nothing has been pushed.
+    pub fn pop_offset(&mut self) -> io::Result<()> {
+        let pos = match self.offsets.last() {
+            Some(&pos) => pos,
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "no saved offset to pop",
+                ))
+            }
+        };
+        self.seek(SeekFrom::Start(pos))?;
+        self.offsets.pop();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{ErrorKind, Read, Seek, SeekFrom};
+
+    use crate::fd::File;
+    use crate::testutil::{self, TempDir};
+
+    fn read_one(f: &mut File) -> u8 {
+        let mut buf = [0; 1];
+        f.read_exact(&mut buf).unwrap();
+        buf[0]
+    }
+
+    #[test]
+    fn nested_push_pop_restores_offsets() {
+        let dir = TempDir::new();
+        let mut f = File::open(dir.file("digits", b"0123456789")).unwrap();
+
+        f.push_offset().unwrap();
+        f.seek(SeekFrom::Start(3)).unwrap();
+        assert_eq!(read_one(&mut f), b'3');
+
+        f.push_offset().unwrap();
+        f.seek(SeekFrom::Start(8)).unwrap();
+        assert_eq!(read_one(&mut f), b'8');
+
+        f.pop_offset().unwrap();
+        assert_eq!(read_one(&mut f), b'4');
+
+        f.pop_offset().unwrap();
+        assert_eq!(f.stream_position().unwrap(), 0);
+        assert_eq!(read_one(&mut f), b'0');
+    }
+
+    #[test]
+    fn stream_position_after_read() {
+        let dir = TempDir::new();
+        let mut f = File::open(dir.file("digits", b"0123456789")).unwrap();
+        let mut buf = [0; 5];
+        f.read_exact(&mut buf).unwrap();
+        let f = &f; // a shared borrow is enough
+        assert_eq!(f.stream_position().unwrap(), 5);
+        assert_eq!(f.stream_position().unwrap(), 5);
+    }
+
+    #[test]
+    fn regular_files_seek_and_pipes_do_not() {
+        let dir = TempDir::new();
+        let f = File::open(dir.file("digits", b"0123456789")).unwrap();
+        assert!(f.is_seekable());
+
+        let (reader, _writer) = testutil::pipe();
+        assert!(!reader.is_seekable());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn clone_at_has_independent_offset() {
+        let dir = TempDir::new();
+        let mut original = File::open(dir.file("digits", b"0123456789")).unwrap();
+        let mut clone = original.clone_at(5).unwrap();
+
+        assert_eq!(original.stream_position().unwrap(), 0);
+        assert_eq!(read_one(&mut clone), b'5');
+
+        original.seek(SeekFrom::Start(9)).unwrap();
+        assert_eq!(read_one(&mut clone), b'6');
+        assert_eq!(read_one(&mut original), b'9');
+    }
+
+    #[test]
+    fn pop_without_push_fails() {
+        let dir = TempDir::new();
+        let mut f = File::open(dir.file("empty", b"")).unwrap();
+        assert_eq!(f.pop_offset().unwrap_err().kind(), ErrorKind::InvalidInput);
+    }
+}
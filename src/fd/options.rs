@@ -0,0 +1,235 @@
+use std::io;
+use std::os::unix::io::RawFd;
+use std::path::Path;
+
+use super::{cstr, cvt_r, File};
+
+/// Options for opening a `File`, in the manner of `std::fs::OpenOptions`.
+///
+/// ```no_run
+/// use references_sharing_vs_mutation::fd::OpenOptions;
+///
+/// let f = OpenOptions::new().read(true).write(true).create(true).open("foo.txt")?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct OpenOptions {
+    read: bool,
+    write: bool,
+    append: bool,
+    truncate: bool,
+    create: bool,
+    create_new: bool,
+    mode: libc::mode_t,
+}
+
+impl OpenOptions {
+    /// All options off, with a creation mode of `0o666` (before the umask).
+    pub fn new() -> OpenOptions {
+        OpenOptions {
+            read: false,
+            write: false,
+            append: false,
+            truncate: false,
+            create: false,
+            create_new: false,
+            mode: 0o666,
+        }
+    }
+
+    pub fn read(&mut self, read: bool) -> &mut OpenOptions {
+        self.read = read;
+        self
+    }
+
+    pub fn write(&mut self, write: bool) -> &mut OpenOptions {
+        self.write = write;
+        self
+    }
+
+    /// Writes always go to the end of the file. Implies write access.
+    pub fn append(&mut self, append: bool) -> &mut OpenOptions {
+        self.append = append;
+        self
+    }
+
+    pub fn truncate(&mut self, truncate: bool) -> &mut OpenOptions {
+        self.truncate = truncate;
+        self
+    }
+
+    pub fn create(&mut self, create: bool) -> &mut OpenOptions {
+        self.create = create;
+        self
+    }
+
+    /// Create the file, failing if it already exists. Overrides `create`
+    /// and `truncate`.
+    pub fn create_new(&mut self, create_new: bool) -> &mut OpenOptions {
+        self.create_new = create_new;
+        self
+    }
+
+    /// Permission bits for a newly created file.
+    pub fn mode(&mut self, mode: u32) -> &mut OpenOptions {
+        self.mode = mode as libc::mode_t;
+        self
+    }
+
+    pub fn open<P: AsRef<Path>>(&self, path: P) -> io::Result<File> {
+        self.open_at(libc::AT_FDCWD, path.as_ref())
+    }
+
+    /// Opens `path` relative to the directory `dirfd`, as `openat(2)` does.
+    pub(super) fn open_at(&self, dirfd: RawFd, path: &Path) -> io::Result<File> {
+        let path = cstr(path)?;
+        let flags = libc::O_CLOEXEC | self.access_flags()? | self.creation_flags()?;
+        let fd = cvt_r(|| unsafe {
+            libc::openat(dirfd, path.as_ptr(), flags, self.mode as libc::c_uint)
+        })?;
+        Ok(File::from_descriptor(fd))
+    }
+
+    fn access_flags(&self) -> io::Result<libc::c_int> {
+        match (self.read, self.write, self.append) {
+            (true, false, false) => Ok(libc::O_RDONLY),
+            (false, true, false) => Ok(libc::O_WRONLY),
+            (true, true, false) => Ok(libc::O_RDWR),
+            (false, _, true) => Ok(libc::O_WRONLY | libc::O_APPEND),
+            (true, _, true) => Ok(libc::O_RDWR | libc::O_APPEND),
+            (false, false, false) => Err(invalid("no access mode requested")),
+        }
+    }
+
+    fn creation_flags(&self) -> io::Result<libc::c_int> {
+        let writable = self.write || self.append;
+        if !writable && (self.truncate || self.create || self.create_new) {
+            return Err(invalid("creating or truncating requires write access"));
+        }
+        if self.append && self.truncate && !self.create_new {
+            return Err(invalid("append and truncate are mutually exclusive"));
+        }
+        Ok(match (self.create, self.truncate, self.create_new) {
+            (_, _, true) => libc::O_CREAT | libc::O_EXCL,
+            (false, false, false) => 0,
+            (true, false, false) => libc::O_CREAT,
+            (false, true, false) => libc::O_TRUNC,
+            (true, true, false) => libc::O_CREAT | libc::O_TRUNC,
+        })
+    }
+}
+
+impl Default for OpenOptions {
+    fn default() -> OpenOptions {
+        OpenOptions::new()
+    }
+}
+
+impl File {
+    /// Shorthand for `OpenOptions::new()`.
+    pub fn options() -> OpenOptions {
+        OpenOptions::new()
+    }
+
+    /// Opens `path` for appending, creating it if it doesn't exist.
+    pub fn open_append<P: AsRef<Path>>(path: P) -> io::Result<File> {
+        OpenOptions::new().append(true).create(true).open(path)
+    }
+
+    /// Opens `/dev/null` read-write: writes are discarded and reads see end
+    /// of file straight away.
+    pub fn null() -> io::Result<File> {
+        OpenOptions::new().read(true).write(true).open("/dev/null")
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::{ErrorKind, Read};
+
+    use super::*;
+    use crate::testutil::TempDir;
+
+    fn status_flags(f: &File) -> libc::c_int {
+        unsafe { libc::fcntl(f.descriptor, libc::F_GETFL) }
+    }
+
+    #[test]
+    fn read_only() {
+        let dir = TempDir::new();
+        let path = dir.file("existing", b"hello");
+        let mut f = OpenOptions::new().read(true).open(&path).unwrap();
+        assert_eq!(status_flags(&f) & libc::O_ACCMODE, libc::O_RDONLY);
+
+        let mut s = String::new();
+        f.read_to_string(&mut s).unwrap();
+        assert_eq!(s, "hello");
+    }
+
+    #[test]
+    fn missing_file_is_not_found() {
+        let dir = TempDir::new();
+        let err = File::open(dir.join("missing")).err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert_eq!(err.raw_os_error(), Some(libc::ENOENT));
+    }
+
+    #[test]
+    fn null_discards_writes_and_reads_empty() {
+        let mut null = File::null().unwrap();
+        null.write_all_raw(b"into the void").unwrap();
+
+        let mut buf = [0; 8];
+        assert_eq!(null.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn read_write_create() {
+        let dir = TempDir::new();
+        let path = dir.join("new");
+        let f = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(&path)
+            .unwrap();
+        assert_eq!(status_flags(&f) & libc::O_ACCMODE, libc::O_RDWR);
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn append_create() {
+        let dir = TempDir::new();
+        let path = dir.join("log");
+        let f = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&path)
+            .unwrap();
+        let flags = status_flags(&f);
+        assert_eq!(flags & libc::O_ACCMODE, libc::O_WRONLY);
+        assert_ne!(flags & libc::O_APPEND, 0);
+        assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn rejects_nonsense_combinations() {
+        let dir = TempDir::new();
+        let path = dir.join("never");
+        let err = OpenOptions::new().open(&path).err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        let err = OpenOptions::new()
+            .read(true)
+            .create(true)
+            .open(&path)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        assert!(!path.exists());
+    }
+}
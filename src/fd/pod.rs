@@ -0,0 +1,68 @@
+use std::io::{self, Read};
+use std::mem::{self, MaybeUninit};
+use std::slice;
+
+use super::File;
+
+impl File {
+    /// Reads `size_of::<T>()` bytes and reinterprets them as a `T`, for
+    /// fixed-layout records in binary files.
+    ///
+    /// The bytes are taken exactly as they are on disk: in the machine's own
+    /// byte order, with padding bytes (if `T` has any) expected to be there
+    /// too. A `#[repr(C, packed)]` struct of integers is the safe shape;
+    /// portable formats should read bytes and convert with `from_le_bytes`
+    /// and friends instead. A short file fails with `UnexpectedEof`.
+    ///
+    /// # Safety
+    ///
+    /// Every bit pattern must be a valid `T`. `Copy` rules out types with
+    /// destructors but not `bool`, `char`, enums or references, which the
+    /// wrong bytes turn into undefined behaviour; hence `unsafe`.
+    pub unsafe fn read_pod<T: Copy>(&mut self) -> io::Result<T> {
+        // Zeroed rather than uninit, since `read_exact` wants a `&mut [u8]`.
+        let mut value = MaybeUninit::<T>::zeroed();
+        let bytes = slice::from_raw_parts_mut(value.as_mut_ptr() as *mut u8, mem::size_of::<T>());
+        self.read_exact(bytes)?;
+        Ok(value.assume_init())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use crate::fd::File;
+    use crate::testutil::TempDir;
+
+    #[repr(C, packed)]
+    #[derive(Clone, Copy)]
+    struct Header {
+        tag: u8,
+        len: u32,
+        flags: u16,
+    }
+
+    #[test]
+    fn reads_a_packed_struct() {
+        let mut bytes = vec![7];
+        bytes.extend_from_slice(&1234u32.to_ne_bytes());
+        bytes.extend_from_slice(&0xbeefu16.to_ne_bytes());
+        bytes.push(0xff);
+        let dir = TempDir::new();
+        let mut f = File::open(dir.file("header", &bytes)).unwrap();
+
+        let header: Header = unsafe { f.read_pod() }.unwrap();
+        let Header { tag, len, flags } = header;
+        assert_eq!((tag, len, flags), (7, 1234, 0xbeef));
+        assert_eq!(unsafe { f.read_pod::<u8>() }.unwrap(), 0xff);
+    }
+
+    #[test]
+    fn short_read_is_an_error() {
+        let dir = TempDir::new();
+        let mut f = File::open(dir.file("short", &[1, 2, 3])).unwrap();
+        let err = unsafe { f.read_pod::<u32>() }.err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,142 @@
+use std::io;
+use std::time::Duration;
+
+use super::{cvt, File};
+
+impl File {
+    /// Waits up to `timeout` for the descriptor to have data to read (or
+    /// reach end of file), returning whether it does.
+    pub fn is_readable(&self, timeout: Duration) -> io::Result<bool> {
+        self.poll_for(libc::POLLIN, timeout)
+    }
+
+    /// Waits up to `timeout` for the descriptor to accept a write without
+    /// blocking, returning whether it will. A pipe whose buffer is full
+    /// isn't writable until the reader makes room.
+    pub fn is_writable(&self, timeout: Duration) -> io::Result<bool> {
+        self.poll_for(libc::POLLOUT, timeout)
+    }
+
+    fn poll_for(&self, events: libc::c_short, timeout: Duration) -> io::Result<bool> {
+        let mut pollfd = libc::pollfd {
+            fd: self.descriptor,
+            events,
+            revents: 0,
+        };
+        loop {
+            match cvt(unsafe { libc::poll(&mut pollfd, 1, timeout_ms(timeout)) }) {
+                Ok(0) => return Ok(false),
+                // Errors and hangups also end the wait: the next read or
+                // write will report them rather than block.
+                Ok(_) => return Ok(true),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Waits up to `timeout` for any of `files` to become readable, returning
+/// the indices of those that are, in order. An empty result means the wait
+/// timed out.
+///
+/// Only shared borrows are needed: waiting doesn't consume any data, so the
+/// same `File` may even appear twice.
+pub fn wait_any(files: &[&File], timeout: Duration) -> io::Result<Vec<usize>> {
+    let mut pollfds: Vec<libc::pollfd> = files
+        .iter()
+        .map(|f| libc::pollfd {
+            fd: f.descriptor,
+            events: libc::POLLIN,
+            revents: 0,
+        })
+        .collect();
+    loop {
+        let ret = unsafe {
+            libc::poll(
+                pollfds.as_mut_ptr(),
+                pollfds.len() as libc::nfds_t,
+                timeout_ms(timeout),
+            )
+        };
+        match cvt(ret) {
+            Ok(_) => break,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(pollfds
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.revents != 0)
+        .map(|(i, _)| i)
+        .collect())
+}
+
+/// `timeout` in milliseconds for `poll`, rounded up so a short timeout
+/// doesn't turn into no wait at all.
+pub(crate) fn timeout_ms(timeout: Duration) -> libc::c_int {
+    let ms = timeout.as_nanos().div_ceil(1_000_000);
+    ms.min(libc::c_int::MAX as u128) as libc::c_int
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::time::Duration;
+
+    use crate::fd;
+    use crate::testutil;
+
+    #[test]
+    fn full_pipe_is_not_writable() {
+        let (mut reader, writer) = fd::pipe().unwrap();
+        writer
+            .fcntl(libc::F_SETFL, libc::O_NONBLOCK)
+            .expect("set O_NONBLOCK");
+
+        let chunk = [0u8; 4096];
+        let mut written = 0;
+        loop {
+            let n = unsafe {
+                libc::write(
+                    writer.descriptor,
+                    chunk.as_ptr() as *const libc::c_void,
+                    chunk.len(),
+                )
+            };
+            if n < 0 {
+                break;
+            }
+            written += n as usize;
+        }
+        assert!(!writer.is_writable(Duration::from_millis(10)).unwrap());
+
+        let mut drained = vec![0; written];
+        reader.read_exact(&mut drained).unwrap();
+        assert!(writer.is_writable(Duration::from_millis(10)).unwrap());
+    }
+
+    #[test]
+    fn wait_any_reports_only_the_ready_pipe() {
+        let (quiet, _quiet_writer) = testutil::pipe();
+        let (busy, mut busy_writer) = testutil::pipe();
+        let timeout = Duration::from_millis(10);
+
+        assert!(fd::wait_any(&[&quiet, &busy], timeout).unwrap().is_empty());
+        busy_writer.write_all(b"x").unwrap();
+        assert_eq!(fd::wait_any(&[&quiet, &busy], timeout).unwrap(), vec![1]);
+        assert_eq!(
+            fd::wait_any(&[&busy, &quiet, &busy], timeout).unwrap(),
+            vec![0, 2]
+        );
+    }
+
+    #[test]
+    fn readable_once_data_arrives() {
+        let (reader, mut writer) = testutil::pipe();
+        assert!(!reader.is_readable(Duration::from_millis(10)).unwrap());
+        writer.write_all(b"x").unwrap();
+        assert!(reader.is_readable(Duration::from_millis(10)).unwrap());
+    }
+}
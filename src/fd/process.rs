@@ -0,0 +1,87 @@
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+
+use super::{cvt, File};
+
+/// A process ID, as `fork` returns it.
+pub type Pid = libc::pid_t;
+
+/// Forks, handing `files` to `child` in the new process, and returns the
+/// child's PID to the parent along with the parent's copies of the files.
+///
+/// `fork` copies every descriptor, so both processes end up with the
+/// files. In the child they belong to `child`, which closes them when it's
+/// done; in the parent they come back, still open. The two copies of each
+/// share an offset, because `fork`, like `dup`, shares the open file
+/// description.
+///
+/// The child exits with status 0 when `child` returns, or 101 if it panics;
+/// it never returns into the caller's code. Reaping it with `waitpid` is up
+/// to the parent.
+///
+/// In a multithreaded program only the calling thread exists in the child.
+/// A lock some other thread held at the time stays locked there, and
+/// panicking takes locks, so `child` should stick to simple work and report
+/// failure by exiting with a status of its own through `libc::_exit`.
+pub fn fork_with<F: FnOnce(Vec<File>)>(files: Vec<File>, child: F) -> io::Result<(Pid, Vec<File>)> {
+    let pid = cvt(unsafe { libc::fork() })?;
+    if pid == 0 {
+        let status = match panic::catch_unwind(AssertUnwindSafe(|| child(files))) {
+            Ok(()) => 0,
+            Err(_) => 101,
+        };
+        // `_exit` rather than `exit`, so the parent's atexit handlers and
+        // stdio buffers aren't run a second time from the child.
+        unsafe { libc::_exit(status) }
+    }
+    Ok((pid, files))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+    use crate::testutil::TempDir;
+
+    fn wait(pid: Pid) -> i32 {
+        let mut status = 0;
+        assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+        assert!(libc::WIFEXITED(status));
+        libc::WEXITSTATUS(status)
+    }
+
+    #[test]
+    fn child_reads_an_inherited_file() {
+        let dir = TempDir::new();
+        let file = File::open(dir.file("greeting", b"hello, child")).unwrap();
+        let (pid, files) = fork_with(vec![file], |mut files| {
+            let mut buf = [0; 5];
+            let ok = files[0].read_exact(&mut buf).is_ok() && &buf == b"hello";
+            unsafe { libc::_exit(if ok { 0 } else { 1 }) }
+        })
+        .unwrap();
+        assert_eq!(wait(pid), 0);
+
+        // The parent's copy is still open, and the child's read moved the
+        // offset they share.
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].stream_position().unwrap(), 5);
+    }
+
+    #[test]
+    fn child_exits_with_0_when_it_returns() {
+        let (pid, files) = fork_with(Vec::new(), |_| ()).unwrap();
+        assert!(files.is_empty());
+        assert_eq!(wait(pid), 0);
+    }
+
+    #[test]
+    fn child_status_reaches_the_parent() {
+        // The child reports failure with its own status instead of
+        // panicking, which could deadlock on a lock another test thread
+        // held at the fork.
+        let (pid, _) = fork_with(Vec::new(), |_| unsafe { libc::_exit(7) }).unwrap();
+        assert_eq!(wait(pid), 7);
+    }
+}
@@ -0,0 +1,157 @@
+use std::convert::TryFrom;
+use std::io;
+use std::mem::{self, ManuallyDrop};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+
+use super::{cvt_r, File};
+
+impl File {
+    /// Gives up ownership of the descriptor without closing it.
+    ///
+    /// `mem::forget` alone would skip closing but also leak the buffers, so
+    /// they are dropped by hand and only `Drop for File` is skipped.
+    pub(crate) fn into_descriptor(self) -> RawFd {
+        let mut this = ManuallyDrop::new(self);
+        drop(mem::take(&mut this.offsets));
+        drop(mem::take(&mut this.scratch));
+        this.descriptor
+    }
+}
+
+impl File {
+    /// Gives up the `File` and returns its descriptor, which is never
+    /// closed from here on: it stays open until the process exits, or until
+    /// whoever it is handed to closes it.
+    ///
+    /// This is a deliberate leak, for passing a descriptor to something that
+    /// keeps it for the life of the program, such as a C library that stores
+    /// it at initialisation. It is `into_raw_fd` under a name that says what
+    /// happens if nobody takes the descriptor over.
+    pub fn leak(self) -> RawFd {
+        self.into_descriptor()
+    }
+}
+
+/// Borrows the descriptor. It stays owned by the `File`, which will close
+/// it, so it must not outlive the borrow of the `File` it came from.
+impl AsRawFd for File {
+    fn as_raw_fd(&self) -> RawFd {
+        self.descriptor
+    }
+}
+
+/// Hands the descriptor over to the caller, who becomes responsible for
+/// closing it; the `File`'s `Drop` doesn't run.
+impl IntoRawFd for File {
+    fn into_raw_fd(self) -> RawFd {
+        self.into_descriptor()
+    }
+}
+
+/// Takes ownership of `fd`, which must be open and not owned by anything
+/// else, or it will be closed twice.
+impl FromRawFd for File {
+    unsafe fn from_raw_fd(fd: RawFd) -> File {
+        File::from_descriptor(fd)
+    }
+}
+
+impl File {
+    /// A `File` of its own for the open file behind `fd`, which stays the
+    /// caller's.
+    ///
+    /// Taking `fd` over outright would close it when the `File` drops, behind
+    /// the back of whatever really owns it, and that's why `from_raw_fd` is
+    /// unsafe. Duplicating it instead (close-on-exec, as `clone` does) only
+    /// needs `fd` to be open right now, which `fcntl` checks: anything else
+    /// fails with `EBADF`.
+    pub fn dup_raw(fd: RawFd) -> io::Result<File> {
+        let dup = cvt_r(|| unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, 0) })?;
+        Ok(File::from_descriptor(dup))
+    }
+}
+
+/// `File::dup_raw`: the `File` gets a duplicate of `fd`, which stays the
+/// caller's.
+impl TryFrom<RawFd> for File {
+    type Error = io::Error;
+
+    fn try_from(fd: RawFd) -> io::Result<File> {
+        File::dup_raw(fd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
+
+    use crate::fd::File;
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn into_raw_fd_leaves_the_descriptor_open() {
+        let dir = TempDir::new();
+        let f = File::open(dir.file("f", b"handed over")).unwrap();
+        let fd = f.as_raw_fd();
+        assert_eq!(f.into_raw_fd(), fd);
+
+        let mut std_file = unsafe { fs::File::from_raw_fd(fd) };
+        let mut contents = String::new();
+        std_file.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "handed over");
+    }
+
+    #[test]
+    fn leaked_descriptor_stays_open() {
+        let dir = TempDir::new();
+        let f = File::open(dir.file("f", b"leaked")).unwrap();
+        let fd = f.leak();
+        assert_ne!(unsafe { libc::fcntl(fd, libc::F_GETFD) }, -1);
+
+        // Reclaim it so the test itself doesn't leak; this is the only close.
+        let mut std_file = unsafe { fs::File::from_raw_fd(fd) };
+        let mut contents = String::new();
+        std_file.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "leaked");
+    }
+
+    #[test]
+    fn from_raw_fd_takes_ownership() {
+        let dir = TempDir::new();
+        let std_file = fs::File::open(dir.file("f", b"adopted")).unwrap();
+        let mut f = unsafe { File::from_raw_fd(std_file.into_raw_fd()) };
+        let mut contents = String::new();
+        f.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "adopted");
+    }
+
+    #[test]
+    fn dup_raw_duplicates_open_descriptors() {
+        let (mut reader, mut writer) = crate::fd::pipe().unwrap();
+        let mut copy = File::dup_raw(writer.as_raw_fd()).unwrap();
+        assert_ne!(copy.as_raw_fd(), writer.as_raw_fd());
+
+        // Both still work, and each closes only its own descriptor.
+        copy.write_all(b"copy ").unwrap();
+        drop(copy);
+        writer.write_all(b"original").unwrap();
+        drop(writer);
+        let mut got = String::new();
+        reader.read_to_string(&mut got).unwrap();
+        assert_eq!(got, "copy original");
+
+        let err = File::dup_raw(-1).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EBADF));
+    }
+
+    #[test]
+    fn try_from_duplicates_too() {
+        let (_reader, writer) = crate::fd::pipe().unwrap();
+        let copy = File::try_from(writer.as_raw_fd()).unwrap();
+        assert_ne!(copy.as_raw_fd(), writer.as_raw_fd());
+        assert!(File::try_from(-1).is_err());
+    }
+}
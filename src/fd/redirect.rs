@@ -0,0 +1,78 @@
+use std::io;
+use std::ops::Deref;
+use std::path::Path;
+
+use super::{cvt_r, File};
+
+/// Standard error sent to a file, until this is dropped.
+///
+/// Derefs to the log `File`. Dropping it puts the original stderr back on
+/// descriptor 2 and closes the log.
+pub struct StderrRedirect {
+    log: File,
+    saved: File,
+}
+
+/// Opens `path` for appending, creating it if needed, and `dup2`s it onto
+/// descriptor 2, so everything written to stderr from here on lands in the
+/// file, including output from C code and child processes.
+///
+/// The original stderr is saved with `dup` first and restored when the
+/// returned guard is dropped. Stderr is per process, not per thread, so
+/// other threads' output is redirected too.
+pub fn redirect_stderr_to(path: &Path) -> io::Result<StderrRedirect> {
+    let log = File::open_append(path)?;
+    let saved = File::from_descriptor(cvt_r(|| unsafe {
+        libc::fcntl(libc::STDERR_FILENO, libc::F_DUPFD_CLOEXEC, 0)
+    })?);
+    cvt_r(|| unsafe { libc::dup2(log.descriptor, libc::STDERR_FILENO) })?;
+    Ok(StderrRedirect { log, saved })
+}
+
+impl Deref for StderrRedirect {
+    type Target = File;
+
+    fn deref(&self) -> &File {
+        &self.log
+    }
+}
+
+impl Drop for StderrRedirect {
+    fn drop(&mut self) {
+        // Nothing useful can be done if this fails; there's no stderr left
+        // to report it on.
+        let _ = cvt_r(|| unsafe { libc::dup2(self.saved.descriptor, libc::STDERR_FILENO) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::{self, Write};
+
+    use crate::fd;
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn captures_and_restores_stderr() {
+        let dir = TempDir::new();
+        let path = dir.join("stderr.log");
+        let before = unsafe { libc::fcntl(libc::STDERR_FILENO, libc::F_GETFL) };
+        {
+            let _redirect = fd::redirect_stderr_to(&path).unwrap();
+            // `eprintln!` would be swallowed by the test harness's capture;
+            // the handle goes straight to descriptor 2.
+            writeln!(io::stderr(), "redirected line").unwrap();
+        }
+        let logged = fs::read_to_string(&path).unwrap();
+        assert!(logged.contains("redirected line\n"), "{:?}", logged);
+
+        // Descriptor 2 is the original again, without the log's O_APPEND.
+        let after = unsafe { libc::fcntl(libc::STDERR_FILENO, libc::F_GETFL) };
+        assert_eq!(after, before);
+        writeln!(io::stderr(), "not redirected").unwrap();
+        assert!(!fs::read_to_string(&path)
+            .unwrap()
+            .contains("not redirected"));
+    }
+}
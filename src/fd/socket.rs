@@ -0,0 +1,230 @@
+use std::io;
+use std::mem;
+use std::ptr;
+
+use super::{cvt, cvt_r, File};
+
+/// A connected pair of Unix stream sockets.
+pub fn socketpair() -> io::Result<(File, File)> {
+    let mut fds = [0; 2];
+    cvt(unsafe {
+        libc::socketpair(
+            libc::AF_UNIX,
+            libc::SOCK_STREAM | libc::SOCK_CLOEXEC,
+            0,
+            fds.as_mut_ptr(),
+        )
+    })?;
+    Ok((File::from_descriptor(fds[0]), File::from_descriptor(fds[1])))
+}
+
+/// Room for one `cmsghdr` carrying a single descriptor, suitably aligned.
+#[repr(C)]
+union FdControl {
+    buf: [u8; 64],
+    _align: libc::cmsghdr,
+}
+
+impl File {
+    /// Reads into `buf` without consuming anything: the next read sees the
+    /// same bytes again. Returns how many bytes were peeked.
+    ///
+    /// Sockets are peeked with `recv(MSG_PEEK)`, which blocks like a read
+    /// when nothing has arrived. Seekable files are read with `pread` at the
+    /// current offset, which stays put. Pipes and terminals have neither, so
+    /// they fail with `Unsupported`.
+    pub fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.fstat()?.st_mode & libc::S_IFMT == libc::S_IFSOCK {
+            let n = cvt_r(|| unsafe {
+                libc::recv(
+                    self.descriptor,
+                    buf.as_mut_ptr() as *mut libc::c_void,
+                    buf.len(),
+                    libc::MSG_PEEK,
+                )
+            })?;
+            return Ok(n as usize);
+        }
+        match self.stream_position() {
+            Ok(pos) => self.read_at(buf, pos),
+            Err(ref e) if e.raw_os_error() == Some(libc::ESPIPE) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "can't peek at a pipe or terminal",
+            )),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Sends this descriptor over the Unix domain socket `socket` as an
+    /// `SCM_RIGHTS` message, for the process at the other end to pick up
+    /// with `File::recv_over`.
+    ///
+    /// The receiver gets its own descriptor for the same open file, so this
+    /// `File` stays open and usable; the two share an offset.
+    pub fn send_over(&self, socket: &File) -> io::Result<()> {
+        // Stream sockets won't carry control data without at least one byte.
+        let mut byte = [0u8; 1];
+        let mut iov = libc::iovec {
+            iov_base: byte.as_mut_ptr() as *mut libc::c_void,
+            iov_len: byte.len(),
+        };
+        let mut control = FdControl { buf: [0; 64] };
+        let space = unsafe { libc::CMSG_SPACE(mem::size_of::<libc::c_int>() as u32) } as usize;
+        assert!(space <= mem::size_of::<FdControl>());
+
+        let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = &mut control as *mut FdControl as *mut libc::c_void;
+        msg.msg_controllen = space as _;
+        unsafe {
+            let cmsg = libc::CMSG_FIRSTHDR(&msg);
+            (*cmsg).cmsg_level = libc::SOL_SOCKET;
+            (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+            (*cmsg).cmsg_len = libc::CMSG_LEN(mem::size_of::<libc::c_int>() as u32) as _;
+            ptr::write_unaligned(libc::CMSG_DATA(cmsg) as *mut libc::c_int, self.descriptor);
+        }
+
+        loop {
+            match cvt(unsafe { libc::sendmsg(socket.descriptor, &msg, 0) }) {
+                Ok(_) => return Ok(()),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Receives a descriptor sent with `File::send_over` from the other end
+    /// of `socket`. The new descriptor is close-on-exec.
+    ///
+    /// A message that carried no descriptor is `EBADMSG`. It doesn't
+    /// allocate, errors included, so it's safe to call in a child just
+    /// after `fork`.
+    pub fn recv_over(socket: &File) -> io::Result<File> {
+        let mut byte = [0u8; 1];
+        let mut iov = libc::iovec {
+            iov_base: byte.as_mut_ptr() as *mut libc::c_void,
+            iov_len: byte.len(),
+        };
+        let mut control = FdControl { buf: [0; 64] };
+
+        let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = &mut control as *mut FdControl as *mut libc::c_void;
+        msg.msg_controllen = mem::size_of::<FdControl>() as _;
+
+        loop {
+            match cvt(unsafe { libc::recvmsg(socket.descriptor, &mut msg, libc::MSG_CMSG_CLOEXEC) })
+            {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(_) => break,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        unsafe {
+            let cmsg = libc::CMSG_FIRSTHDR(&msg);
+            if cmsg.is_null()
+                || (*cmsg).cmsg_level != libc::SOL_SOCKET
+                || (*cmsg).cmsg_type != libc::SCM_RIGHTS
+            {
+                return Err(io::Error::from_raw_os_error(libc::EBADMSG));
+            }
+            let fd = ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const libc::c_int);
+            Ok(File::from_descriptor(fd))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{ErrorKind, Read, Write};
+
+    use crate::fd::{self, File};
+    use crate::testutil::{self, TempDir};
+
+    #[test]
+    fn peeked_socket_bytes_are_still_there() {
+        let (mut a, mut b) = fd::socketpair().unwrap();
+        a.write_all(b"xy").unwrap();
+
+        let mut buf = [0; 1];
+        assert_eq!(b.peek(&mut buf).unwrap(), 1);
+        assert_eq!(&buf, b"x");
+        let mut both = [0; 2];
+        b.read_exact(&mut both).unwrap();
+        assert_eq!(&both, b"xy");
+    }
+
+    #[test]
+    fn peek_on_files_and_pipes() {
+        let dir = TempDir::new();
+        let mut f = File::open(dir.file("f", b"abcdef")).unwrap();
+        let mut buf = [0; 2];
+        f.read_exact(&mut buf).unwrap();
+        assert_eq!(f.peek(&mut buf).unwrap(), 2);
+        assert_eq!(&buf, b"cd");
+        assert_eq!(f.stream_position().unwrap(), 2);
+
+        let (reader, _writer) = testutil::pipe();
+        let err = reader.peek(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn passes_descriptor_to_child() {
+        let dir = TempDir::new();
+        let f = File::open(dir.file("shared", b"passed over")).unwrap();
+        let (parent_end, child_end) = fd::socketpair().unwrap();
+
+        let pid = unsafe { libc::fork() };
+        assert!(pid >= 0);
+        if pid == 0 {
+            // In the child: no allocation, no unwinding, just report by exit
+            // status.
+            let ok = match File::recv_over(&child_end) {
+                Ok(mut received) => {
+                    let mut buf = [0u8; 32];
+                    match received.read(&mut buf) {
+                        Ok(n) => &buf[..n] == b"passed over",
+                        Err(_) => false,
+                    }
+                }
+                Err(_) => false,
+            };
+            unsafe { libc::_exit(if ok { 0 } else { 1 }) };
+        }
+
+        drop(child_end);
+        f.send_over(&parent_end).unwrap();
+        let mut status = 0;
+        assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+        assert!(libc::WIFEXITED(status));
+        assert_eq!(libc::WEXITSTATUS(status), 0);
+    }
+
+    #[test]
+    fn received_file_shares_the_open_file() {
+        let dir = TempDir::new();
+        let mut f = File::open(dir.file("shared", b"0123")).unwrap();
+        let (a, b) = fd::socketpair().unwrap();
+
+        f.send_over(&a).unwrap();
+        let mut received = File::recv_over(&b).unwrap();
+        let mut buf = [0; 2];
+        received.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"01");
+        f.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"23");
+    }
+
+    #[test]
+    fn receiving_a_message_without_a_descriptor() {
+        let (mut a, b) = fd::socketpair().unwrap();
+        a.write_all(b"x").unwrap();
+        let err = File::recv_over(&b).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EBADMSG));
+    }
+}
@@ -0,0 +1,154 @@
+use std::io;
+
+use super::{cvt, File};
+
+impl File {
+    /// Copies up to `count` bytes from this file's offset into `out`,
+    /// returning how many were sent. Fewer than `count` means end of file.
+    ///
+    /// On Linux this is `sendfile(2)`, so the bytes never pass through user
+    /// space. Where the kernel can't do that for this pair of descriptors,
+    /// it falls back to reading and writing through a buffer.
+    pub fn sendfile_to(&self, out: &File, count: usize) -> io::Result<usize> {
+        #[cfg(target_os = "linux")]
+        {
+            let mut sent = 0;
+            while sent < count {
+                let ret = unsafe {
+                    libc::sendfile(
+                        out.descriptor,
+                        self.descriptor,
+                        std::ptr::null_mut(),
+                        count - sent,
+                    )
+                };
+                match cvt(ret) {
+                    Ok(0) => return Ok(sent),
+                    Ok(n) => sent += n as usize,
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(ref e)
+                        if sent == 0
+                            && (e.raw_os_error() == Some(libc::EINVAL)
+                                || e.raw_os_error() == Some(libc::ENOSYS)) =>
+                    {
+                        return copy_through_buffer(self, out, count);
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            Ok(sent)
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            copy_through_buffer(self, out, count)
+        }
+    }
+}
+
+impl File {
+    /// Moves `len` bytes of this file, starting at `offset`, into the pipe
+    /// `pipe_write` with `splice(2)`, returning how many were moved. Fewer
+    /// than `len` means the range ran past end of file.
+    ///
+    /// The file's own offset is left alone. The pipe only holds so much, so
+    /// a range larger than its buffer needs someone reading the other end.
+    #[cfg(target_os = "linux")]
+    pub fn splice_range_to(&self, pipe_write: &File, offset: u64, len: u64) -> io::Result<u64> {
+        let mut off_in = offset as libc::loff_t;
+        let mut moved = 0;
+        while moved < len {
+            let chunk = (len - moved).min(usize::MAX as u64) as usize;
+            let ret = unsafe {
+                libc::splice(
+                    self.descriptor,
+                    &mut off_in,
+                    pipe_write.descriptor,
+                    std::ptr::null_mut(),
+                    chunk,
+                    libc::SPLICE_F_MOVE,
+                )
+            };
+            match cvt(ret) {
+                Ok(0) => break,
+                Ok(n) => moved += n as u64,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(moved)
+    }
+}
+
+fn copy_through_buffer(from: &File, to: &File, count: usize) -> io::Result<usize> {
+    let mut buf = [0u8; 8192];
+    let mut copied = 0;
+    while copied < count {
+        let want = buf.len().min(count - copied);
+        let ret =
+            unsafe { libc::read(from.descriptor, buf.as_mut_ptr() as *mut libc::c_void, want) };
+        let n = match cvt(ret) {
+            Ok(0) => break,
+            Ok(n) => n as usize,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        to.write_all_raw(&buf[..n])?;
+        copied += n;
+    }
+    Ok(copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use crate::fd::{self, File};
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn sends_file_into_pipe() {
+        let dir = TempDir::new();
+        let src = File::open(dir.file("src", b"hello, sendfile")).unwrap();
+        let (mut reader, writer) = fd::pipe().unwrap();
+
+        assert_eq!(src.sendfile_to(&writer, 5).unwrap(), 5);
+        // Asking for more than is left stops at end of file.
+        assert_eq!(src.sendfile_to(&writer, 100).unwrap(), 10);
+        drop(writer);
+
+        let mut received = Vec::new();
+        reader.read_to_end(&mut received).unwrap();
+        assert_eq!(received, b"hello, sendfile");
+    }
+
+    #[test]
+    fn fallback_copies_through_buffer() {
+        let dir = TempDir::new();
+        let src = File::open(dir.file("src", b"0123456789")).unwrap();
+        let (mut reader, writer) = fd::pipe().unwrap();
+
+        assert_eq!(super::copy_through_buffer(&src, &writer, 4).unwrap(), 4);
+        drop(writer);
+
+        let mut received = Vec::new();
+        reader.read_to_end(&mut received).unwrap();
+        assert_eq!(received, b"0123");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn splices_exact_range() {
+        let dir = TempDir::new();
+        let src = File::open(dir.file("src", b"0123456789abcdefghijklmnopqrstuvwxyz")).unwrap();
+        let (mut reader, writer) = fd::pipe().unwrap();
+
+        assert_eq!(src.splice_range_to(&writer, 10, 10).unwrap(), 10);
+        assert_eq!(src.splice_range_to(&writer, 30, 100).unwrap(), 6);
+        drop(writer);
+
+        let mut received = Vec::new();
+        reader.read_to_end(&mut received).unwrap();
+        assert_eq!(received, b"abcdefghijuvwxyz");
+        assert_eq!(src.stream_position().unwrap(), 0);
+    }
+}
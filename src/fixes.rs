@@ -0,0 +1,196 @@
+//! What each rejected example was trying to do, done in a way that
+//! compiles.
+//!
+//! Each function here carries the core of a rejected program as a
+//! `compile_fail` doctest, followed by the fix. Some of those programs are
+//! cases in `tests/compile_fail` as well, such as `extend_with_itself` and
+//! `clone_from_self`; the split borrow and the `entry` lookup appear only
+//! here. The tests check each fix against the operation the rejected code
+//! meant, computed the long way round on copies so nothing aliases.
+
+use std::collections::HashMap;
+
+/// Appends a copy of `v` to itself with `extend_from_within`.
+///
+/// This is the `extend(&mut wave, &wave)` that can't be written:
+///
+/// ```compile_fail,E0502
+/// let mut wave = vec![0.0, 1.0, 0.0, -1.0];
+/// wave.extend_from_slice(&wave); // cannot borrow `wave` as mutable because it is also borrowed as immutable
+/// ```
+///
+/// `extend_from_within` takes a range instead of a slice, so the vector
+/// itself decides when to read the elements, after any reallocation.
+pub fn extend_from_itself<T: Clone>(v: &mut Vec<T>) {
+    let len = v.len();
+    v.extend_from_within(..len);
+}
+
+/// `extend_from_itself` as an index loop.
+///
+/// Each element is cloned out before the push that might reallocate, so
+/// only the index is carried across it, never a reference.
+pub fn extend_from_itself_by_index<T: Clone>(v: &mut Vec<T>) {
+    let len = v.len();
+    v.reserve(len);
+    for i in 0..len {
+        let elt = v[i].clone();
+        v.push(elt);
+    }
+}
+
+/// Adds each element of the front half of `v` into the matching element
+/// of the back half. With an odd length the middle element is in the back
+/// half and is left alone.
+///
+/// Borrowing the halves separately is rejected, because both borrows are
+/// of the whole of `v`:
+///
+/// ```compile_fail,E0502
+/// let mut v = vec![1.0, 2.0, 10.0, 20.0];
+/// let front = &v[..2];
+/// let back = &mut v[2..]; // cannot borrow `v` as mutable because it is also borrowed as immutable
+/// back[0] += front[0];
+/// ```
+///
+/// `split_at_mut` hands out both halves from one borrow, and guarantees
+/// they don't overlap.
+pub fn mix_front_into_back(v: &mut [f64]) {
+    let mid = v.len() / 2;
+    let (front, back) = v.split_at_mut(mid);
+    let back_start = back.len() - front.len();
+    for (b, f) in back[back_start..].iter_mut().zip(front.iter()) {
+        *b += *f;
+    }
+}
+
+/// The group under `key`, created empty if it isn't there yet.
+///
+/// Checking with `get_mut` and inserting on a miss is rejected when the
+/// reference is returned, because the borrow from `get_mut` has to last
+/// for the caller's lifetime on every path, including the one that inserts:
+///
+/// ```compile_fail,E0499
+/// use std::collections::HashMap;
+///
+/// fn group<'a>(map: &'a mut HashMap<String, Vec<f64>>, key: &str) -> &'a mut Vec<f64> {
+///     if let Some(g) = map.get_mut(key) {
+///         return g;
+///     }
+///     map.insert(key.to_owned(), Vec::new()); // cannot borrow `*map` as mutable more than once at a time
+///     map.get_mut(key).unwrap()
+/// }
+/// ```
+///
+/// `entry` does the lookup and the insert under one mutable borrow.
+pub fn group_mut<'a>(map: &'a mut HashMap<String, Vec<f64>>, key: &str) -> &'a mut Vec<f64> {
+    map.entry(key.to_owned()).or_default()
+}
+
+/// Makes `dst` a copy of `src`, reusing `dst`'s resources where it can.
+///
+/// `clone_from` is the self-assignment the C++ operator gets wrong, and
+/// the borrow checker keeps it from ever being handed the same value twice:
+///
+/// ```compile_fail,E0502
+/// let mut v = vec![1, 2, 3];
+/// v.clone_from(&v); // cannot borrow `v` as mutable because it is also borrowed as immutable
+/// ```
+///
+/// For a `Vec` that means copying into the existing buffer instead of
+/// allocating a new one; for [`fd::File`](crate::fd::File) it means a
+/// `dup` onto the old descriptor.
+pub fn assign<T: Clone>(dst: &mut T, src: &T) {
+    dst.clone_from(src);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use super::*;
+    use crate::fd::{socketpair, File};
+
+    /// What extending a vector with itself means, on a separate copy.
+    fn doubled<T: Clone>(v: &[T]) -> Vec<T> {
+        let mut out = v.to_vec();
+        out.extend(v.iter().cloned());
+        out
+    }
+
+    #[test]
+    fn both_self_extends_double_the_vector() {
+        let cases: &[Vec<String>] = &[
+            vec![],
+            vec!["a".to_owned()],
+            vec!["0.0".to_owned(), "1.0".to_owned(), "-1.0".to_owned()],
+        ];
+        for case in cases {
+            let mut within = case.clone();
+            let mut indexed = case.clone();
+            // Full to capacity, so the first push has to reallocate.
+            within.shrink_to_fit();
+            indexed.shrink_to_fit();
+
+            extend_from_itself(&mut within);
+            extend_from_itself_by_index(&mut indexed);
+            assert_eq!(within, doubled(case));
+            assert_eq!(indexed, doubled(case));
+        }
+    }
+
+    #[test]
+    fn mixes_halves_like_separate_vectors() {
+        for len in 0..7 {
+            let original: Vec<f64> = (0..len).map(|i| (i * 10) as f64).collect();
+            let mid = len / 2;
+            let front = original[..mid].to_vec();
+            let mut expected = original.clone();
+            for (i, f) in front.iter().enumerate() {
+                expected[len - mid + i] += f;
+            }
+
+            let mut v = original.clone();
+            mix_front_into_back(&mut v);
+            assert_eq!(v, expected, "length {}", len);
+        }
+    }
+
+    #[test]
+    fn groups_are_created_then_reused() {
+        let mut map = HashMap::new();
+        group_mut(&mut map, "a").push(1.0);
+        group_mut(&mut map, "b");
+        group_mut(&mut map, "a").push(2.0);
+
+        let mut expected = HashMap::new();
+        expected.insert("a".to_owned(), vec![1.0, 2.0]);
+        expected.insert("b".to_owned(), Vec::new());
+        assert_eq!(map, expected);
+    }
+
+    #[test]
+    fn assign_matches_a_fresh_clone() {
+        let src = vec![7; 16];
+        let mut dst = Vec::with_capacity(64);
+        dst.push(1);
+        let buffer = dst.as_ptr();
+        assign(&mut dst, &src);
+        assert_eq!(dst, src.clone());
+        assert_eq!(dst.as_ptr(), buffer, "clone_from allocated a new buffer");
+    }
+
+    #[test]
+    fn assigned_file_writes_where_the_source_does() {
+        let (mut reader, writer) = socketpair().unwrap();
+        let mut dst = File::open("/dev/null").unwrap();
+        assign(&mut dst, &writer);
+        drop(writer);
+
+        dst.write_all(b"via dst").unwrap();
+        drop(dst);
+        let mut got = String::new();
+        reader.read_to_string(&mut got).unwrap();
+        assert_eq!(got, "via dst");
+    }
+}
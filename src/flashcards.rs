@@ -0,0 +1,258 @@
+//! The lessons as flashcards, for Anki.
+//!
+//! Every lesson example becomes a card with the program on the front and
+//! "does this compile?" under it, and on the back how it fares and the
+//! lesson's explanation of why. Every exercise becomes one too, with its
+//! answer and explanation on the back. [`csv`] writes them in the CSV that
+//! Anki's File > Import reads, with header lines naming the columns, so
+//! nothing needs setting up on import:
+//!
+//! - each card has a GUID made from its snippet's id,
+//!   `references-sharing-vs-mutation:dangling/1`, or its exercise's,
+//!   `references-sharing-vs-mutation:quiz:clone-from-itself`, so importing
+//!   a newer export updates the cards already there instead of adding
+//!   copies;
+//! - each goes in a deck under one parent, per chapter by default,
+//!   `References::Interior mutability`, or per tag with [`Decks::ByTag`],
+//!   `References::E0502`;
+//! - each carries the catalog's tags, plus `quiz` for the exercises, for
+//!   filtered decks by error code or outcome.
+//!
+//! The fields are HTML. A packaged `.apkg` would need SQLite and zip, which
+//! the crate doesn't depend on; importing the CSV makes the same notes.
+
+use std::fmt::Write as _;
+
+use crate::catalog::{self, Snippet};
+use crate::cli::{find_lesson, Example, Lesson, Outcome, LESSONS};
+use crate::exercises::{Exercise, Question, EXERCISES};
+use crate::explain::escape_html;
+use crate::export::chapter_title;
+
+/// What every GUID starts with, to keep clear of other decks' cards.
+const GUID_PREFIX: &str = "references-sharing-vs-mutation:";
+
+/// The parent deck.
+const DECK: &str = "References";
+
+/// One note, with its fields as HTML.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Card {
+    pub guid: String,
+    pub deck: String,
+    pub front: String,
+    pub back: String,
+    pub tags: Vec<String>,
+}
+
+/// How the cards are split into decks under `References`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Decks {
+    /// One per chapter, `References::Interior mutability`.
+    #[default]
+    ByChapter,
+    /// One per tag: each card goes in the deck of the first of these it
+    /// carries, `References::E0502`, or in `References` itself if it
+    /// carries none of them.
+    ByTag(Vec<String>),
+}
+
+impl Decks {
+    /// The deck for a card from `lesson` carrying `tags`.
+    fn for_card(&self, lesson: &Lesson, tags: &[String]) -> String {
+        match self {
+            Decks::ByChapter => format!("{}::{}", DECK, chapter_title(lesson.chapter)),
+            Decks::ByTag(deck_tags) => deck_tags
+                .iter()
+                .find(|deck_tag| tags.iter().any(|tag| tag.eq_ignore_ascii_case(deck_tag)))
+                .map_or_else(|| DECK.to_owned(), |tag| format!("{}::{}", DECK, tag)),
+        }
+    }
+}
+
+fn code_html(code: &str) -> String {
+    format!("<pre><code>{}</code></pre>", escape_html(code.trim_end()))
+}
+
+fn example_card(decks: &Decks, lesson: &Lesson, example: &Example, snippet: Snippet) -> Card {
+    let verdict = match example.outcome {
+        Outcome::Compiles => "<p><b>Compiles</b> and runs.</p>".to_owned(),
+        Outcome::Panics(message) => format!(
+            "<p><b>Compiles</b>, but panics when it runs: <code>{}</code></p>",
+            escape_html(message)
+        ),
+        Outcome::Rejected(stderr) => {
+            let first = stderr.lines().next().unwrap_or_default();
+            format!(
+                "<p><b>Rejected:</b> <code>{}</code></p>",
+                escape_html(first)
+            )
+        }
+    };
+    Card {
+        guid: format!("{}{}", GUID_PREFIX, snippet.id),
+        deck: decks.for_card(lesson, &snippet.tags),
+        front: format!("{}<p>Does this compile? Why?</p>", code_html(&snippet.code)),
+        back: format!("{}{}", verdict, lesson.explain().to_html().trim_end()),
+        tags: snippet.tags,
+    }
+}
+
+fn exercise_card(decks: &Decks, lesson: &Lesson, exercise: &Exercise) -> Card {
+    let (front, answer) = match exercise.question {
+        Question::Compiles { code, answer } => (
+            format!("{}<p>Does this compile?</p>", code_html(code)),
+            if answer { "Yes." } else { "No." }.to_owned(),
+        ),
+        Question::Choice {
+            prompt,
+            choices,
+            answer,
+        } => {
+            let mut front = format!("<p>{}</p><ul>", escape_html(prompt));
+            for choice in choices {
+                let _ = write!(front, "<li>{}</li>", escape_html(choice));
+            }
+            front.push_str("</ul>");
+            (front, escape_html(choices[answer]))
+        }
+    };
+    let tags = vec![
+        lesson.name.to_owned(),
+        lesson.chapter.to_owned(),
+        "quiz".to_owned(),
+    ];
+    Card {
+        guid: format!("{}quiz:{}", GUID_PREFIX, exercise.id),
+        deck: decks.for_card(lesson, &tags),
+        front,
+        back: format!(
+            "<p><b>{}</b></p><p>{}</p>",
+            answer,
+            escape_html(exercise.explanation)
+        ),
+        tags,
+    }
+}
+
+/// A card for every example, lesson by lesson, then one for every
+/// exercise, split into `decks`.
+pub fn cards(decks: &Decks) -> Vec<Card> {
+    let mut cards = Vec::new();
+    for lesson in LESSONS {
+        let snippets = catalog::find(lesson.name);
+        for (example, snippet) in lesson.examples.iter().zip(snippets) {
+            cards.push(example_card(decks, lesson, example, snippet));
+        }
+    }
+    for exercise in EXERCISES {
+        let lesson = find_lesson(exercise.lesson).expect("exercises belong to lessons");
+        cards.push(exercise_card(decks, lesson, exercise));
+    }
+    cards
+}
+
+/// `field` quoted for CSV, with any quotes in it doubled.
+fn quote(field: &str) -> String {
+    format!("\"{}\"", field.replace('"', "\"\""))
+}
+
+/// `cards` as a CSV file for Anki to import, headers first.
+pub fn csv(cards: &[Card]) -> String {
+    let mut out = String::from(
+        "#separator:Comma\n#html:true\n#columns:GUID,Deck,Front,Back,Tags\n\
+#guid column:1\n#deck column:2\n#tags column:5\n",
+    );
+    for card in cards {
+        let fields = [
+            quote(&card.guid),
+            quote(&card.deck),
+            quote(&card.front),
+            quote(&card.back),
+            quote(&card.tags.join(" ")),
+        ];
+        let _ = writeln!(out, "{}", fields.join(","));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_card_per_example_and_exercise() {
+        let cards = cards(&Decks::ByChapter);
+        let examples: usize = LESSONS.iter().map(|l| l.examples.len()).sum();
+        assert_eq!(cards.len(), examples + EXERCISES.len());
+
+        let first = &cards[0];
+        assert_eq!(first.guid, "references-sharing-vs-mutation:dangling/1");
+        assert_eq!(first.deck, "References::Sharing");
+        assert!(first.front.starts_with("<pre><code>fn main() {\n"));
+        assert!(first.front.contains("let r = &amp;v;"));
+        assert!(first.back.starts_with(
+            "<p><b>Rejected:</b> <code>error[E0505]: cannot move out of `v` because it is borrowed</code></p><p>"
+        ));
+        assert_eq!(first.tags, ["dangling", "sharing", "rejected", "E0505"]);
+    }
+
+    #[test]
+    fn guids_are_unique_and_stable() {
+        let cards = cards(&Decks::ByChapter);
+        let mut guids: Vec<&str> = cards.iter().map(|c| c.guid.as_str()).collect();
+        guids.sort_unstable();
+        guids.dedup();
+        assert_eq!(guids.len(), cards.len());
+        assert_eq!(cards, super::cards(&Decks::ByChapter));
+
+        let quiz = cards
+            .iter()
+            .find(|c| c.tags.contains(&"quiz".to_owned()))
+            .unwrap();
+        assert_eq!(
+            quiz.guid,
+            "references-sharing-vs-mutation:quiz:move-while-borrowed"
+        );
+
+        let by_tag = super::cards(&Decks::ByTag(vec!["E0505".to_owned()]));
+        let same: Vec<&str> = by_tag.iter().map(|c| c.guid.as_str()).collect();
+        assert_eq!(
+            same,
+            cards.iter().map(|c| c.guid.as_str()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn splits_decks_by_the_first_tag_given() {
+        let tags = vec!["quiz".to_owned(), "e0505".to_owned()];
+        let cards = cards(&Decks::ByTag(tags));
+        assert_eq!(cards[0].tags, ["dangling", "sharing", "rejected", "E0505"]);
+        assert_eq!(cards[0].deck, "References::e0505");
+        for card in &cards {
+            if card.tags.iter().any(|t| t == "quiz") {
+                assert_eq!(card.deck, "References::quiz");
+            } else if card.tags.iter().any(|t| t == "E0505") {
+                assert_eq!(card.deck, "References::e0505");
+            } else {
+                assert_eq!(card.deck, "References");
+            }
+        }
+    }
+
+    #[test]
+    fn quotes_fields_for_csv() {
+        let card = Card {
+            guid: "g".to_owned(),
+            deck: "References::Sharing".to_owned(),
+            front: "say \"hi\",\nthen go".to_owned(),
+            back: "b".to_owned(),
+            tags: vec!["a".to_owned(), "E0505".to_owned()],
+        };
+        let csv = csv(&[card]);
+        assert!(csv.starts_with("#separator:Comma\n#html:true\n"));
+        assert!(csv.ends_with(
+            "\n\"g\",\"References::Sharing\",\"say \"\"hi\"\",\nthen go\",\"b\",\"a E0505\"\n"
+        ));
+    }
+}
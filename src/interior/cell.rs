@@ -0,0 +1,47 @@
+//! `Cell`: assigning while shared references are live.
+//!
+//! `x += 10` is rejected while `r1` and `r2` borrow `x`. Put `x` in a
+//! `Cell` and it compiles, because `set` only needs a shared reference:
+//! a `Cell` never lends out a reference to what's inside, so there's no
+//! reference for the write to invalidate. Reading copies the value out,
+//! which is why `get` needs `T: Copy`:
+//!
+//! ```compile_fail,E0599
+//! use std::cell::Cell;
+//!
+//! let wave = Cell::new(vec![0.0, 1.0]);
+//! let copy = wave.get(); // the method `get` exists for struct `Cell<Vec<f64>>`, but its trait bounds were not satisfied
+//! ```
+//!
+//! Non-`Copy` values can still be swapped in and out whole with `replace`
+//! and `take`.
+
+use std::cell::Cell;
+
+/// Adds `amount` to `x` through one of two shared references to it, and
+/// returns what the other one reads afterwards.
+pub fn add_through_shared(x: &Cell<i32>, amount: i32) -> i32 {
+    let r1 = x;
+    let r2 = x;
+    r1.set(r1.get() + amount);
+    r2.get()
+}
+
+/// The notes' `x` example with `x` in a `Cell`.
+pub fn demo() {
+    let x = Cell::new(10);
+    let seen = add_through_shared(&x, 10);
+    println!("set through r1, read through r2: {}", seen);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn both_references_see_the_write() {
+        let x = Cell::new(10);
+        assert_eq!(add_through_shared(&x, 10), 20);
+        assert_eq!(x.get(), 20);
+    }
+}
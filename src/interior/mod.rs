@@ -0,0 +1,17 @@
+//! Interior mutability: the rules checked at run time instead.
+//!
+//! `Cell`, `RefCell`, `Mutex` and `RwLock` let code mutate through a shared
+//! reference. They don't repeal the rules; they move the check. `Cell`
+//! never hands out references to its contents at all, `RefCell` counts
+//! borrows as the program runs and panics when the counts say the rules
+//! were broken, and the locks do the same job across threads by making the
+//! loser wait. Each module here takes an example the compiler rejected and
+//! shows it again with the check deferred, so the cost shows up: a mistake
+//! rustc would report before running becomes a panic, an error or a wait.
+//! `unsafecell` goes underneath, building `Cell` and `RefCell` again from
+//! the `UnsafeCell` they're made of.
+
+pub mod cell;
+pub mod refcell;
+pub mod sync;
+pub mod unsafecell;
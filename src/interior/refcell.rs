@@ -0,0 +1,107 @@
+//! `RefCell`: extending a vector with itself, checked at run time.
+//!
+//! `extend(&mut wave, &wave)` is rejected at compile time. With the vector
+//! in a `RefCell`, here the one inside [`SharedVec`], the same mistake
+//! compiles, because `borrow` and `borrow_mut` both start from `&RefCell`,
+//! and is caught as it happens:
+//! the `RefCell` knows a shared borrow is out when the mutable one is asked
+//! for, and `borrow_mut` panics. `try_borrow_mut` reports the same thing as
+//! an error.
+//!
+//! That check is all that stands between the program and the dangling
+//! slice, and it only fires on the paths that actually run. A `RefCell`
+//! also can't be shared between threads, where counting borrows without
+//! synchronization would race:
+//!
+//! ```compile_fail,E0277
+//! use std::cell::RefCell;
+//! use std::sync::Arc;
+//! use std::thread;
+//!
+//! let wave = Arc::new(RefCell::new(vec![0.0, 1.0]));
+//! let other = Arc::clone(&wave);
+//! thread::spawn(move || other.borrow_mut().push(0.0)); // `RefCell<Vec<f64>>` cannot be shared between threads safely
+//! ```
+
+use std::cell::BorrowMutError;
+
+use crate::references::SharedVec;
+
+/// Appends `wave` to itself while still holding a snapshot of it, the way
+/// `extend(&mut wave, &wave)` tries to.
+///
+/// [`SharedVec`] keeps its vector in a `RefCell`: the snapshot is a shared
+/// borrow, and each `push` asks for a mutable one.
+///
+/// # Panics
+///
+/// Whenever `wave` isn't empty: the snapshot is still out when the first
+/// `push` asks for its borrow.
+pub fn extend_with_itself(wave: &SharedVec) {
+    let slice = wave.snapshot();
+    for &x in slice.iter() {
+        wave.push(x);
+    }
+}
+
+/// `extend_with_itself`, reporting the conflict instead of panicking. The
+/// vector is left as it was.
+pub fn try_extend_with_itself(wave: &SharedVec) -> Result<(), BorrowMutError> {
+    let slice = wave.snapshot();
+    for &x in slice.iter() {
+        wave.try_push(x)?;
+    }
+    Ok(())
+}
+
+/// The version that works: copy the contents out and let that borrow end
+/// before the pushes start.
+pub fn extend_with_snapshot(wave: &SharedVec) {
+    let snapshot = wave.snapshot().clone();
+    for x in snapshot {
+        wave.push(x);
+    }
+}
+
+/// Tries the overlapping borrows, then the snapshot.
+pub fn demo() {
+    let wave = SharedVec::from(vec![0.0, 1.0, 0.0, -1.0]);
+    if let Err(e) = try_extend_with_itself(&wave) {
+        println!("overlapping borrows refused at run time: {}", e);
+    }
+    extend_with_snapshot(&wave);
+    println!("with a snapshot: {:?}", *wave.snapshot());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use super::*;
+
+    #[test]
+    fn overlapping_borrows_panic() {
+        let wave = SharedVec::from(vec![0.0, 1.0]);
+        let err = panic::catch_unwind(AssertUnwindSafe(|| extend_with_itself(&wave))).unwrap_err();
+        let message = err
+            .downcast_ref::<String>()
+            .map(String::as_str)
+            .or_else(|| err.downcast_ref::<&str>().copied())
+            .unwrap_or_default();
+        assert!(message.contains("already borrowed"), "{:?}", message);
+
+        // The panic released both borrows on the way out.
+        wave.push(2.0);
+        assert_eq!(*wave.snapshot(), vec![0.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn try_reports_and_snapshot_works() {
+        let wave = SharedVec::from(vec![0.0, 1.0]);
+        assert!(try_extend_with_itself(&wave).is_err());
+        assert_eq!(*wave.snapshot(), vec![0.0, 1.0]);
+
+        extend_with_snapshot(&wave);
+        assert_eq!(*wave.snapshot(), vec![0.0, 1.0, 0.0, 1.0]);
+    }
+}
@@ -0,0 +1,88 @@
+//! `Mutex` and `RwLock`: the two rules between threads.
+//!
+//! A `Mutex` guard is the threaded `&mut`: while one is out, nobody else
+//! gets one. An `RwLock` is both rules at once, handing out any number of
+//! read guards or one write guard. Where rustc would reject a second
+//! `&mut y` outright, a second `lock` from another thread just waits its
+//! turn, and one from the same thread deadlocks or panics, so the examples
+//! here use `try_lock` and `try_write` to see the conflict without hanging.
+//! The compile-time rules still apply to the guards themselves.
+
+use std::sync::{Arc, Mutex, RwLock, TryLockError};
+use std::thread;
+
+/// Has `threads` threads each add one to a shared counter `per_thread`
+/// times, and returns the total. The `Mutex` makes each increment
+/// exclusive, so none are lost.
+pub fn count_in_threads(threads: usize, per_thread: usize) -> usize {
+    let counter = Arc::new(Mutex::new(0));
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let counter = Arc::clone(&counter);
+            thread::spawn(move || {
+                for _ in 0..per_thread {
+                    *counter.lock().unwrap() += 1;
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    let total = *counter.lock().unwrap();
+    total
+}
+
+/// Takes the lock, then tries to take it again while holding it: the
+/// `let m1 = &mut y; let m2 = &mut y;` example. Returns whether the second
+/// attempt was refused.
+pub fn second_lock_refused<T>(lock: &Mutex<T>) -> bool {
+    let _m1 = lock.lock().unwrap();
+    matches!(lock.try_lock(), Err(TryLockError::WouldBlock))
+}
+
+/// Takes two read guards, then tries to write: the `let r1 = &x; let m =
+/// &mut x;` example. Returns whether both reads were granted and the write
+/// refused.
+pub fn write_refused_while_reading<T>(lock: &RwLock<T>) -> bool {
+    let r1 = lock.read().unwrap();
+    let r2 = lock.read().unwrap();
+    let refused = matches!(lock.try_write(), Err(TryLockError::WouldBlock));
+    drop((r1, r2));
+    refused
+}
+
+/// Runs the counter and both refused locks.
+pub fn demo() {
+    println!("4 threads x 1000 increments: {}", count_in_threads(4, 1000));
+    println!(
+        "second lock while holding the first refused: {}",
+        second_lock_refused(&Mutex::new(20))
+    );
+    println!(
+        "write while two readers hold the lock refused: {}",
+        write_refused_while_reading(&RwLock::new(10))
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_increment_is_lost() {
+        assert_eq!(count_in_threads(4, 1000), 4000);
+    }
+
+    #[test]
+    fn conflicting_guards_are_refused() {
+        let mutex = Mutex::new(20);
+        assert!(second_lock_refused(&mutex));
+        // Both guards are gone now.
+        assert!(mutex.try_lock().is_ok());
+
+        let rw = RwLock::new(10);
+        assert!(write_refused_while_reading(&rw));
+        assert!(rw.try_write().is_ok());
+    }
+}
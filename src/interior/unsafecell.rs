@@ -0,0 +1,306 @@
+//! `UnsafeCell`: `Cell` and `RefCell` built by hand.
+//!
+//! Shared access is read-only, and the compiler goes further than checking
+//! it: behind a `&T` it may assume the value is frozen, keeping it in a
+//! register across calls or reading it once for many uses. Casting the
+//! `&T` to a `*mut T` and writing through it is undefined behavior for that
+//! reason, whatever the program looks like it does. `UnsafeCell<T>` is the
+//! one exception the language makes. A `&UnsafeCell<T>` promises nothing
+//! about the `T`, its `get` gives a `*mut T` that may be written through,
+//! and every other interior-mutable type is built on it.
+//!
+//! What `UnsafeCell` doesn't do is keep the rules. A `&mut T` made from
+//! its pointer is still meant to be the only way to the `T` while it
+//! lives; the cell just stops the compiler checking that, and the type
+//! around it has to instead. [`MyCell`] keeps the rules by never handing
+//! out a reference at all: it copies values in and out, and no reference
+//! to the contents exists long enough to see a write. [`MyRefCell`] hands
+//! out references, so it counts them, in a `MyCell` of its own, and
+//! refuses a borrow the count says would break the rules, the way
+//! `RefCell` does. The guards it returns put the count back when they're
+//! dropped, and borrow the cell, so the compiler still sees to it that
+//! none outlives it:
+//!
+//! ```compile_fail,E0505
+//! use references_sharing_vs_mutation::interior::unsafecell::MyRefCell;
+//!
+//! let cell = MyRefCell::new(vec![1, 2]);
+//! let first = cell.borrow();
+//! drop(cell); // cannot move out of `cell` because it is borrowed
+//! println!("{:?}", *first);
+//! ```
+//!
+//! `UnsafeCell` isn't `Sync`, so neither are these, and unlike the locks
+//! they can't be shared between threads, where two would count at once.
+//! Their tests are worth running under Miri, which checks each pointer
+//! use against the references alive at the time: `cargo +nightly miri test
+//! --lib unsafecell`.
+
+use std::cell::UnsafeCell;
+use std::ops::{Deref, DerefMut};
+
+/// `Cell`: a value that can be replaced through a shared reference.
+#[derive(Debug, Default)]
+pub struct MyCell<T> {
+    value: UnsafeCell<T>,
+}
+
+impl<T> MyCell<T> {
+    pub fn new(value: T) -> MyCell<T> {
+        MyCell {
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Puts `value` in and returns what was there.
+    pub fn replace(&self, value: T) -> T {
+        // SAFETY: `MyCell` never lends out a reference to its contents,
+        // and isn't `Sync`, so this brief `&mut` is the only access to them.
+        unsafe { std::mem::replace(&mut *self.value.get(), value) }
+    }
+
+    pub fn set(&self, value: T) {
+        self.replace(value);
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: Copy> MyCell<T> {
+    /// A copy of the value.
+    pub fn get(&self) -> T {
+        // SAFETY: as in `replace`; the read copies the value out, and no
+        // reference to it outlives this line.
+        unsafe { *self.value.get() }
+    }
+}
+
+/// What a [`MyRefCell`]'s flag holds while it's mutably borrowed. Zero is
+/// unborrowed, and a positive number counts the shared borrows.
+const WRITING: isize = -1;
+
+/// `RefCell`: a value lent out as `&T` or `&mut T` by a borrow count kept
+/// at run time.
+#[derive(Debug, Default)]
+pub struct MyRefCell<T> {
+    flag: MyCell<isize>,
+    value: UnsafeCell<T>,
+}
+
+impl<T> MyRefCell<T> {
+    pub fn new(value: T) -> MyRefCell<T> {
+        MyRefCell {
+            flag: MyCell::new(0),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// A shared borrow, or `None` if the value is mutably borrowed.
+    pub fn try_borrow(&self) -> Option<Ref<'_, T>> {
+        let flag = self.flag.get();
+        if flag == WRITING || flag == isize::MAX {
+            return None;
+        }
+        self.flag.set(flag + 1);
+        // SAFETY: the flag was not `WRITING`, so no `&mut T` is alive, and
+        // none will be made until this borrow's guard has put it back.
+        let value = unsafe { &*self.value.get() };
+        Some(Ref {
+            value,
+            flag: &self.flag,
+        })
+    }
+
+    /// A mutable borrow, or `None` if the value is borrowed at all.
+    pub fn try_borrow_mut(&self) -> Option<RefMut<'_, T>> {
+        if self.flag.get() != 0 {
+            return None;
+        }
+        self.flag.set(WRITING);
+        // SAFETY: the flag was zero, so no reference to the value is alive,
+        // and the flag now refuses any until this guard is dropped.
+        let value = unsafe { &mut *self.value.get() };
+        Some(RefMut {
+            value,
+            flag: &self.flag,
+        })
+    }
+
+    /// A shared borrow.
+    ///
+    /// # Panics
+    ///
+    /// If the value is mutably borrowed.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.try_borrow().expect("already mutably borrowed")
+    }
+
+    /// A mutable borrow.
+    ///
+    /// # Panics
+    ///
+    /// If the value is borrowed at all.
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        self.try_borrow_mut().expect("already borrowed")
+    }
+
+    /// The value, from an exclusive reference to the cell, which needs no
+    /// count: the compiler has already checked there are no guards.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+/// A shared borrow out of a [`MyRefCell`], counted until it's dropped.
+#[derive(Debug)]
+pub struct Ref<'b, T> {
+    value: &'b T,
+    flag: &'b MyCell<isize>,
+}
+
+impl<T> Deref for Ref<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<T> Drop for Ref<'_, T> {
+    fn drop(&mut self) {
+        self.flag.set(self.flag.get() - 1);
+    }
+}
+
+/// The mutable borrow out of a [`MyRefCell`], until it's dropped.
+#[derive(Debug)]
+pub struct RefMut<'b, T> {
+    value: &'b mut T,
+    flag: &'b MyCell<isize>,
+}
+
+impl<T> Deref for RefMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<T> DerefMut for RefMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value
+    }
+}
+
+impl<T> Drop for RefMut<'_, T> {
+    fn drop(&mut self) {
+        self.flag.set(0);
+    }
+}
+
+/// Counts through a cell by hand, then extends a vector with itself the
+/// way the `refcell` lesson does, refused by the hand-made count.
+pub fn demo() {
+    let count = MyCell::new(0);
+    let (r1, r2) = (&count, &count);
+    r1.set(r1.get() + 10);
+    println!(
+        "set through one reference, read through the other: {}",
+        r2.get()
+    );
+
+    let wave = MyRefCell::new(vec![0.0, 1.0]);
+    {
+        let snapshot = wave.borrow();
+        println!(
+            "mutable borrow while {:?} is shared: {}",
+            *snapshot,
+            if wave.try_borrow_mut().is_some() {
+                "allowed"
+            } else {
+                "refused"
+            }
+        );
+    }
+    let copy = wave.borrow().clone();
+    wave.borrow_mut().extend(copy);
+    println!("with a copy: {:?}", *wave.borrow());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cell_sets_through_shared_references() {
+        let cell = MyCell::new(1);
+        let (a, b) = (&cell, &cell);
+        a.set(5);
+        assert_eq!(b.get(), 5);
+        assert_eq!(b.replace(7), 5);
+        assert_eq!(cell.into_inner(), 7);
+
+        let names = MyCell::new(vec!["a"]);
+        assert_eq!(names.replace(Vec::new()), ["a"]);
+    }
+
+    #[test]
+    fn counts_shared_borrows() {
+        let cell = MyRefCell::new(vec![1, 2]);
+        let a = cell.borrow();
+        let b = cell.borrow();
+        assert_eq!(cell.flag.get(), 2);
+        assert!(cell.try_borrow_mut().is_none());
+        assert_eq!(a.len() + b.len(), 4);
+        drop(a);
+        assert!(cell.try_borrow_mut().is_none());
+        drop(b);
+        assert_eq!(cell.flag.get(), 0);
+        cell.borrow_mut().push(3);
+        assert_eq!(*cell.borrow(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn refuses_any_borrow_while_mutably_borrowed() {
+        let cell = MyRefCell::new(String::from("x"));
+        {
+            let mut m = cell.borrow_mut();
+            assert!(cell.try_borrow().is_none());
+            assert!(cell.try_borrow_mut().is_none());
+            m.push('y');
+        }
+        assert_eq!(*cell.borrow(), "xy");
+        assert_eq!(cell.into_inner(), "xy");
+    }
+
+    #[test]
+    #[should_panic(expected = "already borrowed")]
+    fn mutable_borrow_while_shared_panics() {
+        let cell = MyRefCell::new(0);
+        let _r = cell.borrow();
+        cell.borrow_mut();
+    }
+
+    #[test]
+    #[should_panic(expected = "already mutably borrowed")]
+    fn shared_borrow_while_mutable_panics() {
+        let cell = MyRefCell::new(0);
+        let _m = cell.borrow_mut();
+        cell.borrow();
+    }
+
+    #[test]
+    fn get_mut_needs_no_count() {
+        let mut cell = MyRefCell::new(1);
+        *cell.get_mut() += 1;
+        assert_eq!(cell.flag.get(), 0);
+        assert_eq!(*cell.borrow(), 2);
+    }
+}
@@ -0,0 +1,376 @@
+//! Changing a collection while walking it, without invalidated iterators.
+//!
+//! In C++, erasing from or inserting into a `std::vector` inside a loop over
+//! it leaves the loop's iterator pointing at moved or freed elements. In
+//! Rust the loop holds a shared borrow of the vector and the edit needs a
+//! mutable one, so the same code doesn't compile:
+//!
+//! ```compile_fail,E0502
+//! let mut v = vec![1, 2, 3, 4];
+//! for (i, x) in v.iter().enumerate() {
+//!     if x % 2 == 0 {
+//!         v.remove(i); // cannot borrow `v` as mutable because it is also borrowed as immutable
+//!     }
+//! }
+//! ```
+//!
+//! What's left is to do the walk and the edits some way that never has both
+//! at once. From least to most general:
+//!
+//! - [`remove_where`] hands the whole job to `Vec::retain`, which holds the
+//!   vector itself and lends the test one element at a time.
+//! - [`extract_where`] is `retain` that keeps what it removes, like the
+//!   standard library's `extract_if`, written out by hand.
+//! - [`repeat_where`] walks by index instead, holding no reference across
+//!   an edit and moving its index past what it inserted.
+//! - [`EditQueue`] lets the loop keep its borrow: edits are recorded while
+//!   walking and applied after the loop ends, when the borrow has too.
+
+use std::mem;
+use std::vec;
+
+/// Removes every element of `v` for which `remove` returns `true`, keeping
+/// the rest in order.
+///
+/// ```
+/// use references_sharing_vs_mutation::iterinv::remove_where;
+///
+/// let mut v = vec![1, 2, 3, 4];
+/// remove_where(&mut v, |x| x % 2 == 0);
+/// assert_eq!(v, [1, 3]);
+/// ```
+pub fn remove_where<T>(v: &mut Vec<T>, mut remove: impl FnMut(&T) -> bool) {
+    v.retain(|x| !remove(x));
+}
+
+/// Removes every element of `v` for which `extract` returns `true` and
+/// returns them, both halves in their original order.
+///
+/// `extract` gets each element by `&mut`, so it can change what stays as
+/// well as choose what goes. The vector is taken apart and rebuilt rather
+/// than edited in place, and `v` stays borrowed by the rebuilding until
+/// it's done, so nothing else can see it half done. If `extract` panics,
+/// `v` is left with what it kept and everything not yet decided, the
+/// element it panicked on included; only what it had already extracted is
+/// lost.
+///
+/// ```
+/// use references_sharing_vs_mutation::iterinv::extract_where;
+///
+/// let mut v = vec![1, 2, 3, 4, 5];
+/// let evens = extract_where(&mut v, |x| *x % 2 == 0);
+/// assert_eq!((v, evens), (vec![1, 3, 5], vec![2, 4]));
+/// ```
+pub fn extract_where<T>(v: &mut Vec<T>, mut extract: impl FnMut(&mut T) -> bool) -> Vec<T> {
+    let mut extracted = Vec::new();
+    let rest = mem::take(v).into_iter();
+    v.reserve(rest.len());
+    let mut rebuild = Rebuild { v, rest };
+    // Each element is decided on where it is, so a panic leaves it among
+    // the rest for `Rebuild` to put back.
+    while let Some(x) = rebuild.rest.as_mut_slice().first_mut() {
+        let take = extract(x);
+        let x = rebuild.rest.next().expect("the slice wasn't empty");
+        if take {
+            extracted.push(x);
+        } else {
+            rebuild.v.push(x);
+        }
+    }
+    extracted
+}
+
+/// A vector being rebuilt from `rest`, which gets whatever is left of
+/// `rest` back when it's dropped, by a panic or otherwise.
+struct Rebuild<'a, T> {
+    v: &'a mut Vec<T>,
+    rest: vec::IntoIter<T>,
+}
+
+impl<T> Drop for Rebuild<'_, T> {
+    fn drop(&mut self) {
+        self.v.extend(&mut self.rest);
+    }
+}
+
+/// Inserts a copy of each element of `v` for which `repeat` returns
+/// `true` right after it.
+///
+/// The loop holds an index, not a reference, so nothing dangles when
+/// `insert` shifts the tail along. It steps over the copy it just made, so
+/// each original is looked at once and copies aren't copied again. That
+/// bookkeeping is the part C++ iterator loops get wrong, and Rust doesn't
+/// check it either: an index that's off by one is a logic bug, or a panic,
+/// but never a read of freed memory.
+///
+/// ```
+/// use references_sharing_vs_mutation::iterinv::repeat_where;
+///
+/// let mut v = vec![1, 2, 3];
+/// repeat_where(&mut v, |x| *x != 2);
+/// assert_eq!(v, [1, 1, 2, 3, 3]);
+/// ```
+pub fn repeat_where<T: Clone>(v: &mut Vec<T>, mut repeat: impl FnMut(&T) -> bool) {
+    let mut i = 0;
+    while i < v.len() {
+        if repeat(&v[i]) {
+            let copy = v[i].clone();
+            v.insert(i + 1, copy);
+            i += 1;
+        }
+        i += 1;
+    }
+}
+
+/// Edits to a vector, recorded while walking it and applied afterwards.
+///
+/// Indices are positions in the vector as it was when the edits were
+/// recorded, the ones a loop over it sees, however many earlier edits
+/// there are. Applying the queue sorts out how they shift each other.
+///
+/// ```
+/// use references_sharing_vs_mutation::iterinv::EditQueue;
+///
+/// let mut v = vec![1, 2, 3, 4];
+/// let mut edits = EditQueue::new();
+/// for (i, x) in v.iter().enumerate() {
+///     if x % 2 == 0 {
+///         edits.remove(i);
+///     } else {
+///         edits.insert(i, x * 10);
+///     }
+/// }
+/// edits.apply(&mut v);
+/// assert_eq!(v, [10, 1, 30, 3]);
+/// ```
+#[derive(Debug)]
+pub struct EditQueue<T> {
+    edits: Vec<Edit<T>>,
+}
+
+#[derive(Debug)]
+enum Edit<T> {
+    Remove(usize),
+    Replace(usize, T),
+    Insert(usize, T),
+    Push(T),
+}
+
+impl<T> EditQueue<T> {
+    /// An empty queue.
+    pub fn new() -> Self {
+        EditQueue { edits: Vec::new() }
+    }
+
+    /// Queues removing the element at `index`.
+    pub fn remove(&mut self, index: usize) {
+        self.edits.push(Edit::Remove(index));
+    }
+
+    /// Queues replacing the element at `index` with `value`. Of several
+    /// replacements for one index the last wins, and a removal beats them
+    /// all.
+    pub fn replace(&mut self, index: usize, value: T) {
+        self.edits.push(Edit::Replace(index, value));
+    }
+
+    /// Queues inserting `value` before the element at `index`, or at the
+    /// end if `index` is the length. Values inserted at one index keep the
+    /// order they were queued in, and stay even if that element is removed.
+    pub fn insert(&mut self, index: usize, value: T) {
+        self.edits.push(Edit::Insert(index, value));
+    }
+
+    /// Queues appending `value`, after everything else.
+    pub fn push(&mut self, value: T) {
+        self.edits.push(Edit::Push(value));
+    }
+
+    /// The number of edits queued.
+    pub fn len(&self) -> usize {
+        self.edits.len()
+    }
+
+    /// Whether no edits are queued.
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Applies the queued edits to `v`.
+    ///
+    /// # Panics
+    ///
+    /// If an edit's index is past the end of `v`, or for `insert`, past
+    /// its length. `v` is left unchanged.
+    pub fn apply(self, v: &mut Vec<T>) {
+        let len = v.len();
+        let mut removed = vec![false; len];
+        let mut replaced: Vec<Option<T>> = (0..len).map(|_| None).collect();
+        let mut inserted = Vec::new();
+        let mut pushed = Vec::new();
+        for edit in self.edits {
+            match edit {
+                Edit::Remove(i) => removed[in_bounds(i, len)] = true,
+                Edit::Replace(i, value) => replaced[in_bounds(i, len)] = Some(value),
+                Edit::Insert(i, value) => {
+                    assert!(i <= len, "insertion index {} past length {}", i, len);
+                    inserted.push((i, value));
+                }
+                Edit::Push(value) => pushed.push(value),
+            }
+        }
+        // Stable, so inserts at one index stay in the order they came.
+        inserted.sort_by_key(|&(i, _)| i);
+
+        let mut inserted = inserted.into_iter().peekable();
+        let old = mem::take(v);
+        for (i, (x, new)) in old.into_iter().zip(replaced).enumerate() {
+            while let Some((_, value)) = inserted.next_if(|&(at, _)| at == i) {
+                v.push(value);
+            }
+            if !removed[i] {
+                v.push(new.unwrap_or(x));
+            }
+        }
+        v.extend(inserted.map(|(_, value)| value));
+        v.extend(pushed);
+    }
+}
+
+impl<T> Default for EditQueue<T> {
+    fn default() -> Self {
+        EditQueue::new()
+    }
+}
+
+fn in_bounds(index: usize, len: usize) -> usize {
+    assert!(
+        index < len,
+        "index {} out of bounds for length {}",
+        index,
+        len
+    );
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remove_where_keeps_order() {
+        let mut v: Vec<i32> = (0..10).collect();
+        remove_where(&mut v, |x| x % 3 == 0);
+        assert_eq!(v, [1, 2, 4, 5, 7, 8]);
+
+        let mut empty: Vec<i32> = Vec::new();
+        remove_where(&mut empty, |_| true);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn extract_where_splits_and_edits() {
+        let mut v = vec!["a".to_string(), "bb".to_string(), "c".to_string()];
+        let long = extract_where(&mut v, |s| {
+            s.push('!');
+            s.len() > 2
+        });
+        assert_eq!(v, ["a!", "c!"]);
+        assert_eq!(long, ["bb!"]);
+
+        let mut v = vec![1, 2, 3];
+        assert!(extract_where(&mut v, |_| false).is_empty());
+        assert_eq!(v, [1, 2, 3]);
+        assert_eq!(extract_where(&mut v, |_| true), [1, 2, 3]);
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn extract_where_keeps_the_unvisited_on_panic() {
+        let mut v = vec![1, 2, 3, 4];
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            extract_where(&mut v, |x| {
+                assert_ne!(*x, 3, "extract panicked");
+                *x % 2 == 0
+            })
+        }));
+        assert!(panicked.is_err());
+        // 2 had been extracted, and went with the panic.
+        assert_eq!(v, [1, 3, 4]);
+    }
+
+    #[test]
+    fn repeat_where_looks_at_each_original_once() {
+        let mut v = vec![1, 2, 3];
+        let mut seen = Vec::new();
+        repeat_where(&mut v, |&x| {
+            seen.push(x);
+            true
+        });
+        assert_eq!(seen, [1, 2, 3]);
+        assert_eq!(v, [1, 1, 2, 2, 3, 3]);
+
+        let mut v: Vec<i32> = Vec::new();
+        repeat_where(&mut v, |_| true);
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn edit_queue_uses_original_indices() {
+        let mut v = vec!['a', 'b', 'c', 'd'];
+        let mut edits = EditQueue::new();
+        edits.remove(0);
+        edits.replace(2, 'C');
+        edits.insert(2, 'x');
+        edits.insert(4, 'e');
+        edits.push('z');
+        edits.insert(2, 'y');
+        assert_eq!(edits.len(), 6);
+        edits.apply(&mut v);
+        assert_eq!(v, ['b', 'x', 'y', 'C', 'd', 'e', 'z']);
+    }
+
+    #[test]
+    fn removal_beats_replacement_and_last_replacement_wins() {
+        let mut v = vec![1, 2, 3];
+        let mut edits = EditQueue::new();
+        edits.replace(0, 10);
+        edits.remove(0);
+        edits.replace(1, 20);
+        edits.replace(1, 21);
+        edits.insert(0, 0);
+        edits.apply(&mut v);
+        assert_eq!(v, [0, 21, 3]);
+    }
+
+    #[test]
+    fn empty_queue_changes_nothing() {
+        let mut v = vec![1, 2];
+        let edits: EditQueue<i32> = EditQueue::default();
+        assert!(edits.is_empty());
+        edits.apply(&mut v);
+        assert_eq!(v, [1, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "index 3 out of bounds for length 3")]
+    fn edit_queue_checks_indices() {
+        let mut v = vec![1, 2, 3];
+        let mut edits = EditQueue::new();
+        edits.remove(3);
+        edits.apply(&mut v);
+    }
+
+    #[test]
+    fn edit_queue_leaves_v_alone_when_it_panics() {
+        let mut v = vec![1, 2, 3];
+        let mut edits = EditQueue::new();
+        edits.remove(0);
+        edits.insert(4, 4);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            edits.apply(&mut v);
+        }));
+        assert!(result.is_err());
+        assert_eq!(v, [1, 2, 3]);
+    }
+}
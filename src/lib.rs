@@ -0,0 +1,149 @@
+//! The sharing-versus-mutation notes, as code that compiles and runs.
+//!
+//! `sharing` and `mutation` are the notes themselves, one module per
+//! example: the prose is in the docs, the rejected code is in `compile_fail`
+//! doctests, and each example's fixed version runs from its `demo()`. The
+//! rest follow on from them:
+//!
+//! - `fixes` does what each rejected example meant, the idiomatic way;
+//! - `interior` shows the same examples with the checks moved to run time;
+//! - `rc` covers values with more than one owner, and how to mutate them;
+//! - `cow` covers results that borrow when they can and own when they must;
+//! - `reborrow` covers the reborrows the compiler makes without being asked;
+//! - `nll` covers borrows that end at their last use rather than their scope;
+//! - `selfref` shows what to write instead of a struct that borrows from
+//!   itself;
+//! - `split` shows how to borrow parts of a value mutably at once;
+//! - `perf` measures what following the rules costs against the aliased
+//!   versions they rule out.
+//!
+//! The other modules put the rules to use on real values:
+//!
+//! - `fd` has a descriptor-owning `File` like the one the chapter sketches;
+//! - `vecops` has helpers that borrow, mutate and share vectors by the book;
+//! - `iterinv` edits a vector while walking it;
+//! - `memo` caches computed values in a map without holding a lookup across
+//!   an insert;
+//! - `trace` records the borrows of the learner's own code, for `timeline`
+//!   to draw;
+//! - `diffview` shows what each fix changed;
+//! - `errors` indexes the lessons by rustc's error codes;
+//! - `flashcards` turns the lessons into Anki cards;
+//! - `wasm` hands the lessons to a web page;
+//! - `render` gathers all the crate's output for the snapshot tests.
+//!
+//! With the `cpp_compare` feature, `cpp_compare` runs C versions of the
+//! bugs the lessons describe, to set beside the Rust that rules them out.
+//!
+//! Everything but `vecops` needs the default `std` feature. Without it the
+//! crate is `no_std` and builds `vecops` on `alloc` alone. `fd`,
+//! `progress` and `render`, and the tour and quiz that record progress,
+//! need Unix besides, so that the rest builds for `wasm32` too.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(not(feature = "std"))]
+pub use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+pub mod borrowck;
+#[cfg(feature = "std")]
+pub mod catalog;
+#[cfg(feature = "std")]
+pub mod cli;
+#[cfg(feature = "std")]
+pub mod concurrency;
+#[cfg(feature = "std")]
+pub mod cow;
+#[cfg(feature = "cpp_compare")]
+pub mod cpp_compare;
+#[cfg(feature = "diagnostics")]
+pub mod diagnostics;
+#[cfg(feature = "std")]
+pub mod diagram;
+#[cfg(feature = "std")]
+pub mod diffview;
+#[cfg(feature = "std")]
+pub mod errors;
+#[cfg(feature = "std")]
+pub mod exercises;
+#[cfg(feature = "std")]
+pub mod explain;
+#[cfg(feature = "std")]
+pub mod export;
+#[cfg(all(feature = "std", unix))]
+pub mod fd;
+#[cfg(feature = "std")]
+pub mod fixes;
+#[cfg(feature = "std")]
+pub mod flashcards;
+#[cfg(feature = "std")]
+pub mod interior;
+#[cfg(feature = "std")]
+pub mod iterinv;
+#[cfg(feature = "std")]
+pub mod memo;
+#[cfg(feature = "std")]
+pub mod mutation;
+#[cfg(feature = "std")]
+pub mod nll;
+#[cfg(feature = "std")]
+pub mod perf;
+#[cfg(feature = "std")]
+pub mod playground;
+#[cfg(all(feature = "std", unix))]
+pub mod progress;
+#[cfg(feature = "std")]
+pub mod rc;
+#[cfg(feature = "std")]
+pub mod reborrow;
+#[cfg(feature = "std")]
+pub mod references;
+#[cfg(all(feature = "std", unix))]
+pub mod render;
+#[cfg(feature = "std")]
+pub mod selfref;
+#[cfg(feature = "std")]
+pub mod sharing;
+#[cfg(feature = "std")]
+pub mod split;
+#[cfg(feature = "std")]
+pub mod timeline;
+#[cfg(feature = "std")]
+pub mod tinyvec;
+#[cfg(feature = "std")]
+pub mod trace;
+#[cfg(feature = "unsound-demos")]
+pub mod ub_demos;
+pub mod vecops;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(all(test, feature = "std", unix))]
+mod testutil;
+
+/// Defines a test that checks each named program under
+/// `tests/compile_fail/` fails to compile with exactly the diagnostics in
+/// its `.stderr` file.
+///
+/// For use from an integration test in a crate with `trybuild` as a
+/// dev-dependency:
+///
+/// ```ignore
+/// references_sharing_vs_mutation::compile_fail! {
+///     "move_while_borrowed",
+///     "extend_with_itself",
+/// }
+/// ```
+#[macro_export]
+macro_rules! compile_fail {
+    ($($case:literal),* $(,)?) => {
+        #[test]
+        fn compile_fail() {
+            let cases = ::trybuild::TestCases::new();
+            $(cases.compile_fail(concat!("tests/compile_fail/", $case, ".rs"));)*
+        }
+    };
+}
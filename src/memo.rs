@@ -0,0 +1,203 @@
+//! Get-or-compute caching over a `HashMap`, without holding a lookup
+//! across an insert.
+//!
+//! The natural first draft of a cache looks the key up, returns the value
+//! if it's there, and otherwise inserts and returns that. It's sound, but
+//! rustc rejects it: the reference from `get` is returned on one path, so
+//! it has to live as long as the function's result, and the map stays
+//! borrowed across the `insert` on the other path too.
+//!
+//! ```compile_fail,E0502
+//! use std::collections::HashMap;
+//!
+//! fn get_or_compute(map: &mut HashMap<u32, String>, key: u32) -> &String {
+//!     if let Some(v) = map.get(&key) {
+//!         return v;
+//!     }
+//!     map.insert(key, key.to_string()); // cannot borrow `*map` as mutable because it is also borrowed as immutable
+//!     &map[&key]
+//! }
+//! ```
+//!
+//! `entry` does the lookup and the insert under one mutable borrow, and
+//! [`Cache`] is built on it.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Values computed once per key and kept, with counts of how often a
+/// lookup found one.
+///
+/// ```
+/// use references_sharing_vs_mutation::memo::Cache;
+///
+/// let mut lengths = Cache::new();
+/// assert_eq!(*lengths.get_or_insert_with("wave", |k| k.len()), 4);
+/// assert_eq!(*lengths.get_or_insert_with("wave", |_| unreachable!()), 4);
+/// assert_eq!((lengths.hits(), lengths.misses()), (1, 1));
+/// ```
+///
+/// The compute function can't use the cache itself: `get_or_insert_with`
+/// has it borrowed mutably for the whole call, so a recursive lookup from
+/// inside would be a second mutable borrow. [`fib`] shows the way round
+/// that, computing what a value needs before asking for its slot.
+///
+/// ```compile_fail,E0499
+/// use references_sharing_vs_mutation::memo::Cache;
+///
+/// let mut cache: Cache<u64, u64> = Cache::new();
+/// cache.get_or_insert_with(2, |&n| {
+///     cache.get_or_insert_with(n - 1, |_| 1) + 1 // cannot borrow `cache` as mutable more than once at a time
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cache<K, V> {
+    map: HashMap<K, V>,
+    hits: usize,
+    misses: usize,
+}
+
+impl<K: Eq + Hash, V> Cache<K, V> {
+    /// An empty cache.
+    pub fn new() -> Self {
+        Cache {
+            map: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// The value for `key`, calling `compute` to make it if there isn't
+    /// one yet.
+    ///
+    /// One `entry` call finds or makes the slot, so the only borrow of the
+    /// map is the mutable one, and the returned reference comes from it.
+    pub fn get_or_insert_with(&mut self, key: K, compute: impl FnOnce(&K) -> V) -> &V {
+        let (hits, misses) = (&mut self.hits, &mut self.misses);
+        self.map
+            .entry(key)
+            .and_modify(|_| *hits += 1)
+            .or_insert_with_key(|key| {
+                *misses += 1;
+                compute(key)
+            })
+    }
+
+    /// The value for `key` if it's been computed, without computing it or
+    /// counting the lookup.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.map.get(key)
+    }
+
+    /// How many `get_or_insert_with` calls found their value already there.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// How many `get_or_insert_with` calls had to compute their value.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// The number of values cached.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Whether nothing is cached.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+impl<K: Eq + Hash, V> Default for Cache<K, V> {
+    fn default() -> Self {
+        Cache::new()
+    }
+}
+
+/// The `n`th Fibonacci number, caching every one it computes on the way.
+///
+/// It can't compute inside `get_or_insert_with`, which would need the
+/// cache while it's already borrowed. Instead it checks with `get`, whose
+/// shared borrow ends with the check, works out the two values it depends
+/// on, and only then takes the mutable borrow to store the result.
+pub fn fib(cache: &mut Cache<u64, u64>, n: u64) -> u64 {
+    if let Some(&v) = cache.get(&n) {
+        return v;
+    }
+    let v = if n < 2 {
+        n
+    } else {
+        fib(cache, n - 1) + fib(cache, n - 2)
+    };
+    *cache.get_or_insert_with(n, |_| v)
+}
+
+/// Fills a cache and shows how often it was used.
+pub fn demo() {
+    let mut cache = Cache::new();
+    println!("fib(50) = {}", fib(&mut cache, 50));
+    println!(
+        "{} values cached, {} lookups computed, {} found",
+        cache.len(),
+        cache.misses(),
+        cache.hits()
+    );
+
+    let mut lengths = Cache::new();
+    for word in "the wave and the wave".split(' ') {
+        lengths.get_or_insert_with(word, |w| w.len());
+    }
+    println!(
+        "word lengths: {} computed, {} reused",
+        lengths.misses(),
+        lengths.hits()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_each_key_once() {
+        let mut cache = Cache::new();
+        let mut calls = 0;
+        for &key in &[3, 1, 3, 3, 2, 1] {
+            let v = *cache.get_or_insert_with(key, |&k| {
+                calls += 1;
+                k * 10
+            });
+            assert_eq!(v, key * 10);
+        }
+        assert_eq!(calls, 3);
+        assert_eq!((cache.misses(), cache.hits()), (3, 3));
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[test]
+    fn get_looks_without_computing() {
+        let mut cache: Cache<String, usize> = Cache::default();
+        assert!(cache.is_empty());
+        assert_eq!(cache.get("wave"), None);
+        cache.get_or_insert_with("wave".to_string(), |k| k.len());
+        assert_eq!(cache.get("wave"), Some(&4));
+        assert_eq!((cache.misses(), cache.hits()), (1, 0));
+    }
+
+    #[test]
+    fn fib_fills_the_cache() {
+        let mut cache = Cache::new();
+        assert_eq!(fib(&mut cache, 0), 0);
+        assert_eq!(fib(&mut cache, 10), 55);
+        assert_eq!(fib(&mut cache, 90), 2_880_067_194_370_816_120);
+        assert_eq!(cache.len(), 91);
+        assert_eq!(cache.misses(), 91);
+    }
+}
@@ -0,0 +1,146 @@
+//! Extending a vector with itself.
+//!
+//! [`vecops::extend`](crate::vecops::extend) pushes the elements of a slice
+//! onto a vector. Appending a vector to itself looks harmless:
+//!
+//! ```compile_fail,E0502
+//! use references_sharing_vs_mutation::vecops::extend;
+//!
+//! let mut wave = vec![0.0, 1.0, 0.0, -1.0];
+//! extend(&mut wave, &wave); // cannot borrow `wave` as immutable because it is also borrowed as mutable
+//! ```
+//!
+//! But when `wave`'s buffer is full, pushing the fifth element allocates a
+//! bigger one and frees the old, while `slice` still points into the old
+//! buffer. The bug only shows when the vector happens to be at capacity, so
+//! testing might never find it. Rust reports it at compile time: the mutable
+//! reference must be the only way to reach the vector and its elements, and
+//! the shared slice is another.
+//!
+//! Cut down to the smallest cases, shared borrows may overlap each other
+//! but not an assignment or a mutable borrow:
+//!
+//! ```compile_fail,E0506
+//! let mut x = 10;
+//! let r1 = &x;
+//! let r2 = &x; // ok, multiple shared borrows permitted
+//! x += 10; // cannot assign to `x` because it is borrowed
+//! # let _ = (r1, r2);
+//! ```
+//!
+//! ```compile_fail,E0502
+//! let mut x = 10;
+//! let r1 = &x;
+//! let m = &mut x; // cannot borrow `x` as mutable because it is also borrowed as immutable
+//! # let _ = (r1, m);
+//! ```
+//!
+//! and a mutable borrow overlaps nothing at all:
+//!
+//! ```compile_fail,E0499
+//! let mut y = 20;
+//! let m1 = &mut y;
+//! let m2 = &mut y; // cannot borrow `y` as mutable more than once at a time
+//! # let _ = (m1, m2);
+//! ```
+//!
+//! ```compile_fail,E0503
+//! let mut y = 20;
+//! let m1 = &mut y;
+//! let z = y; // cannot use `y` because it was mutably borrowed
+//! *m1 += z;
+//! ```
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::diagram::{Diagram, Target};
+use crate::tinyvec::{Realloc, TinyVec};
+use crate::vecops::{extend, extend_self};
+
+/// Builds one period of the notes' sine wave from a vector and an array.
+pub fn one_period() -> Vec<f64> {
+    let mut wave = Vec::new();
+    let head = vec![0.0, 1.0];
+    let tail = [0.0, -1.0];
+
+    extend(&mut wave, &head); // extend wave with another vector
+    extend(&mut wave, &tail); // extend wave with an array
+    wave
+}
+
+/// Appends `wave` to itself: the `extend(&mut wave, &wave)` that can't be
+/// written, done by index range so no reference is held across a push.
+pub fn repeat_period(wave: &mut Vec<f64>) {
+    let len = wave.len();
+    extend_self(wave, 0..len);
+}
+
+/// `extend(&mut wave, &wave)` as it would go if it compiled: `slice` starts
+/// out pointing at `wave`'s full buffer, and the fifth push moves the
+/// elements to a bigger one and frees the old.
+pub fn diagrams() -> Vec<Diagram> {
+    let period = ["0.0", "1.0", "0.0", "-1.0"];
+    vec![
+        Diagram::new()
+            .caption("before the fifth push")
+            .stack_var("wave")
+            .heap_buffer(4)
+            .values(&period)
+            .ptr("slice", Target::Buffer(0)),
+        Diagram::new()
+            .caption("after it reallocates: `slice` dangles")
+            .stack_var("wave")
+            .heap_buffer(8)
+            .values(&["0.0", "1.0", "0.0", "-1.0", "0.0"])
+            .heap_buffer(4)
+            .values(&period)
+            .freed()
+            .ptr("slice", Target::Buffer(1)),
+    ]
+}
+
+/// Doubles the wave in a full `TinyVec`, returning the move its fifth push
+/// makes.
+fn fifth_push() -> Realloc {
+    let moved = Rc::new(Cell::new(None));
+    let mut tiny = TinyVec::with_capacity(4);
+    tiny.extend_from_slice(&one_period());
+    let record = Rc::clone(&moved);
+    tiny.on_realloc(move |r| record.set(Some(*r)));
+    tiny.extend_from_within(0..4);
+    moved.get().expect("a full TinyVec reallocates")
+}
+
+/// The move a slice of `wave` would have been left pointing behind, found
+/// by really making it in a `TinyVec`.
+pub fn aftermath() -> String {
+    format!(
+        "Had it compiled, `slice` would still point at the old address after the fifth push: {}",
+        fifth_push()
+    )
+}
+
+/// Builds the wave, then doubles it the way the borrow checker accepts,
+/// and again in a `TinyVec` to show the move a slice would have been left
+/// pointing behind.
+pub fn demo() {
+    let mut wave = one_period();
+    println!("one period: {:?}", wave);
+    repeat_period(&mut wave);
+    println!("two periods: {:?}", wave);
+    println!("the fifth push: {}", fifth_push());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_and_repeats_the_wave() {
+        let mut wave = one_period();
+        assert_eq!(wave, vec![0.0, 1.0, 0.0, -1.0]);
+        repeat_period(&mut wave);
+        assert_eq!(wave, vec![0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0]);
+    }
+}
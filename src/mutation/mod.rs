@@ -0,0 +1,19 @@
+//! The second rule: mutable access is exclusive access.
+//!
+//! A value borrowed by a mutable reference is reachable only through that
+//! reference. Across its lifetime there is no other usable path to the
+//! referent or to anything reachable from it. The only references whose
+//! lifetimes may overlap a mutable reference are those borrowed from it.
+//!
+//! Two classic C++ bugs, using an invalidated iterator and failing to cope
+//! with self-assignment, are the same mistake underneath: code assumes it is
+//! modifying one value while consulting another, when both are the same
+//! value. [`exclusive`] shows the first and [`self_assignment`] the second;
+//! [`iterinv`](crate::iterinv) has the safe ways to edit while iterating.
+//!
+//! The same rule is what rules out data races. A race needs a value that is
+//! both mutable and shared between threads, which is exactly what safe
+//! Rust's references can't express.
+
+pub mod exclusive;
+pub mod self_assignment;
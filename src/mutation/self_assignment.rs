@@ -0,0 +1,94 @@
+//! Assigning a file to itself.
+//!
+//! A C++ class managing a file descriptor might write its copying
+//! assignment operator like this (error handling left out):
+//!
+//! ```c++
+//! struct File {
+//!     int descriptor;
+//!
+//!     File(int d) : descriptor(d) {}
+//!
+//!     File& operator=(const File &rhs) {
+//!         close(descriptor);
+//!         descriptor = dup(rhs.descriptor);
+//!     }
+//! };
+//! ```
+//!
+//! After `f = f`, `rhs` and `*this` are the same object, so the operator
+//! closes the very descriptor it is about to `dup`, destroying the resource
+//! it was meant to copy. The same shape in Rust is
+//! `clone_from(&mut f, &f)`, and it doesn't get past the borrow checker:
+//!
+//! ```compile_fail,E0502
+//! use references_sharing_vs_mutation::fd::File;
+//!
+//! let mut f = File::open("/dev/null").unwrap();
+//! f.clone_from(&f); // cannot borrow `f` as mutable because it is also borrowed as immutable
+//! ```
+//!
+//! [`fd::File`](crate::fd::File) is the idiomatic version of the struct:
+//! `File::open` stands in for the constructor and `Clone` does the `dup`.
+//! Built with the `cpp_compare` feature, the `cpp_compare` module runs the
+//! operator, in C, against a pretend descriptor table.
+
+#[cfg(unix)]
+use std::io;
+
+use crate::diagram::{Diagram, Target};
+#[cfg(unix)]
+use crate::fd::File;
+
+/// Opens `/dev/null`, clones it and assigns the clone back over the
+/// original, returning the descriptor `f` ends up with.
+///
+/// `clone_from` receives two distinct files here, as the borrow checker
+/// guarantees it always will.
+#[cfg(unix)]
+pub fn clone_into_open_file() -> io::Result<File> {
+    let mut f = File::open("/dev/null")?;
+    let g = f.try_clone()?;
+    f.clone_from(&g);
+    Ok(f)
+}
+
+/// `clone_from(&mut f, &f)`: both parameters would reach the same file, and
+/// the `close` through `this` would take `rhs`'s descriptor with it.
+pub fn diagrams() -> Vec<Diagram> {
+    vec![Diagram::new()
+        .caption("rejected: `this` and `rhs` are the same file")
+        .stack_var("f")
+        .value("File { fd: 3 }")
+        .mut_ptr("this", Target::Var("f"))
+        .ptr("rhs", Target::Var("f"))]
+}
+
+/// Runs the assignment that Rust allows.
+#[cfg(unix)]
+pub fn demo() {
+    match clone_into_open_file() {
+        Ok(f) => println!("clone_from left f as {:?}", f),
+        Err(e) => println!("couldn't open /dev/null: {}", e),
+    }
+}
+
+/// Says why there's nothing to run: `fd::File` needs a Unix descriptor
+/// table.
+#[cfg(not(unix))]
+pub fn demo() {
+    println!("the demo opens /dev/null, so it only runs on Unix");
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::io::AsRawFd;
+
+    use super::*;
+
+    #[test]
+    fn assigns_a_distinct_clone() {
+        let f = clone_into_open_file().unwrap();
+        assert!(f.as_raw_fd() >= 0);
+    }
+}
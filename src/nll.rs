@@ -0,0 +1,175 @@
+//! Non-lexical lifetimes: a borrow lasts until its last use, not until the
+//! end of its scope.
+//!
+//! [`sharing::dangling`](crate::sharing::dangling) fixes its program by
+//! putting `r` in an inner block, which is how it had to be done before
+//! Rust 2018. Since then a reference's lifetime ends where it's last used,
+//! so most of the crate's rejected programs are fixed just by using the
+//! reference earlier. Each case below is a pair: the rejected program, and
+//! the same lines with one use moved, which compiles.
+//!
+//! Moving `v` while `r` is still to be read, then reading `r` before the
+//! move instead, with no block needed:
+//!
+//! ```compile_fail,E0505
+//! let v = vec![4, 8, 19, 27, 34, 10];
+//! let r = &v;
+//! let aside = v; // cannot move out of `v` because it is borrowed
+//! println!("{} {:?}", r[0], aside);
+//! ```
+//!
+//! ```
+//! let v = vec![4, 8, 19, 27, 34, 10];
+//! let r = &v;
+//! let first = r[0]; // `r`'s last use
+//! let aside = v;
+//! println!("{} {:?}", first, aside);
+//! ```
+//!
+//! Two mutable borrows of `y`, then the first finished before the second
+//! starts:
+//!
+//! ```compile_fail,E0499
+//! let mut y = 20;
+//! let m1 = &mut y;
+//! let m2 = &mut y; // cannot borrow `y` as mutable more than once at a time
+//! *m1 += 1;
+//! *m2 += 1;
+//! ```
+//!
+//! ```
+//! let mut y = 20;
+//! let m1 = &mut y;
+//! *m1 += 1; // `m1`'s last use
+//! let m2 = &mut y;
+//! *m2 += 1;
+//! ```
+//!
+//! Assigning to `x` while shared borrows of it are still to be read, then
+//! reading them first:
+//!
+//! ```compile_fail,E0506
+//! let mut x = 10;
+//! let r1 = &x;
+//! let r2 = &x;
+//! x += 10; // cannot assign to `x` because it is borrowed
+//! println!("{} {}", r1, r2);
+//! ```
+//!
+//! ```
+//! let mut x = 10;
+//! let r1 = &x;
+//! let r2 = &x;
+//! println!("{} {}", r1, r2); // their last use
+//! x += 10;
+//! # assert_eq!(x, 20);
+//! ```
+//!
+//! The last use isn't always one you can see. A value whose type has a
+//! destructor uses whatever it borrows when it's dropped, at the end of its
+//! scope, so a borrow held by one lasts that long:
+//!
+//! ```compile_fail,E0502
+//! use references_sharing_vs_mutation::nll::LastLook;
+//!
+//! let mut v = vec![1, 2];
+//! let _look = LastLook(&v);
+//! v.push(3); // cannot borrow `v` as mutable because it is also borrowed as immutable
+//! ```
+//!
+//! Dropping it by hand makes the last use visible, and earlier:
+//!
+//! ```
+//! use references_sharing_vs_mutation::nll::LastLook;
+//!
+//! let mut v = vec![1, 2];
+//! let look = LastLook(&v);
+//! drop(look);
+//! v.push(3);
+//! ```
+
+use std::fmt;
+
+/// Reads the first element through a reference, then moves the vector
+/// away, with no block around the reference. Returns the element and
+/// the moved vector.
+pub fn read_then_move(v: Vec<i32>) -> (Option<i32>, Vec<i32>) {
+    let r = &v;
+    let first = r.first().copied();
+    let aside = v;
+    (first, aside)
+}
+
+/// Bumps `y` through one mutable reference and then another, the first
+/// finished before the second is taken.
+pub fn one_mutable_at_a_time(y: &mut i32) {
+    let m1 = &mut *y;
+    *m1 += 1;
+    let m2 = &mut *y;
+    *m2 += 1;
+}
+
+/// Reads `x` through two shared references, then assigns to it once
+/// they're done. Returns what they read.
+pub fn read_then_assign(x: &mut i32) -> (i32, i32) {
+    let r1 = &*x;
+    let r2 = &*x;
+    let seen = (*r1, *r2);
+    *x += 10;
+    seen
+}
+
+/// Prints what it borrows when it's dropped, so the borrow lasts until
+/// then instead of ending at its last visible use.
+pub struct LastLook<'a, T: fmt::Debug>(pub &'a T);
+
+impl<T: fmt::Debug> Drop for LastLook<'_, T> {
+    fn drop(&mut self) {
+        println!("last look: {:?}", self.0);
+    }
+}
+
+/// Runs each fixed program.
+pub fn demo() {
+    let (first, aside) = read_then_move(vec![4, 8, 19, 27, 34, 10]);
+    println!("read {:?}, then moved {:?}", first, aside);
+
+    let mut y = 20;
+    one_mutable_at_a_time(&mut y);
+    println!("y bumped twice: {}", y);
+
+    let mut x = 10;
+    let seen = read_then_assign(&mut x);
+    println!("read {:?}, then assigned {}", seen, x);
+
+    let mut v = vec![1, 2];
+    let look = LastLook(&v);
+    drop(look);
+    v.push(3);
+    println!("pushed after the look: {:?}", v);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_before_moving() {
+        assert_eq!(read_then_move(vec![4, 8]), (Some(4), vec![4, 8]));
+        assert_eq!(read_then_move(Vec::new()), (None, Vec::new()));
+    }
+
+    #[test]
+    fn takes_mutable_borrows_in_turn() {
+        let mut y = 20;
+        one_mutable_at_a_time(&mut y);
+        assert_eq!(y, 22);
+    }
+
+    #[test]
+    fn reads_before_assigning() {
+        let mut x = 10;
+        assert_eq!(read_then_assign(&mut x), (10, 10));
+        assert_eq!(x, 20);
+    }
+}
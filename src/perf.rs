@@ -0,0 +1,311 @@
+//! What following the borrow rules costs at run time, measured.
+//!
+//! The notes claim the rules lead to simpler code that's no slower, and
+//! often faster, than the aliased versions other languages allow. Each of
+//! the [`COMPARISONS`] sets a version the rules push towards beside the
+//! one people write to get around them, or that another language would
+//! let them write:
+//!
+//! - walking a slice with `iter_mut` against indexing it in a loop, which
+//!   is how code that mutates while iterating often ends up;
+//! - borrowing a struct's fields separately, as `split` shows, against
+//!   cloning one of them so a method on all of `self` can be called in
+//!   the loop, the usual first way past the error;
+//! - a plain `&mut` against a `RefCell`, which checks at run time what
+//!   the compiler would otherwise have checked for free;
+//! - an `AtomicU64` against a `Mutex<u64>` for a shared counter, uncontended.
+//!
+//! [`report`] times each pair in-process and prints how they compare;
+//! it's what `perf report` shows. The numbers are rough, the best of a few
+//! runs; `cargo bench --bench aliasing` measures the same functions
+//! carefully with criterion.
+//!
+//! Expect the iterator and the index loop to be within noise of each
+//! other, the bounds checks being optimised out, and the split borrow to
+//! beat the clone by the cost of copying the vector. The `RefCell` costs a
+//! flag check and write per borrow, a little over the `&mut`; the `Mutex`,
+//! even uncontended, costs noticeably more than the atomic.
+
+use std::cell::RefCell;
+use std::fmt::Write as _;
+use std::hint::black_box;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Multiplies every element by `k`, by index.
+pub fn scale_indexed(v: &mut [f64], k: f64) {
+    #[allow(clippy::needless_range_loop)]
+    for i in 0..v.len() {
+        v[i] *= k;
+    }
+}
+
+/// Multiplies every element by `k`, through `iter_mut`.
+pub fn scale_iter(v: &mut [f64], k: f64) {
+    for x in v.iter_mut() {
+        *x *= k;
+    }
+}
+
+/// Amounts, and the running total after each.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Ledger {
+    pub entries: Vec<u64>,
+    pub totals: Vec<u64>,
+}
+
+impl Ledger {
+    fn record(&mut self, total: u64) {
+        self.totals.push(total);
+    }
+
+    /// Recomputes `totals`, calling `record` in the loop. A method call
+    /// borrows all of `self`, so the loop walks a clone of `entries`.
+    pub fn totals_cloning(&mut self) {
+        self.totals.clear();
+        let mut sum = 0;
+        for entry in self.entries.clone() {
+            sum += entry;
+            self.record(sum);
+        }
+    }
+
+    /// Recomputes `totals`, borrowing the two fields separately.
+    pub fn totals_split(&mut self) {
+        self.totals.clear();
+        let totals = &mut self.totals;
+        let mut sum = 0;
+        for entry in &self.entries {
+            sum += entry;
+            totals.push(sum);
+        }
+    }
+}
+
+/// Adds each of `values` to `total`, through a plain `&mut`.
+pub fn add_mut(total: &mut u64, values: &[u64]) {
+    for v in values {
+        *total += v;
+    }
+}
+
+/// Adds each of `values` to `total`, borrowing it from the `RefCell` for
+/// each.
+pub fn add_refcell(total: &RefCell<u64>, values: &[u64]) {
+    for v in values {
+        *total.borrow_mut() += v;
+    }
+}
+
+/// Adds each of `values` to `total` atomically.
+pub fn add_atomic(total: &AtomicU64, values: &[u64]) {
+    for v in values {
+        total.fetch_add(*v, Ordering::Relaxed);
+    }
+}
+
+/// Adds each of `values` to `total`, taking the lock for each.
+pub fn add_mutex(total: &Mutex<u64>, values: &[u64]) {
+    for v in values {
+        *total.lock().unwrap() += v;
+    }
+}
+
+/// How many values each comparison runs over.
+pub const LEN: usize = 10_000;
+
+/// What each version runs on, made fresh for each comparison.
+struct Inputs {
+    floats: Vec<f64>,
+    values: Vec<u64>,
+    ledger: Ledger,
+    total: u64,
+    cell: RefCell<u64>,
+    atomic: AtomicU64,
+    mutex: Mutex<u64>,
+}
+
+impl Inputs {
+    fn new() -> Inputs {
+        let values: Vec<u64> = (0..LEN as u64).map(|i| (i * 7919) % 1000).collect();
+        Inputs {
+            floats: values.iter().map(|&v| v as f64).collect(),
+            ledger: Ledger {
+                entries: values.clone(),
+                totals: Vec::with_capacity(LEN),
+            },
+            values,
+            total: 0,
+            cell: RefCell::new(0),
+            atomic: AtomicU64::new(0),
+            mutex: Mutex::new(0),
+        }
+    }
+}
+
+/// A pattern the rules lead to, beside the alternative.
+pub struct Comparison {
+    pub name: &'static str,
+    /// The versions' names: the one the rules lead to first.
+    pub versions: [&'static str; 2],
+    runs: [fn(&mut Inputs); 2],
+}
+
+pub static COMPARISONS: &[Comparison] = &[
+    Comparison {
+        name: "index loop vs iterator",
+        versions: ["iter_mut", "index loop"],
+        runs: [
+            |i| scale_iter(&mut i.floats, black_box(1.0)),
+            |i| scale_indexed(&mut i.floats, black_box(1.0)),
+        ],
+    },
+    Comparison {
+        name: "clone to appease the borrow checker vs split borrows",
+        versions: ["split borrows", "clone"],
+        runs: [|i| i.ledger.totals_split(), |i| i.ledger.totals_cloning()],
+    },
+    Comparison {
+        name: "RefCell vs &mut",
+        versions: ["&mut", "RefCell"],
+        runs: [
+            |i| add_mut(&mut i.total, &i.values),
+            |i| add_refcell(&i.cell, &i.values),
+        ],
+    },
+    Comparison {
+        name: "Mutex vs atomic",
+        versions: ["AtomicU64", "Mutex"],
+        runs: [
+            |i| add_atomic(&i.atomic, &i.values),
+            |i| add_mutex(&i.mutex, &i.values),
+        ],
+    },
+];
+
+impl Comparison {
+    /// The best of `rounds` runs of each version, after one to warm up.
+    pub fn time(&self, rounds: u32) -> [Duration; 2] {
+        let mut inputs = Inputs::new();
+        let mut best = [Duration::MAX; 2];
+        for (run, best) in self.runs.iter().zip(&mut best) {
+            run(&mut inputs);
+            for _ in 0..rounds {
+                let start = Instant::now();
+                run(black_box(&mut inputs));
+                *best = (*best).min(start.elapsed());
+            }
+        }
+        best
+    }
+}
+
+/// Every comparison timed over `rounds` runs, as a table of nanoseconds
+/// per value and how the second version compares with the first.
+pub fn report(rounds: u32) -> String {
+    let mut out = format!(
+        "Each over {} values, best of {} runs (`cargo bench --bench aliasing` \
+measures them carefully):\n",
+        LEN, rounds
+    );
+    for comparison in COMPARISONS {
+        let times = comparison.time(rounds);
+        let per_value = |t: Duration| t.as_nanos() as f64 / LEN as f64;
+        let _ = writeln!(out, "\n{}", comparison.name);
+        let _ = writeln!(
+            out,
+            "  {:<16} {:>8.2} ns/value",
+            comparison.versions[0],
+            per_value(times[0])
+        );
+        let ratio = times[1].as_secs_f64() / times[0].as_secs_f64().max(f64::MIN_POSITIVE);
+        let _ = writeln!(
+            out,
+            "  {:<16} {:>8.2} ns/value  {:.2}x",
+            comparison.versions[1],
+            per_value(times[1]),
+            ratio
+        );
+    }
+    out
+}
+
+/// Runs both versions of each comparison and shows they agree; `perf
+/// report` times them.
+pub fn demo() {
+    let mut a = vec![1.0, 2.0, 3.0];
+    let mut b = a.clone();
+    scale_iter(&mut a, 2.0);
+    scale_indexed(&mut b, 2.0);
+    println!("scaled by iterator {:?}, by index {:?}", a, b);
+
+    let mut ledger = Ledger {
+        entries: vec![3, 1, 4, 1, 5],
+        totals: Vec::new(),
+    };
+    ledger.totals_split();
+    let split = ledger.totals.clone();
+    ledger.totals_cloning();
+    println!("totals split {:?}, cloned {:?}", split, ledger.totals);
+
+    let values = [3, 1, 4];
+    let mut plain = 0;
+    add_mut(&mut plain, &values);
+    let cell = RefCell::new(0);
+    add_refcell(&cell, &values);
+    let atomic = AtomicU64::new(0);
+    add_atomic(&atomic, &values);
+    let mutex = Mutex::new(0);
+    add_mutex(&mutex, &values);
+    println!(
+        "sums: &mut {}, RefCell {}, atomic {}, Mutex {}",
+        plain,
+        cell.into_inner(),
+        atomic.into_inner(),
+        mutex.into_inner().unwrap()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_agree() {
+        let mut inputs = Inputs::new();
+        let mut floats = inputs.floats.clone();
+        scale_indexed(&mut floats, 0.5);
+        scale_iter(&mut inputs.floats, 0.5);
+        assert_eq!(floats, inputs.floats);
+
+        inputs.ledger.totals_split();
+        let split = inputs.ledger.totals.clone();
+        inputs.ledger.totals_cloning();
+        assert_eq!(split, inputs.ledger.totals);
+        assert_eq!(split.len(), LEN);
+
+        let sum: u64 = inputs.values.iter().sum();
+        add_mut(&mut inputs.total, &inputs.values);
+        add_refcell(&inputs.cell, &inputs.values);
+        add_atomic(&inputs.atomic, &inputs.values);
+        add_mutex(&inputs.mutex, &inputs.values);
+        assert_eq!(inputs.total, sum);
+        assert_eq!(*inputs.cell.borrow(), sum);
+        assert_eq!(inputs.atomic.load(Ordering::Relaxed), sum);
+        assert_eq!(*inputs.mutex.lock().unwrap(), sum);
+    }
+
+    #[test]
+    fn reports_every_comparison() {
+        let report = report(1);
+        assert!(report.starts_with("Each over 10000 values, best of 1 runs"));
+        for comparison in COMPARISONS {
+            assert!(report.contains(&format!("\n{}\n", comparison.name)));
+            for version in &comparison.versions {
+                assert!(report.contains(&format!("\n  {:<16} ", version)));
+            }
+        }
+        assert_eq!(report.matches("ns/value").count(), 2 * COMPARISONS.len());
+    }
+}
@@ -0,0 +1,168 @@
+//! Links that open a snippet in the Rust Playground.
+//!
+//! A link can carry the code itself, percent-encoded into the query
+//! string, which needs nothing but the code and works offline; or it can
+//! name a GitHub gist holding it, which keeps the link short but means
+//! uploading the code first with a GitHub token. The playground builds
+//! with its own set of popular crates, which doesn't include this one, so
+//! snippets that `use` the library won't build there.
+//!
+//! ```
+//! use references_sharing_vs_mutation::playground;
+//!
+//! assert_eq!(
+//!     playground::code_url("fn main() {}\n"),
+//!     "https://play.rust-lang.org/?version=stable&mode=debug&edition=2021\
+//!      &code=fn%20main%28%29%20%7B%7D%0A"
+//! );
+//! ```
+
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+const PLAYGROUND: &str = "https://play.rust-lang.org/?version=stable&mode=debug&edition=2021";
+const GISTS_API: &str = "https://api.github.com/gists";
+
+/// A playground link with `code` in it.
+pub fn code_url(code: &str) -> String {
+    format!("{}&code={}", PLAYGROUND, percent_encode(code))
+}
+
+/// A playground link to the gist with id `gist`.
+pub fn gist_url(gist: &str) -> String {
+    format!("{}&gist={}", PLAYGROUND, percent_encode(gist))
+}
+
+/// Uploads `code` as a secret gist, described by `description`, and
+/// returns the gist's id for [`gist_url`].
+///
+/// `token` is a GitHub token allowed to create gists. The upload runs
+/// `curl`, which must be on `PATH`, and passes it the token on stdin rather
+/// than the command line, where other users could see it.
+pub fn upload_gist(code: &str, description: &str, token: &str) -> io::Result<String> {
+    let body = format!(
+        "{{\"description\":{},\"public\":false,\"files\":{{\"main.rs\":{{\"content\":{}}}}}}}",
+        json_string(description),
+        json_string(code)
+    );
+    let config = format!(
+        "url = {}\noauth2-bearer = {}\nheader = \"Accept: application/vnd.github+json\"\n\
+         user-agent = \"references-sharing-vs-mutation\"\ndata-binary = {}\n\
+         silent\nshow-error\nfail\n",
+        curl_string(GISTS_API),
+        curl_string(token),
+        curl_string(&body)
+    );
+    let mut curl = Command::new("curl")
+        .args(["--config", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // Taking stdin closes it once written, so curl sees the end of its
+    // config.
+    curl.stdin.take().unwrap().write_all(config.as_bytes())?;
+    let output = curl.wait_with_output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!(
+            "uploading the gist failed: {}",
+            stderr.trim()
+        )));
+    }
+    let response = String::from_utf8_lossy(&output.stdout);
+    gist_id(&response).map(str::to_owned).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "GitHub's response had no gist id",
+        )
+    })
+}
+
+/// Percent-encodes everything in `s` except the characters RFC 3986
+/// leaves unreserved.
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for &b in s.as_bytes() {
+        if b.is_ascii_alphanumeric() || b"-_.~".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
+
+/// `s` as a JSON string literal.
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// `s` as a quoted value in a curl config file, which understands `\\` and
+/// `\"` and would otherwise end the value at a newline.
+fn curl_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The value of the first `"id"` key with a string value in `json`: the
+/// gist's own, which GitHub puts before the owner's numeric one.
+fn gist_id(json: &str) -> Option<&str> {
+    json.match_indices("\"id\"").find_map(|(at, key)| {
+        let rest = json[at + key.len()..].trim_start().strip_prefix(':')?;
+        let rest = rest.trim_start().strip_prefix('"')?;
+        rest.split('"').next()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_all_but_unreserved() {
+        assert_eq!(percent_encode("a-b_c.d~e"), "a-b_c.d~e");
+        assert_eq!(percent_encode("&v[0] = 1;"), "%26v%5B0%5D%20%3D%201%3B");
+        assert_eq!(percent_encode("é\n"), "%C3%A9%0A");
+    }
+
+    #[test]
+    fn builds_links() {
+        assert!(code_url("let x = &mut v;").ends_with("&code=let%20x%20%3D%20%26mut%20v%3B"));
+        assert_eq!(
+            gist_url("abc123"),
+            "https://play.rust-lang.org/?version=stable&mode=debug&edition=2021&gist=abc123"
+        );
+    }
+
+    #[test]
+    fn quotes_for_json_and_curl() {
+        assert_eq!(
+            json_string("println!(\"{}\\n\", x);\n\tx"),
+            r#""println!(\"{}\\n\", x);\n\tx""#
+        );
+        assert_eq!(json_string("\u{1}"), r#""\u0001""#);
+        assert_eq!(curl_string(r#"{"a":"\n"}"#), r#""{\"a\":\"\\n\"}""#);
+    }
+
+    #[test]
+    fn finds_the_gist_id() {
+        let response = r#"{"url":"https://api.github.com/gists/aa5a3","id": "aa5a3",
+            "node_id":"G_1","owner":{"login":"someone","id":1}}"#;
+        assert_eq!(gist_id(response), Some("aa5a3"));
+        assert_eq!(gist_id(r#"{"owner":{"id":1}}"#), None);
+        assert_eq!(gist_id("not json"), None);
+    }
+}
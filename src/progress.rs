@@ -0,0 +1,314 @@
+//! Which lessons a learner has finished and how their quizzes went, kept
+//! on disk between runs.
+//!
+//! The file is a small TOML document under the platform's data directory
+//! (see [`default_path`]):
+//!
+//! ```toml
+//! completed = ["dangling", "reborrow"]
+//!
+//! [quiz.dangling]
+//! right = 1
+//! asked = 2
+//! ```
+//!
+//! Only that shape is read back; it's written by [`Progress::save`] and not
+//! meant for editing by hand, though a hand-edited file that keeps the
+//! shape works.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::cli::{Lesson, Score, LESSONS};
+use crate::fd::atomic_write;
+
+/// Where progress is kept: `references-sharing-vs-mutation/progress.toml`
+/// under `$XDG_DATA_HOME` (or `~/.local/share`), or under
+/// `~/Library/Application Support` on macOS. `None` if neither variable
+/// the path depends on is set.
+pub fn default_path() -> Option<PathBuf> {
+    let base = if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| Path::new(&home).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+    };
+    base.map(|dir| dir.join("references-sharing-vs-mutation/progress.toml"))
+}
+
+/// Lessons finished and the latest quiz score for each lesson.
+#[derive(Debug, Default, PartialEq)]
+pub struct Progress {
+    path: Option<PathBuf>,
+    completed: BTreeSet<String>,
+    quiz: BTreeMap<String, Score>,
+}
+
+impl Progress {
+    /// Reads progress from `path`, starting afresh if there's no file yet.
+    /// `save` writes back to the same place.
+    pub fn load(path: &Path) -> io::Result<Progress> {
+        let mut progress = match fs::read_to_string(path) {
+            Ok(text) => Progress::parse(&text)?,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Progress::default(),
+            Err(e) => return Err(e),
+        };
+        progress.path = Some(path.to_owned());
+        Ok(progress)
+    }
+
+    /// Progress with nothing done yet that saves to `path`, whatever is
+    /// there now. Loading it first would fail on a file that's corrupt.
+    pub fn empty_at(path: &Path) -> Progress {
+        Progress {
+            path: Some(path.to_owned()),
+            ..Progress::default()
+        }
+    }
+
+    /// Writes progress back to the file it was loaded from, replacing it
+    /// atomically and creating its directory if need be. Progress that
+    /// wasn't loaded from a file isn't saved anywhere.
+    pub fn save(&self) -> io::Result<()> {
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return Ok(()),
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        atomic_write(path, self.to_toml().as_bytes())
+    }
+
+    /// Forgets everything, keeping the file it saves to.
+    pub fn reset(&mut self) {
+        self.completed.clear();
+        self.quiz.clear();
+    }
+
+    pub fn complete(&mut self, lesson: &str) {
+        self.completed.insert(lesson.to_owned());
+    }
+
+    pub fn is_complete(&self, lesson: &str) -> bool {
+        self.completed.contains(lesson)
+    }
+
+    /// Records `score` as the latest quiz result for `lesson`.
+    pub fn record_quiz(&mut self, lesson: &str, score: Score) {
+        self.quiz.insert(lesson.to_owned(), score);
+    }
+
+    pub fn quiz_score(&self, lesson: &str) -> Option<Score> {
+        self.quiz.get(lesson).copied()
+    }
+
+    /// Where to pick the tour back up: the index in `LESSONS` of the first
+    /// lesson not yet completed, or `None` once they all are.
+    pub fn resume_at(&self) -> Option<usize> {
+        LESSONS.iter().position(|l| !self.is_complete(l.name))
+    }
+
+    /// Each chapter in lesson order, with how many of its lessons are
+    /// complete and how many it has.
+    pub fn chapters(&self) -> Vec<(&'static str, usize, usize)> {
+        let mut chapters: Vec<(&'static str, usize, usize)> = Vec::new();
+        for lesson in LESSONS {
+            let done = self.is_complete(lesson.name) as usize;
+            match chapters
+                .iter_mut()
+                .find(|(name, _, _)| *name == lesson.chapter)
+            {
+                Some(entry) => {
+                    entry.1 += done;
+                    entry.2 += 1;
+                }
+                None => chapters.push((lesson.chapter, done, 1)),
+            }
+        }
+        chapters
+    }
+
+    /// A summary for `progress show`: completion per chapter, then quiz
+    /// scores, then where the tour would resume.
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        for (chapter, done, total) in self.chapters() {
+            let _ = writeln!(
+                out,
+                "{:<10} {}/{} lessons  {:>3}%",
+                chapter,
+                done,
+                total,
+                done * 100 / total
+            );
+        }
+        let scored: Vec<(&Lesson, Score)> = LESSONS
+            .iter()
+            .filter_map(|l| self.quiz_score(l.name).map(|s| (l, s)))
+            .collect();
+        if !scored.is_empty() {
+            out.push_str("\nquiz scores:\n");
+            for (lesson, score) in scored {
+                let _ = writeln!(out, "  {:<16} {}/{}", lesson.name, score.right, score.asked);
+            }
+        }
+        match self.resume_at() {
+            Some(i) => {
+                let _ = writeln!(out, "\nthe tour resumes at `{}`", LESSONS[i].name);
+            }
+            None => out.push_str("\nevery lesson is complete\n"),
+        }
+        out
+    }
+
+    fn to_toml(&self) -> String {
+        let names: Vec<String> = self.completed.iter().map(|n| format!("{:?}", n)).collect();
+        let mut out = format!("completed = [{}]\n", names.join(", "));
+        for (lesson, score) in &self.quiz {
+            let _ = write!(
+                out,
+                "\n[quiz.{}]\nright = {}\nasked = {}\n",
+                lesson, score.right, score.asked
+            );
+        }
+        out
+    }
+
+    fn parse(text: &str) -> io::Result<Progress> {
+        let mut progress = Progress::default();
+        let mut table: Option<String> = None;
+        for (n, raw) in text.lines().enumerate() {
+            let bad = |what: &str| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("progress file line {}: {}", n + 1, what),
+                )
+            };
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let lesson = header
+                    .trim()
+                    .strip_prefix("quiz.")
+                    .ok_or_else(|| bad("unknown table"))?;
+                progress.quiz.entry(lesson.to_owned()).or_default();
+                table = Some(lesson.to_owned());
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| bad("expected `key = value`"))?;
+            let (key, value) = (key.trim(), value.trim());
+            match (table.as_deref(), key) {
+                (None, "completed") => {
+                    let items = value
+                        .strip_prefix('[')
+                        .and_then(|v| v.strip_suffix(']'))
+                        .ok_or_else(|| bad("`completed` should be a list"))?;
+                    for item in items.split(',').map(str::trim).filter(|i| !i.is_empty()) {
+                        let name = item
+                            .strip_prefix('"')
+                            .and_then(|i| i.strip_suffix('"'))
+                            .ok_or_else(|| bad("lesson names should be quoted"))?;
+                        progress.complete(name);
+                    }
+                }
+                (Some(lesson), "right") | (Some(lesson), "asked") => {
+                    let count = value.parse().map_err(|_| bad("expected a number"))?;
+                    let score = progress.quiz.get_mut(lesson).unwrap();
+                    if key == "right" {
+                        score.right = count;
+                    } else {
+                        score.asked = count;
+                    }
+                }
+                _ => return Err(bad(&format!("unknown key `{}`", key))),
+            }
+        }
+        Ok(progress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn round_trips_through_the_file() {
+        let dir = TempDir::new();
+        let path = dir.join("data/progress.toml");
+
+        let mut progress = Progress::load(&path).unwrap();
+        assert_eq!(progress.resume_at(), Some(0));
+        progress.complete("dangling");
+        progress.complete("reborrow");
+        progress.record_quiz("dangling", Score { right: 1, asked: 2 });
+        progress.save().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "completed = [\"dangling\", \"reborrow\"]\n\n[quiz.dangling]\nright = 1\nasked = 2\n"
+        );
+        let loaded = Progress::load(&path).unwrap();
+        assert_eq!(loaded, progress);
+        assert_eq!(loaded.resume_at(), Some(2));
+    }
+
+    #[test]
+    fn empty_progress_replaces_a_corrupt_file() {
+        let dir = TempDir::new();
+        let path = dir.join("progress.toml");
+        fs::write(&path, "completed = [\"dangling\"\n").unwrap();
+        assert!(Progress::load(&path).is_err());
+
+        Progress::empty_at(&path).save().unwrap();
+        assert_eq!(Progress::load(&path).unwrap(), Progress::empty_at(&path));
+    }
+
+    #[test]
+    fn reports_chapters_and_scores() {
+        let mut progress = Progress::default();
+        progress.complete("dangling");
+        progress.record_quiz("exclusive", Score { right: 3, asked: 3 });
+
+        let chapters = progress.chapters();
+        assert_eq!(chapters[0], ("sharing", 1, 2));
+        assert_eq!(chapters[1], ("mutation", 0, 3));
+        let report = progress.report();
+        assert!(report.starts_with("sharing    1/2 lessons   50%\n"));
+        assert!(report.contains("  exclusive        3/3\n"));
+        assert!(report.ends_with("the tour resumes at `reborrow`\n"));
+
+        for lesson in LESSONS {
+            progress.complete(lesson.name);
+        }
+        assert_eq!(progress.resume_at(), None);
+        progress.reset();
+        assert_eq!(progress.resume_at(), Some(0));
+        assert_eq!(progress.quiz_score("exclusive"), None);
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        let err = Progress::parse("completed = [\"a\"]\nlevel = 3\n").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "progress file line 2: unknown key `level`");
+        assert!(Progress::parse("[quiz.a]\nright = lots\n").is_err());
+        assert!(Progress::parse("completed = [a]\n").is_err());
+    }
+
+    #[test]
+    fn unsaved_progress_goes_nowhere() {
+        Progress::default().save().unwrap();
+    }
+}
@@ -0,0 +1,243 @@
+//! `Rc` and `Arc`: values with more than one owner.
+//!
+//! Everything before this module has one owner per value and references
+//! borrowed from it. When no single owner outlives all the users, `Rc`
+//! shares ownership: each clone is another owner, and the value is dropped
+//! with the last of them. Being shared, what an `Rc` points at is read-only,
+//! by the same rule as a shared reference:
+//!
+//! ```compile_fail,E0596
+//! use std::rc::Rc;
+//!
+//! let wave = Rc::new(vec![0.0, 1.0]);
+//! wave.push(0.0); // cannot borrow data in an `Rc` as mutable
+//! ```
+//!
+//! Mutation comes back three ways. [`Rc::get_mut`] gives a `&mut` only
+//! while there's exactly one owner, so it's never an alias. [`Rc::make_mut`]
+//! clones the value first if there are others, so the writer gets a copy of
+//! its own. And for state that really is shared and mutable, the `Rc` holds
+//! a `RefCell`, or across threads an `Arc` holds a `Mutex`, and the rules
+//! are checked at run time as in `interior`.
+//!
+//! Counting owners can't free a cycle: two values that own each other keep
+//! each other's counts above zero forever. A `Weak` pointer refers without
+//! owning, so a child pointing back at its parent through one doesn't keep
+//! the parent alive.
+
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Shares one wave between `owners` owners and returns how many the count
+/// says there are. Every clone points at the same vector.
+pub fn share_wave(owners: usize) -> usize {
+    let wave = Rc::new(vec![0.0, 1.0, 0.0, -1.0]);
+    let clones: Vec<Rc<Vec<f64>>> = (1..owners).map(|_| Rc::clone(&wave)).collect();
+    assert!(clones.iter().all(|c| Rc::ptr_eq(c, &wave)));
+    Rc::strong_count(&wave)
+}
+
+/// Tries to push onto `wave` through `Rc::get_mut`, which only succeeds
+/// while `wave` is the only owner. Returns whether it did.
+pub fn push_if_unique(wave: &mut Rc<Vec<f64>>, x: f64) -> bool {
+    match Rc::get_mut(wave) {
+        Some(v) => {
+            v.push(x);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Pushes onto `wave` through `Rc::make_mut`, which first gives `wave` a
+/// copy of its own if anyone else owns the vector. Returns whether it had
+/// to copy.
+pub fn push_on_write(wave: &mut Rc<Vec<f64>>, x: f64) -> bool {
+    let before = Rc::as_ptr(wave);
+    Rc::make_mut(wave).push(x);
+    before != Rc::as_ptr(wave)
+}
+
+/// A tree node that owns its children and can find its parent.
+#[derive(Debug)]
+pub struct Node {
+    pub value: i32,
+    /// Weak, so a child doesn't keep its parent alive.
+    pub parent: RefCell<Weak<Node>>,
+    pub children: RefCell<Vec<Rc<Node>>>,
+}
+
+impl Node {
+    pub fn new(value: i32) -> Rc<Node> {
+        Rc::new(Node {
+            value,
+            parent: RefCell::new(Weak::new()),
+            children: RefCell::new(Vec::new()),
+        })
+    }
+
+    /// Makes `child` a child of `parent`, pointing back with a `Weak`.
+    pub fn adopt(parent: &Rc<Node>, child: Rc<Node>) {
+        *child.parent.borrow_mut() = Rc::downgrade(parent);
+        parent.children.borrow_mut().push(child);
+    }
+
+    pub fn parent(&self) -> Option<Rc<Node>> {
+        self.parent.borrow().upgrade()
+    }
+}
+
+/// Two nodes that own each other, then dropped by their last outside
+/// owner: is the first still alive? A `Weak` kept aside is how to tell.
+/// With `strong_back` the second points back with an `Rc` and they leak;
+/// otherwise it's a `Weak` and both are freed.
+pub fn cycle_survives(strong_back: bool) -> bool {
+    struct Pair {
+        other: RefCell<Option<Rc<Pair>>>,
+        back: RefCell<Weak<Pair>>,
+    }
+    let new = || {
+        Rc::new(Pair {
+            other: RefCell::new(None),
+            back: RefCell::new(Weak::new()),
+        })
+    };
+    let a = new();
+    let b = new();
+    *a.other.borrow_mut() = Some(Rc::clone(&b));
+    if strong_back {
+        *b.other.borrow_mut() = Some(Rc::clone(&a));
+    } else {
+        *b.back.borrow_mut() = Rc::downgrade(&a);
+    }
+    let watch = Rc::downgrade(&a);
+    drop((a, b));
+    let alive = watch.upgrade().is_some();
+    if let Some(a) = watch.upgrade() {
+        // Break the leaked cycle by hand so the test doesn't leak.
+        a.other.borrow_mut().take();
+    }
+    alive
+}
+
+/// Has `threads` threads each push their index onto one shared vector,
+/// and returns it sorted. `Arc` shares ownership across threads, where
+/// `Rc` isn't allowed, and the `Mutex` makes each push exclusive.
+pub fn collect_in_threads(threads: usize) -> Vec<usize> {
+    let shared = Arc::new(Mutex::new(Vec::new()));
+    let handles: Vec<_> = (0..threads)
+        .map(|i| {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || shared.lock().unwrap().push(i))
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    let mut all = shared.lock().unwrap().clone();
+    all.sort_unstable();
+    all
+}
+
+/// Runs each pattern in turn.
+pub fn demo() {
+    println!("one wave, {} owners", share_wave(3));
+
+    let mut wave = Rc::new(vec![0.0, 1.0]);
+    let other = Rc::clone(&wave);
+    println!(
+        "get_mut with two owners: pushed {}",
+        push_if_unique(&mut wave, 0.0)
+    );
+    println!(
+        "make_mut with two owners: copied {}, now {:?} and {:?}",
+        push_on_write(&mut wave, 0.0),
+        wave,
+        other
+    );
+    drop(other);
+    println!(
+        "get_mut with one owner: pushed {}",
+        push_if_unique(&mut wave, -1.0)
+    );
+
+    let parent = Node::new(1);
+    Node::adopt(&parent, Node::new(2));
+    let child = Rc::clone(&parent.children.borrow()[0]);
+    println!(
+        "child {} finds parent {:?}",
+        child.value,
+        child.parent().map(|p| p.value)
+    );
+    drop(parent);
+    println!(
+        "after the parent is dropped: {:?}",
+        child.parent().map(|p| p.value)
+    );
+
+    println!(
+        "a strong cycle outlives its owners: {}; with a weak back pointer: {}",
+        cycle_survives(true),
+        cycle_survives(false)
+    );
+    println!(
+        "4 threads pushing through Arc<Mutex<_>>: {:?}",
+        collect_in_threads(4)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_are_owners_of_one_value() {
+        assert_eq!(share_wave(1), 1);
+        assert_eq!(share_wave(4), 4);
+    }
+
+    #[test]
+    fn get_mut_refuses_while_aliased() {
+        let mut wave = Rc::new(vec![0.0]);
+        let other = Rc::clone(&wave);
+        assert!(!push_if_unique(&mut wave, 1.0));
+        drop(other);
+        assert!(push_if_unique(&mut wave, 1.0));
+        assert_eq!(*wave, [0.0, 1.0]);
+    }
+
+    #[test]
+    fn make_mut_copies_only_when_shared() {
+        let mut wave = Rc::new(vec![0.0]);
+        let other = Rc::clone(&wave);
+        assert!(push_on_write(&mut wave, 1.0));
+        assert_eq!(*wave, [0.0, 1.0]);
+        assert_eq!(*other, [0.0]);
+        assert!(!push_on_write(&mut wave, 2.0));
+        assert_eq!(Rc::strong_count(&wave), 1);
+    }
+
+    #[test]
+    fn weak_parents_dont_keep_them_alive() {
+        let parent = Node::new(1);
+        Node::adopt(&parent, Node::new(2));
+        let child = Rc::clone(&parent.children.borrow()[0]);
+        assert_eq!(child.parent().map(|p| p.value), Some(1));
+        assert_eq!(Rc::strong_count(&parent), 1);
+        drop(parent);
+        assert!(child.parent().is_none());
+    }
+
+    #[test]
+    fn only_strong_cycles_leak() {
+        assert!(cycle_survives(true));
+        assert!(!cycle_survives(false));
+    }
+
+    #[test]
+    fn every_thread_pushes_once() {
+        assert_eq!(collect_in_threads(4), [0, 1, 2, 3]);
+    }
+}
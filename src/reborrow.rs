@@ -0,0 +1,161 @@
+//! Reborrowing you don't write: implicit reborrows and two-phase borrows.
+//!
+//! [`sharing::reborrow`](crate::sharing::reborrow) borrows fields through a
+//! reference with `&mut m.0` and `&r.1`. Most reborrows aren't spelled out
+//! at all. Passing a `&mut Vec` on to a function that takes one doesn't
+//! move it, though `&mut` isn't `Copy`: where the parameter's type is known
+//! to be a `&mut`, the compiler passes `&mut *m`, a fresh loan of `*m` that
+//! ends with the call, and `m` is usable again afterwards. Method calls do
+//! the same for their receiver, so `m.push(1); m.push(2);` works.
+//!
+//! A generic parameter isn't known to be a reference, so there the `&mut`
+//! itself is moved in:
+//!
+//! ```compile_fail,E0382
+//! fn keep<T>(_: T) {}
+//!
+//! let mut v = vec![1, 2];
+//! let m = &mut v;
+//! keep(m);
+//! m.push(3); // borrow of moved value: `m`
+//! ```
+//!
+//! and writing the reborrow, `keep(&mut *m)`, is the fix, as in
+//! [`write_twice`].
+//!
+//! `v.push(v.len())` looks like it breaks the mutable-is-exclusive rule:
+//! `push` borrows `v` mutably, then `v.len()` reads it. It compiles because
+//! the receiver's `&mut` is a two-phase borrow, only reserved while the
+//! arguments are evaluated and activated for the call itself, so shared
+//! reads may happen in between. That's only for method-call syntax; the
+//! same call with the `&mut` written out is rejected:
+//!
+//! ```compile_fail,E0502
+//! let mut v = vec![1, 2];
+//! Vec::push(&mut v, v.len()); // cannot borrow `v` as immutable because it is also borrowed as mutable
+//! ```
+//!
+//! Non-lexical lifetimes end a borrow at its last use, but a borrow that's
+//! returned on one path is taken to last for the caller's whole lifetime on
+//! every path, so this get-or-insert is rejected even though the insert
+//! only happens when nothing was returned:
+//!
+//! ```compile_fail,E0502
+//! use std::collections::HashMap;
+//!
+//! fn get_or_insert(map: &mut HashMap<u32, String>) -> &String {
+//!     if let Some(name) = map.get(&22) {
+//!         return name;
+//!     }
+//!     map.insert(22, String::from("Hercules")); // cannot borrow `*map` as mutable because it is also borrowed as immutable
+//!     &map[&22]
+//! }
+//! ```
+//!
+//! [`get_or_insert`] uses the entry API instead, as
+//! [`memo::Cache`](crate::memo::Cache) does: one lookup, under one mutable
+//! borrow, that either finds the value or inserts it.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Appends `v`'s length to it: the two-phase borrow `v.push(v.len())`.
+pub fn push_len(v: &mut Vec<usize>) {
+    v.push(v.len());
+}
+
+/// Calls `push_len` twice with the one `m`, each call implicitly
+/// reborrowing `*m` rather than moving `m`.
+pub fn push_len_twice(m: &mut Vec<usize>) {
+    push_len(m);
+    push_len(m);
+}
+
+/// Adds one to the last element and returns it, through the `&mut i32`
+/// that `last_mut` reborrows from `v`.
+pub fn bump_last(v: &mut [i32]) -> Option<i32> {
+    let last = v.last_mut()?;
+    *last += 1;
+    Some(*last)
+}
+
+/// Writes `line` and a newline to `out`, taking the writer by value as
+/// `io` functions generic over `Write` do.
+fn write_line<W: Write>(mut out: W, line: &str) -> io::Result<()> {
+    writeln!(out, "{}", line)
+}
+
+/// Writes `line` to `out` twice. `write_line` would take `out` itself,
+/// since `W` needn't be a reference, so the first call reborrows it by
+/// hand with `&mut *out`.
+pub fn write_twice<W: Write>(out: &mut W, line: &str) -> io::Result<()> {
+    write_line(&mut *out, line)?;
+    write_line(out, line)
+}
+
+/// The name for `key`, inserting what `make` returns if there isn't one.
+///
+/// The get-or-insert that's rejected returns the borrow from `get`; this
+/// returns the one from `entry`, which is mutable on both paths, so there's
+/// no shared borrow for the insert to conflict with.
+pub fn get_or_insert<F>(map: &mut HashMap<u32, String>, key: u32, make: F) -> &String
+where
+    F: FnOnce() -> String,
+{
+    map.entry(key).or_insert_with(make)
+}
+
+/// Runs each of the reborrows the compiler writes for you.
+pub fn demo() {
+    let mut lens = Vec::new();
+    push_len_twice(&mut lens);
+    println!("push(len()) twice: {:?}", lens);
+
+    let mut wave = vec![0, 1, 0, -1];
+    let last = bump_last(&mut wave);
+    println!("last bumped to {:?}: {:?}", last, wave);
+
+    let mut out = Vec::new();
+    write_twice(&mut out, "hello").unwrap();
+    print!("{}", String::from_utf8_lossy(&out));
+
+    let mut names = HashMap::new();
+    get_or_insert(&mut names, 22, || String::from("Hercules"));
+    let name = get_or_insert(&mut names, 22, || unreachable!());
+    println!("22 is {}", name);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pushes_lengths_through_one_reference() {
+        let mut v = vec![7];
+        push_len_twice(&mut v);
+        assert_eq!(v, vec![7, 1, 2]);
+    }
+
+    #[test]
+    fn bumps_the_last_element() {
+        let mut v = vec![1, 2];
+        assert_eq!(bump_last(&mut v), Some(3));
+        assert_eq!(v, vec![1, 3]);
+        assert_eq!(bump_last(&mut []), None);
+    }
+
+    #[test]
+    fn writes_through_both_calls() {
+        let mut out = Vec::new();
+        write_twice(&mut out, "hi").unwrap();
+        assert_eq!(out, b"hi\nhi\n");
+    }
+
+    #[test]
+    fn inserts_only_when_missing() {
+        let mut map = HashMap::new();
+        assert_eq!(get_or_insert(&mut map, 1, || "one".to_owned()), "one");
+        assert_eq!(get_or_insert(&mut map, 1, || unreachable!()), "one");
+        assert_eq!(map.len(), 1);
+    }
+}
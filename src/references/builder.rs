@@ -0,0 +1,83 @@
+/// Settings built up with methods that take `self` by value.
+///
+/// `fd::OpenOptions` is the other kind of builder: its setters take
+/// `&mut self` and return the same borrow, so the options live in a variable
+/// the caller owns and can be set one statement at a time or reused for
+/// several `open`s. A consuming builder moves the value into each call and
+/// gets it back as the result, so it reads well as one chained expression
+/// and needs no `mut` binding, but every step hands ownership along. Keeping
+/// the old binding around doesn't work:
+///
+/// ```compile_fail,E0382
+/// use references_sharing_vs_mutation::references::Config;
+///
+/// let base = Config::new();
+/// let loud = base.with_gain(2.0); // `base` moves into `with_gain`
+/// let quiet = base.with_gain(0.5);
+/// ```
+///
+/// A caller that wants both has to `base.clone().with_gain(2.0)` first.
+/// `Config` is deliberately not `Copy`, so it behaves like a builder that
+/// owns a `Vec` or `String` would.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    gain: f64,
+    offset: f64,
+}
+
+impl Config {
+    /// A gain of one and no offset, which leaves samples unchanged.
+    pub fn new() -> Config {
+        Config {
+            gain: 1.0,
+            offset: 0.0,
+        }
+    }
+
+    pub fn with_gain(self, gain: f64) -> Config {
+        Config { gain, ..self }
+    }
+
+    pub fn with_offset(self, offset: f64) -> Config {
+        Config { offset, ..self }
+    }
+
+    pub fn gain(&self) -> f64 {
+        self.gain
+    }
+
+    pub fn offset(&self) -> f64 {
+        self.offset
+    }
+
+    /// `sample * gain + offset`.
+    pub fn apply(&self, sample: f64) -> f64 {
+        sample * self.gain + self.offset
+    }
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chains_by_value() {
+        let config = Config::new().with_gain(2.0).with_offset(1.0);
+        assert_eq!(config.gain(), 2.0);
+        assert_eq!(config.offset(), 1.0);
+        assert_eq!(config.apply(3.0), 7.0);
+    }
+
+    #[test]
+    fn later_settings_win_and_defaults_pass_through() {
+        let config = Config::default().with_gain(2.0).with_gain(4.0);
+        assert_eq!(config, Config::new().with_gain(4.0));
+        assert_eq!(config.offset(), 0.0);
+    }
+}
@@ -0,0 +1,78 @@
+use std::io::{self, Read};
+
+use crate::fd::File;
+
+/// Wraps `f` in a closure that reads it to the end when called.
+///
+/// The `move` closure takes ownership of the `File`, so the descriptor stays
+/// open as long as the closure exists, however far it travels from the
+/// function that opened it, and is closed when the closure is called (which
+/// consumes it) or dropped.
+pub fn deferred_reader(f: File) -> impl FnOnce() -> io::Result<Vec<u8>> {
+    move || {
+        let mut f = f;
+        let mut contents = Vec::new();
+        f.read_to_end(&mut contents)?;
+        Ok(contents)
+    }
+}
+
+/// A boxed closure returning `data[i]`, or `None` past the end.
+///
+/// The closure captures `data` by reference, so the trait object carries
+/// the borrow: `+ 'a` says the box may not outlive the slice. Without the
+/// bound, `Box<dyn Fn>` would mean `+ 'static` and this wouldn't compile.
+/// With it, the box is checked like any other reference:
+///
+/// ```compile_fail,E0597
+/// use references_sharing_vs_mutation::references::make_read_closure;
+///
+/// let read;
+/// {
+///     let data = vec![1.0, 2.0];
+///     read = make_read_closure(&data);
+/// } // `data` dropped here while still borrowed
+/// read(0);
+/// ```
+pub fn make_read_closure<'a>(data: &'a [f64]) -> Box<dyn Fn(usize) -> Option<f64> + 'a> {
+    Box::new(move |i| data.get(i).copied())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{ErrorKind, Write};
+
+    use super::*;
+    use crate::testutil::{self, TempDir};
+
+    #[test]
+    fn read_closure_indexes_the_borrowed_slice() {
+        let data = vec![0.0, 1.0, 0.0, -1.0];
+        let read = make_read_closure(&data);
+        assert_eq!(read(1), Some(1.0));
+        assert_eq!(read(3), Some(-1.0));
+        assert_eq!(read(4), None);
+        // Only a shared borrow: `data` can still be read alongside.
+        assert_eq!(data.len(), 4);
+    }
+
+    #[test]
+    fn reads_when_called() {
+        let dir = TempDir::new();
+        let read = deferred_reader(File::open(dir.file("later", b"read me later")).unwrap());
+        assert_eq!(read().unwrap(), b"read me later");
+    }
+
+    #[test]
+    fn descriptor_lives_as_long_as_the_closure() {
+        let (reader, mut writer) = testutil::pipe();
+        let read = deferred_reader(reader);
+
+        // The read end is still open inside the closure, so writes succeed.
+        writer.write_all(b"still here").unwrap();
+
+        drop(read);
+        let err = writer.write_all(b"gone").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+    }
+}
@@ -0,0 +1,110 @@
+//! The "mutable access is exclusive" rule, checked at run time.
+//!
+//! This lives in its own module because its guard is also called
+//! `MutGuard`, like the callback guard in `references`; the two do
+//! different jobs.
+
+use std::cell::{Cell, UnsafeCell};
+use std::ops::{Deref, DerefMut};
+
+/// A value that hands out one `&mut` at a time, from a shared `&self`.
+///
+/// Where the borrow checker proves at compile time that two `&mut`s never
+/// overlap, `Mutable` tracks it with a flag and panics on the second
+/// `borrow_mut`. That covers cases the static check can't see, such as a
+/// value reached through several shared references whose borrows depend
+/// on run-time conditions. It is a one-flag `RefCell` without the shared
+/// borrows, and like `RefCell` it is not `Sync`.
+pub struct Mutable<T> {
+    borrowed: Cell<bool>,
+    value: UnsafeCell<T>,
+}
+
+/// Exclusive access to the value in a `Mutable`, given back on drop.
+pub struct MutGuard<'a, T> {
+    borrowed: &'a Cell<bool>,
+    value: &'a mut T,
+}
+
+impl<T> Mutable<T> {
+    pub fn new(value: T) -> Mutable<T> {
+        Mutable {
+            borrowed: Cell::new(false),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Borrows the value mutably until the guard is dropped.
+    ///
+    /// # Panics
+    ///
+    /// If a guard from an earlier `borrow_mut` is still alive.
+    pub fn borrow_mut(&self) -> MutGuard<'_, T> {
+        if self.borrowed.replace(true) {
+            panic!("Mutable already mutably borrowed");
+        }
+        MutGuard {
+            borrowed: &self.borrowed,
+            // The flag was clear, so no other guard holds a `&mut`, and none
+            // can be made until this one resets it.
+            value: unsafe { &mut *self.value.get() },
+        }
+    }
+
+    /// The compile-time path: `&mut self` already proves exclusivity, so no
+    /// flag is checked.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<'a, T> Deref for MutGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<'a, T> DerefMut for MutGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value
+    }
+}
+
+impl<'a, T> Drop for MutGuard<'a, T> {
+    fn drop(&mut self) {
+        self.borrowed.set(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sequential_borrows_succeed() {
+        let wave = Mutable::new(vec![0.0, 1.0]);
+        wave.borrow_mut().push(0.0);
+        {
+            let mut guard = wave.borrow_mut();
+            guard.push(-1.0);
+            assert_eq!(guard.len(), 4);
+        }
+        wave.borrow_mut().push(0.0);
+        assert_eq!(wave.into_inner(), vec![0.0, 1.0, 0.0, -1.0, 0.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "already mutably borrowed")]
+    fn nested_borrow_panics() {
+        let wave = Mutable::new(vec![0.0]);
+        let reader = &wave;
+        let _first = wave.borrow_mut();
+        let _second = reader.borrow_mut();
+    }
+}
@@ -0,0 +1,71 @@
+use std::ops::{Deref, DerefMut};
+
+/// Exclusive access to a `T` that runs a callback when it ends.
+///
+/// The guard holds the only `&mut T` for as long as it lives, so the
+/// callback sees the value after every change made through the guard, and
+/// nothing else could have changed it in the meantime.
+pub struct MutGuard<'a, T> {
+    value: &'a mut T,
+    on_drop: Option<OnDrop<'a, T>>,
+}
+
+type OnDrop<'a, T> = Box<dyn FnOnce(&mut T) + 'a>;
+
+/// Borrows `value` exclusively, calling `on_drop` with it once the returned
+/// guard goes away, e.g. to mark it dirty.
+pub fn guarded_mut<'a, T, F>(value: &'a mut T, on_drop: F) -> MutGuard<'a, T>
+where
+    F: FnOnce(&mut T) + 'a,
+{
+    MutGuard {
+        value,
+        on_drop: Some(Box::new(on_drop)),
+    }
+}
+
+impl<'a, T> Deref for MutGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<'a, T> DerefMut for MutGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value
+    }
+}
+
+impl<'a, T> Drop for MutGuard<'a, T> {
+    fn drop(&mut self) {
+        if let Some(on_drop) = self.on_drop.take() {
+            on_drop(self.value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[test]
+    fn callback_fires_once_on_drop() {
+        let fired = Cell::new(0);
+        let mut wave = vec![0.0, 1.0];
+        {
+            let mut guard = guarded_mut(&mut wave, |w| {
+                fired.set(fired.get() + 1);
+                assert_eq!(w.len(), 4);
+            });
+            guard.push(0.0);
+            guard.push(-1.0);
+            assert_eq!(fired.get(), 0);
+        }
+        assert_eq!(fired.get(), 1);
+        assert_eq!(wave, vec![0.0, 1.0, 0.0, -1.0]);
+    }
+}
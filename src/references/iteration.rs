@@ -0,0 +1,87 @@
+/// Sums `v`, visiting each element through a shared reference.
+///
+/// While the loop holds its borrow of the vector, the vector is frozen, so
+/// it can't be grown out from under the iterator. This is the invalidated
+/// iterator bug the notes compare with C++, and Rust rejects it:
+///
+/// ```compile_fail,E0502
+/// let mut v = vec![1.0, 2.0, 3.0];
+/// for x in &v {
+///     v.push(*x); // cannot borrow `v` as mutable because it is also borrowed as immutable
+/// }
+/// ```
+pub fn sum_refs(v: &[f64]) -> f64 {
+    let mut total = 0.0;
+    for x in v {
+        total += *x;
+    }
+    total
+}
+
+/// Adds `k` to every element of `v` in place, through exclusive references.
+pub fn add_scalar(v: &mut [f64], k: f64) {
+    for x in v {
+        *x += k;
+    }
+}
+
+/// Doubles every element of `v` in place. `iter_mut` hands out one
+/// `&mut f64` at a time, each the only way to reach its element.
+pub fn double_in_place(v: &mut [f64]) {
+    for x in v.iter_mut() {
+        *x *= 2.0;
+    }
+}
+
+/// Sums `v`, taking ownership of it. `into_iter` moves each element out
+/// and the vector is gone afterwards, so the caller can't use it again:
+///
+/// ```compile_fail,E0382
+/// use references_sharing_vs_mutation::references::consume_sum;
+///
+/// let v = vec![1.0, 2.0];
+/// let total = consume_sum(v);
+/// println!("{} {}", total, v.len()); // borrow of moved value: `v`
+/// ```
+pub fn consume_sum(v: Vec<f64>) -> f64 {
+    let mut total = 0.0;
+    for x in v.into_iter() {
+        total += x;
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sums_through_shared_refs() {
+        let v = vec![0.5, 1.5, 2.0];
+        assert_eq!(sum_refs(&v), 4.0);
+        assert_eq!(sum_refs(&[]), 0.0);
+        // `v` is still ours: the sum only borrowed it.
+        assert_eq!(v.len(), 3);
+    }
+
+    #[test]
+    fn adds_in_place_through_mut_refs() {
+        let mut v = vec![0.0, 1.0, -1.0];
+        add_scalar(&mut v, 2.0);
+        assert_eq!(v, vec![2.0, 3.0, 1.0]);
+    }
+
+    #[test]
+    fn doubles_through_iter_mut() {
+        let mut v = vec![1.0, -2.5, 0.0];
+        double_in_place(&mut v);
+        assert_eq!(v, vec![2.0, -5.0, 0.0]);
+    }
+
+    #[test]
+    fn sums_by_consuming() {
+        let v = vec![1.0, 2.0, 3.5];
+        assert_eq!(consume_sum(v), 6.5);
+        assert_eq!(consume_sum(Vec::new()), 0.0);
+    }
+}
@@ -0,0 +1,40 @@
+/// The `n` longest strings in `strings`, longest first, as slices of the
+/// originals. Equal lengths keep their input order.
+///
+/// Nothing is copied: each `&str` points into a `String` owned by the
+/// caller, and the `'a` on both sides says the vector can't outlive them:
+///
+/// ```compile_fail,E0505
+/// use references_sharing_vs_mutation::references::longest_strings;
+///
+/// let words = vec![String::from("shared"), String::from("mutable")];
+/// let longest = longest_strings(&words, 1);
+/// drop(words); // cannot move out of `words` because it is borrowed
+/// println!("{}", longest[0]);
+/// ```
+pub fn longest_strings<'a>(strings: &'a [String], n: usize) -> Vec<&'a str> {
+    let mut refs: Vec<&'a str> = strings.iter().map(String::as_str).collect();
+    refs.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    refs.truncate(n);
+    refs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longest_first_borrowing_the_input() {
+        let words: Vec<String> = ["r", "wave", "aside", "slice", "head"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let longest = longest_strings(&words, 3);
+        assert_eq!(longest, vec!["aside", "slice", "wave"]);
+        // The results are views into `words`, not copies.
+        assert_eq!(longest[0].as_ptr(), words[2].as_ptr());
+
+        assert_eq!(longest_strings(&words, 10).len(), 5);
+        assert!(longest_strings(&words, 0).is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+/// Appends `slice` to the vector stored under `key`, creating it if needed.
+///
+/// `entry` finds or makes the slot and hands back one `&mut Vec<f64>` into
+/// the map, which is the only borrow needed. Looking the key up first and
+/// then inserting would mean holding a borrow of the map across a change
+/// to it, which is the error the notes warn about with `HashMap` keys.
+pub fn append_to_group(map: &mut HashMap<String, Vec<f64>>, key: &str, slice: &[f64]) {
+    map.entry(key.to_owned()).or_default().extend(slice);
+}
+
+/// Adds `amount` to the total under `key`, starting new keys at zero.
+///
+/// One `entry` call gives one `&mut f64` into the map, whether the key was
+/// there or not. `accumulate_two_lookups` does the same job the way it
+/// would be written without `entry`.
+pub fn accumulate(map: &mut HashMap<String, f64>, key: &str, amount: f64) {
+    *map.entry(key.to_owned()).or_default() += amount;
+}
+
+/// `accumulate` by asking first: `contains_key`, then `get_mut` or
+/// `insert`.
+///
+/// It borrow-checks only because the shared borrow from `contains_key`
+/// ends before the mutable one starts. Holding on to a lookup result
+/// across the `insert` instead, as in "get, and insert if that was
+/// `None`", is rejected while the result is still in use. Existing keys
+/// are hashed twice, but unlike `accumulate` they don't need the key
+/// copied into a `String` first.
+pub fn accumulate_two_lookups(map: &mut HashMap<String, f64>, key: &str, amount: f64) {
+    if map.contains_key(key) {
+        *map.get_mut(key).unwrap() += amount;
+    } else {
+        map.insert(key.to_owned(), amount);
+    }
+}
+
+/// Zeroes the largest total in `map` and returns its key, or `None` if
+/// the map is empty.
+///
+/// Finding the largest gives a `&String` borrowed from the map's keys, and
+/// writing needs the map mutably, so the key can't be used to look the
+/// entry up again while it still borrows the map:
+///
+/// ```compile_fail,E0502
+/// use std::collections::HashMap;
+///
+/// fn reset_largest(map: &mut HashMap<String, f64>) -> Option<String> {
+///     let (key, _) = map.iter().max_by(|a, b| a.1.total_cmp(b.1))?;
+///     *map.get_mut(key)? = 0.0; // cannot borrow `*map` as mutable because it is also borrowed as immutable
+///     Some(key.clone())
+/// }
+/// ```
+///
+/// Cloning the key first ends the borrow, at the price of one `String`,
+/// which the caller wanted anyway.
+pub fn reset_largest(map: &mut HashMap<String, f64>) -> Option<String> {
+    let key = map.iter().max_by(|a, b| a.1.total_cmp(b.1))?.0.clone();
+    *map.get_mut(&key).unwrap() = 0.0;
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appends_to_new_and_existing_groups() {
+        let mut map = HashMap::new();
+        append_to_group(&mut map, "wave", &[0.0, 1.0]);
+        assert_eq!(map["wave"], vec![0.0, 1.0]);
+
+        append_to_group(&mut map, "wave", &[0.0, -1.0]);
+        append_to_group(&mut map, "flat", &[]);
+        assert_eq!(map["wave"], vec![0.0, 1.0, 0.0, -1.0]);
+        assert_eq!(map["flat"], Vec::<f64>::new());
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn accumulates_into_new_and_existing_keys() {
+        let mut one = HashMap::new();
+        let mut two = HashMap::new();
+        for &(key, amount) in &[("a", 1.0), ("b", 2.5), ("a", 0.5), ("a", -3.0)] {
+            accumulate(&mut one, key, amount);
+            accumulate_two_lookups(&mut two, key, amount);
+        }
+        assert_eq!(one["a"], -1.5);
+        assert_eq!(one["b"], 2.5);
+        assert_eq!(one, two);
+    }
+
+    #[test]
+    fn resets_the_largest_total() {
+        let mut map = HashMap::new();
+        assert_eq!(reset_largest(&mut map), None);
+
+        map.insert("a".to_string(), 1.0);
+        map.insert("b".to_string(), 4.0);
+        map.insert("c".to_string(), -2.0);
+        assert_eq!(reset_largest(&mut map).as_deref(), Some("b"));
+        assert_eq!(map["b"], 0.0);
+        assert_eq!(reset_largest(&mut map).as_deref(), Some("a"));
+        assert_eq!(map.values().sum::<f64>(), -2.0);
+    }
+}
@@ -0,0 +1,42 @@
+//! Small functions that each put one of the sharing rules to work.
+//!
+//! The notes state the rules: shared access is read-only, mutable access is
+//! exclusive. Everything here either obeys them in a way worth copying or
+//! carries a `compile_fail` example showing the compiler enforcing them.
+
+mod builder;
+#[cfg(unix)]
+mod closure;
+pub mod exclusive;
+mod guard;
+mod iteration;
+mod lifetimes;
+mod maps;
+mod node;
+mod parser;
+mod partial;
+mod replace;
+mod retain;
+mod shared;
+mod split;
+mod strings;
+mod swap;
+mod tree;
+
+pub use self::builder::Config;
+#[cfg(unix)]
+pub use self::closure::{deferred_reader, make_read_closure};
+pub use self::guard::{guarded_mut, MutGuard};
+pub use self::iteration::{add_scalar, consume_sum, double_in_place, sum_refs};
+pub use self::lifetimes::longest_strings;
+pub use self::maps::{accumulate, accumulate_two_lookups, append_to_group, reset_largest};
+pub use self::node::Node;
+pub use self::parser::Parser;
+pub use self::partial::{second_after_moving_first, Pair2};
+pub use self::replace::{reset, swap_buffers};
+pub use self::retain::{remove_below, remove_below_manual};
+pub use self::shared::SharedVec;
+pub use self::split::{disjoint_mut, Pair, Stereo};
+pub use self::strings::{capitalize_first, first_char, maybe_normalize};
+pub use self::swap::swap_elements;
+pub use self::tree::Tree;
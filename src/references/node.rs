@@ -0,0 +1,89 @@
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+/// A tree node that knows its parent, which plain ownership can't express.
+///
+/// A parent owns its children and a child would need to own its parent for
+/// the upward link, which is a cycle: neither could ever be dropped, and
+/// nothing would have the exclusive access needed to build it. So children
+/// are shared with `Rc`, the parent link is a `Weak` that doesn't keep the
+/// parent alive, and `RefCell` allows linking them up through the shared
+/// handles. Dropping the root frees the whole tree.
+#[derive(Debug)]
+pub struct Node {
+    pub value: i32,
+    parent: RefCell<Weak<Node>>,
+    children: RefCell<Vec<Rc<Node>>>,
+}
+
+impl Node {
+    /// A node with no parent and no children yet.
+    pub fn new(value: i32) -> Rc<Node> {
+        Rc::new(Node {
+            value,
+            parent: RefCell::new(Weak::new()),
+            children: RefCell::new(Vec::new()),
+        })
+    }
+
+    /// Makes `child` the last child of `parent`, pointing its parent link
+    /// back at `parent`.
+    pub fn add_child(parent: &Rc<Node>, child: Rc<Node>) {
+        *child.parent.borrow_mut() = Rc::downgrade(parent);
+        parent.children.borrow_mut().push(child);
+    }
+
+    /// The parent, if this node has one that is still alive.
+    pub fn parent(&self) -> Option<Rc<Node>> {
+        self.parent.borrow().upgrade()
+    }
+
+    /// Handles on the children, in the order they were added.
+    pub fn children(&self) -> Vec<Rc<Node>> {
+        self.children.borrow().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn children_reach_their_parent_without_owning_it() {
+        let root = Node::new(1);
+        let left = Node::new(2);
+        let right = Node::new(3);
+        Node::add_child(&root, left.clone());
+        Node::add_child(&root, right.clone());
+
+        assert_eq!(left.parent().unwrap().value, 1);
+        let siblings: Vec<i32> = right
+            .parent()
+            .unwrap()
+            .children()
+            .iter()
+            .map(|n| n.value)
+            .collect();
+        assert_eq!(siblings, vec![2, 3]);
+        assert!(root.parent().is_none());
+
+        // Only `root` itself owns the root; each child is owned by its own
+        // handle and by the root's list.
+        assert_eq!(Rc::strong_count(&root), 1);
+        assert_eq!(Rc::weak_count(&root), 2);
+        assert_eq!(Rc::strong_count(&left), 2);
+    }
+
+    #[test]
+    fn dropping_the_root_frees_the_tree() {
+        let root = Node::new(1);
+        let leaf = Node::new(2);
+        Node::add_child(&root, leaf.clone());
+        let weak_root = Rc::downgrade(&root);
+
+        drop(root);
+        assert!(weak_root.upgrade().is_none());
+        assert!(leaf.parent().is_none());
+        assert_eq!(Rc::strong_count(&leaf), 1);
+    }
+}
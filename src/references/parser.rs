@@ -0,0 +1,69 @@
+/// Splits a string into whitespace-separated tokens without copying it.
+///
+/// The parser holds a shared reference to its input, and the `'a` says so:
+/// the tokens it hands out borrow from the input, not from the parser, so
+/// they can outlive the parser but not the string. The string can't go
+/// away while the parser still needs it:
+///
+/// ```compile_fail,E0597
+/// use references_sharing_vs_mutation::references::Parser;
+///
+/// let mut parser;
+/// {
+///     let input = String::from("shared refs");
+///     parser = Parser::new(&input);
+/// } // `input` dropped here while still borrowed
+/// parser.next_token();
+/// ```
+pub struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(input: &'a str) -> Parser<'a> {
+        Parser { input, pos: 0 }
+    }
+
+    /// The next token, or `None` once the input is used up.
+    pub fn next_token(&mut self) -> Option<&'a str> {
+        let rest = &self.input[self.pos..];
+        let start = self.pos + (rest.len() - rest.trim_start().len());
+        if start == self.input.len() {
+            self.pos = start;
+            return None;
+        }
+        let token_len = self.input[start..]
+            .find(char::is_whitespace)
+            .unwrap_or(self.input.len() - start);
+        self.pos = start + token_len;
+        Some(&self.input[start..self.pos])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenizes_on_whitespace() {
+        let mut parser = Parser::new("  let r = &v;\n\tr[0] ");
+        let mut tokens = Vec::new();
+        while let Some(token) = parser.next_token() {
+            tokens.push(token);
+        }
+        assert_eq!(tokens, vec!["let", "r", "=", "&v;", "r[0]"]);
+        assert_eq!(parser.next_token(), None);
+    }
+
+    #[test]
+    fn tokens_outlive_the_parser() {
+        let input = String::from("first second");
+        let first = {
+            let mut parser = Parser::new(&input);
+            parser.next_token().unwrap()
+        };
+        assert_eq!(first, "first");
+        assert_eq!(Parser::new("   ").next_token(), None);
+    }
+}
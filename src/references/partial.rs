@@ -0,0 +1,50 @@
+/// Two owned strings, for showing that a move can take a single field.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pair2 {
+    pub a: String,
+    pub b: String,
+}
+
+/// Moves `a` out of `p` on its own, then returns `b`.
+///
+/// After `let a = p.a;` the pair is partly moved: `p.b` is still owned by
+/// `p` and can be used or moved in turn, but `p` as a whole can't, since
+/// part of it is gone:
+///
+/// ```compile_fail,E0382
+/// use references_sharing_vs_mutation::references::Pair2;
+///
+/// let p = Pair2 { a: String::from("moved"), b: String::from("kept") };
+/// let a = p.a;
+/// let whole = p; // use of partially moved value: `p`
+/// ```
+///
+/// This only works because `Pair2` has no `Drop` impl. A type with one
+/// must stay whole for its destructor, and moving a field out is an error.
+pub fn second_after_moving_first(p: Pair2) -> String {
+    let a = p.a;
+    drop(a);
+    p.b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_move_leaves_the_other_field() {
+        let p = Pair2 {
+            a: String::from("first"),
+            b: String::from("second"),
+        };
+        assert_eq!(second_after_moving_first(p), "second");
+
+        let p = Pair2 {
+            a: String::from("x"),
+            b: String::from("y"),
+        };
+        let a = p.a;
+        assert_eq!(p.b.len(), 1); // `p.b` is still usable here
+        assert_eq!(a, "x");
+    }
+}
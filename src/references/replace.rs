@@ -0,0 +1,50 @@
+use std::mem;
+
+/// Moves the contents out of `v`, leaving an empty vector behind.
+///
+/// `let old = *v;` would move out of something we only borrowed, leaving
+/// the owner holding nothing:
+///
+/// ```compile_fail,E0507
+/// fn reset(v: &mut Vec<f64>) -> Vec<f64> {
+///     let old = *v;
+///     old
+/// }
+/// ```
+///
+/// `mem::take` puts `Vec::new()` in its place in the same step, so the
+/// referent is never left uninitialized.
+pub fn reset(v: &mut Vec<f64>) -> Vec<f64> {
+    mem::take(v)
+}
+
+/// Exchanges the contents of `a` and `b`, buffers and all; no elements are
+/// copied. The two `&mut`s can't be the same vector, so there is nothing
+/// to guard against.
+pub fn swap_buffers(a: &mut Vec<f64>, b: &mut Vec<f64>) {
+    mem::swap(a, b);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reset_returns_the_old_contents() {
+        let mut v = vec![1.0, 2.0];
+        assert_eq!(reset(&mut v), vec![1.0, 2.0]);
+        assert!(v.is_empty());
+        assert_eq!(reset(&mut v), Vec::<f64>::new());
+    }
+
+    #[test]
+    fn swaps_whole_buffers() {
+        let mut front = vec![1.0, 2.0, 3.0];
+        let mut back = Vec::with_capacity(100);
+        back.push(9.0);
+        swap_buffers(&mut front, &mut back);
+        assert_eq!(front, vec![9.0]);
+        assert!(front.capacity() >= 100);
+        assert_eq!(back, vec![1.0, 2.0, 3.0]);
+    }
+}
@@ -0,0 +1,65 @@
+/// Removes every element of `v` below `threshold`, keeping the rest in
+/// order.
+///
+/// Removing while looking is where C++ code goes wrong: a reference to an
+/// element is left pointing at whatever `erase` shifted into its place.
+/// Rust won't let a reference into the vector live across the `remove`:
+///
+/// ```compile_fail,E0502
+/// let mut v = vec![1.0, 5.0, 2.0];
+/// let first = &v[0];
+/// if *first < 3.0 {
+///     v.remove(0); // cannot borrow `v` as mutable because it is also borrowed as immutable
+/// }
+/// println!("{}", first);
+/// ```
+///
+/// `retain` does the looking and the removing itself, lending the closure
+/// one shared reference at a time.
+pub fn remove_below(v: &mut Vec<f64>, threshold: f64) {
+    v.retain(|&x| x >= threshold);
+}
+
+/// `remove_below` written out by hand, the way `retain` works inside.
+///
+/// Kept elements are copied down to a write cursor that never passes the
+/// read index, and the tail is cut off at the end. Only indices are held
+/// across the loop, never references, so the borrow checker has nothing to
+/// object to.
+pub fn remove_below_manual(v: &mut Vec<f64>, threshold: f64) {
+    let mut write = 0;
+    for read in 0..v.len() {
+        if v[read] >= threshold {
+            v[write] = v[read];
+            write += 1;
+        }
+    }
+    v.truncate(write);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn both_versions_agree() {
+        let cases: &[&[f64]] = &[
+            &[],
+            &[1.0, 5.0, 2.0, 7.0, 3.0, 3.0],
+            &[0.0, 0.0, 0.0],
+            &[9.0, 8.0],
+            &[-1.0, 4.0, f64::NAN, 3.0],
+        ];
+        for case in cases {
+            let mut retained = case.to_vec();
+            let mut manual = case.to_vec();
+            remove_below(&mut retained, 3.0);
+            remove_below_manual(&mut manual, 3.0);
+            assert_eq!(retained, manual);
+        }
+
+        let mut v = vec![1.0, 5.0, 2.0, 7.0, 3.0];
+        remove_below_manual(&mut v, 3.0);
+        assert_eq!(v, [5.0, 7.0, 3.0]);
+    }
+}
@@ -0,0 +1,111 @@
+use std::cell::{BorrowMutError, Ref, RefCell};
+use std::rc::Rc;
+
+/// A vector that any number of owners can push to, through `Rc<RefCell>`.
+///
+/// Every handle is a shared reference to the same vector, yet `push` takes
+/// only `&self`. The rules haven't gone away, only moved: `RefCell` counts
+/// the borrows at run time instead of the compiler proving them at compile
+/// time, so a `push` while a `snapshot` is still held compiles fine and
+/// panics when it runs:
+///
+/// ```should_panic
+/// use references_sharing_vs_mutation::references::SharedVec;
+///
+/// let v = SharedVec::new();
+/// let seen = v.snapshot();
+/// v.push(1.0); // already borrowed
+/// # drop(seen);
+/// ```
+///
+/// As a plain `Vec` the same mistake wouldn't have compiled at all.
+#[derive(Clone, Debug, Default)]
+pub struct SharedVec {
+    inner: Rc<RefCell<Vec<f64>>>,
+}
+
+impl SharedVec {
+    pub fn new() -> SharedVec {
+        SharedVec::default()
+    }
+
+    /// Appends `x`, visible through every handle.
+    ///
+    /// # Panics
+    ///
+    /// If any handle's `snapshot` is still alive.
+    pub fn push(&self, x: f64) {
+        self.inner.borrow_mut().push(x);
+    }
+
+    /// `push`, reporting a live `snapshot` as an error instead of
+    /// panicking. Nothing is pushed then.
+    pub fn try_push(&self, x: f64) -> Result<(), BorrowMutError> {
+        self.inner.try_borrow_mut()?.push(x);
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// A read-only view of the contents. Until it is dropped, the vector is
+    /// frozen for every handle.
+    pub fn snapshot(&self) -> Ref<'_, Vec<f64>> {
+        self.inner.borrow()
+    }
+}
+
+impl From<Vec<f64>> for SharedVec {
+    fn from(v: Vec<f64>) -> SharedVec {
+        SharedVec {
+            inner: Rc::new(RefCell::new(v)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handles_share_one_vector() {
+        let a = SharedVec::new();
+        let b = a.clone();
+        a.push(1.0);
+        b.push(2.0);
+        assert_eq!(a.len(), 2);
+        {
+            let one = a.snapshot();
+            let two = b.snapshot(); // shared borrows overlap happily
+            assert_eq!(*one, vec![1.0, 2.0]);
+            assert_eq!(*one, *two);
+        }
+        b.push(3.0);
+        assert_eq!(*a.snapshot(), vec![1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "already borrowed")]
+    fn push_during_snapshot_panics() {
+        let a = SharedVec::new();
+        let b = a.clone();
+        let _held = a.snapshot();
+        b.push(1.0);
+    }
+
+    #[test]
+    fn try_push_reports_a_live_snapshot() {
+        let a = SharedVec::from(vec![1.0]);
+        {
+            let _held = a.snapshot();
+            assert!(a.try_push(2.0).is_err());
+        }
+        a.try_push(2.0).unwrap();
+        assert_eq!(*a.snapshot(), vec![1.0, 2.0]);
+    }
+}
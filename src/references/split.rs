@@ -0,0 +1,150 @@
+/// Two vectors side by side, for borrowing one field while using the other.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Pair {
+    pub left: Vec<f64>,
+    pub right: Vec<f64>,
+}
+
+impl Pair {
+    /// Mutable references to both fields at once.
+    ///
+    /// The compiler tracks borrows of distinct fields separately, so
+    /// borrowing `left` and `right` at the same time is fine. Borrowing the
+    /// same field twice is not:
+    ///
+    /// ```compile_fail,E0499
+    /// use references_sharing_vs_mutation::references::Pair;
+    ///
+    /// let mut p = Pair::default();
+    /// let a = &mut p.left;
+    /// let b = &mut p.left; // cannot borrow `p.left` as mutable more than once
+    /// a.push(1.0);
+    /// b.push(2.0);
+    /// ```
+    pub fn both_mut(&mut self) -> (&mut Vec<f64>, &mut Vec<f64>) {
+        (&mut self.left, &mut self.right)
+    }
+}
+
+/// Left and right audio channels, each its own vector.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stereo {
+    pub left: Vec<f64>,
+    pub right: Vec<f64>,
+}
+
+impl Stereo {
+    /// Both channels, mutably, for processing them side by side:
+    ///
+    /// ```
+    /// use references_sharing_vs_mutation::references::Stereo;
+    ///
+    /// let mut stereo = Stereo::default();
+    /// let [left, right] = stereo.channels_mut();
+    /// left.extend(&[0.0, 1.0]);
+    /// right.extend(&[0.0, -1.0]);
+    /// assert_eq!(stereo.right, vec![0.0, -1.0]);
+    /// ```
+    ///
+    /// The returned array holds the only way to reach either channel, so
+    /// nothing else may touch them while it's in use:
+    ///
+    /// ```compile_fail,E0499
+    /// use references_sharing_vs_mutation::references::Stereo;
+    ///
+    /// let mut stereo = Stereo::default();
+    /// let left = &mut stereo.left;
+    /// let [_, right] = stereo.channels_mut(); // `stereo` already mutably borrowed
+    /// left.push(1.0);
+    /// right.push(1.0);
+    /// ```
+    pub fn channels_mut(&mut self) -> [&mut Vec<f64>; 2] {
+        [&mut self.left, &mut self.right]
+    }
+}
+
+/// Mutable references to two different elements of `slice`, returned in
+/// the order asked for.
+///
+/// `(&mut slice[i], &mut slice[j])` is rejected even when `i != j`, because
+/// the compiler can't see that at compile time. `split_at_mut` cuts the
+/// slice into two halves that provably don't overlap, and each reference
+/// comes from a different half. [`split::get_two_mut`](crate::split::get_two_mut)
+/// does the cutting, and returns `None` where this panics.
+///
+/// # Panics
+///
+/// If `i == j`, which would alias, or if either index is out of bounds.
+pub fn disjoint_mut<T>(slice: &mut [T], i: usize, j: usize) -> (&mut T, &mut T) {
+    assert!(i != j, "disjoint_mut: indices are both {}", i);
+    let len = slice.len();
+    assert!(
+        i < len && j < len,
+        "disjoint_mut: indices {} and {} out of bounds for length {}",
+        i,
+        j,
+        len
+    );
+    crate::split::get_two_mut(slice, i, j).expect("indices checked above")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mutates_both_fields_at_once() {
+        let mut p = Pair {
+            left: vec![1.0],
+            right: vec![2.0],
+        };
+        let (left, right) = p.both_mut();
+        left.push(right[0]);
+        right.push(left[0]);
+        assert_eq!(p.left, vec![1.0, 2.0]);
+        assert_eq!(p.right, vec![2.0, 1.0]);
+    }
+
+    #[test]
+    fn extends_both_channels() {
+        let mut stereo = Stereo {
+            left: vec![1.0],
+            right: vec![-1.0],
+        };
+        for (channel, samples) in stereo.channels_mut().iter_mut().zip(&[[0.5], [-0.5]]) {
+            channel.extend(samples);
+        }
+        assert_eq!(stereo.left, vec![1.0, 0.5]);
+        assert_eq!(stereo.right, vec![-1.0, -0.5]);
+    }
+
+    #[test]
+    fn disjoint_elements_in_either_order() {
+        let mut v = vec![1, 2, 3, 4];
+        {
+            let (a, b) = disjoint_mut(&mut v, 0, 3);
+            std::mem::swap(a, b);
+        }
+        assert_eq!(v, vec![4, 2, 3, 1]);
+
+        let (a, b) = disjoint_mut(&mut v, 2, 1);
+        assert_eq!((*a, *b), (3, 2));
+        *a += 10;
+        *b += 20;
+        assert_eq!(v, vec![4, 22, 13, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "indices are both 1")]
+    fn equal_indices_panic() {
+        let mut v = vec![1, 2, 3];
+        disjoint_mut(&mut v, 1, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn out_of_range_panics() {
+        let mut v = vec![1, 2, 3];
+        disjoint_mut(&mut v, 0, 3);
+    }
+}
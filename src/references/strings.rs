@@ -0,0 +1,129 @@
+use std::borrow::Cow;
+
+/// Upper-cases the first character of `s` in place.
+///
+/// Some characters upper-case to more than one (`ß` becomes `SS`), which
+/// is why this needs the whole `String` and not just a `&mut str`: the
+/// string may have to grow.
+///
+/// While any `&str` into the string is alive the string is frozen, so this
+/// can't be called:
+///
+/// ```compile_fail,E0502
+/// use references_sharing_vs_mutation::references::{capitalize_first, first_char};
+///
+/// let mut s = String::from("shared");
+/// let word = &s[..];
+/// capitalize_first(&mut s); // cannot borrow `s` as mutable because it is also borrowed as immutable
+/// first_char(word);
+/// ```
+pub fn capitalize_first(s: &mut String) {
+    let first = match s.chars().next() {
+        Some(c) => c,
+        None => return,
+    };
+    let upper: String = first.to_uppercase().collect();
+    s.replace_range(..first.len_utf8(), &upper);
+}
+
+/// The first character of `s`, if it has one. Reading only needs a
+/// shared borrow.
+pub fn first_char(s: &str) -> Option<char> {
+    s.chars().next()
+}
+
+/// `path` with repeated slashes, `.` components and any trailing slash
+/// removed, borrowing `path` itself when there is nothing to remove.
+///
+/// Most paths are already tidy, so most calls allocate nothing and return
+/// `Cow::Borrowed`; only a path that has to change comes back as an owned
+/// `String`. Either way the caller gets something that derefs to `&str`.
+/// `..` is left alone, since resolving it properly means asking the
+/// filesystem about symlinks. An empty path becomes `.`.
+pub fn maybe_normalize(path: &str) -> Cow<'_, str> {
+    if is_normalized(path) {
+        return Cow::Borrowed(path);
+    }
+    let absolute = path.starts_with('/');
+    let parts: Vec<&str> = path
+        .split('/')
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect();
+    let mut normalized = String::with_capacity(path.len());
+    if absolute {
+        normalized.push('/');
+    }
+    normalized.push_str(&parts.join("/"));
+    if normalized.is_empty() {
+        normalized.push('.');
+    }
+    Cow::Owned(normalized)
+}
+
+fn is_normalized(path: &str) -> bool {
+    if path == "/" || path == "." {
+        return true;
+    }
+    // Only the empty component before a leading slash is allowed.
+    !path.is_empty()
+        && path
+            .split('/')
+            .enumerate()
+            .all(|(i, part)| part != "." && (i == 0 || !part.is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capitalizes_in_place() {
+        let mut s = String::from("mutation");
+        capitalize_first(&mut s);
+        assert_eq!(s, "Mutation");
+
+        let mut s = String::from("ßig");
+        capitalize_first(&mut s);
+        assert_eq!(s, "SSig");
+
+        let mut s = String::new();
+        capitalize_first(&mut s);
+        assert_eq!(s, "");
+    }
+
+    #[test]
+    fn tidy_paths_are_borrowed() {
+        for &path in &["/", ".", "a", "/usr/lib", "../up", "a/../b"] {
+            match maybe_normalize(path) {
+                Cow::Borrowed(b) => assert_eq!(b.as_ptr(), path.as_ptr()),
+                Cow::Owned(o) => panic!("{:?} was copied into {:?}", path, o),
+            }
+        }
+    }
+
+    #[test]
+    fn untidy_paths_are_rewritten() {
+        let cases = [
+            ("//usr///lib/", "/usr/lib"),
+            ("./a/./b", "a/b"),
+            ("a/.", "a"),
+            ("/./", "/"),
+            ("./", "."),
+            ("", "."),
+        ];
+        for &(path, expected) in &cases {
+            match maybe_normalize(path) {
+                Cow::Owned(o) => assert_eq!(o, expected),
+                Cow::Borrowed(b) => panic!("{:?} came back unchanged as {:?}", path, b),
+            }
+        }
+    }
+
+    #[test]
+    fn reads_first_char() {
+        let s = String::from("élan");
+        assert_eq!(first_char(&s), Some('é'));
+        assert_eq!(first_char(&s[2..]), Some('l'));
+        assert_eq!(first_char(""), None);
+    }
+}
@@ -0,0 +1,39 @@
+/// Exchanges `v[i]` and `v[j]`, panicking if either is out of bounds.
+///
+/// The hand-written version works here only because `f64` is `Copy`:
+///
+/// ```text
+/// let tmp = v[i];
+/// v[i] = v[j];
+/// v[j] = tmp;
+/// ```
+///
+/// `let tmp = v[i]` copies the element out. For a `String` it would try to
+/// move out of the vector, leaving a hole in something we only borrowed, and
+/// Rust refuses. `slice::swap` (or `std::mem::swap` on two disjoint `&mut`s)
+/// exchanges the values without ever leaving either slot empty.
+#[allow(clippy::ptr_arg)] // Vec to match the `extend` examples in the notes.
+pub fn swap_elements(v: &mut Vec<f64>, i: usize, j: usize) {
+    v.swap(i, j);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swaps_valid_indices() {
+        let mut v = vec![1.0, 2.0, 3.0];
+        swap_elements(&mut v, 0, 2);
+        assert_eq!(v, vec![3.0, 2.0, 1.0]);
+        swap_elements(&mut v, 1, 1);
+        assert_eq!(v, vec![3.0, 2.0, 1.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_range_panics() {
+        let mut v = vec![1.0, 2.0];
+        swap_elements(&mut v, 0, 2);
+    }
+}
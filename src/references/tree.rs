@@ -0,0 +1,75 @@
+/// A tree that owns its children outright.
+///
+/// Ownership runs strictly downward, so the rules apply at every level: a
+/// shared borrow of a node freezes its whole subtree, and a mutable borrow
+/// of a node is the only way into its subtree until it ends. Changing one
+/// child while iterating over the children is caught like any other
+/// aliasing:
+///
+/// ```compile_fail,E0502
+/// use references_sharing_vs_mutation::references::Tree;
+///
+/// let mut tree = Tree::new(1, vec![Tree::leaf(2), Tree::leaf(3)]);
+/// for child in &tree.children {
+///     tree.children[0].scale(child.value); // `tree.children` is borrowed by the loop
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tree {
+    pub value: i32,
+    pub children: Vec<Tree>,
+}
+
+impl Tree {
+    pub fn new(value: i32, children: Vec<Tree>) -> Tree {
+        Tree { value, children }
+    }
+
+    pub fn leaf(value: i32) -> Tree {
+        Tree::new(value, Vec::new())
+    }
+
+    /// The sum of every value in the tree, through shared borrows all the
+    /// way down.
+    pub fn sum(&self) -> i32 {
+        self.value + self.children.iter().map(Tree::sum).sum::<i32>()
+    }
+
+    /// Multiplies every value in the tree by `k`. Each recursive call
+    /// reborrows one child mutably from the `&mut self` it was given.
+    pub fn scale(&mut self, k: i32) {
+        self.value *= k;
+        for child in &mut self.children {
+            child.scale(k);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn three_levels() -> Tree {
+        Tree::new(
+            1,
+            vec![
+                Tree::new(2, vec![Tree::leaf(4), Tree::leaf(5)]),
+                Tree::new(3, vec![Tree::leaf(6)]),
+            ],
+        )
+    }
+
+    #[test]
+    fn sums_every_level() {
+        assert_eq!(three_levels().sum(), 21);
+        assert_eq!(Tree::leaf(7).sum(), 7);
+    }
+
+    #[test]
+    fn scales_every_level() {
+        let mut tree = three_levels();
+        tree.scale(2);
+        assert_eq!(tree.sum(), 42);
+        assert_eq!(tree.children[0].children[1].value, 10);
+    }
+}
@@ -0,0 +1,196 @@
+//! Everything the crate draws or writes out, for snapshot tests.
+//!
+//! Content edits change the output of the diagrams, timelines,
+//! explanations, exported pages and tour screens, and it's easy not to look
+//! at all of it. [`all`] renders every one of them under a path-like name,
+//! `timelines/dangling-1.txt`, and `tests/render` compares each with the
+//! file of that name under `tests/render/snapshots`, so a change shows up
+//! as a failing test with a diff. `render --all` writes the files afresh
+//! once the change is the one wanted, and `render <name>` prints one.
+//!
+//! ```
+//! use references_sharing_vs_mutation::render;
+//!
+//! let chart = render::find("timelines/dangling-1.txt").unwrap();
+//! assert!(chart.text.contains("r = &v"));
+//! ```
+
+use std::fs;
+use std::io::{self, Cursor};
+use std::path::Path;
+use std::slice;
+
+use crate::cli::{tour, Lesson, LESSONS};
+use crate::diagram::Style;
+use crate::explain::Locale;
+use crate::export;
+use crate::progress::Progress;
+use crate::timeline::Timeline;
+
+/// One piece of output and the name of its snapshot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rendering {
+    /// A path relative to the snapshot directory.
+    pub name: String,
+    pub text: String,
+}
+
+fn rendering(name: String, text: String) -> Rendering {
+    Rendering {
+        name,
+        text: redact(&text),
+    }
+}
+
+/// Every rendering, lesson by lesson within each kind. A test checks the
+/// names are unique.
+pub fn all() -> Vec<Rendering> {
+    let mut out = Vec::new();
+    for lesson in LESSONS {
+        let diagrams = (lesson.diagrams)();
+        if diagrams.is_empty() {
+            continue;
+        }
+        for (style, suffix) in &[(Style::Unicode, "txt"), (Style::Ascii, "ascii.txt")] {
+            let text: Vec<String> = diagrams.iter().map(|d| d.render(*style)).collect();
+            out.push(rendering(
+                format!("diagrams/{}.{}", lesson.name, suffix),
+                text.join("\n"),
+            ));
+        }
+    }
+    for lesson in LESSONS {
+        for (n, example) in lesson.examples.iter().enumerate() {
+            if !example.events.is_empty() {
+                out.push(rendering(
+                    format!("timelines/{}-{}.txt", lesson.name, n + 1),
+                    Timeline::new(example.events).to_string(),
+                ));
+            }
+        }
+    }
+    for locale in Locale::ALL {
+        for lesson in LESSONS {
+            if lesson.explanations.iter().any(|(l, _)| l == locale) {
+                out.push(rendering(
+                    format!("explanations/{}/{}.txt", locale.code(), lesson.name),
+                    lesson.explanation(*locale).to_text(),
+                ));
+            }
+        }
+    }
+    out.push(rendering("export/SUMMARY.md".to_owned(), export::summary()));
+    for lesson in LESSONS {
+        out.push(rendering(
+            format!("export/{}.md", lesson.name),
+            export::lesson_page(lesson),
+        ));
+    }
+    for lesson in LESSONS {
+        out.push(rendering(
+            format!("tour/{}.txt", lesson.name),
+            tour_screen(lesson),
+        ));
+    }
+    out
+}
+
+/// The rendering called `name`.
+pub fn find(name: &str) -> Option<Rendering> {
+    all().into_iter().find(|r| r.name == name)
+}
+
+/// The tour of `lesson`, answering every example rightly. The demos print
+/// to stdout rather than to the screen being drawn, so the tour is of the
+/// lesson with its demo left out; the line introducing it stays.
+fn tour_screen(lesson: &Lesson) -> String {
+    let quiet = Lesson {
+        demo: || {},
+        ..*lesson
+    };
+    let answers: String = lesson
+        .examples
+        .iter()
+        .map(|e| if e.compiles() { "y\n" } else { "n\n" })
+        .collect();
+    let mut out = Vec::new();
+    tour(
+        slice::from_ref(&quiet),
+        Locale::En,
+        &mut Progress::default(),
+        &mut Cursor::new(answers),
+        &mut out,
+    )
+    .expect("writing to a Vec doesn't fail");
+    String::from_utf8(out).expect("the tour writes UTF-8")
+}
+
+/// `text` with each hex address, which changes from run to run, replaced
+/// by `0x…`.
+fn redact(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(at) = rest.find("0x") {
+        out.push_str(&rest[..at]);
+        let after = &rest[at + 2..];
+        let digits = after
+            .find(|c: char| !c.is_ascii_hexdigit())
+            .unwrap_or(after.len());
+        if digits == 0 {
+            out.push_str("0x");
+        } else {
+            out.push_str("0x…");
+        }
+        rest = &after[digits..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Writes every rendering under `dir`, creating directories as need be and
+/// replacing what's there, and returns how many it wrote. Snapshots of
+/// output that no longer exists are left for the tests to point out.
+pub fn write_all(dir: &Path) -> io::Result<usize> {
+    let renderings = all();
+    for r in &renderings {
+        let path = dir.join(&r.name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, &r.text)?;
+    }
+    Ok(renderings.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_unique() {
+        let all = all();
+        let mut names: Vec<&str> = all.iter().map(|r| r.name.as_str()).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), all.len());
+    }
+
+    #[test]
+    fn redacts_addresses() {
+        assert_eq!(
+            redact("moved from 0x7f3a10 to 0x55e0c4a8 (capacity 4 -> 8)"),
+            "moved from 0x… to 0x… (capacity 4 -> 8)"
+        );
+        assert_eq!(redact("0x, 0xz"), "0x, 0xz");
+    }
+
+    #[test]
+    fn tours_without_running_demos() {
+        let screen = find("tour/exclusive.txt").unwrap().text;
+        assert!(screen.starts_with("\n== Lesson 1 of 1: "));
+        assert!(screen.contains("It compiles. The lesson's demo runs it:\n"));
+        assert!(!screen.contains("one period"));
+        assert!(screen.contains("after the fifth push: buffer moved from 0x… to 0x…"));
+        assert!(screen.ends_with("right.\n"));
+    }
+}
@@ -0,0 +1,217 @@
+//! Structs that borrow from themselves, and what to write instead.
+//!
+//! A parser that owns its input and keeps a cursor into it is the obvious
+//! shape:
+//!
+//! ```compile_fail,E0505
+//! struct Parser<'a> {
+//!     buf: String,
+//!     cursor: &'a str,
+//! }
+//!
+//! fn parser<'a>(text: &str) -> Parser<'a> {
+//!     let buf = text.to_owned();
+//!     let cursor = buf.as_str();
+//!     Parser { buf, cursor } // cannot move out of `buf` because it is borrowed
+//! }
+//! ```
+//!
+//! but it can't be built. `cursor` borrows `buf`, and putting `buf` in the
+//! struct moves it, which a shared borrow forbids; returning the struct
+//! would also return a reference to the function's local. No lifetime `'a`
+//! can name "as long as this struct's own field", so the borrow checker
+//! has no way to know the reference and what it points into travel
+//! together.
+//!
+//! [`Parser`] keeps a position instead of a reference: an index is only a
+//! number, so the struct owns everything in it and moves freely, and
+//! `rest` turns the index back into a `&str` whenever it's asked, borrowed
+//! from `&self` for as long as that lasts.
+//!
+//! [`PinnedParser`] keeps the real pointer, the way crates like
+//! `ouroboros` do behind a macro. The reference becomes a raw pointer the
+//! borrow checker doesn't follow, and the struct takes on the checks
+//! itself: the text is never changed after the cursor points into it, and
+//! the struct is pinned, so it never moves either. A `String`'s bytes are
+//! on the heap and wouldn't move with the struct in any case; pinning is
+//! what makes the pattern sound for data held inline, and costs nothing
+//! here.
+
+use std::marker::PhantomPinned;
+use std::pin::Pin;
+use std::ptr::NonNull;
+
+/// The next word of `rest` and what follows it, skipping leading
+/// whitespace. `None` if there are no words left.
+fn split_word(rest: &str) -> Option<(&str, &str)> {
+    let rest = rest.trim_start();
+    if rest.is_empty() {
+        return None;
+    }
+    let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    Some(rest.split_at(end))
+}
+
+/// Splits owned text into words, remembering how far it's got as an index.
+///
+/// ```
+/// use references_sharing_vs_mutation::selfref::Parser;
+///
+/// let mut p = Parser::new("let x = 1;");
+/// assert_eq!(p.next_word(), Some("let"));
+/// let moved = p; // fine: there's no reference in it to invalidate
+/// assert_eq!(moved.rest(), " x = 1;");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parser {
+    text: String,
+    pos: usize,
+}
+
+impl Parser {
+    pub fn new<S: Into<String>>(text: S) -> Parser {
+        Parser {
+            text: text.into(),
+            pos: 0,
+        }
+    }
+
+    /// The text not yet split off.
+    pub fn rest(&self) -> &str {
+        &self.text[self.pos..]
+    }
+
+    /// The next word, or `None` once there are no more.
+    pub fn next_word(&mut self) -> Option<&str> {
+        let start = self.pos;
+        let (word, after) = split_word(&self.text[start..])?;
+        self.pos = self.text.len() - after.len();
+        Some(word)
+    }
+}
+
+/// [`Parser`] with a pointer into its text in place of an index.
+///
+/// It's only ever handed out pinned, and its methods take `self` pinned, so
+/// safe code can't move it or swap its text out from under the cursor.
+///
+/// ```
+/// use references_sharing_vs_mutation::selfref::PinnedParser;
+///
+/// let mut p = PinnedParser::new("let x = 1;");
+/// assert_eq!(p.as_mut().next_word(), Some("let"));
+/// assert_eq!(p.as_ref().rest(), " x = 1;");
+/// ```
+#[derive(Debug)]
+pub struct PinnedParser {
+    text: String,
+    /// Always a slice of `text`, which nothing changes after `new`.
+    cursor: NonNull<str>,
+    _pinned: PhantomPinned,
+}
+
+impl PinnedParser {
+    pub fn new<S: Into<String>>(text: S) -> Pin<Box<PinnedParser>> {
+        let mut parser = Box::pin(PinnedParser {
+            text: text.into(),
+            cursor: NonNull::from(""),
+            _pinned: PhantomPinned,
+        });
+        let cursor = NonNull::from(parser.text.as_str());
+        // SAFETY: this writes a field in place; nothing is moved out of
+        // the pinned value.
+        unsafe { parser.as_mut().get_unchecked_mut().cursor = cursor };
+        parser
+    }
+
+    /// The text not yet split off.
+    pub fn rest(self: Pin<&Self>) -> &str {
+        // SAFETY: `cursor` points into `text`, which outlives this borrow
+        // of the parser and isn't changed while it lasts.
+        unsafe { self.get_ref().cursor.as_ref() }
+    }
+
+    /// The next word, or `None` once there are no more.
+    pub fn next_word(self: Pin<&mut Self>) -> Option<&str> {
+        // SAFETY: only `cursor` is written, in place; the parser isn't
+        // moved.
+        let this = unsafe { self.get_unchecked_mut() };
+        // SAFETY: as in `rest`. The word returned borrows the parser
+        // mutably, so `text` can't be reached any other way while it lives.
+        let rest = unsafe { this.cursor.as_ref() };
+        let (word, after) = split_word(rest)?;
+        this.cursor = NonNull::from(after);
+        Some(word)
+    }
+}
+
+/// Splits the same line with both parsers, moving the index-based one
+/// half-way through to show it doesn't mind.
+pub fn demo() {
+    let line = "let wave = vec![0.0, 1.0];";
+    let mut parser = Parser::new(line);
+    let first = parser.next_word().map(str::to_owned);
+    let mut moved = parser;
+    println!(
+        "index cursor: {:?}, then {:?} after moving the parser",
+        first,
+        moved.next_word()
+    );
+
+    let mut pinned = PinnedParser::new(line);
+    let mut words = Vec::new();
+    while let Some(word) = pinned.as_mut().next_word() {
+        words.push(word.to_owned());
+    }
+    println!("pinned cursor: {:?}", words);
+}
+
+#[cfg(test)]
+mod tests {
+    //! The pinned parser's tests are the ones that matter under Miri, which
+    //! checks its pointer stays valid: `cargo +nightly miri test --lib
+    //! selfref`.
+
+    use super::*;
+
+    const LINE: &str = "  let x =\t1;  ";
+
+    #[test]
+    fn index_parser_splits_words() {
+        let mut p = Parser::new(LINE);
+        let mut words = Vec::new();
+        while let Some(w) = p.next_word() {
+            words.push(w.to_owned());
+        }
+        assert_eq!(words, ["let", "x", "=", "1;"]);
+        assert_eq!(p.rest(), "  ");
+        assert_eq!(p.next_word(), None);
+    }
+
+    #[test]
+    fn pinned_parser_agrees_with_the_index_one() {
+        let mut index = Parser::new(LINE);
+        let mut pinned = PinnedParser::new(LINE);
+        loop {
+            let word = pinned.as_mut().next_word().map(str::to_owned);
+            assert_eq!(word.as_deref(), index.next_word());
+            assert_eq!(pinned.as_ref().rest(), index.rest());
+            if word.is_none() {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn pinned_parser_survives_its_box_moving() {
+        let mut parsers = Vec::new();
+        for line in &["one two", "three", ""] {
+            parsers.push(PinnedParser::new(*line));
+        }
+        let mut last = parsers.swap_remove(0);
+        assert_eq!(last.as_mut().next_word(), Some("one"));
+        assert_eq!(parsers[0].as_ref().rest(), "");
+        assert_eq!(parsers[1].as_mut().next_word(), Some("three"));
+        assert_eq!(last.as_ref().rest(), " two");
+    }
+}
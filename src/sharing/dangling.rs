@@ -0,0 +1,72 @@
+//! Moving a value out from under a reference.
+//!
+//! Here `v` stays in scope for the whole of `r`'s lifetime, but its vector
+//! is moved to `aside`, leaving `v` uninitialized and `r` pointing at
+//! nothing:
+//!
+//! ```compile_fail,E0505
+//! let v = vec![4, 8, 19, 27, 34, 10];
+//! let r = &v;
+//! let aside = v; // cannot move out of `v` because it is borrowed
+//! r[0]; // would use `v`, which is now uninitialized
+//! ```
+//!
+//! A shared reference makes its referent read-only for its whole lifetime,
+//! and that includes not moving it. `r`'s lifetime contains the move, so
+//! the program is rejected. Ending the borrow before the move fixes it; see
+//! [`demo`]. [`diagrams`] draws both programs at the point of the move.
+
+use crate::diagram::{Diagram, Target};
+
+/// Borrows the vector in an inner block and moves it once the borrow is
+/// over, returning the moved vector's first element.
+pub fn read_then_move() -> i32 {
+    let v = vec![4, 8, 19, 27, 34, 10];
+    let first = {
+        let r = &v;
+        r[0] // ok, the vector is still there
+    };
+    let aside = v;
+    assert_eq!(aside[0], first);
+    first
+}
+
+/// `v`'s vector just after the move: in the rejected program `r` still
+/// points at `v`, which no longer owns anything; in the fixed one `r` is
+/// already gone.
+pub fn diagrams() -> Vec<Diagram> {
+    let values = ["4", "8", "19", "27", "34", "10"];
+    vec![
+        Diagram::new()
+            .caption("rejected: `r` outlives the move")
+            .stack_var("v")
+            .value("moved")
+            .stack_var("aside")
+            .heap_buffer(6)
+            .values(&values)
+            .ptr("r", Target::Var("v")),
+        Diagram::new()
+            .caption("fixed: `r` ended before the move")
+            .stack_var("v")
+            .value("moved")
+            .stack_var("aside")
+            .heap_buffer(6)
+            .values(&values),
+    ]
+}
+
+/// Runs the fixed version: the reference's lifetime ends before `v` is
+/// moved aside, and all is well.
+pub fn demo() {
+    println!("read {} through r, then moved v aside", read_then_move());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves_after_the_borrow_ends() {
+        assert_eq!(read_then_move(), 4);
+    }
+}
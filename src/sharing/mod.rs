@@ -0,0 +1,13 @@
+//! The first rule: shared access is read-only access.
+//!
+//! Across the lifetime of a shared reference, neither its referent nor
+//! anything reachable from it can be changed by anything. There are no live
+//! mutable references into that structure, its owner is held read-only, and
+//! nothing can be moved out of it. It's frozen.
+//!
+//! Rust already makes sure no reference outlives the variable it points to.
+//! These modules cover the other ways a reference could dangle, and what
+//! you may still borrow from a reference you already hold.
+
+pub mod dangling;
+pub mod reborrow;
@@ -0,0 +1,88 @@
+//! Borrowing from a reference you already hold.
+//!
+//! A shared reference can lend out more shared references to its referent
+//! or anything inside it, but never a mutable one, since that would make a
+//! frozen value writable:
+//!
+//! ```compile_fail,E0596
+//! let mut w = (107, 109);
+//! let r = &w;
+//! let r0 = &r.0; // ok, reborrowing shared as shared
+//! let m1 = &mut r.1; // cannot borrow `r.1` as mutable, as it is behind a `&` reference
+//! # let _ = (r0, m1);
+//! ```
+//!
+//! A mutable reference can lend out either kind, as long as the loans
+//! don't overlap each other. What it can't do is share its referent with
+//! any path that doesn't go through it:
+//!
+//! ```compile_fail,E0503
+//! let mut v = (136, 139);
+//! let m = &mut v;
+//! let m0 = &mut m.0; // ok, reborrowing mutable from mutable
+//! let r1 = &m.1; // ok, reborrowing shared from mutable, and doesn't overlap with m0
+//! v.1; // cannot use `v.1` because it was mutably borrowed
+//! *m0 += *r1;
+//! ```
+
+use crate::diagram::{Diagram, Target};
+
+/// Reads both fields of `w` through references borrowed from one shared
+/// reference.
+pub fn shared_from_shared(w: &(i32, i32)) -> i32 {
+    let r0 = &w.0;
+    let r1 = &w.1;
+    *r0 + *r1
+}
+
+/// Bumps `v.0` by `v.1` through a mutable and a shared reference both
+/// borrowed from `v`. They may coexist because they cover different fields.
+pub fn split_from_mutable(v: &mut (i32, i32)) {
+    let m0 = &mut v.0;
+    let r1 = &v.1;
+    *m0 += *r1;
+}
+
+/// The two legal reborrows: shared from shared, and a disjoint mutable and
+/// shared pair from one mutable reference.
+pub fn diagrams() -> Vec<Diagram> {
+    vec![
+        Diagram::new()
+            .caption("shared from shared: everything stays read-only")
+            .stack_var("w")
+            .value("(107, 109)")
+            .ptr("r", Target::Var("w"))
+            .ptr("r0", Target::Var("w.0")),
+        Diagram::new()
+            .caption("from `m`: each field reached one way only")
+            .stack_var("v")
+            .value("(136, 139)")
+            .mut_ptr("m", Target::Var("v"))
+            .mut_ptr("m0", Target::Var("v.0"))
+            .ptr("r1", Target::Var("v.1")),
+    ]
+}
+
+/// Runs both kinds of reborrowing on the notes' pairs.
+pub fn demo() {
+    let w = (107, 109);
+    println!("{:?} sums to {}", w, shared_from_shared(&w));
+
+    let mut v = (136, 139);
+    split_from_mutable(&mut v);
+    println!("(136, 139) becomes {:?}", v);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reborrows_both_ways() {
+        assert_eq!(shared_from_shared(&(107, 109)), 216);
+
+        let mut v = (136, 139);
+        split_from_mutable(&mut v);
+        assert_eq!(v, (275, 139));
+    }
+}
@@ -0,0 +1,168 @@
+//! Splitting one mutable borrow into several that don't overlap.
+//!
+//! Mutable access is exclusive, but exclusive to a place, not to a whole
+//! value. The compiler tracks each field separately, so `&mut p.left` and
+//! `&mut p.right` can be held at once, as
+//! [`Pair::both_mut`](crate::references::Pair::both_mut) does. It only
+//! sees the fields apart where they're named, though; a method call
+//! borrows all of `self`, even when the method only touches one field:
+//!
+//! ```compile_fail,E0502
+//! struct Playlist {
+//!     songs: Vec<String>,
+//!     log: Vec<String>,
+//! }
+//!
+//! impl Playlist {
+//!     fn note(&mut self, line: String) {
+//!         self.log.push(line);
+//!     }
+//!
+//!     fn announce(&mut self) {
+//!         for song in &self.songs {
+//!             self.note(format!("now playing {}", song)); // cannot borrow `*self` as mutable because it is also borrowed as immutable
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! [`Playlist::announce`] borrows the two fields by name instead.
+//!
+//! Elements of a slice aren't told apart at all, since an index is only
+//! known at run time, so two `&mut` into one vector are rejected however
+//! different the indices:
+//!
+//! ```compile_fail,E0499
+//! let mut v = vec![1, 2, 3];
+//! let (a, b) = (&mut v[0], &mut v[2]); // cannot borrow `v` as mutable more than once at a time
+//! std::mem::swap(a, b);
+//! ```
+//!
+//! `split_at_mut` turns one borrow of a slice into borrows of two halves
+//! that can't overlap, checking where to cut at run time. [`get_two_mut`]
+//! cuts between two indices that way, after checking they're in bounds and
+//! not the same element, so each reference comes from its own half.
+
+/// Mutable references to the elements at `i` and `j`, in that order, or
+/// `None` if either is out of bounds or they're the same element, which
+/// would be two `&mut` to one place.
+///
+/// ```
+/// use references_sharing_vs_mutation::split::get_two_mut;
+///
+/// let mut v = vec![1, 2, 3];
+/// if let Some((a, b)) = get_two_mut(&mut v, 2, 0) {
+///     std::mem::swap(a, b);
+/// }
+/// assert_eq!(v, [3, 2, 1]);
+/// assert!(get_two_mut(&mut v, 1, 1).is_none());
+/// ```
+pub fn get_two_mut<T>(slice: &mut [T], i: usize, j: usize) -> Option<(&mut T, &mut T)> {
+    if i == j || i >= slice.len() || j >= slice.len() {
+        return None;
+    }
+    if i < j {
+        let (low, high) = slice.split_at_mut(j);
+        Some((&mut low[i], &mut high[0]))
+    } else {
+        let (low, high) = slice.split_at_mut(i);
+        Some((&mut high[0], &mut low[j]))
+    }
+}
+
+/// Moves `amount` from `balances[from]` to `balances[to]`. Returns whether
+/// it did: not if the accounts are the same or don't exist, or `from`
+/// holds less than `amount`.
+pub fn transfer(balances: &mut [u64], from: usize, to: usize, amount: u64) -> bool {
+    match get_two_mut(balances, from, to) {
+        Some((source, dest)) if *source >= amount => {
+            *source -= amount;
+            *dest += amount;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Songs, and a log of what's been done with them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Playlist {
+    pub songs: Vec<String>,
+    pub log: Vec<String>,
+}
+
+impl Playlist {
+    /// Logs each song as it's played. The loop reads `songs` while `log`
+    /// is pushed to, which is fine because each is borrowed by name rather
+    /// than through a method on all of `self`.
+    pub fn announce(&mut self) {
+        let log = &mut self.log;
+        for song in &self.songs {
+            log.push(format!("now playing {}", song));
+        }
+    }
+}
+
+/// Swaps, transfers and announces, each through a split borrow.
+pub fn demo() {
+    let mut wave = vec![0, 1, 0, -1];
+    if let Some((first, last)) = get_two_mut(&mut wave, 0, 3) {
+        std::mem::swap(first, last);
+    }
+    println!("first and last swapped: {:?}", wave);
+
+    let mut balances = vec![100, 20];
+    let moved = transfer(&mut balances, 0, 1, 30);
+    println!("transferred: {}, balances now {:?}", moved, balances);
+
+    let mut playlist = Playlist {
+        songs: vec![String::from("Hercules"), String::from("Korobeiniki")],
+        log: Vec::new(),
+    };
+    playlist.announce();
+    println!("{:?}", playlist.log);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gets_two_elements_in_either_order() {
+        let mut v = vec![1, 2, 3, 4];
+        let (a, b) = get_two_mut(&mut v, 3, 1).unwrap();
+        assert_eq!((*a, *b), (4, 2));
+        *a += 10;
+        *b += 20;
+        assert_eq!(v, [1, 22, 3, 14]);
+    }
+
+    #[test]
+    fn refuses_aliasing_and_out_of_bounds_indices() {
+        let mut v = vec![1, 2, 3];
+        assert!(get_two_mut(&mut v, 1, 1).is_none());
+        assert!(get_two_mut(&mut v, 0, 3).is_none());
+        assert!(get_two_mut(&mut v, 3, 0).is_none());
+        assert!(get_two_mut::<i32>(&mut [], 0, 1).is_none());
+    }
+
+    #[test]
+    fn transfers_only_what_there_is() {
+        let mut balances = vec![50, 0];
+        assert!(transfer(&mut balances, 0, 1, 30));
+        assert!(!transfer(&mut balances, 0, 1, 30));
+        assert!(!transfer(&mut balances, 1, 1, 10));
+        assert!(!transfer(&mut balances, 0, 2, 10));
+        assert_eq!(balances, [20, 30]);
+    }
+
+    #[test]
+    fn logs_every_song() {
+        let mut playlist = Playlist {
+            songs: vec!["a".to_owned(), "b".to_owned()],
+            log: vec!["start".to_owned()],
+        };
+        playlist.announce();
+        assert_eq!(playlist.log, ["start", "now playing a", "now playing b"]);
+    }
+}
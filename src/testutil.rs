@@ -0,0 +1,60 @@
+use std::fs;
+use std::os::unix::io::{FromRawFd, IntoRawFd};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::fd::File;
+
+static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+/// A scratch directory under the system temp dir, removed on drop.
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub fn new() -> TempDir {
+        let n = NEXT.fetch_add(1, Ordering::Relaxed);
+        let path =
+            std::env::temp_dir().join(format!("sharing-vs-mutation-{}-{}", std::process::id(), n));
+        fs::create_dir_all(&path).unwrap();
+        TempDir { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Path to `name` inside the directory.
+    pub fn join(&self, name: &str) -> PathBuf {
+        self.path.join(name)
+    }
+
+    /// Creates `name` with the given contents and returns its path.
+    pub fn file(&self, name: &str, contents: &[u8]) -> PathBuf {
+        let path = self.join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// A pipe whose read end is one of our `File`s and whose write end is a
+/// `std::fs::File`, for feeding data in from tests.
+pub fn pipe() -> (File, fs::File) {
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    let reader = File::from_descriptor(fds[0]);
+    let writer = unsafe { fs::File::from_raw_fd(fds[1]) };
+    (reader, writer)
+}
+
+/// Takes over the descriptor of a `std::fs::File` opened by the test.
+pub fn adopt(f: fs::File) -> File {
+    File::from_descriptor(f.into_raw_fd())
+}
@@ -0,0 +1,380 @@
+//! Lifetimes drawn as bars, to show where two of them collide.
+//!
+//! A [`Timeline`] takes the same [`Event`]s as the borrow checker and lays
+//! them out left to right, one column per event and one row per variable.
+//! A value's row runs from when it comes into being until it's moved out or
+//! goes out of scope; a reference's row runs from its borrow to its last
+//! use, as rustc's non-lexical lifetimes do. Where the checker finds an
+//! error, the references live across it are drawn with `#` and the error
+//! is printed under its column:
+//!
+//! ```
+//! use references_sharing_vs_mutation::borrowck::{Borrow, Event};
+//! use references_sharing_vs_mutation::timeline::Timeline;
+//!
+//! let events = [
+//!     Event::Let("v"),
+//!     Event::Borrow { name: "r", kind: Borrow::Shared, place: "v" },
+//!     Event::Move { from: "v", to: "aside" },
+//!     Event::Use("r"),
+//! ];
+//! let chart = Timeline::new(&events).to_string();
+//! let lines: Vec<&str> = chart.lines().collect();
+//! assert_eq!(lines[1], "v       o===*===x");
+//! assert_eq!(lines[2], "r = &v      &---#---*");
+//! assert_eq!(lines[3], "aside           o====");
+//! assert_eq!(
+//!     lines[4],
+//!     "                ^ error[E0505]: cannot move out of `v` because it is borrowed"
+//! );
+//! ```
+
+use std::fmt;
+
+use crate::borrowck::{check, last_uses, place_of, root, Borrow, Event, Violation};
+
+/// How far apart the event columns are.
+const COLUMN: usize = 4;
+
+const KEY: &str = "o comes into being  = holds its value  x moved out  | out of scope\n\
+& borrowed  - live  * used  # live where the error is";
+
+/// A chart of `events`, with the violations the borrow checker finds in
+/// them.
+#[derive(Debug)]
+pub struct Timeline<'a> {
+    events: &'a [Event<'a>],
+    violations: Vec<Violation>,
+}
+
+/// One variable's bar: a mark per column it's active in, and whether the
+/// bar carries on to the next column.
+struct Row {
+    label: String,
+    marks: Vec<Option<char>>,
+    links: Vec<bool>,
+    fill: char,
+}
+
+/// A reference variable's loan, for finding which are live across an
+/// error.
+struct Loan<'a> {
+    row: usize,
+    name: &'a str,
+    /// The value variable it borrows from, through any reborrows.
+    target: &'a str,
+    /// The references it was reborrowed through.
+    through: Vec<&'a str>,
+    start: usize,
+    last_use: Option<usize>,
+}
+
+impl<'a> Timeline<'a> {
+    pub fn new(events: &'a [Event<'a>]) -> Timeline<'a> {
+        Timeline {
+            events,
+            violations: check(events),
+        }
+    }
+
+    /// What the borrow checker makes of the events.
+    pub fn violations(&self) -> &[Violation] {
+        &self.violations
+    }
+
+    fn render(&self) -> String {
+        let events = self.events;
+        let last_use = last_uses(events);
+        let mut names: Vec<&str> = Vec::new();
+        let mut loans: Vec<Loan> = Vec::new();
+        for (at, event) in events.iter().enumerate() {
+            let name = match *event {
+                Event::Let(name) | Event::Move { to: name, .. } => name,
+                Event::Borrow { name, place, .. } => {
+                    let from = root(place);
+                    let (target, through) = match loans.iter().rev().find(|l| l.name == from) {
+                        Some(l) => {
+                            let mut through = vec![l.name];
+                            through.extend_from_slice(&l.through);
+                            (l.target, through)
+                        }
+                        None => (from, Vec::new()),
+                    };
+                    loans.push(Loan {
+                        row: 0,
+                        name,
+                        target,
+                        through,
+                        start: at,
+                        last_use: last_use[loans.len()],
+                    });
+                    name
+                }
+                _ => continue,
+            };
+            let row = match names.iter().position(|&n| n == name) {
+                Some(row) => row,
+                None => {
+                    names.push(name);
+                    names.len() - 1
+                }
+            };
+            if let Event::Borrow { .. } = *event {
+                loans.last_mut().unwrap().row = row;
+            }
+        }
+
+        let mut rows: Vec<Row> = names.iter().map(|&name| self.value_row(name)).collect();
+        for loan in &loans {
+            self.draw_loan(&mut rows[loan.row], loan);
+        }
+        for violation in &self.violations {
+            let at = violation.at;
+            let place = place_of(&events[at]).map(root).unwrap_or("");
+            let (target, via) = match loans.iter().rev().find(|l| l.name == place && l.start < at) {
+                Some(l) => {
+                    let mut via = vec![l.name];
+                    via.extend_from_slice(&l.through);
+                    (l.target, via)
+                }
+                None => (place, Vec::new()),
+            };
+            for loan in &loans {
+                let live = loan.start < at && loan.last_use.is_some_and(|last| last > at);
+                if live && loan.target == target && !via.contains(&loan.name) {
+                    rows[loan.row].marks[at] = Some('#');
+                }
+            }
+        }
+
+        let width = rows.iter().map(|r| r.label.len()).max().unwrap_or(0) + 2;
+        let mut out = String::new();
+        let header: String = (0..events.len())
+            .map(|at| format!("{:<width$}", at, width = COLUMN))
+            .collect();
+        out.push_str(format!("{:width$}{}", "", header, width = width).trim_end());
+        out.push('\n');
+        for row in &rows {
+            let mut line = format!("{:<width$}", row.label, width = width);
+            for (at, mark) in row.marks.iter().enumerate() {
+                line.push(mark.unwrap_or(' '));
+                if at + 1 < events.len() {
+                    let gap = if row.links[at] { row.fill } else { ' ' };
+                    line.extend(std::iter::repeat_n(gap, COLUMN - 1));
+                }
+            }
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        for violation in &self.violations {
+            let column = width + violation.at * COLUMN;
+            out.push_str(&format!(
+                "{:column$}^ error[{}]: {}\n",
+                "",
+                violation.code,
+                violation.message,
+                column = column
+            ));
+        }
+        out.push('\n');
+        for (at, event) in events.iter().enumerate() {
+            out.push_str(&format!("{:>3}: {}\n", at, describe(event)));
+        }
+        out.push('\n');
+        out.push_str(KEY);
+        out.push('\n');
+        out
+    }
+
+    /// The bar for `name` as a value: from its `let` until it's moved out
+    /// or dropped, with a mark wherever it's touched. References get their
+    /// bars from `draw_loan`, over the top.
+    fn value_row(&self, name: &str) -> Row {
+        let len = self.events.len();
+        let mut row = Row {
+            label: name.to_owned(),
+            marks: vec![None; len],
+            links: vec![false; len],
+            fill: '=',
+        };
+        let mut alive = false;
+        for (at, event) in self.events.iter().enumerate() {
+            let touched = place_of(event).map(root) == Some(name);
+            let mark = match *event {
+                Event::Let(n) | Event::Move { to: n, .. } if n == name => {
+                    alive = true;
+                    'o'
+                }
+                Event::Move { from, .. } if from == name => {
+                    alive = false;
+                    'x'
+                }
+                Event::Assign(place) if place == name && !alive => {
+                    alive = true;
+                    'o'
+                }
+                Event::Drop(n) if n == name => {
+                    alive = false;
+                    '|'
+                }
+                _ if touched => '*',
+                _ if alive => '=',
+                _ => continue,
+            };
+            row.marks[at] = Some(mark);
+            row.links[at] = alive;
+        }
+        row
+    }
+
+    fn draw_loan(&self, row: &mut Row, loan: &Loan) {
+        let kind = match self.events[loan.start] {
+            Event::Borrow {
+                kind: Borrow::Mut,
+                place,
+                ..
+            } => format!("&mut {}", place),
+            Event::Borrow { place, .. } => format!("&{}", place),
+            _ => unreachable!(),
+        };
+        row.label = format!("{} = {}", loan.name, kind);
+        row.fill = '-';
+        row.marks = vec![None; self.events.len()];
+        row.links = vec![false; self.events.len()];
+        row.marks[loan.start] = Some('&');
+        let end = loan.last_use.unwrap_or(loan.start);
+        for at in loan.start + 1..=end {
+            let through = place_of(&self.events[at]).map(root) == Some(loan.name);
+            row.marks[at] = Some(if through { '*' } else { '-' });
+        }
+        for link in &mut row.links[loan.start..end] {
+            *link = true;
+        }
+    }
+}
+
+impl fmt::Display for Timeline<'_> {
+    /// The chart, the errors under it, a numbered list of the events and a
+    /// key to the marks.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render())
+    }
+}
+
+/// An event as the Rust it stands for.
+fn describe(event: &Event) -> String {
+    match *event {
+        Event::Let(name) => format!("let {}", name),
+        Event::Borrow {
+            name,
+            kind: Borrow::Shared,
+            place,
+        } => format!("let {} = &{}", name, place),
+        Event::Borrow {
+            name,
+            kind: Borrow::Mut,
+            place,
+        } => format!("let {} = &mut {}", name, place),
+        Event::Move { from, to } => format!("let {} = {}", to, from),
+        Event::Assign(place) => format!("assign to {}", place),
+        Event::Use(place) => format!("use {}", place),
+        Event::Drop(name) => format!("{} goes out of scope", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::borrowck::Borrow::{Mut, Shared};
+    use crate::borrowck::Event::{Assign, Drop, Let, Use};
+
+    fn borrow<'a>(name: &'a str, kind: Borrow, place: &'a str) -> Event<'a> {
+        Event::Borrow { name, kind, place }
+    }
+
+    /// The chart without the event list and key.
+    fn chart(events: &[Event]) -> String {
+        let rendered = Timeline::new(events).to_string();
+        format!("\n{}\n", rendered.split("\n\n").next().unwrap())
+    }
+
+    #[test]
+    fn draws_the_fix_without_conflicts() {
+        let events = [
+            Let("v"),
+            borrow("r", Shared, "v"),
+            Use("r"),
+            Drop("r"),
+            Event::Move {
+                from: "v",
+                to: "aside",
+            },
+            Use("aside"),
+        ];
+        assert!(Timeline::new(&events).violations().is_empty());
+        assert_eq!(
+            chart(&events),
+            "
+        0   1   2   3   4   5
+v       o===*===========x
+r = &v      &---*
+aside                   o===*
+"
+        );
+    }
+
+    #[test]
+    fn marks_every_loan_live_across_the_error() {
+        let events = [
+            Let("x"),
+            borrow("r1", Shared, "x"),
+            borrow("r2", Shared, "x"),
+            Assign("x"),
+            Use("r1"),
+            Use("r2"),
+        ];
+        assert_eq!(
+            chart(&events),
+            "
+         0   1   2   3   4   5
+x        o===*===*===*========
+r1 = &x      &-------#---*
+r2 = &x          &---#-------*
+                     ^ error[E0506]: cannot assign to `x` because it is borrowed
+"
+        );
+    }
+
+    #[test]
+    fn reborrows_extend_their_source() {
+        let events = [
+            Let("v"),
+            borrow("m", Mut, "v"),
+            borrow("m0", Mut, "m.0"),
+            Use("v.1"),
+            Assign("m0"),
+        ];
+        let chart = chart(&events);
+        assert!(
+            chart.contains("\nm = &mut v         &---*---#----\n"),
+            "{}",
+            chart
+        );
+        assert!(
+            chart.contains("\nm0 = &mut m.0          &---#---*\n"),
+            "{}",
+            chart
+        );
+        assert!(chart.contains("^ error[E0503]: cannot use `v.1`"));
+    }
+
+    #[test]
+    fn lists_the_events() {
+        let events = [Let("y"), borrow("m", Mut, "y"), Assign("m"), Drop("y")];
+        let rendered = Timeline::new(&events).to_string();
+        assert!(rendered.contains(
+            "\n  0: let y\n  1: let m = &mut y\n  2: assign to m\n  3: y goes out of scope\n"
+        ));
+        assert!(rendered.ends_with(&format!("{}\n", KEY)));
+    }
+}
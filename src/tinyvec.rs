@@ -0,0 +1,397 @@
+//! A vector small enough to watch it move.
+//!
+//! The `extend(&mut wave, &wave)` lesson turns on reallocation: a push that
+//! finds the buffer full copies the elements somewhere bigger and frees the
+//! old buffer, and any slice into the old one dangles. `Vec` does this out of
+//! sight, and may even grow in place. [`TinyVec`] always moves, and says so:
+//! its capacity goes 4, 8, 16 and so on, [`buffer_address`] shows where the
+//! elements are, and a hook set with [`on_realloc`] hears about every move.
+//!
+//! ```
+//! use std::cell::RefCell;
+//! use std::rc::Rc;
+//! use references_sharing_vs_mutation::tinyvec::TinyVec;
+//!
+//! let moves = Rc::new(RefCell::new(Vec::new()));
+//! let log = Rc::clone(&moves);
+//! let mut wave = TinyVec::new();
+//! wave.on_realloc(move |r| log.borrow_mut().push(*r));
+//! for x in &[0.0, 1.0, 0.0, -1.0] {
+//!     wave.push(*x);
+//! }
+//! let before = wave.buffer_address();
+//! wave.extend_from_within(0..4);
+//!
+//! let moves = moves.borrow();
+//! assert_eq!(moves.len(), 1);
+//! assert_eq!(moves[0].from, before);
+//! assert_eq!(moves[0].to, wave.buffer_address());
+//! assert_eq!((moves[0].old_capacity, moves[0].new_capacity), (4, 8));
+//! ```
+//!
+//! [`buffer_address`]: TinyVec::buffer_address
+//! [`on_realloc`]: TinyVec::on_realloc
+
+use std::alloc::{self, Layout};
+use std::fmt;
+use std::mem;
+use std::ops::{Deref, DerefMut, Range};
+use std::ptr::{self, NonNull};
+use std::slice;
+
+/// The capacity of the first buffer.
+const FIRST_CAPACITY: usize = 4;
+
+/// One move of a `TinyVec`'s elements to a bigger buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Realloc {
+    /// Where the elements were, in a buffer that's now freed.
+    pub from: usize,
+    /// Where they are now.
+    pub to: usize,
+    pub old_capacity: usize,
+    pub new_capacity: usize,
+}
+
+impl fmt::Display for Realloc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "buffer moved from {:#x} to {:#x} (capacity {} -> {})",
+            self.from, self.to, self.old_capacity, self.new_capacity
+        )
+    }
+}
+
+type Hook = Box<dyn FnMut(&Realloc)>;
+
+/// A growable array that reallocates by doubling and never in place.
+///
+/// It holds the same guarantees as `Vec` where it has the same methods, so
+/// the lessons' code works on either, but it's deliberately simple: no
+/// zero-sized elements, and no shrinking.
+pub struct TinyVec<T> {
+    ptr: NonNull<T>,
+    cap: usize,
+    len: usize,
+    hook: Option<Hook>,
+}
+
+impl<T> TinyVec<T> {
+    /// An empty vector, with no buffer until the first push.
+    ///
+    /// Panics if `T` is zero-sized.
+    pub fn new() -> TinyVec<T> {
+        assert!(
+            mem::size_of::<T>() != 0,
+            "TinyVec doesn't hold zero-sized types"
+        );
+        TinyVec {
+            ptr: NonNull::dangling(),
+            cap: 0,
+            len: 0,
+            hook: None,
+        }
+    }
+
+    /// An empty vector with room for `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> TinyVec<T> {
+        let mut v = TinyVec::new();
+        if capacity > 0 {
+            v.move_to(capacity);
+        }
+        v
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    /// Where the elements live. Meaningless while the capacity is 0, when
+    /// there's no buffer.
+    pub fn buffer_address(&self) -> usize {
+        self.ptr.as_ptr() as usize
+    }
+
+    /// Calls `hook` after each move to a bigger buffer, replacing any hook
+    /// set before. Getting the first buffer isn't a move, and doesn't call
+    /// it.
+    pub fn on_realloc<F: FnMut(&Realloc) + 'static>(&mut self, hook: F) {
+        self.hook = Some(Box::new(hook));
+    }
+
+    /// Appends `elt`, moving everything to a buffer twice the size first if
+    /// this one is full.
+    pub fn push(&mut self, elt: T) {
+        if self.len == self.cap {
+            let doubled = self.cap.checked_mul(2).expect("capacity overflow");
+            self.move_to(doubled.max(FIRST_CAPACITY));
+        }
+        // SAFETY: `len < cap`, so the slot is inside the buffer and unused.
+        unsafe { self.ptr.as_ptr().add(self.len).write(elt) };
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        // SAFETY: the slot was initialized, and lowering `len` first means
+        // nothing reads or drops it again.
+        Some(unsafe { self.ptr.as_ptr().add(self.len).read() })
+    }
+
+    /// Makes room for `additional` more elements, moving at most once.
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = self.len.checked_add(additional).expect("capacity overflow");
+        if needed > self.cap {
+            let mut cap = self.cap.max(FIRST_CAPACITY);
+            while cap < needed {
+                cap = cap.checked_mul(2).expect("capacity overflow");
+            }
+            self.move_to(cap);
+        }
+    }
+
+    /// Copies the elements to a new buffer of `cap` elements and frees the
+    /// old one.
+    fn move_to(&mut self, cap: usize) {
+        let layout = Layout::array::<T>(cap).expect("capacity overflow");
+        // SAFETY: `T` isn't zero-sized and `cap > 0`, so the layout isn't
+        // empty.
+        let new = unsafe { alloc::alloc(layout) } as *mut T;
+        let new = NonNull::new(new).unwrap_or_else(|| alloc::handle_alloc_error(layout));
+        let old = self.ptr;
+        let old_cap = self.cap;
+        // SAFETY: the first `len` elements of the old buffer are
+        // initialized and the new buffer has room for them; two live
+        // allocations can't overlap. After the copy the old buffer holds
+        // nothing that needs dropping, and it was allocated with this
+        // layout for `old_cap`.
+        unsafe {
+            ptr::copy_nonoverlapping(old.as_ptr(), new.as_ptr(), self.len);
+            if old_cap > 0 {
+                alloc::dealloc(
+                    old.as_ptr() as *mut u8,
+                    Layout::array::<T>(old_cap).unwrap(),
+                );
+            }
+        }
+        self.ptr = new;
+        self.cap = cap;
+        if old_cap > 0 {
+            if let Some(hook) = self.hook.as_mut() {
+                hook(&Realloc {
+                    from: old.as_ptr() as usize,
+                    to: new.as_ptr() as usize,
+                    old_capacity: old_cap,
+                    new_capacity: cap,
+                });
+            }
+        }
+    }
+}
+
+impl<T: Clone> TinyVec<T> {
+    /// Appends a clone of each element of `slice`.
+    pub fn extend_from_slice(&mut self, slice: &[T]) {
+        self.reserve(slice.len());
+        for elt in slice {
+            self.push(elt.clone());
+        }
+    }
+
+    /// Appends a clone of `self[range]`, the `extend(&mut wave, &wave)`
+    /// that compiles: each element is read by index just before it's
+    /// pushed, so nothing points into the buffer when a push moves it.
+    ///
+    /// Unlike `Vec::extend_from_within` it doesn't reserve first, so the
+    /// moves happen mid-way, where the rejected version's slice would have
+    /// been left dangling.
+    pub fn extend_from_within(&mut self, range: Range<usize>) {
+        assert!(
+            range.end <= self.len,
+            "range end {} out of bounds",
+            range.end
+        );
+        for i in range {
+            let elt = self[i].clone();
+            self.push(elt);
+        }
+    }
+}
+
+impl<T> Default for TinyVec<T> {
+    fn default() -> TinyVec<T> {
+        TinyVec::new()
+    }
+}
+
+impl<T> Deref for TinyVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        // SAFETY: the first `len` elements are initialized, and the pointer
+        // is dangling but aligned when there are none.
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> DerefMut for TinyVec<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        // SAFETY: as for `deref`, and `&mut self` makes the access unique.
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> Drop for TinyVec<T> {
+    fn drop(&mut self) {
+        // SAFETY: the elements are initialized and dropped exactly once
+        // here; the buffer, if any, was allocated with this layout.
+        unsafe {
+            ptr::drop_in_place(&mut **self as *mut [T]);
+            if self.cap > 0 {
+                alloc::dealloc(
+                    self.ptr.as_ptr() as *mut u8,
+                    Layout::array::<T>(self.cap).unwrap(),
+                );
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for TinyVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Reads through a pointer into a full `TinyVec` after a push has moved its
+/// elements: the dangling slice from `extend(&mut wave, &wave)`, made by
+/// hand with a raw pointer since a reference would be rejected.
+///
+/// This is undefined behaviour. Natively it usually returns the old value,
+/// because the freed memory hasn't been reused yet, which is what makes the
+/// bug so hard to find by testing. Miri reports it as a use after free,
+/// stopping the test that calls it:
+///
+/// ```text
+/// cargo +nightly miri test --features unsound-demos read_after_move
+/// ```
+#[cfg(feature = "unsound-demos")]
+pub fn read_after_move() -> f64 {
+    let mut wave = TinyVec::with_capacity(4);
+    for x in &[0.0, 1.0, 0.0, -1.0] {
+        wave.push(*x);
+    }
+    let stale = wave.as_ptr();
+    wave.push(0.0);
+    // SAFETY: none. `stale` points into the buffer the push just freed.
+    unsafe { stale.add(1).read() }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    fn logged<T>() -> (TinyVec<T>, Rc<RefCell<Vec<Realloc>>>) {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut v = TinyVec::new();
+        let hook_log = Rc::clone(&log);
+        v.on_realloc(move |r| hook_log.borrow_mut().push(*r));
+        (v, log)
+    }
+
+    #[test]
+    fn behaves_like_vec() {
+        let mut tiny = TinyVec::new();
+        let mut vec = Vec::new();
+        for i in 0..100 {
+            tiny.push(i);
+            vec.push(i);
+            if i % 3 == 0 {
+                assert_eq!(tiny.pop(), vec.pop());
+            }
+        }
+        assert_eq!(&*tiny, &vec[..]);
+        tiny[0] = 7;
+        assert_eq!(tiny[0], 7);
+        assert_eq!(format!("{:?}", TinyVec::<u8>::new()), "[]");
+        assert_eq!(TinyVec::<u8>::new().pop(), None);
+    }
+
+    #[test]
+    fn doubles_and_logs_every_move() {
+        let (mut v, log) = logged();
+        let mut capacities = vec![v.capacity()];
+        let mut addresses = Vec::new();
+        for i in 0..17u64 {
+            v.push(i);
+            if v.capacity() != *capacities.last().unwrap() {
+                capacities.push(v.capacity());
+                addresses.push(v.buffer_address());
+            }
+        }
+        assert_eq!(capacities, [0, 4, 8, 16, 32]);
+
+        let log = log.borrow();
+        assert_eq!(log.len(), 3);
+        for (i, r) in log.iter().enumerate() {
+            assert_eq!(r.from, addresses[i]);
+            assert_eq!(r.to, addresses[i + 1]);
+            assert_ne!(r.from, r.to);
+            assert_eq!(r.new_capacity, 2 * r.old_capacity);
+        }
+        assert!(log[0]
+            .to_string()
+            .starts_with(&format!("buffer moved from {:#x} to ", addresses[0])));
+        assert!(log[0].to_string().ends_with(" (capacity 4 -> 8)"));
+    }
+
+    #[test]
+    fn extends_from_within_across_a_move() {
+        let (mut wave, log) = logged();
+        wave.extend_from_slice(&[0.0, 1.0, 0.0, -1.0]);
+        assert_eq!(wave.capacity(), 4);
+        wave.extend_from_within(0..4);
+        assert_eq!(&*wave, &[0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0]);
+        assert_eq!(log.borrow().len(), 1);
+
+        let mut reserved = TinyVec::with_capacity(4);
+        reserved.extend_from_slice(&[1; 9]);
+        assert_eq!(reserved.capacity(), 16);
+    }
+
+    /// Only meaningful under Miri, which fails it: see `read_after_move`.
+    #[cfg(feature = "unsound-demos")]
+    #[test]
+    #[cfg_attr(not(miri), ignore = "undefined behaviour, for Miri to report")]
+    fn read_after_move_is_a_use_after_free() {
+        read_after_move();
+    }
+
+    #[test]
+    fn drops_each_element_once() {
+        let counted = Rc::new(());
+        let mut v = TinyVec::new();
+        for _ in 0..10 {
+            v.push(Rc::clone(&counted));
+        }
+        drop(v.pop());
+        assert_eq!(Rc::strong_count(&counted), 10);
+        drop(v);
+        assert_eq!(Rc::strong_count(&counted), 1);
+    }
+}
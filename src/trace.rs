@@ -0,0 +1,268 @@
+//! Borrows recorded from a real program, for drawing as a [`Timeline`].
+//!
+//! The lessons' timelines are written out by hand as [`Event`]s. To see
+//! your own code's borrows, wrap each value of interest in a [`Tracked`]
+//! from a [`Recorder`] and borrow it through `borrow` and `borrow_mut`,
+//! naming each reference as you would the variable holding it. The
+//! recorder logs an event as each happens: the value coming into being,
+//! each borrow, each read or write through a borrow or the value itself,
+//! moves with `move_to`, and the drop at the end of the value's scope.
+//!
+//! ```
+//! use references_sharing_vs_mutation::trace::Recorder;
+//!
+//! let rec = Recorder::new();
+//! let mut v = rec.track("v", vec![1, 2]);
+//! let r = v.borrow("r");
+//! println!("{}", r[0]);
+//! v.borrow_mut("m").push(3);
+//! let aside = v.move_to("aside");
+//! drop(aside);
+//!
+//! let chart = rec.timeline();
+//! assert!(chart.contains("r = &v"));
+//! assert!(chart.contains("m = &mut v"));
+//! assert!(!chart.contains("error["));
+//! ```
+//!
+//! A program that compiles has no conflicts for the chart to show; what it
+//! shows is where each borrow starts and where its last use ends it, which
+//! is what the borrow checker went by when it accepted the code. The
+//! guards `borrow` returns don't implement `Drop`, so wrapping a value
+//! doesn't make any borrow of it last longer than it would have.
+
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+
+use crate::borrowck::{Borrow, Event};
+use crate::timeline::Timeline;
+
+/// Where the events are logged, in the order they happen.
+#[derive(Debug, Default)]
+pub struct Recorder {
+    events: RefCell<Vec<Event<'static>>>,
+}
+
+impl Recorder {
+    pub fn new() -> Recorder {
+        Recorder::default()
+    }
+
+    fn log(&self, event: Event<'static>) {
+        self.events.borrow_mut().push(event);
+    }
+
+    /// `value`, recorded from here on as the variable `name`.
+    pub fn track<T>(&self, name: &'static str, value: T) -> Tracked<'_, T> {
+        self.log(Event::Let(name));
+        Tracked {
+            recorder: self,
+            name,
+            value: Some(value),
+        }
+    }
+
+    /// What's been recorded so far.
+    pub fn events(&self) -> Vec<Event<'static>> {
+        self.events.borrow().clone()
+    }
+
+    /// What's been recorded so far, drawn as a timeline.
+    pub fn timeline(&self) -> String {
+        Timeline::new(&self.events()).to_string()
+    }
+}
+
+/// A value whose borrows, reads, writes, move and drop are recorded.
+///
+/// Reading it through `Deref` records a use; changing it through
+/// `DerefMut` records an assignment.
+#[derive(Debug)]
+pub struct Tracked<'r, T> {
+    recorder: &'r Recorder,
+    name: &'static str,
+    /// `None` once it's been moved out by `move_to`.
+    value: Option<T>,
+}
+
+impl<'r, T> Tracked<'r, T> {
+    /// A shared reference, recorded as the variable `name`.
+    pub fn borrow(&self, name: &'static str) -> Shared<'_, T> {
+        self.recorder.log(Event::Borrow {
+            name,
+            kind: Borrow::Shared,
+            place: self.name,
+        });
+        Shared {
+            recorder: self.recorder,
+            name,
+            value: self.value.as_ref().expect("tracked values hold a value"),
+        }
+    }
+
+    /// A mutable reference, recorded as the variable `name`.
+    pub fn borrow_mut(&mut self, name: &'static str) -> Exclusive<'_, T> {
+        self.recorder.log(Event::Borrow {
+            name,
+            kind: Borrow::Mut,
+            place: self.name,
+        });
+        Exclusive {
+            recorder: self.recorder,
+            name,
+            value: self.value.as_mut().expect("tracked values hold a value"),
+        }
+    }
+
+    /// Replaces the value, recorded as an assignment.
+    pub fn set(&mut self, value: T) {
+        self.recorder.log(Event::Assign(self.name));
+        self.value = Some(value);
+    }
+
+    /// Moves the value into a new variable, `to`.
+    pub fn move_to(mut self, to: &'static str) -> Tracked<'r, T> {
+        self.recorder.log(Event::Move {
+            from: self.name,
+            to,
+        });
+        Tracked {
+            recorder: self.recorder,
+            name: to,
+            value: self.value.take(),
+        }
+    }
+
+    /// The value, no longer recorded.
+    pub fn into_inner(mut self) -> T {
+        self.value.take().expect("tracked values hold a value")
+    }
+}
+
+impl<T> Deref for Tracked<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.recorder.log(Event::Use(self.name));
+        self.value.as_ref().expect("tracked values hold a value")
+    }
+}
+
+impl<T> DerefMut for Tracked<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.recorder.log(Event::Assign(self.name));
+        self.value.as_mut().expect("tracked values hold a value")
+    }
+}
+
+impl<T> Drop for Tracked<'_, T> {
+    /// Records the end of its scope, unless it was moved out.
+    fn drop(&mut self) {
+        if self.value.is_some() {
+            self.recorder.log(Event::Drop(self.name));
+        }
+    }
+}
+
+/// A shared borrow of a [`Tracked`] value. Each read through it is
+/// recorded as a use.
+#[derive(Debug)]
+pub struct Shared<'a, T> {
+    recorder: &'a Recorder,
+    name: &'static str,
+    value: &'a T,
+}
+
+impl<T> Deref for Shared<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.recorder.log(Event::Use(self.name));
+        self.value
+    }
+}
+
+/// A mutable borrow of a [`Tracked`] value. Each read or write through it
+/// is recorded as a use.
+#[derive(Debug)]
+pub struct Exclusive<'a, T> {
+    recorder: &'a Recorder,
+    name: &'static str,
+    value: &'a mut T,
+}
+
+impl<T> Deref for Exclusive<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.recorder.log(Event::Use(self.name));
+        self.value
+    }
+}
+
+impl<T> DerefMut for Exclusive<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.recorder.log(Event::Use(self.name));
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::borrowck::check;
+    use crate::borrowck::Event::{Assign, Drop, Let, Move, Use};
+
+    #[test]
+    fn records_borrows_uses_moves_and_drops() {
+        let rec = Recorder::new();
+        {
+            let mut v = rec.track("v", vec![1, 2]);
+            let r = v.borrow("r");
+            assert_eq!(r.len(), 2);
+            v.borrow_mut("m").push(3);
+            v.set(vec![4]);
+            let aside = v.move_to("aside");
+            assert_eq!(aside[0], 4);
+        }
+        assert_eq!(
+            rec.events(),
+            [
+                Let("v"),
+                Event::Borrow {
+                    name: "r",
+                    kind: Borrow::Shared,
+                    place: "v"
+                },
+                Use("r"),
+                Event::Borrow {
+                    name: "m",
+                    kind: Borrow::Mut,
+                    place: "v"
+                },
+                Use("m"),
+                Assign("v"),
+                Move {
+                    from: "v",
+                    to: "aside"
+                },
+                Use("aside"),
+                Drop("aside"),
+            ]
+        );
+        assert!(check(&rec.events()).is_empty());
+    }
+
+    #[test]
+    fn draws_what_it_recorded() {
+        let rec = Recorder::new();
+        let mut x = rec.track("x", 10);
+        *x += 1;
+        let r = x.borrow("r");
+        assert_eq!(*r, 11);
+        assert_eq!(x.into_inner(), 11);
+        let chart = rec.timeline();
+        assert!(chart.contains("\nx "));
+        assert!(chart.contains("\nr = &x "));
+    }
+}
@@ -0,0 +1,125 @@
+//! The rejected programs again, with `unsafe` standing in for the borrow
+//! checker's refusal.
+//!
+//! Each lesson says the code it rejects would be wrong, not just
+//! unfamiliar. These are those programs written with raw pointers, which
+//! rustc lets through, so the claim can be checked: natively they usually
+//! seem to work, and Miri reports each as undefined behaviour. Built only
+//! with the `unsound-demos` feature.
+//!
+//! `tests/miri.rs` runs every demo under Miri and checks the report; it
+//! needs a nightly toolchain with the `miri` component:
+//!
+//! ```text
+//! rustup +nightly component add miri
+//! cargo miri-demos
+//! ```
+//!
+//! `cargo miri-demos` is an alias, in `.cargo/config.toml`, for running
+//! that test's ignored cases.
+
+use std::hint::black_box;
+
+/// A demo's name, what Miri's report must mention, and the demo.
+pub type Demo = (&'static str, &'static str, unsafe fn() -> i32);
+
+/// Lists the demos in `DEMOS` and gives each a test of the same name, for
+/// `tests/miri.rs` to run under Miri one at a time and watch fail.
+macro_rules! demos {
+    ($($name:ident: $reason:literal,)*) => {
+        /// Every demo, with the phrase Miri's report on it includes.
+        pub static DEMOS: &[Demo] = &[$((stringify!($name), $reason, $name),)*];
+
+        #[cfg(test)]
+        mod tests {
+            //! Natively the tests would only be running undefined
+            //! behaviour, so they're ignored.
+
+            $(
+                #[test]
+                #[cfg_attr(not(miri), ignore = "undefined behaviour, for Miri to report")]
+                fn $name() {
+                    // SAFETY: none, but this only runs under Miri.
+                    unsafe { super::$name() };
+                }
+            )*
+        }
+    };
+}
+
+demos! {
+    dangling_after_move: "has been freed",
+    write_through_shared: "SharedReadOnly",
+    aliasing_mut: "does not exist in the borrow stack",
+    read_freed_buffer: "has been freed",
+}
+
+/// `sharing::dangling`: a pointer to `v`'s first element outlives `v`,
+/// which is moved away and dropped before the pointer is read.
+///
+/// # Safety
+///
+/// Never call it outside Miri: it's undefined behaviour every time.
+pub unsafe fn dangling_after_move() -> i32 {
+    let v = vec![4, 8, 19, 27, 34, 10];
+    let r: *const i32 = &v[0];
+    let aside = v;
+    drop(black_box(aside));
+    // SAFETY: none. The buffer `r` points into was freed with `aside`.
+    unsafe { *r }
+}
+
+/// `sharing::reborrow`: a shared reference cast to a mutable pointer and
+/// written through, which rustc refuses as E0596 when spelled `&mut *r`.
+///
+/// Spelled with casts it's still caught, by the deny-by-default
+/// `invalid_reference_casting` lint, which has to be allowed here.
+///
+/// # Safety
+///
+/// Never call it outside Miri: it's undefined behaviour every time.
+#[allow(invalid_reference_casting)]
+pub unsafe fn write_through_shared() -> i32 {
+    let w = (107, 109);
+    let r = &w;
+    let m1 = &r.1 as *const i32 as *mut i32;
+    // SAFETY: none. `w` is frozen while `r` lives, and `m1` came from it.
+    unsafe { *m1 += 1 };
+    black_box(r.1)
+}
+
+/// `mutation::exclusive`: two mutable references to `y` at once, the E0499
+/// program, made from one raw pointer.
+///
+/// # Safety
+///
+/// Never call it outside Miri: it's undefined behaviour every time.
+pub unsafe fn aliasing_mut() -> i32 {
+    let mut y = 20;
+    let p: *mut i32 = &mut y;
+    // SAFETY: none. Making `m2` from `p` invalidates `m1`, which is still
+    // used afterwards.
+    unsafe {
+        let m1 = &mut *p;
+        let m2 = &mut *p;
+        *m1 += 1;
+        *m2 += 1;
+    }
+    y
+}
+
+/// `mutation::exclusive` again: the `extend(&mut wave, &wave)` slice, kept
+/// as a pointer into a full `Vec` while a push reallocates it.
+///
+/// # Safety
+///
+/// Never call it outside Miri: it's undefined behaviour every time.
+pub unsafe fn read_freed_buffer() -> i32 {
+    let mut wave = Vec::with_capacity(4);
+    wave.extend_from_slice(&[0, 1, 0, -1]);
+    let slice = wave.as_ptr();
+    wave.push(0);
+    // SAFETY: none. The push moved the elements and freed what `slice`
+    // points into.
+    unsafe { *slice.add(1) }
+}
@@ -0,0 +1,170 @@
+use alloc::vec::Vec;
+
+/// Appends `values` to a packed bit vector, eight to a byte.
+///
+/// `bit_len` is the number of bits already stored in `bits` and is advanced
+/// past the new ones; a partly filled last byte is topped up before a new
+/// byte is started. Bits are stored LSB-first: bit `i` lives in
+/// `bits[i / 8] >> (i % 8) & 1`. Anything in `bits` past `bit_len` is
+/// discarded first, including stale bits at the top of the last byte.
+///
+/// # Panics
+///
+/// If `bits` is too short to hold `bit_len` bits.
+pub fn extend_packed(bits: &mut Vec<u8>, bit_len: &mut usize, values: &[bool]) {
+    assert!(
+        bits.len() * 8 >= *bit_len,
+        "bit_len is past the end of bits"
+    );
+    bits.truncate(bit_len.div_ceil(8));
+    if let Some(last) = bits.last_mut() {
+        // All ones when the last byte is full.
+        *last &= u8::MAX >> ((8 - *bit_len % 8) % 8);
+    }
+    bits.reserve((*bit_len + values.len()).div_ceil(8) - bits.len());
+    for &value in values {
+        let shift = *bit_len % 8;
+        if shift == 0 {
+            bits.push(0);
+        }
+        if value {
+            *bits.last_mut().unwrap() |= 1 << shift;
+        }
+        *bit_len += 1;
+    }
+}
+
+/// Appends the absolute values encoded by `deltas`, each one the previous
+/// value plus its delta. Decoding carries on from the last element of
+/// `vec`, or from zero if it is empty; this undoes `extend_diff`.
+///
+/// Sums wrap on overflow, so any `i64` sequence round-trips through
+/// wrapping differences.
+pub fn extend_delta_decode(vec: &mut Vec<i64>, deltas: &[i64]) {
+    let mut value = vec.last().copied().unwrap_or(0);
+    vec.reserve(deltas.len());
+    for &delta in deltas {
+        value = value.wrapping_add(delta);
+        vec.push(value);
+    }
+}
+
+/// Appends the run-length encoded `runs`: `count` copies of `value` for
+/// each `(value, count)` pair, in order. Zero-length runs add nothing.
+///
+/// The whole decoded length is reserved before anything is appended.
+///
+/// # Panics
+///
+/// If the counts add up to more than `usize::MAX`, before anything is
+/// appended.
+pub fn extend_run_decode<T: Clone>(vec: &mut Vec<T>, runs: &[(T, usize)]) {
+    let total = runs
+        .iter()
+        .try_fold(0usize, |total, &(_, count)| total.checked_add(count))
+        .expect("extend_run_decode: total run length overflows usize");
+    vec.reserve(total);
+    for (value, count) in runs {
+        vec.resize(vec.len() + count, value.clone());
+    }
+}
+
+/// Appends each of `values` as four little-endian bytes.
+pub fn extend_u32_le(buf: &mut Vec<u8>, values: &[u32]) {
+    buf.reserve(values.len() * 4);
+    for value in values {
+        buf.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+/// Appends each of `values` as four big-endian bytes, network order.
+pub fn extend_u32_be(buf: &mut Vec<u8>, values: &[u32]) {
+    buf.reserve(values.len() * 4);
+    for value in values {
+        buf.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    fn unpack(bits: &[u8], bit_len: usize) -> Vec<bool> {
+        (0..bit_len)
+            .map(|i| bits[i / 8] >> (i % 8) & 1 == 1)
+            .collect()
+    }
+
+    #[test]
+    fn packs_lsb_first() {
+        let mut bits = Vec::new();
+        let mut len = 0;
+        extend_packed(&mut bits, &mut len, &[true, false, true]);
+        assert_eq!(bits, vec![0b101]);
+        assert_eq!(len, 3);
+        assert_eq!(unpack(&bits, len), vec![true, false, true]);
+    }
+
+    #[test]
+    fn tops_up_partial_byte() {
+        let mut bits = Vec::new();
+        let mut len = 0;
+        extend_packed(&mut bits, &mut len, &[true; 6]);
+        extend_packed(&mut bits, &mut len, &[false, true, true]);
+        assert_eq!(len, 9);
+        assert_eq!(bits, vec![0b1011_1111, 0b1]);
+    }
+
+    #[test]
+    fn clears_stale_bits_past_bit_len() {
+        let mut bits = vec![0xff, 0xff];
+        let mut len = 3;
+        extend_packed(&mut bits, &mut len, &[false, false]);
+        assert_eq!(len, 5);
+        assert_eq!(bits, vec![0b111]);
+        assert_eq!(unpack(&bits, len), vec![true, true, true, false, false]);
+    }
+
+    #[test]
+    fn delta_decode_from_empty() {
+        let mut v = Vec::new();
+        extend_delta_decode(&mut v, &[1, 2, 3]);
+        assert_eq!(v, vec![1, 3, 6]);
+    }
+
+    #[test]
+    fn delta_decode_continues_from_last() {
+        let mut v = vec![100];
+        extend_delta_decode(&mut v, &[-50, 0, 5]);
+        assert_eq!(v, vec![100, 50, 50, 55]);
+        extend_delta_decode(&mut v, &[]);
+        assert_eq!(v.len(), 4);
+    }
+
+    #[test]
+    fn decodes_runs() {
+        let mut v = vec![0];
+        extend_run_decode(&mut v, &[(1, 2), (9, 0), (2, 3)]);
+        assert_eq!(v, vec![0, 1, 1, 2, 2, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "overflows")]
+    fn run_length_overflow_panics() {
+        let mut v: Vec<u8> = Vec::new();
+        extend_run_decode(&mut v, &[(1, usize::MAX), (2, 1)]);
+    }
+
+    #[test]
+    fn u32_byte_orders() {
+        let mut le = vec![0xff];
+        extend_u32_le(&mut le, &[0x0102_0304, 1]);
+        assert_eq!(le, vec![0xff, 0x04, 0x03, 0x02, 0x01, 1, 0, 0, 0]);
+
+        let mut be = Vec::new();
+        extend_u32_be(&mut be, &[0x0102_0304]);
+        assert_eq!(be, vec![0x01, 0x02, 0x03, 0x04]);
+    }
+}
@@ -0,0 +1,368 @@
+use alloc::collections::{TryReserveError, VecDeque};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Appends a clone of each element of `slice`, the notes' `extend` made
+/// generic. For `Copy` elements the clone is just a copy.
+///
+/// This stays slice-based because borrowing its input is the point of the
+/// lesson; `extend_iter` is the general way to grow a vector.
+///
+/// Returns how many elements were appended, which is always `slice.len()`,
+/// so callers stitching segments together can keep a running offset. An
+/// empty slice appends nothing and leaves the capacity alone.
+///
+/// ```
+/// use references_sharing_vs_mutation::vecops::extend;
+///
+/// let mut wave = Vec::new();
+/// let head = vec![0.0, 1.0];
+/// let tail = [0.0, -1.0];
+/// extend(&mut wave, &head);
+/// extend(&mut wave, &tail);
+/// assert_eq!(wave, vec![0.0, 1.0, 0.0, -1.0]);
+/// ```
+pub fn extend<T: Clone>(vec: &mut Vec<T>, slice: &[T]) -> usize {
+    for elt in slice {
+        vec.push(elt.clone());
+    }
+    slice.len()
+}
+
+/// `extend` for `Copy` elements, growing the vector once and copying into
+/// the new space instead of pushing one element at a time.
+///
+/// Stable Rust can't specialize `extend` itself for `Copy` types, so this is
+/// the fast path under its own name. With no `clone` to call and no
+/// capacity check per element, the copy loop compiles to a `memcpy`, and
+/// it keeps close to `extend_from_slice` on slices large enough for the
+/// per-element work to show, where `extend` falls well behind. Run
+/// `cargo bench --bench extend` to see how they compare on your machine.
+///
+/// ```
+/// use references_sharing_vs_mutation::vecops::extend_copied;
+///
+/// let mut wave = vec![0.0, 1.0];
+/// assert_eq!(extend_copied(&mut wave, &[0.0, -1.0]), 2);
+/// assert_eq!(wave, vec![0.0, 1.0, 0.0, -1.0]);
+/// ```
+pub fn extend_copied<T: Copy>(vec: &mut Vec<T>, slice: &[T]) -> usize {
+    vec.reserve(slice.len());
+    let spare = &mut vec.spare_capacity_mut()[..slice.len()];
+    for (slot, &elt) in spare.iter_mut().zip(slice) {
+        slot.write(elt);
+    }
+    // SAFETY: `reserve` made room for `slice.len()` more elements, and the
+    // loop initialized exactly that many just past the old length.
+    unsafe { vec.set_len(vec.len() + slice.len()) };
+    slice.len()
+}
+
+/// Appends the elements of `slice` that differ from the element before
+/// them, counting the last element of `vec` as the one before `slice[0]`.
+///
+/// Only consecutive duplicates are dropped, as with `Vec::dedup`. At the
+/// seam that means a `slice` starting with the value `vec` ends with loses
+/// that first element, which is what stitching periods of a wave together
+/// wants. Into an empty `vec` the first element always goes.
+pub fn extend_dedup<T: PartialEq + Clone>(vec: &mut Vec<T>, slice: &[T]) {
+    for elt in slice {
+        if vec.last() != Some(elt) {
+            vec.push(elt.clone());
+        }
+    }
+}
+
+/// `extend` for any collection that implements `Extend`: a `VecDeque`, a
+/// `String` from `char`s, a `HashSet`, and so on. The sink only has to
+/// accept items at its end, so nothing about it needs to be a `Vec`.
+pub fn extend_into<T: Clone, E: Extend<T>>(sink: &mut E, slice: &[T]) {
+    sink.extend(slice.iter().cloned());
+}
+
+/// Appends every item `items` yields, taking them by value.
+///
+/// Anything iterable works: a `Vec` moved in, a range, a `HashSet`, an
+/// adapter chain. Space for the iterator's lower size bound is reserved
+/// first, so exact-size sources grow the vector at most once.
+pub fn extend_iter<T, I: IntoIterator<Item = T>>(vec: &mut Vec<T>, items: I) {
+    let items = items.into_iter();
+    vec.reserve(items.size_hint().0);
+    for item in items {
+        vec.push(item);
+    }
+}
+
+/// Builds a new vector from `slices` laid end to end, in one allocation of
+/// exactly the total length.
+///
+/// ```
+/// use references_sharing_vs_mutation::vecops::concat_slices;
+///
+/// let head = vec![0.0, 1.0];
+/// let tail = [0.0, -1.0];
+/// let wave = concat_slices(&[&head, &tail, &head, &tail]);
+/// assert_eq!(wave, vec![0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0]);
+/// ```
+pub fn concat_slices<T: Clone>(slices: &[&[T]]) -> Vec<T> {
+    let total = slices.iter().map(|s| s.len()).sum();
+    let mut vec = Vec::with_capacity(total);
+    for slice in slices {
+        extend(&mut vec, slice);
+    }
+    vec
+}
+
+/// `extend` that reports allocation failure instead of aborting.
+///
+/// All the space is reserved with `try_reserve` before anything is pushed,
+/// so on `Err` the vector is exactly as it was, and once it succeeds the
+/// pushes can't need to allocate. The error comes back when the allocator
+/// refuses, or as a capacity overflow when the new length in bytes wouldn't
+/// fit in an `isize`, which is checked without allocating at all.
+pub fn try_extend<T: Clone>(vec: &mut Vec<T>, slice: &[T]) -> Result<(), TryReserveError> {
+    vec.try_reserve(slice.len())?;
+    for elt in slice {
+        vec.push(elt.clone());
+    }
+    Ok(())
+}
+
+/// The `VecDeque` version of `extend`, growing at both ends: each element
+/// of `front` is pushed onto the front in turn, and each of `back` onto the
+/// back.
+///
+/// Pushing `front` one element at a time leaves it reversed, so
+/// `front = [f1, f2]` and `back = [b1, b2]` around `[x]` gives
+/// `[f2, f1, x, b1, b2]`: the deque reads as though walked outward from the
+/// existing elements.
+pub fn extend_both_ends<T: Clone>(vec: &mut VecDeque<T>, front: &[T], back: &[T]) {
+    vec.reserve(front.len() + back.len());
+    for elt in front {
+        vec.push_front(elt.clone());
+    }
+    for elt in back {
+        vec.push_back(elt.clone());
+    }
+}
+
+/// Appends a clone of `vec[range]` to `vec` itself: the `extend(&mut wave,
+/// &wave)` the notes can't write.
+///
+/// No slice of `vec` is held across a push. Each element is cloned by index
+/// just before it is pushed, and the space is reserved up front, so the
+/// buffer doesn't move during the loop either.
+///
+/// ```
+/// use references_sharing_vs_mutation::vecops::extend_self;
+///
+/// let mut wave = vec![0.0, 1.0, 0.0, -1.0];
+/// extend_self(&mut wave, 0..4);
+/// assert_eq!(wave, vec![0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0]);
+/// ```
+///
+/// # Panics
+///
+/// If `range` runs backwards or past the end of `vec`.
+pub fn extend_self<T: Clone>(vec: &mut Vec<T>, range: Range<usize>) {
+    assert!(
+        range.start <= range.end && range.end <= vec.len(),
+        "extend_self: range {:?} out of bounds for length {}",
+        range,
+        vec.len()
+    );
+    vec.reserve(range.len());
+    for i in range {
+        let elt = vec[i].clone();
+        vec.push(elt);
+    }
+}
+
+/// Appends `count` elements, the `i`th of which is `f(i)`, counting from
+/// zero regardless of how long `vec` already is.
+pub fn extend_indexed<T, F: FnMut(usize) -> T>(vec: &mut Vec<T>, count: usize, mut f: F) {
+    vec.reserve(count);
+    for i in 0..count {
+        vec.push(f(i));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn extends_integers() {
+        let mut v = vec![1];
+        extend(&mut v, &[2, 3]);
+        extend(&mut v, &[]);
+        assert_eq!(v, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn copied_matches_extend() {
+        let mut by_clone = vec![1u8, 2];
+        let mut by_copy = by_clone.clone();
+        by_copy.shrink_to_fit();
+        for slice in &[&[3u8, 4, 5][..], &[], &[6; 100]] {
+            assert_eq!(
+                extend_copied(&mut by_copy, slice),
+                extend(&mut by_clone, slice)
+            );
+        }
+        assert_eq!(by_copy, by_clone);
+    }
+
+    #[test]
+    fn counts_track_segment_offsets() {
+        let mut wave = Vec::with_capacity(2);
+        let mut starts = Vec::new();
+        let mut total = 0;
+        for segment in &[&[0.0, 1.0][..], &[], &[0.0, -1.0, 0.0]] {
+            starts.push(total);
+            total += extend(&mut wave, segment);
+        }
+        assert_eq!(total, wave.len());
+        assert_eq!(starts, vec![0, 2, 2]);
+
+        let mut full = vec![1.0; 4];
+        full.shrink_to_fit();
+        let capacity = full.capacity();
+        assert_eq!(extend(&mut full, &[]), 0);
+        assert_eq!(full.capacity(), capacity);
+    }
+
+    #[test]
+    fn concat_allocates_exactly_once() {
+        let head = vec![0.0, 1.0];
+        let tail = [0.0, -1.0];
+        let wave = concat_slices(&[&head, &[], &tail, &head, &tail]);
+        assert_eq!(wave, vec![0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0]);
+        assert_eq!(wave.capacity(), 8);
+
+        let none: Vec<i32> = concat_slices(&[]);
+        assert!(none.is_empty());
+        assert_eq!(none.capacity(), 0);
+    }
+
+    #[test]
+    fn try_extend_appends_when_space_is_available() {
+        let mut v = vec![1, 2];
+        try_extend(&mut v, &[3, 4]).unwrap();
+        assert_eq!(v, vec![1, 2, 3, 4]);
+        assert!(v.capacity() >= 4);
+    }
+
+    #[test]
+    fn extends_owned_strings() {
+        let words = vec![String::from("shared"), String::from("mutable")];
+        let mut v = vec![String::from("either")];
+        extend(&mut v, &words);
+        assert_eq!(v, ["either", "shared", "mutable"]);
+        assert_eq!(words.len(), 2); // only cloned from
+    }
+
+    #[test]
+    fn dedup_drops_the_doubled_seam_sample() {
+        let mut wave = vec![0.0, 1.0, 0.0, -1.0];
+        extend_dedup(&mut wave, &[-1.0, 0.0, 1.0, 1.0, 0.0]);
+        assert_eq!(wave, vec![0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn dedup_edge_cases() {
+        let mut v: Vec<i32> = Vec::new();
+        extend_dedup(&mut v, &[]);
+        assert!(v.is_empty());
+
+        extend_dedup(&mut v, &[3, 3, 3]);
+        assert_eq!(v, vec![3]);
+        extend_dedup(&mut v, &[3, 3]);
+        assert_eq!(v, vec![3]);
+
+        // Duplicates that aren't adjacent are kept.
+        extend_dedup(&mut v, &[4, 3]);
+        assert_eq!(v, vec![3, 4, 3]);
+    }
+
+    #[test]
+    fn extends_into_a_deque() {
+        let mut d: VecDeque<i32> = VecDeque::new();
+        d.push_front(0);
+        extend_into(&mut d, &[1, 2]);
+        assert_eq!(d, [0, 1, 2]);
+    }
+
+    #[test]
+    fn extends_into_a_string() {
+        let mut s = String::from("mut");
+        extend_into(&mut s, &['a', 'b', 'l', 'e']);
+        assert_eq!(s, "mutable");
+    }
+
+    #[test]
+    fn extends_from_a_vec_by_value() {
+        let mut v = vec![String::from("kept")];
+        let moved = vec![String::from("moved"), String::from("in")];
+        extend_iter(&mut v, moved);
+        assert_eq!(v, ["kept", "moved", "in"]);
+    }
+
+    #[test]
+    fn extends_from_a_range_in_one_allocation() {
+        let mut v: Vec<i32> = Vec::new();
+        extend_iter(&mut v, 0..4);
+        assert_eq!(v, vec![0, 1, 2, 3]);
+        assert_eq!(v.capacity(), 4);
+    }
+
+    #[test]
+    fn extends_from_a_filtered_iterator() {
+        let mut v = vec![0];
+        extend_iter(&mut v, (1..10).filter(|n| n % 3 == 0));
+        assert_eq!(v, vec![0, 3, 6, 9]);
+    }
+
+    #[test]
+    fn extends_a_deque_at_both_ends() {
+        let mut d: VecDeque<&str> = vec!["x", "y"].into();
+        extend_both_ends(&mut d, &["f1", "f2"], &["b1", "b2"]);
+        assert_eq!(d, ["f2", "f1", "x", "y", "b1", "b2"]);
+
+        extend_both_ends(&mut d, &[], &[]);
+        assert_eq!(d.len(), 6);
+    }
+
+    #[test]
+    fn extends_with_a_middle_range() {
+        let mut v = vec![String::from("a"), String::from("b"), String::from("c")];
+        extend_self(&mut v, 1..3);
+        assert_eq!(v, ["a", "b", "c", "b", "c"]);
+        extend_self(&mut v, 2..2);
+        assert_eq!(v.len(), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn self_range_past_the_end_panics() {
+        let mut v = vec![1, 2];
+        extend_self(&mut v, 1..3);
+    }
+
+    #[test]
+    fn indexed_squares() {
+        let mut v = Vec::new();
+        extend_indexed(&mut v, 4, |i| i * i);
+        assert_eq!(v, vec![0, 1, 4, 9]);
+    }
+
+    #[test]
+    fn index_is_relative_to_the_call() {
+        let mut v = vec![10];
+        extend_indexed(&mut v, 2, |i| i);
+        assert_eq!(v, vec![10, 0, 1]);
+    }
+}
@@ -0,0 +1,37 @@
+use alloc::vec::Vec;
+
+/// Appends `row` as one new entry in each column of a column-major matrix.
+///
+/// `row[j]` is pushed onto `vec[j]`, with empty columns added as needed when
+/// `row` is wider than the matrix so far. Columns past the end of a short
+/// row are left alone.
+pub fn extend_transpose<T: Clone>(vec: &mut Vec<Vec<T>>, row: &[T]) {
+    if vec.len() < row.len() {
+        vec.resize_with(row.len(), Vec::new);
+    }
+    for (column, elt) in vec.iter_mut().zip(row) {
+        column.push(elt.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn rows_become_columns() {
+        let mut columns = Vec::new();
+        extend_transpose(&mut columns, &[1, 2, 3]);
+        extend_transpose(&mut columns, &[4, 5, 6]);
+        assert_eq!(columns, vec![vec![1, 4], vec![2, 5], vec![3, 6]]);
+    }
+
+    #[test]
+    fn wider_row_adds_columns() {
+        let mut columns = vec![vec![1]];
+        extend_transpose(&mut columns, &[2, 3]);
+        assert_eq!(columns, vec![vec![1, 2], vec![3]]);
+    }
+}
@@ -0,0 +1,25 @@
+//! Vector-building helpers in the spirit of the notes' `extend`.
+//!
+//! Most take the vector they grow by `&mut` and their input by shared
+//! reference, so the input can never be the vector being grown. The few
+//! that do read from the vector they extend, such as `extend_self`, go
+//! through it by index between pushes instead of holding a slice.
+
+mod encode;
+mod extend;
+mod matrix;
+mod signal;
+
+pub use self::encode::{
+    extend_delta_decode, extend_packed, extend_run_decode, extend_u32_be, extend_u32_le,
+};
+pub use self::extend::{
+    concat_slices, extend, extend_both_ends, extend_copied, extend_dedup, extend_indexed,
+    extend_into, extend_iter, extend_self, try_extend,
+};
+pub use self::matrix::extend_transpose;
+pub use self::signal::{
+    extend_biased, extend_clamped, extend_crossfade, extend_cumulative, extend_diff,
+    extend_downsample, extend_modulated, extend_moving_average, extend_pad_pow2, extend_quantized,
+    extend_resample_to, interleave_extend,
+};
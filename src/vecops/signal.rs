@@ -0,0 +1,376 @@
+use alloc::vec::Vec;
+
+/// Appends the running sum of `slice`, carrying on from the last element of
+/// `vec` if there is one.
+pub fn extend_cumulative(vec: &mut Vec<f64>, slice: &[f64]) {
+    let mut total = vec.last().copied().unwrap_or(0.0);
+    vec.reserve(slice.len());
+    for elt in slice {
+        total += *elt;
+        vec.push(total);
+    }
+}
+
+/// Appends the differences between consecutive elements of `slice`, one
+/// fewer than its length. Slices shorter than two elements add nothing.
+///
+/// Only `slice` is looked at; the last element of `vec` plays no part.
+pub fn extend_diff(vec: &mut Vec<f64>, slice: &[f64]) {
+    vec.extend(slice.windows(2).map(|pair| pair[1] - pair[0]));
+}
+
+/// Appends a copy of `carrier`'s current samples, each multiplied by the
+/// matching `envelope` value. A short envelope is cycled; an empty one
+/// appends nothing.
+///
+/// This is the `extend(&mut wave, &wave)` situation: the input is the vector
+/// being grown. Rather than hold a slice of `carrier` while pushing to it,
+/// it remembers the original length and reads each sample by index just
+/// before the push, so no borrow outlives a reallocation.
+pub fn extend_modulated(carrier: &mut Vec<f64>, envelope: &[f64]) {
+    if envelope.is_empty() {
+        return;
+    }
+    let len = carrier.len();
+    carrier.reserve(len);
+    for i in 0..len {
+        let sample = carrier[i] * envelope[i % envelope.len()];
+        carrier.push(sample);
+    }
+}
+
+/// Appends each element of `slice` plus `bias`, saturating at `i32::MIN`
+/// and `i32::MAX` instead of overflowing.
+pub fn extend_biased(vec: &mut Vec<i32>, slice: &[i32], bias: i32) {
+    vec.extend(slice.iter().map(|x| x.saturating_add(bias)));
+}
+
+/// Appends `slice` with each element clamped to `[min, max]`. `NaN`
+/// elements are appended unchanged.
+///
+/// # Panics
+///
+/// If `min > max` or either bound is `NaN`, as `f64::clamp` does.
+pub fn extend_clamped(vec: &mut Vec<f64>, slice: &[f64], min: f64, max: f64) {
+    vec.extend(slice.iter().map(|x| x.clamp(min, max)));
+}
+
+/// Appends `next`, overlapping its first `fade_len` samples with the last
+/// `fade_len` of `vec` and blending them linearly, so one clip runs into the
+/// next without a click.
+///
+/// Across the overlap the weight of `next` ramps from just above zero to
+/// just below one: sample `k` of `n` is `old * (1 - t) + new * t` with
+/// `t = (k + 1) / (n + 1)`. `fade_len` is clamped to the lengths of both
+/// `vec` and `next`; the rest of `next` is appended unchanged.
+pub fn extend_crossfade(vec: &mut Vec<f64>, next: &[f64], fade_len: usize) {
+    let fade = fade_len.min(vec.len()).min(next.len());
+    let tail = vec.len() - fade;
+    for (k, (old, &new)) in vec[tail..].iter_mut().zip(&next[..fade]).enumerate() {
+        let t = (k + 1) as f64 / (fade + 1) as f64;
+        *old = *old * (1.0 - t) + new * t;
+    }
+    vec.extend_from_slice(&next[fade..]);
+}
+
+/// Appends `samples` converted to 16-bit PCM.
+///
+/// Samples are scaled by 32768 and rounded, so `-1.0` maps to `i16::MIN`
+/// and `1.0` to `i16::MAX`; anything outside `[-1.0, 1.0]` is clamped to
+/// those ends. `NaN` becomes silence (`0`).
+pub fn extend_quantized(vec: &mut Vec<i16>, samples: &[f64]) {
+    vec.reserve(samples.len());
+    for &sample in samples {
+        // `as` saturates and sends NaN to zero.
+        vec.push(round(sample.clamp(-1.0, 1.0) * 32768.0) as i16);
+    }
+}
+
+/// `f64::round` for the PCM range, which `core` doesn't provide. Rounds half
+/// away from zero; truncating through `i32` is exact for these magnitudes.
+fn round(x: f64) -> f64 {
+    let whole = x as i32 as f64;
+    let frac = x - whole;
+    if frac >= 0.5 {
+        whole + 1.0
+    } else if frac <= -0.5 {
+        whole - 1.0
+    } else {
+        whole
+    }
+}
+
+/// Appends every `factor`th element of `source`, starting with the first.
+///
+/// # Panics
+///
+/// If `factor` is zero.
+pub fn extend_downsample(vec: &mut Vec<f64>, source: &[f64], factor: usize) {
+    assert!(factor != 0, "extend_downsample: factor must be at least 1");
+    vec.extend(source.iter().step_by(factor));
+}
+
+/// Appends `left` and `right` interleaved as stereo frames,
+/// `[l0, r0, l1, r1, ...]`, for as long as both channels last. Whatever is
+/// left of the longer channel follows on its own, as mono.
+pub fn interleave_extend(out: &mut Vec<f64>, left: &[f64], right: &[f64]) {
+    out.reserve(left.len() + right.len());
+    for (&l, &r) in left.iter().zip(right) {
+        out.push(l);
+        out.push(r);
+    }
+    let frames = left.len().min(right.len());
+    out.extend_from_slice(&left[frames..]);
+    out.extend_from_slice(&right[frames..]);
+}
+
+/// Appends `source` linearly resampled to exactly `target_len` samples.
+///
+/// The first and last output samples are the first and last of `source`
+/// (a single output sample is just the first), with the rest interpolated
+/// between the two nearest inputs. A one-element `source` is repeated; an
+/// empty one has nothing to resample, so nothing is appended.
+pub fn extend_resample_to(vec: &mut Vec<f64>, source: &[f64], target_len: usize) {
+    match source {
+        [] => {}
+        [only] => vec.resize(vec.len() + target_len, *only),
+        _ => {
+            vec.reserve(target_len);
+            let last = source.len() - 1;
+            for i in 0..target_len {
+                let pos = if target_len == 1 {
+                    0.0
+                } else {
+                    (i * last) as f64 / (target_len - 1) as f64
+                };
+                let lo = (pos as usize).min(last - 1);
+                let t = pos - lo as f64;
+                vec.push(source[lo] * (1.0 - t) + source[lo + 1] * t);
+            }
+        }
+    }
+}
+
+/// Appends the centred moving average of `source` over `window` samples,
+/// one output per input.
+///
+/// Near the ends the window is cut off at the edge of `source` rather than
+/// padded, so the first output of `[1, 2, 3]` with a window of 3 is the
+/// mean of `[1, 2]`.
+///
+/// # Panics
+///
+/// If `window` is zero or even, since then there is no centre sample.
+pub fn extend_moving_average(vec: &mut Vec<f64>, source: &[f64], window: usize) {
+    assert!(
+        window % 2 == 1,
+        "extend_moving_average: window must be odd, got {}",
+        window
+    );
+    let half = window / 2;
+    vec.reserve(source.len());
+    for i in 0..source.len() {
+        let lo = i.saturating_sub(half);
+        let hi = (i + half + 1).min(source.len());
+        let taken = &source[lo..hi];
+        vec.push(taken.iter().sum::<f64>() / taken.len() as f64);
+    }
+}
+
+/// Appends copies of `fill` until the length is a power of two, as an FFT
+/// wants. A vector already that long is left alone; an empty one grows to a
+/// single element.
+pub fn extend_pad_pow2(vec: &mut Vec<f64>, fill: f64) {
+    let target = vec.len().next_power_of_two();
+    vec.resize(target, fill);
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn cumulative_from_empty() {
+        let mut v = Vec::new();
+        extend_cumulative(&mut v, &[1.0, 2.0, 3.0]);
+        assert_eq!(v, vec![1.0, 3.0, 6.0]);
+    }
+
+    #[test]
+    fn cumulative_continues_from_last() {
+        let mut v = vec![10.0];
+        extend_cumulative(&mut v, &[1.0, 2.0, 3.0]);
+        assert_eq!(v, vec![10.0, 11.0, 13.0, 16.0]);
+    }
+
+    #[test]
+    fn diff_of_consecutive_elements() {
+        let mut v = vec![0.0];
+        extend_diff(&mut v, &[1.0, 3.0, 6.0, 10.0]);
+        assert_eq!(v, vec![0.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn diff_of_short_slices_is_empty() {
+        let mut v = Vec::new();
+        extend_diff(&mut v, &[]);
+        extend_diff(&mut v, &[5.0]);
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn modulates_own_samples() {
+        let mut v = vec![2.0, 4.0];
+        extend_modulated(&mut v, &[1.0, 0.5]);
+        assert_eq!(v, vec![2.0, 4.0, 2.0, 2.0]);
+    }
+
+    #[test]
+    fn short_envelope_cycles() {
+        let mut v = vec![1.0, 2.0, 3.0];
+        extend_modulated(&mut v, &[-1.0]);
+        assert_eq!(v, vec![1.0, 2.0, 3.0, -1.0, -2.0, -3.0]);
+        extend_modulated(&mut v, &[]);
+        assert_eq!(v.len(), 6);
+    }
+
+    #[test]
+    fn quantizes_to_pcm() {
+        let mut pcm = Vec::new();
+        extend_quantized(&mut pcm, &[0.0, 1.0, -1.0]);
+        assert_eq!(pcm, vec![0, 32767, -32768]);
+    }
+
+    #[test]
+    fn quantize_clamps_and_rounds() {
+        let mut pcm = vec![7];
+        extend_quantized(&mut pcm, &[2.0, -3.5, 0.5, f64::NAN, 1.0 / 65536.0]);
+        assert_eq!(pcm, vec![7, 32767, -32768, 16384, 0, 1]);
+    }
+
+    #[test]
+    fn downsamples_by_factor() {
+        let mut v = vec![-1.0];
+        extend_downsample(&mut v, &[0.0, 1.0, 2.0, 3.0, 4.0, 5.0], 2);
+        assert_eq!(v, vec![-1.0, 0.0, 2.0, 4.0]);
+        extend_downsample(&mut v, &[9.0, 8.0], 1);
+        assert_eq!(v, vec![-1.0, 0.0, 2.0, 4.0, 9.0, 8.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "factor must be at least 1")]
+    fn downsample_by_zero_panics() {
+        extend_downsample(&mut Vec::new(), &[1.0], 0);
+    }
+
+    #[test]
+    fn clamps_into_range() {
+        let mut v = Vec::new();
+        extend_clamped(&mut v, &[-2.0, 0.5, 3.0], -1.0, 1.0);
+        assert_eq!(v, vec![-1.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn clamp_passes_nan_through() {
+        let mut v = Vec::new();
+        extend_clamped(&mut v, &[f64::NAN, 7.0], 0.0, 1.0);
+        assert!(v[0].is_nan());
+        assert_eq!(v[1], 1.0);
+    }
+
+    #[test]
+    fn crossfades_the_overlap() {
+        let mut v = vec![1.0, 1.0, 1.0, 1.0];
+        extend_crossfade(&mut v, &[0.0, 0.0, 0.0, 5.0, 6.0], 3);
+        assert_eq!(v, vec![1.0, 0.75, 0.5, 0.25, 5.0, 6.0]);
+    }
+
+    #[test]
+    fn crossfade_length_is_clamped() {
+        let mut v = vec![1.0];
+        extend_crossfade(&mut v, &[0.0, 2.0], 10);
+        assert_eq!(v, vec![0.5, 2.0]);
+
+        let mut v = vec![4.0, 4.0];
+        extend_crossfade(&mut v, &[], 2);
+        assert_eq!(v, vec![4.0, 4.0]);
+
+        let mut v = Vec::new();
+        extend_crossfade(&mut v, &[3.0], 1);
+        assert_eq!(v, vec![3.0]);
+    }
+
+    #[test]
+    fn bias_saturates() {
+        let mut v = vec![0];
+        extend_biased(&mut v, &[1, i32::MAX, i32::MIN], 10);
+        assert_eq!(v, vec![0, 11, i32::MAX, i32::MIN + 10]);
+        extend_biased(&mut v, &[i32::MIN], -1);
+        assert_eq!(v[4], i32::MIN);
+    }
+
+    #[test]
+    fn pads_up_to_a_power_of_two() {
+        let mut v = vec![1.0, 2.0, 3.0];
+        extend_pad_pow2(&mut v, 0.0);
+        assert_eq!(v, vec![1.0, 2.0, 3.0, 0.0]);
+
+        extend_pad_pow2(&mut v, 9.0);
+        assert_eq!(v, vec![1.0, 2.0, 3.0, 0.0]);
+
+        let mut empty = Vec::new();
+        extend_pad_pow2(&mut empty, 5.0);
+        assert_eq!(empty, vec![5.0]);
+    }
+
+    #[test]
+    fn resamples_linearly() {
+        let mut v = vec![9.0];
+        extend_resample_to(&mut v, &[0.0, 1.0], 3);
+        assert_eq!(v, vec![9.0, 0.0, 0.5, 1.0]);
+
+        let mut v = Vec::new();
+        extend_resample_to(&mut v, &[0.0, 2.0, 4.0, 6.0], 2);
+        assert_eq!(v, vec![0.0, 6.0]);
+    }
+
+    #[test]
+    fn resample_edge_cases() {
+        let mut v = Vec::new();
+        extend_resample_to(&mut v, &[1.0, 2.0], 0);
+        assert!(v.is_empty());
+        extend_resample_to(&mut v, &[7.0], 3);
+        assert_eq!(v, vec![7.0, 7.0, 7.0]);
+        extend_resample_to(&mut v, &[], 3);
+        assert_eq!(v.len(), 3);
+    }
+
+    #[test]
+    fn interleaves_then_appends_the_longer_channel() {
+        let mut out = Vec::new();
+        interleave_extend(&mut out, &[1.0, 2.0, 3.0], &[4.0, 5.0]);
+        assert_eq!(out, vec![1.0, 4.0, 2.0, 5.0, 3.0]);
+
+        interleave_extend(&mut out, &[], &[6.0, 7.0]);
+        assert_eq!(out, vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0, 7.0]);
+    }
+
+    #[test]
+    fn moving_average_shrinks_at_the_edges() {
+        let mut v = Vec::new();
+        extend_moving_average(&mut v, &[1.0, 2.0, 3.0, 4.0, 5.0], 3);
+        assert_eq!(v, vec![1.5, 2.0, 3.0, 4.0, 4.5]);
+
+        let mut v = Vec::new();
+        extend_moving_average(&mut v, &[1.0, 2.0], 1);
+        assert_eq!(v, vec![1.0, 2.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "window must be odd")]
+    fn even_window_panics() {
+        extend_moving_average(&mut Vec::new(), &[1.0], 2);
+    }
+}
@@ -0,0 +1,129 @@
+//! The lessons for a web page, through `wasm-bindgen`.
+//!
+//! Built with the `wasm` feature, these are the catalog's entry points for
+//! JavaScript, so a site can show the lessons and quiz on them without a
+//! copy of the content of its own. Lessons are named as `--lesson` names
+//! them. Lists and quizzes cross as JSON strings, pages and diagrams as
+//! HTML fragments from [`export`], and a name that isn't a
+//! lesson gives `undefined`.
+//!
+//! Natively they're plain functions, which is how the tests call them. For
+//! the browser the crate goes through `wasm-bindgen` as a `cdylib`:
+//!
+//! ```text
+//! cargo rustc --lib --release --features wasm \
+//!     --target wasm32-unknown-unknown --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg \
+//!     target/wasm32-unknown-unknown/release/references_sharing_vs_mutation.wasm
+//! ```
+
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::cli::{find_lesson, LESSONS};
+use crate::exercises::{self, Question};
+use crate::export;
+
+/// Every lesson in tour order, as a JSON array of objects with its `name`,
+/// `chapter` and `title`.
+#[wasm_bindgen]
+pub fn lessons() -> String {
+    let lessons: Vec<Value> = LESSONS
+        .iter()
+        .map(|lesson| {
+            json!({
+                "name": lesson.name,
+                "chapter": lesson.chapter,
+                "title": lesson.title,
+            })
+        })
+        .collect();
+    Value::Array(lessons).to_string()
+}
+
+/// The lesson called `id` as an HTML `<article>`.
+#[wasm_bindgen]
+pub fn lesson_html(id: &str) -> Option<String> {
+    find_lesson(id).map(export::lesson_html)
+}
+
+/// The lesson's diagrams as HTML, one `<pre class="diagram">` each.
+#[wasm_bindgen]
+pub fn diagrams_html(id: &str) -> Option<String> {
+    let lesson = find_lesson(id)?;
+    let diagrams = (lesson.diagrams)();
+    Some(diagrams.iter().map(export::diagram_html).collect())
+}
+
+/// The lesson's exercises as a JSON array, in the order listed, for the
+/// page to shuffle and mark. Each has a `kind` of `"compiles"`, with the
+/// `code` and whether it does as `answer`, or `"choice"`, with a `prompt`,
+/// `choices` and the index of the right one as `answer`; and each has the
+/// `explanation` to show after it's answered.
+#[wasm_bindgen]
+pub fn quiz(id: &str) -> Option<String> {
+    let lesson = find_lesson(id)?;
+    let questions: Vec<Value> = exercises::for_lesson(lesson.name)
+        .map(|exercise| {
+            let mut question = match exercise.question {
+                Question::Compiles { code, answer } => json!({
+                    "kind": "compiles",
+                    "code": code,
+                    "answer": answer,
+                }),
+                Question::Choice {
+                    prompt,
+                    choices,
+                    answer,
+                } => json!({
+                    "kind": "choice",
+                    "prompt": prompt,
+                    "choices": choices,
+                    "answer": answer,
+                }),
+            };
+            question["explanation"] = exercise.explanation.into();
+            question
+        })
+        .collect();
+    Some(Value::Array(questions).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_every_lesson() {
+        let list: Value = serde_json::from_str(&lessons()).unwrap();
+        let list = list.as_array().unwrap();
+        assert_eq!(list.len(), LESSONS.len());
+        assert_eq!(list[0]["name"], "dangling");
+        assert_eq!(list[0]["chapter"], "sharing");
+    }
+
+    #[test]
+    fn unknown_lessons_give_nothing() {
+        assert_eq!(lesson_html("nope"), None);
+        assert_eq!(diagrams_html("nope"), None);
+        assert_eq!(quiz("nope"), None);
+    }
+
+    #[test]
+    fn serves_pages_diagrams_and_quizzes() {
+        let page = lesson_html("exclusive").unwrap();
+        assert!(page.starts_with("<article class=\"lesson\" id=\"exclusive\">"));
+        let diagrams = diagrams_html("exclusive").unwrap();
+        assert_eq!(diagrams.matches("<pre class=\"diagram\">").count(), 2);
+        assert!(page.contains(&diagrams));
+
+        let quiz: Value = serde_json::from_str(&quiz("self_assignment").unwrap()).unwrap();
+        let quiz = quiz.as_array().unwrap();
+        assert_eq!(quiz.len(), 2);
+        assert_eq!(quiz[0]["kind"], "compiles");
+        assert_eq!(quiz[0]["answer"], false);
+        assert_eq!(quiz[1]["kind"], "choice");
+        assert_eq!(quiz[1]["answer"], 0);
+        assert!(quiz[1]["explanation"].as_str().unwrap().contains("dup"));
+    }
+}
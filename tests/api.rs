@@ -0,0 +1,57 @@
+//! The library as a downstream crate sees it: only `pub` items, through
+//! the crate name.
+
+#![cfg(feature = "std")]
+
+use std::io::{Read, Seek, SeekFrom};
+
+use references_sharing_vs_mutation::fd::{self, File};
+use references_sharing_vs_mutation::vecops::extend;
+
+#[test]
+fn extend_builds_the_sine_wave() {
+    let mut wave = Vec::new();
+    let head = vec![0.0, 1.0];
+    let tail = [0.0, -1.0];
+    extend(&mut wave, &head);
+    extend(&mut wave, &tail);
+    assert_eq!(wave, vec![0.0, 1.0, 0.0, -1.0]);
+}
+
+#[test]
+fn open_reports_missing_files() {
+    let err = File::open("/nonexistent/sharing-vs-mutation")
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+fn clone_from_takes_over_the_write_end() {
+    let (reader, writer) = fd::pipe().unwrap();
+    let mut clone = reader.try_clone().unwrap();
+    let mut target = File::null().unwrap();
+    target.clone_from(&writer);
+    writer.close().unwrap();
+
+    // `target` now holds the only write end; closing it is end of file.
+    drop(target);
+    let mut buf = Vec::new();
+    clone.read_to_end(&mut buf).unwrap();
+    assert!(buf.is_empty());
+    drop(reader);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn clone_of_a_regular_file_reads_the_same_bytes() {
+    let mut f = File::open("/proc/self/exe").unwrap();
+    let mut g = f.clone();
+    let mut a = [0; 4];
+    let mut b = [0; 4];
+    f.read_exact(&mut a).unwrap();
+    g.seek(SeekFrom::Start(0)).unwrap();
+    g.read_exact(&mut b).unwrap();
+    assert_eq!(a, b);
+    assert_eq!(&a, b"\x7fELF");
+}
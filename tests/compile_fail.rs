@@ -0,0 +1,33 @@
+//! The examples the notes say won't compile, checked against the compiler's
+//! actual diagnostics.
+//!
+//! Each case is a `tests/compile_fail/<name>.rs` with the expected output
+//! beside it in `<name>.stderr`. Add a case by writing the pair and listing
+//! its name below; regenerate the `.stderr` files with `TRYBUILD=overwrite`
+//! when a new Rust release rewords a message, and review the diff. The `tour`
+//! command shows these same files and `.stderr` output to the learner.
+
+#![cfg(feature = "std")]
+
+references_sharing_vs_mutation::compile_fail! {
+    "move_while_borrowed",
+    "mutable_from_shared",
+    "bypass_mutable",
+    "extend_with_itself",
+    "assign_while_shared",
+    "mutable_while_shared",
+    "two_mutable_borrows",
+    "use_while_mutable",
+    "clone_from_self",
+    "mutate_through_rc",
+    "return_local_reference",
+    "key_across_insert",
+    "generic_moves_reference",
+    "explicit_two_phase",
+    "returned_on_one_path",
+    "borrow_held_by_drop",
+    "self_borrowing_struct",
+    "two_elements_mut",
+    "method_borrows_all_of_self",
+    "record_while_iterating",
+}
@@ -0,0 +1,9 @@
+// mutation::exclusive: assigning to `x` while shared borrows of it live.
+
+fn main() {
+    let mut x = 10;
+    let r1 = &x;
+    let r2 = &x;
+    x += 10;
+    println!("{} {}", r1, r2);
+}
@@ -0,0 +1,15 @@
+// nll: a value with a destructor uses its borrow when it's dropped.
+
+struct LastLook<'a>(&'a Vec<i32>);
+
+impl Drop for LastLook<'_> {
+    fn drop(&mut self) {
+        println!("{:?}", self.0);
+    }
+}
+
+fn main() {
+    let mut v = vec![1, 2];
+    let _look = LastLook(&v);
+    v.push(3);
+}
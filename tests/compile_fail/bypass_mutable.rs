@@ -0,0 +1,10 @@
+// sharing::reborrow: reaching `v.1` without going through `m`.
+
+fn main() {
+    let mut v = (136, 139);
+    let m = &mut v;
+    let m0 = &mut m.0;
+    let r1 = &m.1;
+    let other = v.1;
+    *m0 += *r1 + other;
+}
@@ -0,0 +1,8 @@
+// mutation::self_assignment: `clone_from(&mut f, &f)`.
+
+use references_sharing_vs_mutation::fd::File;
+
+fn main() {
+    let mut f = File::open("/dev/null").unwrap();
+    f.clone_from(&f);
+}
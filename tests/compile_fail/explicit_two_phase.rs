@@ -0,0 +1,7 @@
+// reborrow: two-phase borrows only cover method-call syntax.
+
+fn main() {
+    let mut v = vec![1, 2];
+    Vec::push(&mut v, v.len());
+    println!("{:?}", v);
+}
@@ -0,0 +1,8 @@
+// mutation::exclusive: the vector is both the destination and the source.
+
+use references_sharing_vs_mutation::vecops::extend;
+
+fn main() {
+    let mut wave = vec![0.0, 1.0, 0.0, -1.0];
+    extend(&mut wave, &wave);
+}
@@ -0,0 +1,10 @@
+// reborrow: a generic parameter takes the `&mut` itself, not a reborrow.
+
+fn keep<T>(_: T) {}
+
+fn main() {
+    let mut v = vec![1, 2];
+    let m = &mut v;
+    keep(m);
+    m.push(3);
+}
@@ -0,0 +1,11 @@
+// maps: holding a key from the map while inserting into it.
+
+use std::collections::HashMap;
+
+fn main() {
+    let mut totals: HashMap<String, f64> = HashMap::new();
+    totals.insert("wave".to_string(), 1.5);
+    let key = totals.keys().next().unwrap();
+    totals.insert(key.to_uppercase(), 0.0);
+    println!("copied {}", key);
+}
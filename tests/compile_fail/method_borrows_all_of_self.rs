@@ -0,0 +1,22 @@
+// split: a method call borrows the whole struct, not just the field it uses.
+
+struct Playlist {
+    songs: Vec<String>,
+    log: Vec<String>,
+}
+
+impl Playlist {
+    fn note(&mut self, line: String) {
+        self.log.push(line);
+    }
+}
+
+fn main() {
+    let mut p = Playlist {
+        songs: vec![String::from("Hercules")],
+        log: Vec::new(),
+    };
+    for song in &p.songs {
+        p.note(format!("now playing {}", song));
+    }
+}
@@ -0,0 +1,8 @@
+// sharing::dangling: moving `v` while `r` still borrows it.
+
+fn main() {
+    let v = vec![4, 8, 19, 27, 34, 10];
+    let r = &v;
+    let aside = v;
+    println!("{} {:?}", r[0], aside);
+}
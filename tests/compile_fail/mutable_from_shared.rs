@@ -0,0 +1,9 @@
+// sharing::reborrow: a shared reference can't lend out a mutable one.
+
+fn main() {
+    let mut w = (107, 109);
+    let r = &w;
+    let m1 = &mut r.1;
+    *m1 += 1;
+    w.0 += 1;
+}
@@ -0,0 +1,9 @@
+// mutation::exclusive: a mutable borrow overlapping a shared one.
+
+fn main() {
+    let mut x = 10;
+    let r1 = &x;
+    let m = &mut x;
+    *m += 1;
+    println!("{}", r1);
+}
@@ -0,0 +1,10 @@
+// rc: what an `Rc` points at is shared, so it's read-only.
+
+use std::rc::Rc;
+
+fn main() {
+    let wave = Rc::new(vec![0.0, 1.0]);
+    let other = Rc::clone(&wave);
+    wave.push(0.0);
+    println!("{:?}", other);
+}
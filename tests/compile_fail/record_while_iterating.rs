@@ -0,0 +1,24 @@
+// perf: calling a method on all of `ledger` while a loop borrows one of its fields.
+
+struct Ledger {
+    entries: Vec<u64>,
+    totals: Vec<u64>,
+}
+
+impl Ledger {
+    fn record(&mut self, total: u64) {
+        self.totals.push(total);
+    }
+}
+
+fn main() {
+    let mut ledger = Ledger {
+        entries: vec![3, 1, 4],
+        totals: Vec::new(),
+    };
+    let mut sum = 0;
+    for entry in &ledger.entries {
+        sum += entry;
+        ledger.record(sum);
+    }
+}
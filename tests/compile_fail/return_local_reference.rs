@@ -0,0 +1,10 @@
+// cow: returning a reference to a value the function made.
+
+fn normalize_path(path: &str) -> &str {
+    let fixed = path.replace("//", "/");
+    &fixed
+}
+
+fn main() {
+    println!("{}", normalize_path("a//b"));
+}
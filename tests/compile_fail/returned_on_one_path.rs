@@ -0,0 +1,16 @@
+// reborrow: a borrow returned on one path is still live on the other.
+
+use std::collections::HashMap;
+
+fn get_or_insert(map: &mut HashMap<u32, String>) -> &String {
+    if let Some(name) = map.get(&22) {
+        return name;
+    }
+    map.insert(22, String::from("Hercules"));
+    &map[&22]
+}
+
+fn main() {
+    let mut map = HashMap::new();
+    println!("{}", get_or_insert(&mut map));
+}
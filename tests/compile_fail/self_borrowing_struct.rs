@@ -0,0 +1,13 @@
+// selfref: a struct holding a `String` and a reference into it.
+
+struct Parser<'a> {
+    buf: String,
+    cursor: &'a str,
+}
+
+fn main() {
+    let buf = String::from("let x = 1;");
+    let cursor = buf.as_str();
+    let p = Parser { buf, cursor };
+    println!("{} {}", p.buf, p.cursor);
+}
@@ -0,0 +1,7 @@
+// split: the compiler can't tell two indices into one vector apart.
+
+fn main() {
+    let mut v = vec![1, 2, 3];
+    let (a, b) = (&mut v[0], &mut v[2]);
+    std::mem::swap(a, b);
+}
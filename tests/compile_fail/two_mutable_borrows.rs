@@ -0,0 +1,9 @@
+// mutation::exclusive: two mutable borrows of `y` at once.
+
+fn main() {
+    let mut y = 20;
+    let m1 = &mut y;
+    let m2 = &mut y;
+    *m1 += 1;
+    *m2 += 1;
+}
@@ -0,0 +1,8 @@
+// mutation::exclusive: reading `y` directly while `m1` borrows it mutably.
+
+fn main() {
+    let mut y = 20;
+    let m1 = &mut y;
+    let z = y;
+    *m1 += z;
+}
@@ -0,0 +1,31 @@
+//! Every interleaving of the `concurrency` counters, under loom's model of
+//! threads and atomics.
+//!
+//! Only built with `--cfg loom`:
+//!
+//! ```text
+//! RUSTFLAGS="--cfg loom" cargo test --release --features unsound-demos --test loom
+//! ```
+
+#![cfg(loom)]
+
+use references_sharing_vs_mutation::concurrency;
+
+#[test]
+fn mutex_counter_is_always_exact() {
+    loom::model(|| assert_eq!(concurrency::count_with_mutex(2, 2), 4));
+}
+
+#[test]
+fn atomic_counter_is_always_exact() {
+    loom::model(|| assert_eq!(concurrency::count_with_atomic(2, 2), 4));
+}
+
+#[cfg(feature = "unsound-demos")]
+#[test]
+#[should_panic(expected = "Causality violation")]
+fn racing_counter_is_a_data_race() {
+    loom::model(|| {
+        concurrency::count_racing(2, 1);
+    });
+}
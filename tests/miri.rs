@@ -0,0 +1,65 @@
+//! Runs each of `ub_demos` under Miri and checks Miri calls it undefined
+//! behaviour, for the reason expected.
+//!
+//! Needs a nightly toolchain with Miri, so the tests are ignored unless
+//! asked for:
+//!
+//! ```text
+//! cargo miri-demos
+//! ```
+
+#![cfg(feature = "unsound-demos")]
+
+use std::process::Command;
+
+use references_sharing_vs_mutation::ub_demos::DEMOS;
+
+/// Runs the demo's unit test under Miri, returning its output.
+fn under_miri(name: &str) -> String {
+    let filter = format!("ub_demos::tests::{}", name);
+    let output = Command::new("cargo")
+        .args([
+            "+nightly",
+            "miri",
+            "test",
+            "--features",
+            "unsound-demos",
+            "--lib",
+        ])
+        .args(["--", "--exact", &filter])
+        .env(
+            "CARGO_TARGET_DIR",
+            concat!(env!("CARGO_MANIFEST_DIR"), "/target/miri-demos"),
+        )
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .expect("couldn't run `cargo +nightly miri`");
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    assert!(
+        !stderr.contains("is not installed"),
+        "Miri isn't installed: `rustup +nightly component add miri`"
+    );
+    assert!(!output.status.success(), "Miri accepted `{}`", name);
+    stderr
+}
+
+#[test]
+#[ignore = "needs Miri; run with `cargo miri-demos`"]
+fn miri_reports_every_demo() {
+    for &(name, reason, _) in DEMOS {
+        let report = under_miri(name);
+        assert!(
+            report.contains("Undefined Behavior"),
+            "{}:\n{}",
+            name,
+            report
+        );
+        assert!(
+            report.contains(reason),
+            "{} should mention {:?}:\n{}",
+            name,
+            reason,
+            report
+        );
+    }
+}
@@ -0,0 +1,172 @@
+//! Property tests: the hand-written utilities against the standard
+//! library's versions of them, on random inputs.
+//!
+//! Each property runs on [`CASES`] inputs from `proptest`. A failure is
+//! shrunk to the smallest input proptest can find that still fails, and
+//! its seed saved in a regressions file beside the test so that it's tried
+//! first from then on.
+
+#![cfg(feature = "std")]
+
+use std::ops::Range;
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use references_sharing_vs_mutation::fixes::{extend_from_itself, extend_from_itself_by_index};
+use references_sharing_vs_mutation::split::get_two_mut;
+use references_sharing_vs_mutation::tinyvec::TinyVec;
+use references_sharing_vs_mutation::vecops::{extend, extend_copied, extend_self};
+
+/// How many inputs each property is checked on.
+const CASES: u32 = 256;
+
+/// Up to `max_len` numbers from -50 to 49.
+fn numbers(max_len: usize) -> impl Strategy<Value = Vec<i32>> {
+    vec(-50..50, 0..=max_len)
+}
+
+/// A vector whose capacity is `len` or a little more, so some pushes
+/// reallocate and some don't.
+fn with_slack(max_len: usize) -> impl Strategy<Value = Vec<i32>> {
+    (numbers(max_len), 0..4usize).prop_map(|(v, slack)| {
+        let mut out = Vec::with_capacity(v.len() + slack);
+        out.extend_from_slice(&v);
+        out
+    })
+}
+
+/// A vector and a range within it.
+fn with_range(max_len: usize) -> impl Strategy<Value = (Vec<i32>, Range<usize>)> {
+    with_slack(max_len)
+        .prop_flat_map(|v| {
+            let len = v.len();
+            (Just(v), 0..=len)
+        })
+        .prop_flat_map(|(v, end)| (Just(v), 0..=end, Just(end)))
+        .prop_map(|(v, start, end)| (v, start..end))
+}
+
+/// A vector and two indices up to `len + 1`, so some are out of bounds.
+fn with_two_indices(max_len: usize) -> impl Strategy<Value = (Vec<i32>, usize, usize)> {
+    numbers(max_len).prop_flat_map(|v| {
+        let bound = v.len() + 2;
+        (Just(v), 0..bound, 0..bound)
+    })
+}
+
+/// One step of a `TinyVec` and `Vec` run side by side.
+#[derive(Debug, Clone)]
+enum Op {
+    Push(i32),
+    Pop,
+    ExtendFromSlice(Vec<i32>),
+    Reserve(usize),
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        5 => (0..100).prop_map(Op::Push),
+        3 => Just(Op::Pop),
+        1 => numbers(6).prop_map(Op::ExtendFromSlice),
+        1 => (0..8usize).prop_map(Op::Reserve),
+    ]
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(CASES))]
+
+    #[test]
+    fn extend_matches_extend_from_slice(v in with_slack(16), slice in numbers(16)) {
+        let mut expected = v.clone();
+        expected.extend_from_slice(&slice);
+
+        let mut by_extend = v.clone();
+        prop_assert_eq!(extend(&mut by_extend, &slice), slice.len());
+        prop_assert_eq!(&by_extend, &expected);
+
+        let mut by_copy = v;
+        prop_assert_eq!(extend_copied(&mut by_copy, &slice), slice.len());
+        prop_assert_eq!(&by_copy, &expected);
+    }
+
+    #[test]
+    fn extending_from_itself_doubles_the_vector(v in with_slack(16)) {
+        let doubled: Vec<i32> = v.iter().chain(&v).copied().collect();
+
+        let mut within = v.clone();
+        extend_from_itself(&mut within);
+        prop_assert_eq!(&within, &doubled);
+
+        let mut by_index = v.clone();
+        extend_from_itself_by_index(&mut by_index);
+        prop_assert_eq!(&by_index, &doubled);
+
+        let mut by_range = v.clone();
+        extend_self(&mut by_range, 0..v.len());
+        prop_assert_eq!(&by_range, &doubled);
+    }
+
+    #[test]
+    fn extend_self_matches_extend_from_within((v, range) in with_range(16)) {
+        let mut expected = v.clone();
+        expected.extend_from_within(range.clone());
+        let mut actual = v;
+        extend_self(&mut actual, range);
+        prop_assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn get_two_mut_never_aliases_and_matches_indexing((mut v, i, j) in with_two_indices(8)) {
+        let original = v.clone();
+        let len = v.len();
+        let split = match get_two_mut(&mut v, i, j) {
+            Some((a, b)) => {
+                prop_assert!(i != j && i < len && j < len);
+                prop_assert!(!std::ptr::eq(&*a, &*b));
+                prop_assert_eq!((*a, *b), (original[i], original[j]));
+                *a += 1000;
+                *b += 2000;
+                true
+            }
+            None => {
+                prop_assert!(i == j || i >= len || j >= len);
+                false
+            }
+        };
+        for (k, (&after, &before)) in v.iter().zip(&original).enumerate() {
+            let expected = if split && k == i {
+                before + 1000
+            } else if split && k == j {
+                before + 2000
+            } else {
+                before
+            };
+            prop_assert_eq!(after, expected, "element {}", k);
+        }
+    }
+
+    #[test]
+    fn tinyvec_behaves_like_vec(ops in vec(op(), 0..40)) {
+        let mut tiny = TinyVec::new();
+        let mut model = Vec::new();
+        for op in ops {
+            match op {
+                Op::Push(x) => {
+                    tiny.push(x);
+                    model.push(x);
+                }
+                Op::Pop => prop_assert_eq!(tiny.pop(), model.pop()),
+                Op::ExtendFromSlice(slice) => {
+                    tiny.extend_from_slice(&slice);
+                    model.extend_from_slice(&slice);
+                }
+                Op::Reserve(n) => {
+                    tiny.reserve(n);
+                    prop_assert!(tiny.capacity() >= tiny.len() + n);
+                }
+            }
+            prop_assert_eq!(&*tiny, &model[..]);
+            prop_assert!(tiny.capacity() >= tiny.len());
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Snapshot tests: every diagram, timeline, explanation, exported page and
+//! tour screen against the copy kept under `snapshots/`.
+//!
+//! A failure shows how the output changed. If the change is the one
+//! wanted, `cargo run -- render --all` writes the snapshots afresh, or
+//! running the tests with `UPDATE_SNAPSHOTS=1` does the same for the ones
+//! that differ; either way the new snapshots go in the commit with the
+//! change, for review.
+
+#![cfg(feature = "std")]
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use references_sharing_vs_mutation::diffview::{diff, unified};
+use references_sharing_vs_mutation::render;
+
+fn snapshots() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/render/snapshots")
+}
+
+/// Every file under `dir`, as a path relative to `root`.
+fn files(root: &Path, dir: &Path, out: &mut Vec<String>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            files(root, &path, out);
+        } else {
+            let name = path.strip_prefix(root).unwrap().to_str().unwrap();
+            out.push(name.replace('\\', "/"));
+        }
+    }
+}
+
+#[test]
+fn renderings_match_their_snapshots() {
+    let dir = snapshots();
+    let update = env::var_os("UPDATE_SNAPSHOTS").is_some();
+    let mut failures = Vec::new();
+    for rendering in render::all() {
+        let path = dir.join(&rendering.name);
+        let kept = fs::read_to_string(&path).unwrap_or_default();
+        if kept == rendering.text {
+            continue;
+        }
+        if update {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, &rendering.text).unwrap();
+        } else {
+            failures.push(format!(
+                "{} changed:\n{}",
+                rendering.name,
+                unified(&diff(&kept, &rendering.text), false)
+            ));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{}\n{} snapshots differ; run `cargo run -- render --all` if that's intended",
+        failures.join("\n"),
+        failures.len()
+    );
+}
+
+#[test]
+fn every_snapshot_is_still_rendered() {
+    let dir = snapshots();
+    let mut kept = Vec::new();
+    files(&dir, &dir, &mut kept);
+    let rendered: Vec<String> = render::all().into_iter().map(|r| r.name).collect();
+    let stale: Vec<&String> = kept.iter().filter(|k| !rendered.contains(k)).collect();
+    assert!(stale.is_empty(), "nothing renders {:?}; delete them", stale);
+}